@@ -19,6 +19,11 @@ pub struct Parameters {
     #[serde(skip)]
     pub db_path: PathBuf,
 
+    /// Keep the consensus DB in memory instead of at `db_path`. Nothing survives a restart of
+    /// the authority, so this is only meant for tests and ephemeral runs.
+    #[serde(default)]
+    pub in_memory_db: bool,
+
     /// Time to wait for parent round leader before sealing a block, from when parent round
     /// has a quorum.
     #[serde(default = "Parameters::default_leader_timeout")]
@@ -99,6 +104,10 @@ pub struct Parameters {
     /// Tonic network settings.
     #[serde(default = "TonicParameters::default")]
     pub tonic: TonicParameters,
+
+    /// RocksDB settings of the consensus DB. Ignored with `in_memory_db`.
+    #[serde(default = "RocksDbParameters::default")]
+    pub rocksdb: RocksDbParameters,
 }
 
 impl Parameters {
@@ -210,6 +219,7 @@ impl Default for Parameters {
     fn default() -> Self {
         Self {
             db_path: PathBuf::default(),
+            in_memory_db: false,
             leader_timeout: Parameters::default_leader_timeout(),
             min_round_delay: Parameters::default_min_round_delay(),
             max_forward_time_drift: Parameters::default_max_forward_time_drift(),
@@ -227,6 +237,7 @@ impl Default for Parameters {
             commit_sync_batches_ahead: Parameters::default_commit_sync_batches_ahead(),
             anemo: AnemoParameters::default(),
             tonic: TonicParameters::default(),
+            rocksdb: RocksDbParameters::default(),
         }
     }
 }
@@ -312,3 +323,40 @@ impl Default for TonicParameters {
         }
     }
 }
+
+/// Tuning of the RocksDB column families of the consensus DB. Unset fields keep the defaults of
+/// the store, which are tuned for write throughput.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RocksDbParameters {
+    /// Size of the memtable of every column family, in MiB. Larger memtables absorb write
+    /// bursts and reduce compactions, at the cost of memory (up to several memtables per column
+    /// family) and of longer recoveries. Values between 64 and 1024 are reasonable.
+    ///
+    /// If unspecified, this will default to the store default (256MiB).
+    #[serde(default)]
+    pub write_buffer_size_mb: Option<usize>,
+
+    /// Maximum number of concurrent background flushes and compactions. Compactions falling
+    /// behind stall writes; more jobs than cores starve consensus. Values between 2 and the
+    /// number of cores are reasonable.
+    ///
+    /// If unspecified, this will default to the store default.
+    #[serde(default)]
+    pub max_background_jobs: Option<usize>,
+
+    /// Compression of the column families. `none` trades disk space for CPU.
+    ///
+    /// If unspecified, this will default to the store default (lz4).
+    #[serde(default)]
+    pub compression: Option<RocksDbCompression>,
+}
+
+/// A compression algorithm of RocksDB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RocksDbCompression {
+    None,
+    Snappy,
+    Lz4,
+    Zstd,
+}
@@ -37,40 +37,104 @@ pub fn local_committee_and_keys(
     (committee, key_pairs)
 }
 
-/// Creates a committee for Docker network testing with static IP addresses.
-pub fn docker_committee_and_keys(
+/// Creates a committee whose authorities listen on the provided addresses, and the
+/// corresponding key pairs for the authorities.
+pub fn committee_and_keys_with_addresses(
     epoch: Epoch,
     authorities_stake: Vec<Stake>,
+    addresses: Vec<Multiaddr>,
 ) -> (Committee, Vec<(NetworkKeyPair, ProtocolKeyPair)>) {
-    // Ensure we don't exceed the available Docker IPs
-    if authorities_stake.len() > 4 {
-        panic!(
-            "Docker committee supports maximum 4 authorities, got {}",
-            authorities_stake.len()
-        );
-    }
+    assert_eq!(
+        authorities_stake.len(),
+        addresses.len(),
+        "Expected one address per authority"
+    );
 
     let mut authorities = vec![];
     let mut key_pairs = vec![];
     let mut rng = StdRng::from_seed([0; 32]);
+    for (i, (stake, address)) in authorities_stake.into_iter().zip(addresses).enumerate() {
+        let authority_keypair = AuthorityKeyPair::generate(&mut rng);
+        let protocol_keypair = ProtocolKeyPair::generate(&mut rng);
+        let network_keypair = NetworkKeyPair::generate(&mut rng);
+        authorities.push(Authority {
+            stake,
+            address,
+            hostname: format!("test_host_{i}").to_string(),
+            authority_key: authority_keypair.public(),
+            protocol_key: protocol_keypair.public(),
+            network_key: network_keypair.public(),
+        });
+        key_pairs.push((network_keypair, protocol_keypair));
+    }
 
-    // Docker network IP addresses for the 4-node network
-    let docker_ips = ["172.20.0.10", "172.20.0.11", "172.20.0.12", "172.20.0.13"];
-    let network_ports = [26657, 26657, 26657, 26657]; // All nodes use port 26657 internally
+    let committee = Committee::new(epoch, authorities);
+    (committee, key_pairs)
+}
+
+/// Expands an address template into one address per authority. The template may contain
+/// the placeholders `{i}` (the authority index) and `{port}` (`base_port + i`), for
+/// example `/dns/node{i}/udp/{port}`.
+pub fn addresses_from_template(
+    template: &str,
+    committee_size: usize,
+    base_port: u16,
+) -> Result<Vec<Multiaddr>, String> {
+    (0..committee_size)
+        .map(|i| {
+            let port = u16::try_from(i)
+                .ok()
+                .and_then(|i| base_port.checked_add(i))
+                .ok_or_else(|| format!("Port of authority {i} overflows"))?;
+            let address = template
+                .replace("{i}", &i.to_string())
+                .replace("{port}", &port.to_string());
+            address
+                .parse()
+                .map_err(|e| format!("Invalid address '{address}': {e}"))
+        })
+        .collect()
+}
+
+/// Parses a comma-separated list of addresses, one per authority.
+pub fn parse_addresses(addresses: &str) -> Result<Vec<Multiaddr>, String> {
+    addresses
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(|address| {
+            address
+                .parse()
+                .map_err(|e| format!("Invalid address '{address}': {e}"))
+        })
+        .collect()
+}
+
+/// Creates a committee for Docker network testing. The authorities are addressed by their
+/// docker-compose service name (`mysticeti-node{i}`), resolved by the Docker DNS whenever a
+/// peer connects, so the committee does not depend on the subnet of the network and survives
+/// containers being recreated with another IP.
+pub fn docker_committee_and_keys(
+    epoch: Epoch,
+    authorities_stake: Vec<Stake>,
+) -> (Committee, Vec<(NetworkKeyPair, ProtocolKeyPair)>) {
+    let mut authorities = vec![];
+    let mut key_pairs = vec![];
+    let mut rng = StdRng::from_seed([0; 32]);
 
     for (i, stake) in authorities_stake.into_iter().enumerate() {
         let authority_keypair = AuthorityKeyPair::generate(&mut rng);
         let protocol_keypair = ProtocolKeyPair::generate(&mut rng);
         let network_keypair = NetworkKeyPair::generate(&mut rng);
 
-        // Use Docker network address
-        let address = format!("/ip4/{}/udp/{}", docker_ips[i], network_ports[i]);
-        let address = address.parse().unwrap();
+        // All nodes use port 26657 internally
+        let hostname = format!("mysticeti-node{i}");
+        let address = format!("/dns/{hostname}/udp/26657").parse().unwrap();
 
         authorities.push(Authority {
             stake,
             address,
-            hostname: format!("mysticeti-node{}", i).to_string(),
+            hostname,
             authority_key: authority_keypair.public(),
             protocol_key: protocol_keypair.public(),
             network_key: network_keypair.public(),
@@ -3,6 +3,7 @@
 
 use consensus_config::{
     Authority, AuthorityKeyPair, Committee, NetworkKeyPair, ProtocolKeyPair, Stake,
+    addresses_from_template, committee_and_keys_with_addresses, docker_committee_and_keys,
 };
 use insta::assert_yaml_snapshot;
 use mysten_network::Multiaddr;
@@ -35,3 +36,34 @@ fn committee_snapshot_matches() {
 
     assert_yaml_snapshot!("committee", committee)
 }
+
+#[test]
+fn committee_addresses_from_template() {
+    let addresses = addresses_from_template("/dns/node{i}/udp/{port}", 3, 26657).unwrap();
+    let (committee, keypairs) = committee_and_keys_with_addresses(0, vec![1; 3], addresses);
+
+    assert_eq!(keypairs.len(), 3);
+    for (i, (_, authority)) in committee.authorities().enumerate() {
+        assert_eq!(
+            authority.address.to_string(),
+            format!("/dns/node{i}/udp/{}", 26657 + i)
+        );
+    }
+
+    assert!(addresses_from_template("/dns/node{i}/udp/{port}", 2, u16::MAX).is_err());
+}
+
+#[test]
+fn docker_committee_uses_service_names() {
+    // Not limited by a list of static IPs.
+    let (committee, keypairs) = docker_committee_and_keys(0, vec![1; 7]);
+
+    assert_eq!(keypairs.len(), 7);
+    for (i, (_, authority)) in committee.authorities().enumerate() {
+        assert_eq!(authority.hostname, format!("mysticeti-node{i}"));
+        assert_eq!(
+            authority.address.to_string(),
+            format!("/dns/mysticeti-node{i}/udp/26657")
+        );
+    }
+}
@@ -34,7 +34,7 @@ use crate::{
     proposed_block_handler::ProposedBlockHandler,
     round_prober::{RoundProber, RoundProberHandle},
     round_tracker::PeerRoundTracker,
-    storage::rocksdb_store::RocksDBStore,
+    storage::{mem_store::MemStore, rocksdb_store::RocksDBStore, Store},
     subscriber::Subscriber,
     synchronizer::{Synchronizer, SynchronizerHandle},
     transaction::{TransactionClient, TransactionConsumer, TransactionVerifier},
@@ -236,8 +236,13 @@ where
             ))
         };
 
-        let store_path = context.parameters.db_path.as_path().to_str().unwrap();
-        let store = Arc::new(RocksDBStore::new(store_path));
+        let store: Arc<dyn Store> = if context.parameters.in_memory_db {
+            info!("Using an in-memory consensus store");
+            Arc::new(MemStore::new())
+        } else {
+            let store_path = context.parameters.db_path.as_path().to_str().unwrap();
+            Arc::new(RocksDBStore::new(store_path, &context.parameters.rocksdb))
+        };
         let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store.clone())));
 
         let block_verifier = Arc::new(SignedBlockVerifier::new(
@@ -890,19 +890,25 @@ fn peer_info_from_certs(
     Some(PeerInfo { authority_index })
 }
 
-/// Attempts to convert a multiaddr of the form `/[ip4,ip6,dns]/{}/udp/{port}` into
-/// a host:port string.
+/// Attempts to convert a multiaddr of the form `/[ip4,ip6,dns,dns4,dns6]/{}/[udp,tcp]/{port}`
+/// into a host:port string.
+///
+/// Hostnames are left to the connector, which resolves them every time it (re)connects: peers
+/// can be addressed by docker-compose or k8s service names, and are still reached after a
+/// rolling restart moves them to another IP.
 fn to_host_port_str(addr: &Multiaddr) -> Result<String, String> {
     let mut iter = addr.iter();
 
     match (iter.next(), iter.next()) {
-        (Some(Protocol::Ip4(ipaddr)), Some(Protocol::Udp(port))) => {
+        (Some(Protocol::Ip4(ipaddr)), Some(Protocol::Udp(port) | Protocol::Tcp(port))) => {
             Ok(format!("{}:{}", ipaddr, port))
         }
-        (Some(Protocol::Ip6(ipaddr)), Some(Protocol::Udp(port))) => {
-            Ok(format!("{}:{}", ipaddr, port))
+        (Some(Protocol::Ip6(ipaddr)), Some(Protocol::Udp(port) | Protocol::Tcp(port))) => {
+            Ok(format!("[{}]:{}", ipaddr, port))
         }
-        (Some(Protocol::Dns(hostname)), Some(Protocol::Udp(port))) => {
+        (Some(Protocol::Dns(hostname)), Some(Protocol::Udp(port) | Protocol::Tcp(port)))
+        | (Some(Protocol::Dns4(hostname)), Some(Protocol::Udp(port) | Protocol::Tcp(port)))
+        | (Some(Protocol::Dns6(hostname)), Some(Protocol::Udp(port) | Protocol::Tcp(port))) => {
             Ok(format!("{}:{}", hostname, port))
         }
 
@@ -1136,3 +1142,28 @@ fn chunk_blocks(blocks: Vec<Bytes>, chunk_limit: usize) -> Vec<Vec<Bytes>> {
     }
     chunks
 }
+
+#[cfg(test)]
+mod test {
+    use super::to_host_port_str;
+
+    #[test]
+    fn host_port_from_multiaddr() {
+        let host_port = |addr: &str| to_host_port_str(&addr.parse().unwrap());
+        assert_eq!(
+            host_port("/ip4/10.0.0.1/udp/26657").unwrap(),
+            "10.0.0.1:26657"
+        );
+        assert_eq!(host_port("/ip6/::1/tcp/26657").unwrap(), "[::1]:26657");
+        // Hostnames are resolved when connecting.
+        assert_eq!(
+            host_port("/dns/mysticeti-node1/tcp/26657").unwrap(),
+            "mysticeti-node1:26657"
+        );
+        assert_eq!(
+            host_port("/dns4/node.validators.svc/udp/26657").unwrap(),
+            "node.validators.svc:26657"
+        );
+        assert!(host_port("/dns/mysticeti-node1").is_err());
+    }
+}
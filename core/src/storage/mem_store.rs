@@ -19,13 +19,11 @@ use crate::{
     error::ConsensusResult,
 };
 
-/// In-memory storage for testing.
-#[allow(unused)]
+/// In-memory storage, for tests and ephemeral authorities.
 pub(crate) struct MemStore {
     inner: RwLock<Inner>,
 }
 
-#[allow(unused)]
 struct Inner {
     blocks: BTreeMap<(Round, AuthorityIndex, BlockDigest), VerifiedBlock>,
     digests_by_authorities: BTreeSet<(AuthorityIndex, Round, BlockDigest)>,
@@ -35,7 +33,6 @@ struct Inner {
 }
 
 impl MemStore {
-    #[cfg(test)]
     pub(crate) fn new() -> Self {
         MemStore {
             inner: RwLock::new(Inner {
@@ -4,12 +4,13 @@
 use std::{collections::VecDeque, ops::Bound::Included, time::Duration};
 
 use bytes::Bytes;
-use consensus_config::AuthorityIndex;
+use consensus_config::{AuthorityIndex, RocksDbCompression, RocksDbParameters};
 use sui_macros::fail_point;
 use typed_store::{
     metrics::SamplingInterval,
     reopen,
     rocks::{default_db_options, open_cf_opts, DBMap, MetricConf, ReadWriteOptions},
+    rocksdb::{DBCompressionType, Options},
     Map as _,
 };
 
@@ -42,22 +43,31 @@ impl RocksDBStore {
     const COMMIT_VOTES_CF: &'static str = "commit_votes";
     const COMMIT_INFO_CF: &'static str = "commit_info";
 
-    /// Creates a new instance of RocksDB storage.
-    pub(crate) fn new(path: &str) -> Self {
+    /// Creates a new instance of RocksDB storage, tuned with the specified parameters.
+    pub(crate) fn new(path: &str, parameters: &RocksDbParameters) -> Self {
         // Consensus data has high write throughput (all transactions) and is rarely read
         // (only during recovery and when helping peers catch up).
-        let db_options = default_db_options().optimize_db_for_write_throughput(2);
+        let mut db_options = default_db_options().optimize_db_for_write_throughput(2);
+        if let Some(jobs) = parameters.max_background_jobs {
+            db_options.options.set_max_background_jobs(jobs as i32);
+        }
         let mut metrics_conf = MetricConf::new("consensus");
         metrics_conf.read_sample_interval = SamplingInterval::new(Duration::from_secs(60), 0);
-        let cf_options = default_db_options().optimize_for_write_throughput().options;
+        let cf_options = Self::tune(
+            default_db_options().optimize_for_write_throughput().options,
+            parameters,
+        );
         let column_family_options = vec![
             (
                 Self::BLOCKS_CF,
-                default_db_options()
-                    .optimize_for_write_throughput_no_deletion()
-                    // Using larger block is ok since there is not much point reads on the cf.
-                    .set_block_options(512, 128 << 10)
-                    .options,
+                Self::tune(
+                    default_db_options()
+                        .optimize_for_write_throughput_no_deletion()
+                        // Using larger block is ok since there is not much point reads on the cf.
+                        .set_block_options(512, 128 << 10)
+                        .options,
+                    parameters,
+                ),
             ),
             (Self::DIGESTS_BY_AUTHORITIES_CF, cf_options.clone()),
             (Self::COMMITS_CF, cf_options.clone()),
@@ -88,6 +98,22 @@ impl RocksDBStore {
             commit_info,
         }
     }
+
+    /// Applies the configured overrides to the options of a column family.
+    fn tune(mut options: Options, parameters: &RocksDbParameters) -> Options {
+        if let Some(size_mb) = parameters.write_buffer_size_mb {
+            options.set_write_buffer_size(size_mb << 20);
+        }
+        if let Some(compression) = parameters.compression {
+            options.set_compression_type(match compression {
+                RocksDbCompression::None => DBCompressionType::None,
+                RocksDbCompression::Snappy => DBCompressionType::Snappy,
+                RocksDbCompression::Lz4 => DBCompressionType::Lz4,
+                RocksDbCompression::Zstd => DBCompressionType::Zstd,
+            });
+        }
+        options
+    }
 }
 
 impl Store for RocksDBStore {
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use consensus_config::AuthorityIndex;
+use consensus_config::{AuthorityIndex, RocksDbParameters};
 use rstest::rstest;
 use tempfile::TempDir;
 
@@ -29,7 +29,10 @@ impl TestStore {
 fn new_rocksdb_teststore() -> TestStore {
     let temp_dir = TempDir::new().unwrap();
     TestStore::RocksDB((
-        RocksDBStore::new(temp_dir.path().to_str().unwrap()),
+        RocksDBStore::new(
+            temp_dir.path().to_str().unwrap(),
+            &RocksDbParameters::default(),
+        ),
         temp_dir,
     ))
 }
@@ -0,0 +1,20 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+// Build script to generate the messages, client and server of the `MysticetiService`.
+fn main() -> Result<()> {
+    // Use the vendored `protoc` unless one is provided, so that building does not require
+    // installing it.
+    if std::env::var_os("PROTOC").is_none() {
+        // SAFETY: the build script is single-threaded.
+        unsafe { std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?) };
+    }
+    tonic_build::configure().compile(&["proto/mysticeti.proto"], &["proto"])?;
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=PROTOC");
+
+    Ok(())
+}
@@ -1,36 +1,206 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
+
+use fastcrypto::hash::{HashFunction, Sha256};
+use parking_lot::RwLock;
 use tendermint_abci::Application;
 use tendermint_proto::v0_38::abci::{
-    RequestCheckTx, RequestFinalizeBlock, RequestInfo, RequestInitChain, RequestQuery,
-    ResponseCheckTx, ResponseFinalizeBlock, ResponseInfo, ResponseInitChain, ResponseQuery,
+    ExecTxResult, RequestCheckTx, RequestFinalizeBlock, RequestInfo, RequestInitChain,
+    RequestQuery, ResponseCheckTx, ResponseFinalizeBlock, ResponseInfo, ResponseInitChain,
+    ResponseQuery,
 };
 use tokio::sync::mpsc;
 use tracing::info;
 
+/// The `check_tx` code of accepted transactions.
+pub const CODE_OK: u32 = 0;
+/// The `check_tx` code of empty transactions.
+pub const CODE_EMPTY_TRANSACTION: u32 = 1;
+/// The `check_tx` code of transactions exceeding the maximum size.
+pub const CODE_TRANSACTION_TOO_LARGE: u32 = 2;
+/// The default code of transactions rejected by a `TransactionValidator`.
+pub const CODE_REJECTED: u32 = 3;
+/// The `check_tx` code of transactions that could not be forwarded to consensus, because it
+/// is overloaded or stopped.
+pub const CODE_UNAVAILABLE: u32 = 4;
+/// The `query` code of keys absent from the state.
+pub const CODE_UNKNOWN_KEY: u32 = 5;
+
+/// The prefix of the transactions writing the state, as `kv:<key>=<value>`.
+pub const KV_WRITE_PREFIX: &[u8] = b"kv:";
+
+/// Why a `TransactionValidator` rejected a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rejection {
+    /// The ABCI response code.
+    pub code: u32,
+    /// The reason, reported in the log of the ABCI response.
+    pub reason: String,
+}
+
+impl Rejection {
+    /// Reject with the default `CODE_REJECTED` code.
+    pub fn new<S: Into<String>>(reason: S) -> Self {
+        Self {
+            code: CODE_REJECTED,
+            reason: reason.into(),
+        }
+    }
+
+    /// Reject with the specified ABCI response code instead of `CODE_REJECTED`.
+    pub fn with_code(mut self, code: u32) -> Self {
+        self.code = code;
+        self
+    }
+}
+
+/// Application-specific rules applied to every transaction in `check_tx` and `finalize_block`,
+/// after the built-in checks. This is the ABCI counterpart of the consensus
+/// `TransactionVerifier`. Implemented by any `Fn(&[u8]) -> Result<(), Rejection>` closure.
+pub trait TransactionValidator: Send + Sync + 'static {
+    fn validate(&self, tx: &[u8]) -> Result<(), Rejection>;
+}
+
+impl<F> TransactionValidator for F
+where
+    F: Fn(&[u8]) -> Result<(), Rejection> + Send + Sync + 'static,
+{
+    fn validate(&self, tx: &[u8]) -> Result<(), Rejection> {
+        self(tx)
+    }
+}
+
+/// Executes the transactions of a finalized block, one at a time and in block order, after
+/// they passed the validation. The result (code, gas, events, data) is reported as is in the
+/// `tx_results` of `finalize_block`; a non-zero code marks a failed execution, but the
+/// transaction stays in the block. Implemented by any `Fn(&[u8]) -> ExecTxResult` closure.
+pub trait TransactionExecutor: Send + Sync + 'static {
+    fn execute(&self, tx: &[u8]) -> ExecTxResult;
+}
+
+impl<F> TransactionExecutor for F
+where
+    F: Fn(&[u8]) -> ExecTxResult + Send + Sync + 'static,
+{
+    fn execute(&self, tx: &[u8]) -> ExecTxResult {
+        self(tx)
+    }
+}
+
+/// The key-value state of the application. Every successful `kv:<key>=<value>` transaction
+/// of a finalized block writes its value; other transactions (e.g. benchmark payloads, which
+/// may well contain a `=`) leave the state as is.
+#[derive(Default)]
+struct KvState {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// The height of the last finalized block.
+    height: i64,
+    /// The hash of the entries after the last finalized block.
+    app_hash: Vec<u8>,
+}
+
+impl KvState {
+    /// The key and value written by a `kv:` transaction, split at the first `=` after the
+    /// prefix.
+    fn parse_write(tx: &[u8]) -> Option<(&[u8], &[u8])> {
+        let tx = tx.strip_prefix(KV_WRITE_PREFIX)?;
+        let separator = tx.iter().position(|byte| *byte == b'=')?;
+        Some((&tx[..separator], &tx[separator + 1..]))
+    }
+
+    /// The SHA-256 digest of the entries in key order, each length-prefixed, so that the nodes
+    /// applying the same blocks agree on it.
+    fn hash(&self) -> Vec<u8> {
+        let mut hasher = Sha256::default();
+        for (key, value) in &self.entries {
+            hasher.update((key.len() as u64).to_le_bytes());
+            hasher.update(key);
+            hasher.update((value.len() as u64).to_le_bytes());
+            hasher.update(value);
+        }
+        hasher.finalize().digest.to_vec()
+    }
+}
+
 #[derive(Clone)]
 pub struct MysticetiAbciApp {
     transaction_sender: Arc<mpsc::Sender<Vec<u8>>>,
+    max_transaction_size: usize,
+    validator: Option<Arc<dyn TransactionValidator>>,
+    executor: Option<Arc<dyn TransactionExecutor>>,
+    state: Arc<RwLock<KvState>>,
 }
 
 impl MysticetiAbciApp {
+    /// The default maximum size (in bytes) of a transaction accepted by `check_tx`.
+    pub const DEFAULT_MAX_TRANSACTION_SIZE: usize = 256 * 1024;
+
     pub fn new(transaction_sender: mpsc::Sender<Vec<u8>>) -> Self {
         Self {
             transaction_sender: Arc::new(transaction_sender),
+            max_transaction_size: Self::DEFAULT_MAX_TRANSACTION_SIZE,
+            validator: None,
+            executor: None,
+            state: Arc::new(RwLock::new(KvState::default())),
         }
     }
+
+    /// Set the maximum size (in bytes) of a transaction accepted by `check_tx`.
+    pub fn with_max_transaction_size(mut self, max_transaction_size: usize) -> Self {
+        self.max_transaction_size = max_transaction_size;
+        self
+    }
+
+    /// Apply the rules of the specified validator to every transaction, on top of the built-in
+    /// checks.
+    pub fn with_validator<V: TransactionValidator>(mut self, validator: V) -> Self {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Execute the valid transactions of finalized blocks with the specified executor, instead
+    /// of accepting all of them without effects.
+    pub fn with_executor<E: TransactionExecutor>(mut self, executor: E) -> Self {
+        self.executor = Some(Arc::new(executor));
+        self
+    }
+
+    /// Run the cheap stateless checks, then the validator (if any), on a transaction. Returns
+    /// the rejection code and reason, if any.
+    fn validate_transaction(&self, tx: &[u8]) -> Option<(u32, String)> {
+        if tx.is_empty() {
+            return Some((CODE_EMPTY_TRANSACTION, "Empty transaction".to_string()));
+        }
+        if tx.len() > self.max_transaction_size {
+            return Some((
+                CODE_TRANSACTION_TOO_LARGE,
+                format!(
+                    "Transaction of {} bytes exceeds the maximum size of {} bytes",
+                    tx.len(),
+                    self.max_transaction_size
+                ),
+            ));
+        }
+        let validator = self.validator.as_ref()?;
+        validator
+            .validate(tx)
+            .err()
+            .map(|rejection| (rejection.code, rejection.reason))
+    }
 }
 
 impl Application for MysticetiAbciApp {
     fn info(&self, _request: RequestInfo) -> ResponseInfo {
+        let state = self.state.read();
         ResponseInfo {
             data: "Mysticeti ABCI App".to_string(),
             version: "0.1.0".to_string(),
             app_version: 1,
-            last_block_height: 0,
-            last_block_app_hash: vec![].into(),
+            last_block_height: state.height,
+            last_block_app_hash: state.app_hash.clone().into(),
         }
     }
 
@@ -40,17 +210,29 @@ impl Application for MysticetiAbciApp {
 
     fn check_tx(&self, request: RequestCheckTx) -> ResponseCheckTx {
         info!("ABCI check_tx called: {} bytes", request.tx.len());
-        // Forward transaction to Mysticeti for validation
-        let sender = self.transaction_sender.clone();
-        let tx = request.tx.to_vec();
-        tokio::spawn(async move {
-            if let Err(e) = sender.send(tx).await {
-                info!("Failed to forward transaction to Mysticeti: {}", e);
-            }
-        });
+        if let Some((code, log)) = self.validate_transaction(&request.tx) {
+            info!("ABCI check_tx rejected transaction: {}", log);
+            return ResponseCheckTx {
+                code,
+                log,
+                ..Default::default()
+            };
+        }
+
+        // Forward the transaction to Mysticeti consensus. The ABCI server calls this from its
+        // own threads, outside of the runtime, so the transaction is rejected rather than
+        // waited for when the queue is full.
+        if let Err(e) = self.transaction_sender.try_send(request.tx.to_vec()) {
+            info!("Failed to forward transaction to Mysticeti: {}", e);
+            return ResponseCheckTx {
+                code: CODE_UNAVAILABLE,
+                log: format!("Failed to forward transaction to consensus: {e}"),
+                ..Default::default()
+            };
+        }
 
         ResponseCheckTx {
-            code: 0,
+            code: CODE_OK,
             ..Default::default()
         }
     }
@@ -61,33 +243,326 @@ impl Application for MysticetiAbciApp {
             request.txs.len()
         );
 
-        // Forward all transactions to Mysticeti consensus
-        let sender = self.transaction_sender.clone();
+        // The transactions of a finalized block are already ordered: they were forwarded to
+        // Mysticeti consensus by `check_tx`, and are only executed here.
+        let mut state = self.state.write();
+        let mut tx_results = Vec::with_capacity(request.txs.len());
         for (i, tx) in request.txs.iter().enumerate() {
             info!("Processing transaction {}: {} bytes", i, tx.len());
-            let tx_clone = tx.to_vec();
-            let sender_clone = sender.clone();
-            tokio::spawn(async move {
-                if let Err(e) = sender_clone.send(tx_clone).await {
-                    info!("Failed to forward transaction {} to Mysticeti: {}", i, e);
-                }
-            });
+            if let Some((code, log)) = self.validate_transaction(tx) {
+                info!("ABCI finalize_block rejected transaction {}: {}", i, log);
+                tx_results.push(ExecTxResult {
+                    code,
+                    log,
+                    ..Default::default()
+                });
+                continue;
+            }
+            let result = match &self.executor {
+                Some(executor) => executor.execute(tx),
+                None => ExecTxResult {
+                    code: CODE_OK,
+                    ..Default::default()
+                },
+            };
+            // Failed executions have no effect on the state.
+            let write = KvState::parse_write(tx).filter(|_| result.code == CODE_OK);
+            if let Some((key, value)) = write {
+                state.entries.insert(key.to_vec(), value.to_vec());
+            }
+            tx_results.push(result);
         }
 
+        state.height = request.height;
+        state.app_hash = state.hash();
         ResponseFinalizeBlock {
             events: vec![],
-            tx_results: vec![],
+            tx_results,
             validator_updates: vec![],
             consensus_param_updates: None,
-            app_hash: vec![].into(),
+            app_hash: state.app_hash.clone().into(),
+        }
+    }
+
+    /// Read the value of the key in `data`, or in `path` (without its leading `/`) if `data`
+    /// is empty.
+    fn query(&self, request: RequestQuery) -> ResponseQuery {
+        let key = match request.data.is_empty() {
+            true => request.path.trim_start_matches('/').as_bytes().to_vec(),
+            false => request.data.to_vec(),
+        };
+        let state = self.state.read();
+        match state.entries.get(&key) {
+            Some(value) => ResponseQuery {
+                code: CODE_OK,
+                log: "exists".to_string(),
+                key: key.into(),
+                value: value.clone().into(),
+                height: state.height,
+                ..Default::default()
+            },
+            None => ResponseQuery {
+                code: CODE_UNKNOWN_KEY,
+                log: "does not exist".to_string(),
+                key: key.into(),
+                height: state.height,
+                ..Default::default()
+            },
         }
     }
+}
+
+#[cfg(test)]
+mod test {
+    use tendermint_abci::Application;
+    use tendermint_proto::v0_38::abci::{
+        Event, EventAttribute, ExecTxResult, RequestCheckTx, RequestFinalizeBlock, RequestInfo,
+        RequestQuery,
+    };
+    use tokio::sync::mpsc;
+
+    use super::{
+        CODE_EMPTY_TRANSACTION, CODE_OK, CODE_REJECTED, CODE_TRANSACTION_TOO_LARGE,
+        CODE_UNAVAILABLE, CODE_UNKNOWN_KEY, MysticetiAbciApp, Rejection,
+    };
+
+    /// Only accepts transactions starting with a version byte of 1.
+    fn versioned(tx: &[u8]) -> Result<(), Rejection> {
+        match tx.first() {
+            Some(1) => Ok(()),
+            _ => Err(Rejection::new("Unsupported transaction version")),
+        }
+    }
+
+    fn check_tx(app: &MysticetiAbciApp, tx: Vec<u8>) -> u32 {
+        app.check_tx(RequestCheckTx {
+            tx: tx.into(),
+            ..Default::default()
+        })
+        .code
+    }
+
+    #[test]
+    fn check_tx_rejects_oversized_transactions() {
+        let (sender, _receiver) = mpsc::channel(10);
+        let app = MysticetiAbciApp::new(sender).with_max_transaction_size(16);
 
-    fn query(&self, _request: RequestQuery) -> ResponseQuery {
-        ResponseQuery {
-            code: 0,
-            value: b"Mysticeti query stub".to_vec().into(),
+        let response = app.check_tx(RequestCheckTx {
+            tx: vec![0u8; 17].into(),
             ..Default::default()
+        });
+        assert_eq!(response.code, CODE_TRANSACTION_TOO_LARGE);
+        assert!(!response.log.is_empty());
+    }
+
+    #[test]
+    fn check_tx_rejects_empty_transactions() {
+        let (sender, _receiver) = mpsc::channel(10);
+        let app = MysticetiAbciApp::new(sender);
+        assert_eq!(check_tx(&app, vec![]), CODE_EMPTY_TRANSACTION);
+    }
+
+    #[tokio::test]
+    async fn check_tx_accepts_transactions_within_limit() {
+        let (sender, mut receiver) = mpsc::channel(10);
+        let app = MysticetiAbciApp::new(sender).with_max_transaction_size(16);
+
+        assert_eq!(check_tx(&app, vec![1u8; 16]), CODE_OK);
+        assert_eq!(receiver.recv().await, Some(vec![1u8; 16]));
+    }
+
+    #[test]
+    fn check_tx_rejects_transactions_when_unavailable() {
+        let (sender, receiver) = mpsc::channel(1);
+        let app = MysticetiAbciApp::new(sender);
+
+        assert_eq!(check_tx(&app, vec![1]), CODE_OK);
+        // The queue is full.
+        assert_eq!(check_tx(&app, vec![2]), CODE_UNAVAILABLE);
+        // Consensus is gone.
+        drop(receiver);
+        assert_eq!(check_tx(&app, vec![3]), CODE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn check_tx_applies_validator() {
+        let (sender, mut receiver) = mpsc::channel(10);
+        let app = MysticetiAbciApp::new(sender).with_validator(versioned);
+
+        assert_eq!(check_tx(&app, vec![1, 2, 3]), CODE_OK);
+        assert_eq!(receiver.recv().await, Some(vec![1, 2, 3]));
+
+        let response = app.check_tx(RequestCheckTx {
+            tx: vec![2, 2, 3].into(),
+            ..Default::default()
+        });
+        assert_eq!(response.code, CODE_REJECTED);
+        assert_eq!(response.log, "Unsupported transaction version");
+
+        // Custom codes are reported as is.
+        let app = app.with_validator(|_: &[u8]| Err(Rejection::new("Banned").with_code(42)));
+        assert_eq!(check_tx(&app, vec![1]), 42);
+    }
+
+    #[test]
+    fn finalize_block_skips_rejected_transactions() {
+        let (sender, mut receiver) = mpsc::channel(10);
+        let app = MysticetiAbciApp::new(sender).with_validator(versioned);
+
+        let response = app.finalize_block(RequestFinalizeBlock {
+            txs: vec![vec![2].into(), vec![1, 7].into()],
+            ..Default::default()
+        });
+        let codes: Vec<_> = response.tx_results.iter().map(|r| r.code).collect();
+        assert_eq!(codes, vec![CODE_REJECTED, CODE_OK]);
+        // Finalized transactions are not submitted to consensus again.
+        assert!(receiver.try_recv().is_err());
+    }
+
+    /// Charges one unit of gas per byte, fails transactions ending with a zero byte, and emits
+    /// a transfer event for the others.
+    fn execute(tx: &[u8]) -> ExecTxResult {
+        if tx.last() == Some(&0) {
+            return ExecTxResult {
+                code: 7,
+                log: "Insufficient balance".to_string(),
+                gas_wanted: tx.len() as i64,
+                gas_used: 1,
+                ..Default::default()
+            };
+        }
+        ExecTxResult {
+            code: CODE_OK,
+            data: vec![tx.len() as u8].into(),
+            gas_wanted: tx.len() as i64,
+            gas_used: tx.len() as i64,
+            events: vec![Event {
+                r#type: "transfer".to_string(),
+                attributes: vec![EventAttribute {
+                    key: "amount".to_string(),
+                    value: tx[1].to_string(),
+                    index: true,
+                }],
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finalize_block_executes_transactions() {
+        let (sender, mut receiver) = mpsc::channel(10);
+        let app = MysticetiAbciApp::new(sender)
+            .with_validator(versioned)
+            .with_executor(execute);
+
+        let response = app.finalize_block(RequestFinalizeBlock {
+            txs: vec![vec![1, 5].into(), vec![1, 5, 0].into(), vec![2, 5].into()],
+            ..Default::default()
+        });
+        let results = &response.tx_results;
+        assert_eq!(results.len(), 3);
+
+        assert_eq!(results[0].code, CODE_OK);
+        assert_eq!(results[0].gas_used, 2);
+        assert_eq!(results[0].data.to_vec(), vec![2]);
+        assert_eq!(results[0].events[0].r#type, "transfer");
+        assert_eq!(results[0].events[0].attributes[0].value, "5");
+
+        assert_eq!(results[1].code, 7);
+        assert_eq!(results[1].log, "Insufficient balance");
+        assert!(results[1].events.is_empty());
+
+        // Invalid transactions are not executed.
+        assert_eq!(results[2].code, CODE_REJECTED);
+        assert_eq!(results[2].gas_used, 0);
+
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn finalize_block_accepts_all_without_executor() {
+        let (sender, _receiver) = mpsc::channel(10);
+        let app = MysticetiAbciApp::new(sender);
+
+        let response = app.finalize_block(RequestFinalizeBlock {
+            txs: vec![vec![1, 5, 0].into()],
+            ..Default::default()
+        });
+        assert_eq!(response.tx_results[0].code, CODE_OK);
+        assert!(response.tx_results[0].events.is_empty());
+    }
+
+    fn finalize(app: &MysticetiAbciApp, height: i64, txs: &[&[u8]]) -> Vec<u8> {
+        app.finalize_block(RequestFinalizeBlock {
+            txs: txs.iter().map(|tx| tx.to_vec().into()).collect(),
+            height,
+            ..Default::default()
+        })
+        .app_hash
+        .to_vec()
+    }
+
+    /// Rejects the writes of the key `c`.
+    fn read_only_c(tx: &[u8]) -> Result<(), Rejection> {
+        match tx.starts_with(b"kv:c=") {
+            true => Err(Rejection::new("Read-only key")),
+            false => Ok(()),
+        }
+    }
+
+    #[test]
+    fn finalize_block_writes_the_state() {
+        let (sender, _receiver) = mpsc::channel(10);
+        let app = MysticetiAbciApp::new(sender).with_validator(read_only_c);
+        finalize(&app, 1, &[b"kv:a=1", b"kv:b=2", b"kv:c=3"]);
+        let app_hash = finalize(&app, 2, &[b"kv:a=3", b"kv:no value"]);
+
+        let response = app.query(RequestQuery {
+            data: b"a".to_vec().into(),
+            ..Default::default()
+        });
+        assert_eq!(response.code, CODE_OK);
+        assert_eq!(response.value.to_vec(), b"3");
+        assert_eq!(response.height, 2);
+        let response = app.query(RequestQuery {
+            path: "/b".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(response.value.to_vec(), b"2");
+        // Rejected transactions do not write.
+        let response = app.query(RequestQuery {
+            data: b"c".to_vec().into(),
+            ..Default::default()
+        });
+        assert_eq!(response.code, CODE_UNKNOWN_KEY);
+
+        let info = app.info(RequestInfo::default());
+        assert_eq!(info.last_block_height, 2);
+        assert_eq!(info.last_block_app_hash.to_vec(), app_hash);
+
+        // Another node applying the same transactions, in different blocks, agrees on the hash.
+        let (sender, _receiver) = mpsc::channel(10);
+        let other = MysticetiAbciApp::new(sender).with_validator(read_only_c);
+        finalize(&other, 1, &[b"kv:b=2", b"kv:a=1"]);
+        assert_ne!(finalize(&other, 2, &[]), app_hash);
+        assert_eq!(finalize(&other, 3, &[b"kv:a=3"]), app_hash);
+    }
+
+    #[test]
+    fn only_kv_transactions_write_the_state() {
+        let (sender, _receiver) = mpsc::channel(10);
+        let app = MysticetiAbciApp::new(sender);
+        let empty_hash = finalize(&app, 1, &[]);
+
+        // Payloads happening to contain a `=` are not writes.
+        let app_hash = finalize(&app, 2, &[b"a=1", b"MYST\x01\x00x=y", b"kv=a=1"]);
+        assert_eq!(app_hash, empty_hash);
+        for key in [&b"a"[..], b"MYST\x01\x00x", b"kv"] {
+            let response = app.query(RequestQuery {
+                data: key.to_vec().into(),
+                ..Default::default()
+            });
+            assert_eq!(response.code, CODE_UNKNOWN_KEY);
         }
     }
 }
@@ -0,0 +1,70 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use consensus_core::CommittedSubDag;
+use tendermint_abci::Application;
+use tracing::debug;
+
+use crate::abci::replay::finalize_request;
+use crate::validator::handler::CommitHandler;
+use crate::validator::journal::JournalEntry;
+
+/// Delivers every commit to an ABCI application as a block of its accepted transactions (at
+/// the height of the commit index), then commits the block.
+pub struct AbciCommitHandler<A> {
+    app: A,
+}
+
+impl<A: Application + Sync> AbciCommitHandler<A> {
+    pub fn new(app: A) -> Self {
+        Self { app }
+    }
+}
+
+impl<A: Application + Sync> CommitHandler for AbciCommitHandler<A> {
+    fn on_commit(&self, subdag: &CommittedSubDag) {
+        let entry = JournalEntry::from_subdag(subdag);
+        let response = self.app.finalize_block(finalize_request(&entry));
+        self.app.commit();
+        debug!(
+            "Delivered commit {} to the ABCI application ({} transactions)",
+            entry.commit_index,
+            response.tx_results.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+    use tendermint_abci::Application;
+    use tendermint_proto::v0_38::abci::{RequestFinalizeBlock, ResponseFinalizeBlock};
+
+    use super::AbciCommitHandler;
+    use crate::validator::handler::{CommitHandler, test::subdag};
+
+    /// Records the height and number of transactions of every finalized block.
+    #[derive(Clone, Default)]
+    struct RecordingApp {
+        blocks: Arc<Mutex<Vec<(i64, usize)>>>,
+    }
+
+    impl Application for RecordingApp {
+        fn finalize_block(&self, request: RequestFinalizeBlock) -> ResponseFinalizeBlock {
+            self.blocks.lock().push((request.height, request.txs.len()));
+            ResponseFinalizeBlock::default()
+        }
+    }
+
+    #[test]
+    fn deliver_commits_as_blocks() {
+        let app = RecordingApp::default();
+        let handler = AbciCommitHandler::new(app.clone());
+        handler.on_commit(&subdag(3, &[b"a", b"b"]));
+        handler.on_commit(&subdag(4, &[]));
+
+        assert_eq!(*app.blocks.lock(), [(3, 2), (4, 0)]);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod app;
-//pub mod enhanced_app;
+pub mod handler;
+pub mod replay;
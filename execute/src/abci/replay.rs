@@ -0,0 +1,48 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use tendermint_abci::Application;
+use tendermint_proto::google::protobuf::Timestamp;
+use tendermint_proto::v0_38::abci::{RequestFinalizeBlock, ResponseFinalizeBlock};
+use tracing::info;
+
+use crate::validator::journal::JournalEntry;
+
+/// Replay a recorded commit sequence into an ABCI application, one block per commit, without
+/// running consensus. Returns the responses of the application in commit order.
+pub fn replay_journal<A: Application>(
+    app: &A,
+    entries: &[JournalEntry],
+) -> Vec<ResponseFinalizeBlock> {
+    entries
+        .iter()
+        .map(|entry| {
+            info!(
+                "Replaying commit {} with {} transactions",
+                entry.commit_index,
+                entry.transactions.len()
+            );
+            let request = finalize_request(entry);
+            let response = app.finalize_block(request);
+            app.commit();
+            response
+        })
+        .collect()
+}
+
+/// The ABCI block of a commit: its accepted transactions, at the height of the commit index.
+pub fn finalize_request(entry: &JournalEntry) -> RequestFinalizeBlock {
+    RequestFinalizeBlock {
+        txs: entry
+            .transactions
+            .iter()
+            .map(|tx| tx.clone().into())
+            .collect(),
+        height: entry.commit_index as i64,
+        time: Some(Timestamp {
+            seconds: (entry.timestamp_ms / 1000) as i64,
+            nanos: ((entry.timestamp_ms % 1000) * 1_000_000) as i32,
+        }),
+        ..Default::default()
+    }
+}
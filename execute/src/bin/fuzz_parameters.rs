@@ -0,0 +1,97 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::{Parser, command};
+use eyre::Result;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::{EnvFilter, fmt};
+
+use execute::fuzz::{FuzzSettings, ParameterFuzzer, fuzz_parameters};
+
+/// Run the in-process network with randomized consensus parameters and a smoke load, to find
+/// the parameter combinations that break liveness.
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The number of parameter sets to try.
+    #[clap(long, value_name = "N", default_value = "10")]
+    iterations: usize,
+
+    /// The seed of the first parameter set; the following sets use the next seeds. Pass the
+    /// seed of a failing set with `--iterations 1` to reproduce it. Random if unset.
+    #[clap(long, value_name = "SEED")]
+    seed: Option<u64>,
+
+    /// The number of validators of the network.
+    #[clap(long, value_name = "N", default_value = "4")]
+    committee_size: usize,
+
+    /// The number of transactions of the smoke load.
+    #[clap(long, value_name = "N", default_value = "10")]
+    transactions: usize,
+
+    /// How long every transaction has to commit on every node, in seconds.
+    #[clap(long, value_name = "SECS", default_value = "30")]
+    commit_timeout_secs: u64,
+
+    /// Write the outcome of every parameter set to this json file.
+    #[clap(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Nice colored error messages.
+    color_eyre::install()?;
+
+    // Setup logging
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+    fmt().with_env_filter(filter).init();
+
+    // Parse command line arguments
+    let args = Args::parse();
+    let seed = args.seed.unwrap_or_else(rand::random);
+    println!("Fuzzing the consensus parameters from seed {seed}");
+
+    let settings = FuzzSettings {
+        iterations: args.iterations,
+        committee_size: args.committee_size,
+        transactions: args.transactions,
+        commit_timeout: Duration::from_secs(args.commit_timeout_secs),
+    };
+    let outcomes = fuzz_parameters(&ParameterFuzzer::new(seed), &settings).await;
+
+    if let Some(path) = &args.output {
+        std::fs::write(path, serde_json::to_string_pretty(&outcomes)?)?;
+        println!("Outcomes written to {}", path.display());
+    }
+
+    let failed: Vec<_> = outcomes
+        .iter()
+        .filter(|outcome| !outcome.is_success())
+        .collect();
+    println!(
+        "{}/{} parameter sets committed the smoke load",
+        outcomes.len() - failed.len(),
+        outcomes.len()
+    );
+    for outcome in &failed {
+        println!(
+            "  seed {}: {} ({:?})",
+            outcome.seed,
+            outcome.failure.as_deref().unwrap_or_default(),
+            outcome.parameters
+        );
+    }
+    if !failed.is_empty() {
+        return Err(eyre::eyre!(
+            "{} parameter sets failed to commit",
+            failed.len()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use consensus_config::Committee;
+use execute::genesis::CometBftGenesis;
+use eyre::{Context, Result};
+
+#[derive(Parser)]
+#[command(author, version, about = "Genesis and committee tooling", long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert a genesis between the CometBFT and the Mysticeti formats. The committees
+    /// converted from a CometBFT genesis are for tests only: their authority keys are derived
+    /// from the public validator keys, so the conversion requires `--insecure-test-keys`.
+    Convert {
+        /// The format of the input file.
+        #[clap(long, value_enum)]
+        from: GenesisFormat,
+
+        /// The input file: a CometBFT genesis.json or a Mysticeti committee.yaml.
+        #[clap(long = "in", value_name = "FILE")]
+        input: PathBuf,
+
+        /// Where to write the converted file (defaults to stdout).
+        #[clap(long = "out", value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Template of the consensus addresses of the authorities (CometBFT genesis files
+        /// hold no addresses), where `{i}` is replaced by the authority index and `{port}`
+        /// by the base port plus the index.
+        #[clap(
+            long,
+            value_name = "TEMPLATE",
+            default_value = "/dns/mysticeti-node{i}/udp/{port}"
+        )]
+        address_template: String,
+
+        /// The base port substituted for `{port}` in the address template.
+        #[clap(long, value_name = "PORT", default_value_t = 26657)]
+        address_base_port: u16,
+
+        /// The chain id of the produced CometBFT genesis.
+        #[clap(long, value_name = "ID", default_value = "mysticeti")]
+        chain_id: String,
+
+        /// Acknowledge that the authority keys of a committee converted from a CometBFT
+        /// genesis can be derived by anyone, and are only fit for tests. Required to convert
+        /// from CometBFT.
+        #[clap(long, required_if_eq("from", "cometbft"))]
+        insecure_test_keys: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GenesisFormat {
+    /// A CometBFT genesis.json.
+    Cometbft,
+    /// A Mysticeti committee.yaml.
+    Mysticeti,
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+
+    let Command::Convert {
+        from,
+        input,
+        output,
+        address_template,
+        address_base_port,
+        chain_id,
+        ..
+    } = Args::parse().command;
+
+    let converted = match from {
+        GenesisFormat::Cometbft => {
+            let genesis = CometBftGenesis::load(&input)
+                .map_err(|e| eyre::eyre!("Failed to load genesis: {}", e))?;
+            let committee = genesis
+                .to_committee(&address_template, address_base_port)
+                .map_err(|e| eyre::eyre!("Failed to convert genesis: {}", e))?;
+            eprintln!(
+                "WARNING: the authority keys of this committee are derived from the public \
+                validator keys, only use it for tests"
+            );
+            serde_yaml::to_string(&committee)?
+        }
+        GenesisFormat::Mysticeti => {
+            let content = std::fs::read_to_string(&input)
+                .wrap_err(format!("Failed to read committee {}", input.display()))?;
+            let committee: Committee = serde_yaml::from_str(&content)
+                .wrap_err(format!("Failed to parse committee {}", input.display()))?;
+            let genesis = CometBftGenesis::from_committee(&committee, &chain_id);
+            serde_json::to_string_pretty(&genesis)?
+        }
+    };
+
+    match output {
+        Some(path) => std::fs::write(&path, converted)
+            .wrap_err(format!("Failed to write {}", path.display()))?,
+        None => println!("{converted}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::Args;
+
+    #[test]
+    fn converting_from_cometbft_requires_insecure_test_keys() {
+        let parse = |args: &[&str]| Args::try_parse_from([&["genesis", "convert"], args].concat());
+
+        let cometbft = ["--from", "cometbft", "--in", "genesis.json"];
+        assert!(parse(&cometbft).is_err());
+        assert!(parse(&[&cometbft[..], &["--insecure-test-keys"]].concat()).is_ok());
+        assert!(parse(&["--from", "mysticeti", "--in", "committee.yaml"]).is_ok());
+    }
+}
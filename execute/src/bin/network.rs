@@ -4,10 +4,12 @@
 use clap::{Parser, command};
 use eyre::Result;
 use std::path::PathBuf;
+use std::time::Duration;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::{EnvFilter, fmt};
 
-use execute::validator::ValidatorNetwork;
+use execute::shutdown::shutdown_signal;
+use execute::validator::{ShutdownOrder, ValidatorNetwork};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -15,6 +17,27 @@ struct Args {
     /// The working directory where the validator nodes will store their data.
     #[clap(long, value_name = "DIR", default_value = ".data")]
     working_directory: PathBuf,
+
+    /// The number of validator nodes (at most 13 with --abci), serving RPC on consecutive ports
+    /// from 26657.
+    #[clap(long, value_name = "N", default_value = "4")]
+    committee_size: usize,
+
+    /// Keep the consensus DB of every node in memory instead of on disk.
+    #[clap(long)]
+    in_memory: bool,
+
+    /// Run the ABCI server of every node, on consecutive ports from 26670.
+    #[clap(long)]
+    abci: bool,
+
+    /// The order in which the nodes are stopped on shutdown (index or leader-last).
+    #[clap(long, value_name = "ORDER", default_value = "index")]
+    shutdown_order: ShutdownOrder,
+
+    /// The delay between stopping two nodes, in milliseconds.
+    #[clap(long, value_name = "MS", default_value = "200")]
+    shutdown_grace_ms: u64,
 }
 
 #[tokio::main]
@@ -32,7 +55,13 @@ async fn main() -> Result<()> {
     let args = Args::parse();
 
     // Create and start the validator network
-    let mut network = ValidatorNetwork::new(args.working_directory);
+    let mut network = ValidatorNetwork::new(args.working_directory, args.committee_size)
+        .map_err(|e| eyre::eyre!("Invalid validator network: {}", e))?
+        .with_abci(args.abci)
+        .map_err(|e| eyre::eyre!("Invalid validator network: {}", e))?
+        .with_in_memory(args.in_memory)
+        .with_shutdown_order(args.shutdown_order)
+        .with_shutdown_grace(Duration::from_millis(args.shutdown_grace_ms));
 
     // Start the network
     network
@@ -53,7 +82,7 @@ async fn main() -> Result<()> {
     println!("\nPress Ctrl+C to stop the network");
 
     // Wait for shutdown signal
-    tokio::signal::ctrl_c().await.unwrap();
+    shutdown_signal().await;
 
     // Stop the network
     network.stop().await;
@@ -0,0 +1,55 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::{Parser, command};
+use eyre::Result;
+use std::time::Duration;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::{EnvFilter, fmt};
+
+use execute::selftest::{SelfTestSettings, run_selftest};
+
+/// Start an in-process network, submit a few transactions, and check that they commit in the
+/// same order on every node, printing the outcome of every stage. Fails if any stage fails.
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The number of validators of the network.
+    #[clap(long, value_name = "N", default_value = "4")]
+    committee_size: usize,
+
+    /// The number of transactions to submit.
+    #[clap(long, value_name = "N", default_value = "8")]
+    transactions: usize,
+
+    /// How long the transactions have to commit on every node, in seconds.
+    #[clap(long, value_name = "SECS", default_value = "30")]
+    commit_timeout_secs: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Nice colored error messages.
+    color_eyre::install()?;
+
+    // Setup logging
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::WARN.into())
+        .from_env_lossy();
+    fmt().with_env_filter(filter).init();
+
+    // Parse command line arguments
+    let args = Args::parse();
+    let settings = SelfTestSettings {
+        committee_size: args.committee_size,
+        transactions: args.transactions,
+        commit_timeout: Duration::from_secs(args.commit_timeout_secs),
+    };
+    let report = run_selftest(&settings).await;
+    println!("{report}");
+
+    if !report.passed() {
+        return Err(eyre::eyre!("The self-test failed"));
+    }
+    Ok(())
+}
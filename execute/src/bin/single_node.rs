@@ -14,6 +14,8 @@ use consensus_core::{
     Clock, CommitConsumer, ConsensusAuthority, TransactionIndex, TransactionVerifier,
     ValidationError,
 };
+use execute::shutdown::shutdown_signal;
+use execute::validator::committee::log_committee;
 use mysten_metrics::RegistryService;
 use prometheus::Registry;
 use sui_protocol_config::{ConsensusNetwork, ProtocolConfig};
@@ -99,15 +101,18 @@ async fn start_four_nodes(working_directory: PathBuf) -> Result<()> {
     // Generate committee and keypairs for 4 nodes
     let committee_size = 4;
     let (committee, keypairs) = local_committee_and_keys(0, vec![1; committee_size]);
+    log_committee(&committee);
 
     // Create parameters with default values
     let parameters = Parameters::default();
 
     // Create registry service for metrics
     let registry_service = RegistryService::new(Registry::new());
-
-    // Start all 4 nodes
-    let mut handles = Vec::new();
+    // Start all 4 nodes. A node that fails to start (e.g. its port is taken) panics inside
+    // `ConsensusAuthority::start`; run each start in its own task so that the failure is
+    // reported instead of leaving the others running a network that cannot commit.
+    let mut nodes = Vec::new();
+    let mut failure = None;
     for (i, (network_keypair, protocol_keypair)) in keypairs.iter().enumerate().take(committee_size)
     {
         let authority = AuthorityIndex::new_for_test(i as u32);
@@ -117,9 +122,6 @@ async fn start_four_nodes(working_directory: PathBuf) -> Result<()> {
         // Create directory for this node
         fs::create_dir_all(&node_dir)?;
 
-        // Get keypairs for this node
-        // let (network_keypair, protocol_keypair) = &keypairs[i];
-
         // Create parameters with correct db path
         let mut node_parameters = parameters.clone();
         node_parameters.db_path = db_path;
@@ -128,36 +130,60 @@ async fn start_four_nodes(working_directory: PathBuf) -> Result<()> {
         let (commit_consumer, _commit_receiver, _block_receiver) = CommitConsumer::new(0);
 
         // Start the authority node
-        let authority_node = ConsensusAuthority::start(
-            ConsensusNetwork::Anemo,
-            authority,
-            committee.clone(),
-            node_parameters,
-            ProtocolConfig::get_for_max_version_UNSAFE(),
-            protocol_keypair.clone(),
-            network_keypair.clone(),
-            Arc::new(Clock::new_for_test(0)),
-            Arc::new(SimpleTransactionVerifier),
-            commit_consumer,
-            registry_service.default_registry().clone(),
-            0, // boot_counter
-        )
+        let committee = committee.clone();
+        let protocol_keypair = protocol_keypair.clone();
+        let network_keypair = network_keypair.clone();
+        let registry = registry_service.default_registry().clone();
+        let started = tokio::spawn(async move {
+            ConsensusAuthority::start(
+                ConsensusNetwork::Anemo,
+                authority,
+                committee,
+                node_parameters,
+                ProtocolConfig::get_for_max_version_UNSAFE(),
+                protocol_keypair,
+                network_keypair,
+                Arc::new(Clock::new_for_test(0)),
+                Arc::new(SimpleTransactionVerifier),
+                commit_consumer,
+                registry,
+                0, // boot_counter
+            )
+            .await
+        })
         .await;
 
-        handles.push(tokio::spawn(async move {
-            tracing::info!("Node {} started successfully", authority);
-            // Keep the node running
-            tokio::signal::ctrl_c().await.unwrap();
-            tracing::info!("Shutting down node {}", authority);
-            authority_node.stop().await;
-        }));
+        match started {
+            Ok(authority_node) => {
+                tracing::info!("Node {} started successfully", authority);
+                nodes.push(authority_node);
+            }
+            Err(e) => {
+                tracing::error!("Node {} failed to start: {}", authority, e);
+                failure = Some((authority, e));
+                break;
+            }
+        }
+    }
+
+    // Do not run a partial committee: stop the nodes that did start and report the failure.
+    if let Some((authority, e)) = failure {
+        tracing::error!(
+            "Only {}/{} nodes started, stopping them",
+            nodes.len(),
+            committee_size
+        );
+        future::join_all(nodes.into_iter().map(|node| node.stop())).await;
+        return Err(eyre::eyre!("Node {} failed to start: {}", authority, e));
     }
 
     tracing::info!("All 4 consensus authority nodes started successfully!");
     tracing::info!("Press Ctrl+C to stop all nodes");
 
-    // Wait for all nodes to complete
-    future::join_all(handles).await;
+    // Keep the nodes running
+    shutdown_signal().await;
+    tracing::info!("Shutting down all nodes");
+    future::join_all(nodes.into_iter().map(|node| node.stop())).await;
 
     Ok(())
 }
@@ -179,6 +205,7 @@ async fn start_single_node(authority_index: u32, working_directory: PathBuf) ->
     // Generate committee and keypairs for 4 nodes (we need a full committee)
     let committee_size = 4;
     let (committee, keypairs) = local_committee_and_keys(0, vec![1; committee_size]);
+    log_committee(&committee);
 
     // Create parameters with default values
     let parameters = Parameters::default();
@@ -222,7 +249,7 @@ async fn start_single_node(authority_index: u32, working_directory: PathBuf) ->
     tracing::info!("Press Ctrl+C to stop the node");
 
     // Keep the node running
-    tokio::signal::ctrl_c().await.unwrap();
+    shutdown_signal().await;
     tracing::info!("Shutting down node {}", authority_index);
     authority_node.stop().await;
 
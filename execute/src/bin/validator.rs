@@ -1,30 +1,61 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use clap::{Parser, command};
-use execute::validator::ValidatorNode;
-use eyre::Result;
+use clap::{ArgGroup, Parser, Subcommand, command};
+use execute::abci::app::{CODE_OK, MysticetiAbciApp};
+use execute::abci::replay::replay_journal as replay_journal_into;
+use execute::reload::{ConfigReload, on_hangup};
+use execute::shutdown::shutdown_signal;
+use execute::validator::bench::{
+    BenchVerifier, SWEEP_BATCH_SIZES, SWEEP_THREADS, VerifierBenchConfig, run_verifier_bench,
+    run_verifier_sweep,
+};
+use execute::validator::committee;
+use execute::validator::journal::read_journal;
+use execute::validator::verifier::DEFAULT_MIN_PARALLEL_BATCH;
+use execute::validator::{
+    AckMode, EffectiveConfig, MemoryGuardConfig, NodeConfig, TransactionFault, ValidatorNode,
+    WebhookConfig,
+};
+use eyre::{Context, Result};
 use mysten_metrics::RegistryService;
 use prometheus::Registry;
-use std::path::PathBuf;
-use tracing::info;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
 use tracing_subscriber::filter::LevelFilter;
-use tracing_subscriber::{EnvFilter, fmt};
+use tracing_subscriber::{EnvFilter, Registry as Subscriber, fmt, prelude::*, reload};
 
-#[derive(Parser)]
+/// Replaces the log filter of the running node.
+type FilterHandle = reload::Handle<EnvFilter, Subscriber>;
+
+/// The environment variable holding the bearer token of the admin endpoints. It is not a flag
+/// so that the token does not show in the process list.
+const ADMIN_TOKEN_VARIABLE: &str = "MYSTICETI_ADMIN_TOKEN";
+
+#[derive(Parser, Clone)]
 #[command(author, version, about, long_about = None)]
+#[command(group(ArgGroup::new("committee_source").args(["committee_url", "committee_path"])))]
 struct Args {
-    /// The working directory where the validator node will store its data.
-    #[clap(long, value_name = "DIR", default_value = "validator-node")]
-    working_directory: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// The authority index for this validator node (0-3 for 4-node network).
-    #[clap(long, value_name = "INDEX", default_value = "0")]
-    authority_index: u32,
+    /// Path to a yaml node configuration file. The other flags override its values.
+    #[clap(long, value_name = "FILE")]
+    config: Option<PathBuf>,
 
-    /// The RPC port for this validator node.
-    #[clap(long, value_name = "PORT", default_value = "26657")]
-    rpc_port: u16,
+    /// The working directory where the validator node will store its data
+    /// [default: validator-node].
+    #[clap(long, value_name = "DIR")]
+    working_directory: Option<PathBuf>,
+
+    /// The authority index for this validator node (0-3 for 4-node network) [default: 0].
+    #[clap(long, value_name = "INDEX")]
+    authority_index: Option<u32>,
+
+    /// The RPC port for this validator node [default: 26657].
+    #[clap(long, value_name = "PORT")]
+    rpc_port: Option<u16>,
 
     /// The ABCI port for this validator node.
     #[clap(long, value_name = "PORT")]
@@ -34,11 +65,280 @@ struct Args {
     #[clap(long, value_name = "ADDRESSES")]
     peer_addresses: Option<String>,
 
+    /// Comma-separated list of consensus addresses of the committee members, one per
+    /// authority (e.g., "/ip4/10.0.0.1/udp/26657,/dns/validator-1.example.com/udp/26657").
+    /// Hostnames are resolved whenever the node dials the peer.
+    #[clap(long, value_name = "MULTIADDRS", conflicts_with = "address_template")]
+    committee_addresses: Option<String>,
+
+    /// Template of the consensus addresses of the committee members, where `{i}` is replaced
+    /// by the authority index and `{port}` by the base port plus the index
+    /// (e.g., "/dns/mysticeti-node{i}/udp/{port}").
+    #[clap(long, value_name = "TEMPLATE")]
+    address_template: Option<String>,
+
+    /// The base port substituted for `{port}` in the address template [default: 26657].
+    #[clap(long, value_name = "PORT")]
+    address_base_port: Option<u16>,
+
+    /// Load the committee file (yaml, as written by the genesis tool) from this HTTP(S) URL at
+    /// startup, or from this local path if it is not a URL. Overrides the other committee
+    /// flags.
+    #[clap(long, value_name = "URL")]
+    committee_url: Option<String>,
+
+    /// Load the committee file from this local path. Same as `--committee-url` with a path.
+    #[clap(long, value_name = "PATH", conflicts_with = "committee_url")]
+    committee_path: Option<PathBuf>,
+
+    /// Load the private keys of the node from this yaml file (`network_private_key` and
+    /// `protocol_private_key`, in base64) instead of deriving them. They must match the entry
+    /// of the node in the committee file.
+    #[clap(long, value_name = "PATH")]
+    keys_path: Option<PathBuf>,
+
+    /// The expected SHA-256 checksum (in hex) of the committee file (from `--committee-url` or
+    /// `--committee-path`), to make sure every node runs the committee the coordinator
+    /// published.
+    #[clap(long, value_name = "SHA256", requires = "committee_source")]
+    committee_checksum: Option<String>,
+
+    /// The number of threads used to verify transaction batches [default: 1].
+    #[clap(long, value_name = "THREADS")]
+    verification_threads: Option<usize>,
+
+    /// Verify the Ed25519 signature of every transaction (as sent by benchmark clients
+    /// with `--sign`), rejecting the others [default: false].
+    #[clap(long, value_name = "BOOL")]
+    verify_signatures: Option<bool>,
+
+    /// Reject the empty transactions and those larger than this many bytes in consensus (0
+    /// accepts any size) [default: 0].
+    #[clap(long, value_name = "BYTES")]
+    max_transaction_size: Option<usize>,
+
+    /// Journal the committed sub-dags of the node to this file.
+    #[clap(long, value_name = "FILE")]
+    commit_journal: Option<PathBuf>,
+
+    /// Append the ids of the committed transactions to this file, in commit order, to
+    /// compare the commit sequences of the nodes.
+    #[clap(long, value_name = "FILE")]
+    commit_order: Option<PathBuf>,
+
+    /// The maximum size (in bytes) of the body of an RPC request [default: 10485760].
+    #[clap(long, value_name = "BYTES")]
+    max_request_bytes: Option<usize>,
+
+    /// How submitted transactions are tracked: none, submitted, or committed
+    /// [default: submitted].
+    #[clap(long, value_name = "MODE")]
+    ack_mode: Option<AckMode>,
+
+    /// The number of tasks concurrently submitting RPC transactions to consensus [default: 1].
+    #[clap(long, value_name = "WORKERS")]
+    forward_workers: Option<usize>,
+
+    /// Forward the queued transactions of highest priority (the class byte of structured
+    /// transactions) to consensus first, rather than in arrival order [default: false].
+    #[clap(long, value_name = "BOOL")]
+    prioritize_submissions: Option<bool>,
+
+    /// The maximum number of transactions in a proposed block (0 keeps the protocol default).
+    #[clap(long, value_name = "COUNT")]
+    max_transactions_per_block: Option<u64>,
+
+    /// Keep the consensus DB in memory instead of on disk (for tests and ephemeral runs).
+    #[clap(long)]
+    in_memory: bool,
+
+    /// UNSAFE, for fault-tolerance tests only: silently drop or corrupt a fraction of the
+    /// transactions received from clients, e.g. "drop=0.1,corrupt=0.05" ("none" disables it).
+    #[clap(long, value_name = "FAULT")]
+    unsafe_transaction_fault: Option<TransactionFault>,
+
+    /// POST a JSON event to this URL whenever a transaction submitted through the node
+    /// commits (batched and rate-limited).
+    #[clap(long, value_name = "URL")]
+    commit_webhook: Option<String>,
+
+    /// Reject new submissions (with 503) while less than this many MiB of memory are
+    /// available, until memory frees up.
+    #[clap(long, value_name = "MB")]
+    min_free_memory_mb: Option<u64>,
+
+    /// How often to check the available memory, if the memory guard is enabled.
+    #[clap(long, value_name = "MS")]
+    memory_check_interval_ms: Option<u64>,
+
+    /// Write the effective configuration (after applying defaults, the config file, and the
+    /// flags) to this yaml file.
+    #[clap(long, value_name = "FILE")]
+    dump_config: Option<PathBuf>,
+
+    /// Replay a commit journal into the ABCI application (without running consensus) and exit.
+    #[clap(long, value_name = "FILE", conflicts_with = "config")]
+    replay_journal: Option<PathBuf>,
+
     /// Enable debug logging.
     #[clap(long)]
     debug: bool,
 }
 
+/// Tools run instead of the node.
+#[derive(Subcommand, Clone)]
+enum Command {
+    /// Benchmark a transaction verifier on synthetic batches, without consensus or
+    /// networking, and report its verifications per second and latency.
+    BenchVerifier {
+        /// The verifier to benchmark: accept-all or signature.
+        #[clap(long, value_name = "VERIFIER", default_value = "signature")]
+        verifier: BenchVerifier,
+
+        /// The number of transactions per batch.
+        #[clap(long, value_name = "COUNT", default_value_t = 512)]
+        batch_size: usize,
+
+        /// The number of batches to verify.
+        #[clap(long, value_name = "COUNT", default_value_t = 100)]
+        batches: usize,
+
+        /// The size of the transactions, in bytes.
+        #[clap(long, value_name = "BYTES", default_value_t = 512)]
+        transaction_size: usize,
+
+        /// The number of verification threads.
+        #[clap(long, value_name = "THREADS", default_value_t = 1)]
+        threads: usize,
+
+        /// The batch size below which verification runs on a single thread.
+        #[clap(long, value_name = "COUNT", default_value_t = DEFAULT_MIN_PARALLEL_BATCH)]
+        min_parallel_batch: usize,
+
+        /// Verify batches of increasing size on an increasing number of threads instead, and
+        /// report the batch size from which splitting the batches pays off.
+        #[clap(long)]
+        sweep: bool,
+    },
+}
+
+impl Args {
+    /// Build the node configuration from the config file (if any), overridden by the flags.
+    fn node_config(&self) -> Result<NodeConfig> {
+        let mut config = match &self.config {
+            Some(path) => {
+                NodeConfig::load(path).map_err(|e| eyre::eyre!("Failed to load config: {}", e))?
+            }
+            None => {
+                let authority_index = self.authority_index.unwrap_or_default();
+                let mut config = NodeConfig::new(authority_index, PathBuf::from("validator-node"))
+                    .map_err(|e| eyre::eyre!("Invalid authority index: {}", e))?;
+                // All nodes default to the same RPC port, as each runs on its own host.
+                config.rpc_port = NodeConfig::DEFAULT_RPC_PORT;
+                config
+            }
+        };
+
+        if let Some(authority_index) = self.authority_index {
+            config.authority_index = authority_index;
+        }
+        if let Some(working_directory) = &self.working_directory {
+            config.working_directory = working_directory.clone();
+        }
+        if let Some(rpc_port) = self.rpc_port {
+            config.rpc_port = rpc_port;
+        }
+        if let Some(abci_port) = self.abci_port {
+            config.abci_port = abci_port;
+        }
+        if let Some(threads) = self.verification_threads {
+            config.verifier.threads = threads;
+        }
+        if let Some(signatures) = self.verify_signatures {
+            config.verifier.signatures = signatures;
+        }
+        if let Some(max_size) = self.max_transaction_size {
+            config.verifier.max_transaction_size = (max_size > 0).then_some(max_size);
+        }
+        if let Some(addresses) = &self.committee_addresses {
+            config.committee.addresses = addresses.split(',').map(|a| a.trim().into()).collect();
+        }
+        if let Some(template) = &self.address_template {
+            config.committee.address_template = Some(template.clone());
+        }
+        if let Some(port) = self.address_base_port {
+            config.committee.address_base_port = port;
+        }
+        if self.peer_addresses.is_some() {
+            config.committee.docker = true;
+        }
+        if let Some(url) = &self.committee_url {
+            config.committee.url = Some(url.clone());
+        }
+        if let Some(path) = &self.committee_path {
+            config.committee.url = Some(path.display().to_string());
+        }
+        if let Some(path) = &self.keys_path {
+            config.committee.keys = Some(path.clone());
+        }
+        if let Some(checksum) = &self.committee_checksum {
+            config.committee.checksum = Some(checksum.clone());
+        }
+        if let Some(path) = &self.commit_journal {
+            config.commit_journal = Some(path.clone());
+        }
+        if let Some(path) = &self.commit_order {
+            config.commit_order = Some(path.clone());
+        }
+        if let Some(max_request_bytes) = self.max_request_bytes {
+            config.max_request_bytes = max_request_bytes;
+        }
+        if let Some(ack_mode) = self.ack_mode {
+            config.ack_mode = ack_mode;
+        }
+        if let Some(workers) = self.forward_workers {
+            config.forward_workers = workers;
+        }
+        if let Some(prioritize) = self.prioritize_submissions {
+            config.prioritize_submissions = prioritize;
+        }
+        if self.in_memory {
+            config.in_memory = true;
+        }
+        if let Some(fault) = self.unsafe_transaction_fault {
+            config.unsafe_transaction_fault = fault.is_active().then_some(fault);
+        }
+        if let Some(url) = &self.commit_webhook {
+            match config.commit_webhook.as_mut() {
+                Some(webhook) => webhook.url = url.clone(),
+                None => config.commit_webhook = Some(WebhookConfig::new(url.clone())),
+            }
+        }
+        if let Some(min_free_mb) = self.min_free_memory_mb {
+            let guard = config
+                .memory_guard
+                .get_or_insert_with(|| MemoryGuardConfig::new(min_free_mb));
+            guard.min_free_mb = min_free_mb;
+        }
+        if let Some(interval) = self.memory_check_interval_ms {
+            if let Some(guard) = config.memory_guard.as_mut() {
+                guard.check_interval_ms = interval;
+            }
+        }
+        if let Ok(token) = std::env::var(ADMIN_TOKEN_VARIABLE) {
+            config.admin_token = (!token.is_empty()).then_some(token);
+        }
+        if let Some(max_transactions) = self.max_transactions_per_block {
+            config.max_transactions_per_block = (max_transactions > 0).then_some(max_transactions);
+        }
+
+        config
+            .validate()
+            .map_err(|e| eyre::eyre!("Invalid node configuration: {}", e))?;
+        Ok(config)
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Nice colored error messages.
@@ -48,73 +348,152 @@ async fn main() -> Result<()> {
     let args = Args::parse();
 
     // Setup logging
-    let log_level = if args.debug {
-        LevelFilter::DEBUG
-    } else {
-        LevelFilter::INFO
-    };
+    let (filter, filter_handle) = reload::Layer::new(default_log_filter(args.debug));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .init();
 
-    let filter = EnvFilter::builder()
-        .with_default_directive(log_level.into())
-        .from_env_lossy();
-    fmt().with_env_filter(filter).init();
+    // Benchmark a verifier instead of running a node
+    if let Some(Command::BenchVerifier {
+        verifier,
+        batch_size,
+        batches,
+        transaction_size,
+        threads,
+        min_parallel_batch,
+        sweep,
+    }) = args.command
+    {
+        let config = VerifierBenchConfig {
+            verifier,
+            batch_size,
+            batches,
+            transaction_size,
+            threads,
+            min_parallel_batch,
+        };
+        return match sweep {
+            true => sweep_verifier(&config),
+            false => bench_verifier(&config),
+        };
+    }
 
-    // Create working directory
-    std::fs::create_dir_all(&args.working_directory)?;
+    // Replay a commit journal instead of running a node
+    if let Some(path) = &args.replay_journal {
+        return replay_journal(path).await;
+    }
 
-    // Determine ABCI port
-    let abci_port = args
-        .abci_port
-        .unwrap_or(26670 + args.authority_index as u16);
+    // Assemble the node configuration
+    let config = args.node_config()?;
+    if let Some(filter) = &config.log_filter {
+        set_log_filter(&filter_handle, filter);
+    }
+    let authority_index = config.authority_index;
+    let rpc_port = config.rpc_port;
+    let abci_port = config.abci_port;
+    let working_directory = config.working_directory.clone();
+
+    // Create working directory
+    std::fs::create_dir_all(&working_directory)?;
 
     info!(
         "Starting single Mysticeti validator node {} on RPC port {} and ABCI port {}",
-        args.authority_index, args.rpc_port, abci_port
-    );
-
-    // Create validator node
-    let mut validator = ValidatorNode::new(
-        args.authority_index,
-        args.working_directory.clone(),
-        args.rpc_port,
+        authority_index, rpc_port, abci_port
     );
 
     // Create committee and keypairs - use Docker configuration if peer addresses are provided
-    let committee_size = 4; // We'll create a 4-node committee even for single node
-    let (committee, keypairs) = if args.peer_addresses.is_some() {
+    if config.committee.docker {
         info!(
             "Using Docker network configuration with peer addresses: {:?}",
             args.peer_addresses
         );
-        consensus_config::docker_committee_and_keys(0, vec![1; committee_size])
-    } else {
-        info!("Using local network configuration");
-        consensus_config::local_committee_and_keys(0, vec![1; committee_size])
+    }
+    let (committee, derived_keypair) = match &config.committee.url {
+        Some(url) => {
+            let checksum = config.committee.checksum.as_deref();
+            if checksum.is_none() {
+                warn!(
+                    "Loading the committee from {} without checking its checksum",
+                    url
+                );
+            }
+            let committee = committee::fetch_committee(url, checksum)
+                .await
+                .map_err(|e| eyre::eyre!("Failed to load committee: {}", e))?;
+            info!("Loaded the committee from {}", url);
+            (committee, None)
+        }
+        None => {
+            let (committee, keypairs) = config
+                .committee
+                .committee_and_keys()
+                .map_err(|e| eyre::eyre!("Failed to build committee: {}", e))?;
+            let keypair = keypairs.get(authority_index as usize).cloned();
+            (committee, keypair)
+        }
     };
+    let keypair = match (&config.committee.keys, derived_keypair) {
+        (Some(path), _) => {
+            info!("Loading the keys of the node from {}", path.display());
+            committee::load_node_keys(path, &committee, authority_index)
+        }
+        (None, Some(keypair)) => Ok(keypair),
+        // Also reports an authority index out of range of a generated committee.
+        (None, None) => committee::committee_keys(&committee, authority_index),
+    }
+    .map_err(|e| eyre::eyre!("Failed to load the keys of the node: {}", e))?;
+
+    // Record the effective configuration
+    let effective_config = EffectiveConfig::new(&config, &committee);
+    info!("Effective configuration:\n{}", effective_config.to_yaml());
+    if let Some(path) = &args.dump_config {
+        effective_config
+            .dump(path)
+            .wrap_err(format!("Failed to dump config to {}", path.display()))?;
+    }
+
+    // Reload the tunable parts of the configuration on SIGHUP
+    let mut running_config = config.clone();
+    let reload_args = args.clone();
+    let admin_filter_handle = filter_handle.clone();
+    tokio::spawn(on_hangup(move || {
+        reload_config(&reload_args, &mut running_config, &filter_handle)
+    }));
+
+    // Create validator node
+    if config.admin_token.is_none() {
+        info!(
+            "Admin endpoints disabled (set {} to enable them)",
+            ADMIN_TOKEN_VARIABLE
+        );
+    }
+    let mut validator =
+        ValidatorNode::new(config).with_log_filter_control(Arc::new(admin_filter_handle));
 
     // Create metrics registry
     let registry_service = RegistryService::new(Registry::new());
 
     // Start the validator node
     validator
-        .start(committee, keypairs, registry_service)
+        .start(committee, keypair, registry_service)
         .await
         .map_err(|e| eyre::eyre!("Failed to start validator node: {}", e))?;
 
     // Print endpoints
     println!("\n=== Single Validator Node Started ===");
-    println!("Authority Index: {}", args.authority_index);
+    println!("Authority Index: {}", authority_index);
     println!(
         "RPC Endpoint: http://127.0.0.1:{}/broadcast_tx_async",
-        args.rpc_port
+        rpc_port
     );
-    println!("Health Check: http://127.0.0.1:{}/health", args.rpc_port);
+    println!("Health Check: http://127.0.0.1:{}/health", rpc_port);
     println!("ABCI Port: {}", abci_port);
-    println!("Working Directory: {}", args.working_directory.display());
+    println!("Working Directory: {}", working_directory.display());
     println!("\nPress Ctrl+C to stop the node");
 
     // Wait for shutdown signal
-    tokio::signal::ctrl_c().await.unwrap();
+    shutdown_signal().await;
 
     // Stop the validator
     validator.stop().await;
@@ -122,3 +501,133 @@ async fn main() -> Result<()> {
     println!("Validator node stopped");
     Ok(())
 }
+
+/// The log filter used unless the configuration sets one: `RUST_LOG`, or the level selected
+/// by the `--debug` flag.
+fn default_log_filter(debug: bool) -> EnvFilter {
+    let log_level = if debug {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    };
+
+    EnvFilter::builder()
+        .with_default_directive(log_level.into())
+        .from_env_lossy()
+}
+
+/// Replace the log filter of the running node, keeping the current one if it is invalid.
+fn set_log_filter(handle: &FilterHandle, filter: &str) {
+    let result = EnvFilter::try_new(filter)
+        .map_err(|e| e.to_string())
+        .and_then(|filter| handle.reload(filter).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => info!("Log filter set to '{}'", filter),
+        Err(e) => warn!("Failed to set the log filter to '{}': {}", filter, e),
+    }
+}
+
+/// Reload the configuration file and apply the changes that do not require a restart.
+fn reload_config(args: &Args, running: &mut NodeConfig, handle: &FilterHandle) {
+    if args.config.is_none() {
+        warn!("No configuration file to reload (the node was started without --config)");
+        return;
+    }
+    let reloaded = match args.node_config() {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("Keeping the running configuration: {}", e);
+            return;
+        }
+    };
+
+    let reload = ConfigReload::between(running, &reloaded);
+    if reload.is_empty() {
+        info!("The configuration did not change");
+        return;
+    }
+    match &reload.log_filter {
+        Some(Some(filter)) => set_log_filter(handle, filter),
+        Some(None) => match handle.reload(default_log_filter(args.debug)) {
+            Ok(()) => info!("Log filter reset to the default"),
+            Err(e) => warn!("Failed to reset the log filter: {}", e),
+        },
+        None => (),
+    }
+    running.log_filter = reloaded.log_filter;
+    if !reload.requires_restart.is_empty() {
+        warn!(
+            "Changes to {} take effect on the next restart (consensus does not support live reconfiguration)",
+            reload.requires_restart.join(", ")
+        );
+    }
+}
+
+/// Benchmark a transaction verifier and print its report.
+fn bench_verifier(config: &VerifierBenchConfig) -> Result<()> {
+    info!(
+        "Verifying {} batches of {} transactions of {} bytes with the {} verifier on {} threads",
+        config.batches, config.batch_size, config.transaction_size, config.verifier, config.threads
+    );
+    let report = run_verifier_bench(config).map_err(|e| eyre::eyre!(e))?;
+    println!("{report}");
+    Ok(())
+}
+
+/// Sweep the batch sizes and numbers of threads of a transaction verifier and print the
+/// throughput of each.
+fn sweep_verifier(config: &VerifierBenchConfig) -> Result<()> {
+    info!(
+        "Verifying {} batches of {:?} transactions of {} bytes with the {} verifier on {:?} threads",
+        config.batches, SWEEP_BATCH_SIZES, config.transaction_size, config.verifier, SWEEP_THREADS
+    );
+    let report =
+        run_verifier_sweep(config, SWEEP_BATCH_SIZES, SWEEP_THREADS).map_err(|e| eyre::eyre!(e))?;
+    println!("{report}");
+    Ok(())
+}
+
+/// Replay a commit journal into the ABCI application.
+async fn replay_journal(path: &Path) -> Result<()> {
+    let entries = read_journal(path).wrap_err(format!(
+        "Failed to read commit journal '{}'",
+        path.display()
+    ))?;
+    info!(
+        "Replaying {} commits from {}",
+        entries.len(),
+        path.display()
+    );
+
+    // Finalized blocks are executed without being submitted to consensus again.
+    let (sender, _receiver) = tokio::sync::mpsc::channel(1);
+    let app = MysticetiAbciApp::new(sender);
+    let responses = replay_journal_into(&app, &entries);
+    let executed = responses
+        .iter()
+        .flat_map(|response| &response.tx_results)
+        .filter(|result| result.code == CODE_OK)
+        .count();
+
+    println!("Replayed {} commits", responses.len());
+    println!("Executed {} transactions", executed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::Args;
+
+    #[test]
+    fn checksum_requires_a_committee_file() {
+        let parse = |args: &[&str]| Args::try_parse_from([&["validator"], args].concat());
+
+        let url = ["--committee-url", "https://example.com/c.yaml"];
+        assert!(parse(&[&url[..], &["--committee-checksum", "ab"]].concat()).is_ok());
+        let path = ["--committee-path", "committee.yaml"];
+        assert!(parse(&[&path[..], &["--committee-checksum", "ab"]].concat()).is_ok());
+        assert!(parse(&["--committee-checksum", "ab"]).is_err());
+    }
+}
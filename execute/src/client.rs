@@ -0,0 +1,353 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A client of the RPC servers of a committee. Besides submitting transactions, it can confirm
+//! that a transaction committed on a quorum of nodes rather than only on the node it was sent
+//! to: once a quorum agrees on its commit, the transaction is durably in the ordered log even if
+//! that node crashes. This is slower than waiting for the submission acknowledgment, but gives
+//! the strongest guarantee a client can get, which is what correctness tests need.
+//!
+//! Submissions failing transiently (unreachable node, overload) can be retried. Every attempt
+//! carries the same idempotency key, so that the node forwards the transaction to consensus
+//! only once even if an attempt the client saw fail actually went through.
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use base64::Engine;
+use futures::future::join_all;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use tokio::time::{Instant, sleep};
+use tracing::warn;
+
+use crate::validator::committed::TransactionStatus;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// A transaction confirmed by a quorum of nodes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuorumConfirmation {
+    pub transaction_id: String,
+    /// The index of the commit including the transaction.
+    pub commit_index: u32,
+    /// The nodes that reported the commit when the quorum was reached.
+    pub confirmed_by: Vec<usize>,
+    /// How long it took to reach the quorum, from the submission (or the start of the
+    /// confirmation if the transaction was submitted separately).
+    pub elapsed: Duration,
+}
+
+/// A client of the RPC servers of the nodes of a committee.
+pub struct MysticetiClient {
+    client: reqwest::Client,
+    /// The base url of the RPC server of every node, by authority index.
+    nodes: Vec<String>,
+    /// The node receiving the next submission.
+    next_node: AtomicUsize,
+    /// The number of nodes that must report the commit of a transaction to confirm it.
+    confirmation_quorum: usize,
+    /// How long a confirmation waits for the quorum.
+    confirmation_timeout: Duration,
+    /// How often the nodes are polled while confirming a transaction.
+    poll_interval: Duration,
+    /// The number of times a submission failing transiently is retried.
+    submission_retries: usize,
+    /// The delay before the first retry of a submission, doubled after every failed attempt.
+    retry_backoff: Duration,
+}
+
+/// The outcome of an attempt to submit a transaction.
+enum Attempt {
+    Accepted(String),
+    /// The attempt may succeed if retried.
+    Transient(Error),
+    Failed(Error),
+}
+
+impl MysticetiClient {
+    /// The default time a confirmation waits for the quorum.
+    pub const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(30);
+    /// The default interval between two polls of the nodes.
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+    /// The default delay before the first retry of a submission.
+    pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+    /// Create a client of the nodes with the specified RPC urls (e.g. `http://127.0.0.1:26657`).
+    /// Transactions are confirmed by a quorum of `2f+1` nodes, assuming equal stake.
+    pub fn new(nodes: Vec<String>) -> Self {
+        let confirmation_quorum = nodes.len() - nodes.len().saturating_sub(1) / 3;
+        Self {
+            client: reqwest::Client::new(),
+            nodes,
+            next_node: AtomicUsize::new(0),
+            confirmation_quorum,
+            confirmation_timeout: Self::DEFAULT_CONFIRMATION_TIMEOUT,
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+            submission_retries: 0,
+            retry_backoff: Self::DEFAULT_RETRY_BACKOFF,
+        }
+    }
+
+    /// Set the number of nodes that must report the commit of a transaction to confirm it
+    /// (between 1 and all the nodes).
+    pub fn with_confirmation_quorum(mut self, confirmation_quorum: usize) -> Self {
+        self.confirmation_quorum = confirmation_quorum.clamp(1, self.nodes.len().max(1));
+        self
+    }
+
+    /// Set how long a confirmation waits for the quorum, and how often it polls the nodes.
+    pub fn with_confirmation_timeout(
+        mut self,
+        confirmation_timeout: Duration,
+        poll_interval: Duration,
+    ) -> Self {
+        self.confirmation_timeout = confirmation_timeout;
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Retry submissions failing transiently up to `retries` times, waiting `backoff` before the
+    /// first retry and doubling it after every failed attempt. Retries are idempotent: the node
+    /// acknowledges a retry of an accepted submission without submitting it again.
+    pub fn with_submission_retries(mut self, retries: usize, backoff: Duration) -> Self {
+        self.submission_retries = retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// The number of nodes that must report the commit of a transaction to confirm it.
+    pub fn confirmation_quorum(&self) -> usize {
+        self.confirmation_quorum
+    }
+
+    /// Submit a transaction to the next node (in round-robin order). Returns the id of the
+    /// transaction.
+    pub async fn submit(&self, transaction: &[u8]) -> Result<String, Error> {
+        let node = self.next_node.fetch_add(1, Ordering::Relaxed) % self.nodes.len();
+        self.submit_to(node, transaction).await
+    }
+
+    /// Submit a transaction to the specified node, retrying it if it fails transiently. Returns
+    /// the id of the transaction.
+    pub async fn submit_to(&self, node: usize, transaction: &[u8]) -> Result<String, Error> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(transaction);
+        // Without retries, there is nothing to deduplicate.
+        let idempotency_key =
+            (self.submission_retries > 0).then(|| format!("{:032x}", rand::random::<u128>()));
+        let mut backoff = self.retry_backoff;
+        let mut attempt = 0;
+        loop {
+            match self
+                .attempt_submission(node, &encoded, idempotency_key.as_deref())
+                .await
+            {
+                Attempt::Accepted(transaction_id) => return Ok(transaction_id),
+                Attempt::Transient(e) if attempt < self.submission_retries => {
+                    attempt += 1;
+                    warn!(
+                        "Submission to node {node} failed ({e}), retrying in {backoff:?} \
+                         (attempt {attempt}/{})",
+                        self.submission_retries
+                    );
+                    sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Attempt::Transient(e) | Attempt::Failed(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn attempt_submission(
+        &self,
+        node: usize,
+        encoded: &str,
+        idempotency_key: Option<&str>,
+    ) -> Attempt {
+        #[derive(Deserialize)]
+        struct Response {
+            message: String,
+            transaction_id: Option<String>,
+        }
+
+        let mut request = serde_json::json!({ "transaction": encoded });
+        if let Some(key) = idempotency_key {
+            request["idempotency_key"] = key.into();
+        }
+        let response = match self
+            .client
+            .post(format!("{}/broadcast_tx_async", self.nodes[node]))
+            .json(&request)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) if e.is_connect() || e.is_timeout() => return Attempt::Transient(e.into()),
+            Err(e) => return Attempt::Failed(e.into()),
+        };
+        let status = response.status();
+        let response: Response = match response.json().await {
+            Ok(response) => response,
+            Err(e) => return Attempt::Failed(e.into()),
+        };
+        match response.transaction_id {
+            Some(transaction_id) => Attempt::Accepted(transaction_id),
+            None => {
+                let e = format!("Node {node} rejected the transaction: {}", response.message);
+                match is_transient(status) {
+                    true => Attempt::Transient(e.into()),
+                    false => Attempt::Failed(e.into()),
+                }
+            }
+        }
+    }
+
+    /// Look the transactions up on the `/tx_status` endpoint of the specified node.
+    pub async fn transaction_status(
+        &self,
+        node: usize,
+        transaction_ids: &[String],
+    ) -> Result<Vec<TransactionStatus>, Error> {
+        #[derive(Deserialize)]
+        struct Response {
+            transactions: Vec<TransactionStatus>,
+        }
+
+        let response: Response = self
+            .client
+            .post(format!("{}/tx_status", self.nodes[node]))
+            .json(&serde_json::json!({ "transaction_ids": transaction_ids }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.transactions)
+    }
+
+    /// Submit a transaction to the next node, then wait until a quorum of nodes committed it.
+    pub async fn submit_and_confirm(
+        &self,
+        transaction: &[u8],
+    ) -> Result<QuorumConfirmation, Error> {
+        let start = Instant::now();
+        let transaction_id = self.submit(transaction).await?;
+        let mut confirmation = self.confirm(&transaction_id).await?;
+        confirmation.elapsed = start.elapsed();
+        Ok(confirmation)
+    }
+
+    /// Wait until a quorum of nodes committed the transaction. Unreachable nodes count as not
+    /// having committed it. Fails if the quorum is not reached within the confirmation timeout,
+    /// or if nodes disagree on the commit of the transaction.
+    pub async fn confirm(&self, transaction_id: &str) -> Result<QuorumConfirmation, Error> {
+        let start = Instant::now();
+        let transaction_ids = [transaction_id.to_string()];
+        loop {
+            let lookups =
+                (0..self.nodes.len()).map(|node| self.transaction_status(node, &transaction_ids));
+            let commits: Vec<_> = join_all(lookups)
+                .await
+                .into_iter()
+                .map(|statuses| {
+                    statuses
+                        .ok()
+                        .and_then(|statuses| statuses.first().and_then(|s| s.commit_index))
+                })
+                .collect();
+            if let Some((commit_index, confirmed_by)) =
+                quorum_commit(&commits, self.confirmation_quorum)?
+            {
+                return Ok(QuorumConfirmation {
+                    transaction_id: transaction_id.to_string(),
+                    commit_index,
+                    confirmed_by,
+                    elapsed: start.elapsed(),
+                });
+            }
+            if start.elapsed() >= self.confirmation_timeout {
+                let committed = commits.iter().filter(|commit| commit.is_some()).count();
+                return Err(format!(
+                    "Transaction {transaction_id} committed on {committed}/{} nodes after {:?} \
+                     (quorum of {})",
+                    self.nodes.len(),
+                    self.confirmation_timeout,
+                    self.confirmation_quorum
+                )
+                .into());
+            }
+            sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// Whether a submission rejected with the specified status may succeed if retried: the node is
+/// overloaded, not running yet, or still forwarding an earlier attempt.
+fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::CONFLICT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// The commit of a transaction and the nodes reporting it, if at least `quorum` nodes did.
+/// Fails if nodes report different commits, which would break the total order.
+fn quorum_commit(
+    commits: &[Option<u32>],
+    quorum: usize,
+) -> Result<Option<(u32, Vec<usize>)>, String> {
+    let mut reported = commits
+        .iter()
+        .enumerate()
+        .filter_map(|(node, commit)| commit.map(|commit| (node, commit)));
+    let Some((first_node, commit_index)) = reported.next() else {
+        return Ok(None);
+    };
+    let mut confirmed_by = vec![first_node];
+    for (node, commit) in reported {
+        if commit != commit_index {
+            return Err(format!(
+                "Nodes disagree on the commit of the transaction: {commit_index} on node \
+                 {first_node}, {commit} on node {node}"
+            ));
+        }
+        confirmed_by.push(node);
+    }
+    match confirmed_by.len() >= quorum {
+        true => Ok(Some((commit_index, confirmed_by))),
+        false => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::StatusCode;
+
+    use super::{MysticetiClient, is_transient, quorum_commit};
+
+    #[test]
+    fn confirm_on_quorum() {
+        let nodes = |n| (0..n).map(|i| format!("http://node-{i}")).collect();
+        assert_eq!(MysticetiClient::new(nodes(4)).confirmation_quorum(), 3);
+        assert_eq!(MysticetiClient::new(nodes(7)).confirmation_quorum(), 5);
+        assert_eq!(MysticetiClient::new(nodes(1)).confirmation_quorum(), 1);
+        let client = MysticetiClient::new(nodes(4)).with_confirmation_quorum(10);
+        assert_eq!(client.confirmation_quorum(), 4);
+
+        assert_eq!(quorum_commit(&[Some(7), None, Some(7), None], 3), Ok(None));
+        assert_eq!(
+            quorum_commit(&[Some(7), None, Some(7), Some(7)], 3),
+            Ok(Some((7, vec![0, 2, 3])))
+        );
+        assert!(quorum_commit(&[Some(7), Some(8), None, None], 1).is_err());
+    }
+
+    #[test]
+    fn retry_transient_rejections() {
+        assert!(is_transient(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_transient(StatusCode::CONFLICT));
+        assert!(!is_transient(StatusCode::BAD_REQUEST));
+        assert!(!is_transient(StatusCode::PAYLOAD_TOO_LARGE));
+    }
+}
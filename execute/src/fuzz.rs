@@ -0,0 +1,206 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs the in-process network with randomized consensus parameters and a short smoke load, to
+//! find the parameter combinations that break liveness before sweeping them in a benchmark.
+//! Enabled by the `testing` feature.
+
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::Serialize;
+use tracing::{error, info};
+
+use crate::{testing::TestNetwork, validator::ConsensusConfig};
+
+/// Draws consensus parameters within their valid ranges. Every set is drawn from its own seed,
+/// so that a failing set can be reproduced alone.
+#[derive(Clone, Debug)]
+pub struct ParameterFuzzer {
+    seed: u64,
+}
+
+impl ParameterFuzzer {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// The seed of the `iteration`-th parameter set.
+    pub fn iteration_seed(&self, iteration: usize) -> u64 {
+        self.seed.wrapping_add(iteration as u64)
+    }
+
+    /// The parameter set drawn from the specified seed.
+    pub fn sample(seed: u64) -> ConsensusConfig {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let round_prober_interval_ms = rng.gen_range(500..=10_000);
+        ConsensusConfig {
+            leader_timeout_ms: Some(rng.gen_range(50..=2_000)),
+            min_round_delay_ms: Some(rng.gen_range(0..=500)),
+            max_forward_time_drift_ms: Some(rng.gen_range(100..=2_000)),
+            max_blocks_per_sync: Some(rng.gen_range(1..=64)),
+            max_blocks_per_fetch: Some(rng.gen_range(1..=2_000)),
+            round_prober_interval_ms: Some(round_prober_interval_ms),
+            round_prober_request_timeout_ms: Some(rng.gen_range(100..=round_prober_interval_ms)),
+            propagation_delay_stop_proposal_threshold: Some(rng.gen_range(1..=20)),
+            // Fewer cached rounds hurt performance, not liveness.
+            dag_state_cached_rounds: Some(rng.gen_range(50..=1_000)),
+            commit_sync_parallel_fetches: Some(rng.gen_range(1..=16)),
+            commit_sync_batch_size: Some(rng.gen_range(1..=200)),
+            commit_sync_batches_ahead: Some(rng.gen_range(1..=200)),
+            // The fuzzed networks keep their DBs in memory.
+            ..ConsensusConfig::default()
+        }
+    }
+}
+
+/// The settings of a fuzzing campaign.
+#[derive(Clone, Debug)]
+pub struct FuzzSettings {
+    /// The number of parameter sets to try.
+    pub iterations: usize,
+    /// The number of validators of the network.
+    pub committee_size: usize,
+    /// The number of transactions of the smoke load.
+    pub transactions: usize,
+    /// How long every transaction has to commit on every node.
+    pub commit_timeout: Duration,
+}
+
+impl Default for FuzzSettings {
+    fn default() -> Self {
+        Self {
+            iterations: 10,
+            committee_size: 4,
+            transactions: 10,
+            commit_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// The outcome of one parameter set.
+#[derive(Clone, Debug, Serialize)]
+pub struct FuzzOutcome {
+    /// The seed the parameters were drawn from.
+    pub seed: u64,
+    /// The parameters.
+    pub parameters: ConsensusConfig,
+    /// How long the smoke load took to commit, if it did.
+    pub committed_in: Option<Duration>,
+    /// Why the network failed, if it did.
+    pub failure: Option<String>,
+}
+
+impl FuzzOutcome {
+    pub fn is_success(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Try the parameter sets drawn from consecutive seeds, one network after the other, logging
+/// every set that fails to commit the smoke load.
+pub async fn fuzz_parameters(
+    fuzzer: &ParameterFuzzer,
+    settings: &FuzzSettings,
+) -> Vec<FuzzOutcome> {
+    let mut outcomes = Vec::with_capacity(settings.iterations);
+    for iteration in 0..settings.iterations {
+        let seed = fuzzer.iteration_seed(iteration);
+        let parameters = ParameterFuzzer::sample(seed);
+        info!(
+            "Parameter set {}/{} (seed {}): {:?}",
+            iteration + 1,
+            settings.iterations,
+            seed,
+            parameters
+        );
+
+        let outcome = match smoke_test(&parameters, settings).await {
+            Ok(elapsed) => {
+                info!("Seed {}: committed in {:.2}s", seed, elapsed.as_secs_f64());
+                FuzzOutcome {
+                    seed,
+                    parameters,
+                    committed_in: Some(elapsed),
+                    failure: None,
+                }
+            }
+            Err(e) => {
+                error!(
+                    "Seed {}: the network failed to commit ({}) with parameters {:?}",
+                    seed, e, parameters
+                );
+                FuzzOutcome {
+                    seed,
+                    parameters,
+                    committed_in: None,
+                    failure: Some(e.to_string()),
+                }
+            }
+        };
+        outcomes.push(outcome);
+    }
+    outcomes
+}
+
+/// Start a network with the parameters, submit the smoke load, and wait for every node to
+/// commit it. Returns how long the load took to commit.
+async fn smoke_test(
+    parameters: &ConsensusConfig,
+    settings: &FuzzSettings,
+) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+    let network = TestNetwork::start_with(settings.committee_size, |config| {
+        config.consensus = parameters.clone();
+    })
+    .await?
+    .with_commit_timeout(settings.commit_timeout);
+
+    let start = Instant::now();
+    let mut result = Ok(());
+    let mut ids = Vec::with_capacity(settings.transactions);
+    for i in 0..settings.transactions {
+        match network
+            .submit(format!("fuzz transaction {i}").as_bytes())
+            .await
+        {
+            Ok(id) => ids.push(id),
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    if result.is_ok() {
+        for id in &ids {
+            if let Err(e) = network.await_commit(id).await {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    network.shutdown().await;
+    result.map(|_| elapsed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::ParameterFuzzer;
+
+    #[test]
+    fn reproducible_parameters() {
+        let fuzzer = ParameterFuzzer::new(42);
+        let seed = fuzzer.iteration_seed(3);
+        assert_eq!(seed, 45);
+        assert_eq!(ParameterFuzzer::sample(seed), ParameterFuzzer::sample(45));
+        assert_ne!(ParameterFuzzer::sample(seed), ParameterFuzzer::sample(46));
+
+        for seed in 0..100 {
+            let parameters = ParameterFuzzer::sample(seed);
+            parameters.validate().unwrap();
+            assert!(
+                parameters.round_prober_request_timeout_ms <= parameters.round_prober_interval_ms
+            );
+        }
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversion between CometBFT `genesis.json` files and Mysticeti committees.
+//!
+//! CometBFT validators are identified by a single ed25519 key, which becomes both the protocol
+//! and the network key of the matching authority, and their voting power becomes the stake.
+//! CometBFT has no equivalent of the (bls12381) authority key: it is derived
+//! deterministically from the validator key, so repeated conversions yield the same committee.
+//! The genesis does not hold network addresses either; they are built from an address
+//! template.
+//!
+//! WARNING: the committees converted from a CometBFT genesis are for tests only. Their
+//! authority keys are seeded with the public validator keys, so anyone reading the genesis can
+//! derive the matching private keys. Production committees must be built from authority keys
+//! generated by their operators.
+
+use std::path::Path;
+
+use base64::Engine;
+use consensus_config::{
+    Authority, AuthorityKeyPair, Committee, NetworkPublicKey, ProtocolPublicKey,
+};
+use fastcrypto::{
+    ed25519::Ed25519PublicKey,
+    hash::{HashFunction, Sha256},
+    traits::ToFromBytes,
+};
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// The CometBFT key type of ed25519 validator keys, the only curve Mysticeti supports.
+pub const ED25519_KEY_TYPE: &str = "tendermint/PubKeyEd25519";
+
+/// The fields of a CometBFT genesis relevant to the committee. Other fields (consensus
+/// params, app hash, ...) are preserved as is.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CometBftGenesis {
+    #[serde(default)]
+    pub genesis_time: String,
+    pub chain_id: String,
+    #[serde(default)]
+    pub validators: Vec<CometBftValidator>,
+    #[serde(default)]
+    pub app_state: serde_json::Value,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A validator of a CometBFT genesis.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CometBftValidator {
+    /// The hex-encoded address of the validator (derived from its key).
+    #[serde(default)]
+    pub address: String,
+    pub pub_key: CometBftPubKey,
+    /// The voting power, encoded as a decimal string.
+    pub power: String,
+    #[serde(default)]
+    pub name: String,
+}
+
+/// A typed, base64-encoded public key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CometBftPubKey {
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub value: String,
+}
+
+impl CometBftGenesis {
+    /// Load a genesis from a json file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read genesis '{}': {e}", path.display()))?;
+        let genesis = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse genesis '{}': {e}", path.display()))?;
+        Ok(genesis)
+    }
+
+    /// Build the committee matching the validators of the genesis. Authority `i` listens on
+    /// the address obtained by substituting `{i}` and `{port}` in `address_template`.
+    ///
+    /// For tests only: the authority keys of the committee are derived from public material
+    /// (see the module documentation).
+    pub fn to_committee(
+        &self,
+        address_template: &str,
+        base_port: u16,
+    ) -> Result<Committee, Box<dyn std::error::Error + Send + Sync>> {
+        if self.validators.is_empty() {
+            return Err("The genesis has no validators".into());
+        }
+        let addresses = consensus_config::addresses_from_template(
+            address_template,
+            self.validators.len(),
+            base_port,
+        )?;
+
+        warn!(
+            "The authority keys of the committee converted from the genesis are derived from \
+            the public validator keys: it is only fit for tests"
+        );
+        let mut authorities = Vec::with_capacity(self.validators.len());
+        for (i, (validator, address)) in self.validators.iter().zip(addresses).enumerate() {
+            let key = validator
+                .ed25519_key()
+                .map_err(|e| format!("Validator {i}: {e}"))?;
+            let stake: u64 = validator
+                .power
+                .parse()
+                .map_err(|e| format!("Validator {i}: invalid power '{}': {e}", validator.power))?;
+            if stake == 0 {
+                return Err(format!("Validator {i} has no voting power").into());
+            }
+
+            // NOT SECURE: the seed is the public key of the validator, so the private authority
+            // key is known to anyone. Only acceptable for test committees.
+            let mut rng = StdRng::from_seed(key.as_bytes().try_into().expect("32-byte key"));
+            let hostname = match validator.name.is_empty() {
+                true => format!("validator-{i}"),
+                false => validator.name.clone(),
+            };
+            authorities.push(Authority {
+                stake,
+                address,
+                hostname,
+                authority_key: AuthorityKeyPair::generate(&mut rng).public(),
+                protocol_key: ProtocolPublicKey::new(key.clone()),
+                network_key: NetworkPublicKey::new(key),
+            });
+        }
+        Ok(Committee::new(0, authorities))
+    }
+
+    /// Build a genesis whose validators match the authorities of the committee.
+    pub fn from_committee(committee: &Committee, chain_id: &str) -> Self {
+        let validators = committee
+            .authorities()
+            .map(|(_, authority)| {
+                let key = authority.protocol_key.to_bytes();
+                CometBftValidator {
+                    address: validator_address(key),
+                    pub_key: CometBftPubKey {
+                        key_type: ED25519_KEY_TYPE.to_string(),
+                        value: base64::engine::general_purpose::STANDARD.encode(key),
+                    },
+                    power: authority.stake.to_string(),
+                    name: authority.hostname.clone(),
+                }
+            })
+            .collect();
+
+        Self {
+            genesis_time: String::new(),
+            chain_id: chain_id.to_string(),
+            validators,
+            app_state: serde_json::Value::Object(Default::default()),
+            other: Default::default(),
+        }
+    }
+}
+
+impl CometBftValidator {
+    /// Decode the ed25519 key of the validator, rejecting other curves.
+    fn ed25519_key(&self) -> Result<Ed25519PublicKey, String> {
+        if self.pub_key.key_type != ED25519_KEY_TYPE {
+            return Err(format!(
+                "unsupported key type '{}' (only {ED25519_KEY_TYPE} is supported)",
+                self.pub_key.key_type
+            ));
+        }
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&self.pub_key.value)
+            .map_err(|e| format!("invalid base64 key: {e}"))?;
+        Ed25519PublicKey::from_bytes(&bytes).map_err(|e| format!("invalid ed25519 key: {e}"))
+    }
+}
+
+/// The CometBFT address of a validator: the first 20 bytes of the sha256 of its key, in
+/// upper-case hex.
+fn validator_address(key: &[u8]) -> String {
+    Sha256::digest(key).digest[..20]
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use base64::Engine;
+    use consensus_config::NetworkKeyPair;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{
+        CometBftGenesis, CometBftPubKey, CometBftValidator, ED25519_KEY_TYPE, validator_address,
+    };
+
+    fn genesis(validators: usize) -> CometBftGenesis {
+        let mut rng = StdRng::from_seed([7; 32]);
+        let mut genesis: CometBftGenesis = serde_json::from_str(
+            r#"{"chain_id": "cosmos-test", "consensus_params": {"block": {"max_bytes": "1024"}}}"#,
+        )
+        .unwrap();
+        for i in 0..validators {
+            let key = NetworkKeyPair::generate(&mut rng).public().to_bytes();
+            genesis.validators.push(CometBftValidator {
+                address: validator_address(&key),
+                pub_key: CometBftPubKey {
+                    key_type: ED25519_KEY_TYPE.to_string(),
+                    value: base64::engine::general_purpose::STANDARD.encode(key),
+                },
+                power: (i + 1).to_string(),
+                name: format!("validator-{i}"),
+            });
+        }
+        genesis
+    }
+
+    #[test]
+    fn cometbft_round_trip() {
+        let original = genesis(4);
+        let committee = original
+            .to_committee("/dns/node{i}/udp/{port}", 26657)
+            .unwrap();
+        assert_eq!(committee.size(), 4);
+        assert_eq!(committee.total_stake(), 1 + 2 + 3 + 4);
+
+        // The conversion is deterministic.
+        let again = original
+            .to_committee("/dns/node{i}/udp/{port}", 26657)
+            .unwrap();
+        assert_eq!(
+            serde_json::to_string(&committee).unwrap(),
+            serde_json::to_string(&again).unwrap()
+        );
+
+        let converted = CometBftGenesis::from_committee(&committee, "cosmos-test");
+        for (original, converted) in original.validators.iter().zip(&converted.validators) {
+            assert_eq!(original.pub_key, converted.pub_key);
+            assert_eq!(original.power, converted.power);
+            assert_eq!(original.name, converted.name);
+        }
+        assert!(original.other.contains_key("consensus_params"));
+    }
+
+    #[test]
+    fn reject_unsupported_curves() {
+        let mut genesis = genesis(4);
+        genesis.validators[2].pub_key = CometBftPubKey {
+            key_type: "tendermint/PubKeySecp256k1".to_string(),
+            value: "A".repeat(44),
+        };
+        let error = genesis
+            .to_committee("/dns/node{i}/udp/{port}", 26657)
+            .unwrap_err();
+        assert!(error.to_string().contains("Validator 2"));
+    }
+}
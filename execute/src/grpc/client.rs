@@ -0,0 +1,212 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{future::Future, time::Duration};
+
+use tokio::time::sleep;
+use tonic::{
+    Code, Response, Status, Streaming,
+    transport::{Channel, Endpoint},
+};
+use tracing::{debug, warn};
+
+use crate::grpc::proto::{
+    CommitSummary, ConsensusStatus, ConsensusStatusRequest, MysticetiServiceClient,
+    SubscribeCommitsRequest, TransactionRequest, TransactionResponse,
+};
+
+/// A client of the `MysticetiService` gRPC server. Calls failing with a transport error are
+/// retried over a new connection, with exponential backoff.
+#[derive(Clone)]
+pub struct MysticetiGrpcClient {
+    /// The endpoint of the server, used to reconnect.
+    endpoint: Endpoint,
+    /// The current connection to the server.
+    inner: MysticetiServiceClient<Channel>,
+    /// The number of times a failed call is retried.
+    max_retries: usize,
+    /// The delay before the first retry, doubled after every failed attempt.
+    initial_backoff: Duration,
+    /// The maximum delay between two attempts.
+    max_backoff: Duration,
+    /// The number of times the client reconnected.
+    reconnects: usize,
+}
+
+impl MysticetiGrpcClient {
+    /// The default time to wait for the connection to the server.
+    pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+    /// The default number of times a failed call is retried.
+    pub const DEFAULT_MAX_RETRIES: usize = 5;
+    /// The default delay before the first retry.
+    pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+    /// The default maximum delay between two attempts.
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+    /// Connect to the server at the specified address (e.g. `http://127.0.0.1:50051`),
+    /// failing if it is not reachable within `connect_timeout`.
+    pub async fn connect(
+        address: &str,
+        connect_timeout: Duration,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let endpoint = Self::endpoint(address, connect_timeout)?;
+        let channel = endpoint
+            .connect()
+            .await
+            .map_err(|e| format!("Failed to connect to the gRPC server at {address}: {e}"))?;
+        Ok(Self::from_channel(endpoint, channel))
+    }
+
+    /// Create a client for the server at the specified address without connecting to it: the
+    /// connection is established on the first call. Useful when the server may not be up yet.
+    pub fn connect_lazy(
+        address: &str,
+        connect_timeout: Duration,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let endpoint = Self::endpoint(address, connect_timeout)?;
+        let channel = endpoint.connect_lazy();
+        Ok(Self::from_channel(endpoint, channel))
+    }
+
+    /// Set how many times and how fast failed calls are retried.
+    pub fn with_retries(
+        mut self,
+        max_retries: usize,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+    ) -> Self {
+        self.max_retries = max_retries;
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff.max(initial_backoff);
+        self
+    }
+
+    /// The number of times the client reconnected to the server.
+    pub fn reconnects(&self) -> usize {
+        self.reconnects
+    }
+
+    /// Submit a transaction to consensus.
+    pub async fn submit(&mut self, transaction: Vec<u8>) -> Result<TransactionResponse, Status> {
+        self.call(|mut client| {
+            let request = TransactionRequest {
+                transaction: transaction.clone(),
+            };
+            async move { client.submit_transaction(request).await }
+        })
+        .await
+    }
+
+    /// Get the current consensus status of the node.
+    pub async fn status(&mut self) -> Result<ConsensusStatus, Status> {
+        self.call(|mut client| async move {
+            client.get_consensus_status(ConsensusStatusRequest {}).await
+        })
+        .await
+    }
+
+    /// Stream the commits of the node as they happen. Only establishing the stream is
+    /// retried: if the stream breaks, subscribe again.
+    pub async fn subscribe_commits(&mut self) -> Result<Streaming<CommitSummary>, Status> {
+        self.call(
+            |mut client| async move { client.subscribe_commits(SubscribeCommitsRequest {}).await },
+        )
+        .await
+    }
+
+    /// Whether the call failed because of the connection rather than the server rejecting it.
+    pub fn is_transport_error(status: &Status) -> bool {
+        match status.code() {
+            Code::Unavailable => true,
+            Code::Unknown => status.message().contains("transport error"),
+            _ => false,
+        }
+    }
+
+    fn endpoint(
+        address: &str,
+        connect_timeout: Duration,
+    ) -> Result<Endpoint, Box<dyn std::error::Error + Send + Sync>> {
+        let endpoint = Endpoint::from_shared(address.to_string())
+            .map_err(|e| format!("Invalid gRPC server address '{address}': {e}"))?;
+        Ok(endpoint.connect_timeout(connect_timeout))
+    }
+
+    fn from_channel(endpoint: Endpoint, channel: Channel) -> Self {
+        Self {
+            endpoint,
+            inner: MysticetiServiceClient::new(channel),
+            max_retries: Self::DEFAULT_MAX_RETRIES,
+            initial_backoff: Self::DEFAULT_INITIAL_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+            reconnects: 0,
+        }
+    }
+
+    /// Run the call, retrying it over a new connection (with exponential backoff) as long as
+    /// it fails with a transport error.
+    async fn call<F, Fut, R>(&mut self, f: F) -> Result<R, Status>
+    where
+        F: Fn(MysticetiServiceClient<Channel>) -> Fut,
+        Fut: Future<Output = Result<Response<R>, Status>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 0;
+        loop {
+            match f(self.inner.clone()).await.map(Response::into_inner) {
+                Err(status) if Self::is_transport_error(&status) && attempt < self.max_retries => {
+                    attempt += 1;
+                    warn!(
+                        "gRPC call to {} failed ({}), reconnecting in {:?} (attempt {}/{})",
+                        self.endpoint.uri(),
+                        status.message(),
+                        backoff,
+                        attempt,
+                        self.max_retries
+                    );
+                    sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                    self.reconnect().await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Replace the connection to the server. If the server is still unreachable, the next
+    /// call fails (and is retried) as usual.
+    async fn reconnect(&mut self) {
+        self.reconnects += 1;
+        match self.endpoint.connect().await {
+            Ok(channel) => self.inner = MysticetiServiceClient::new(channel),
+            Err(e) => debug!("Failed to reconnect to {}: {e}", self.endpoint.uri()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::MysticetiGrpcClient;
+
+    #[tokio::test]
+    async fn retry_unreachable_server() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let mut client = MysticetiGrpcClient::connect_lazy(&address, Duration::from_millis(100))
+            .unwrap()
+            .with_retries(2, Duration::from_millis(1), Duration::from_millis(2));
+        let status = client.status().await.unwrap_err();
+        assert!(MysticetiGrpcClient::is_transport_error(&status));
+        assert_eq!(client.reconnects(), 2);
+
+        assert!(
+            MysticetiGrpcClient::connect(&address, Duration::from_millis(100))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,9 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod client;
+pub mod proto;
+pub mod server;
+
+pub use client::MysticetiGrpcClient;
+pub use server::MysticetiGrpcServer;
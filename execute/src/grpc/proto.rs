@@ -0,0 +1,59 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The messages, client and server of the `mysticeti.grpc.MysticetiService`, generated by
+//! `build.rs` from `proto/mysticeti.proto`.
+
+tonic::include_proto!("mysticeti.grpc");
+
+pub use mysticeti_service_client::MysticetiServiceClient;
+pub use mysticeti_service_server::{MysticetiService, MysticetiServiceServer};
+
+impl From<consensus_core::BlockRef> for BlockRef {
+    fn from(block_ref: consensus_core::BlockRef) -> Self {
+        Self {
+            round: block_ref.round as u64,
+            authority: block_ref.author.value() as u32,
+            sequence: 0,
+        }
+    }
+}
+
+impl From<&consensus_core::CommittedSubDag> for CommitSummary {
+    fn from(subdag: &consensus_core::CommittedSubDag) -> Self {
+        Self {
+            commit_index: subdag.commit_ref.index as u64,
+            timestamp_ms: subdag.timestamp_ms,
+            blocks: subdag
+                .blocks
+                .iter()
+                .map(|block| block.reference().into())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prost::Message;
+
+    use super::{BlockRef, TransactionResponse};
+
+    #[test]
+    fn roundtrip_transaction_response() {
+        let response = TransactionResponse {
+            success: true,
+            message: "Transaction included in block".to_string(),
+            block_ref: Some(BlockRef {
+                round: 7,
+                authority: 2,
+                sequence: 0,
+            }),
+        };
+        let bytes = response.encode_to_vec();
+        assert_eq!(
+            TransactionResponse::decode(bytes.as_slice()).unwrap(),
+            response
+        );
+    }
+}
@@ -0,0 +1,305 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `MysticetiService` gRPC server of the validator, implementing the service generated
+//! from `proto/mysticeti.proto`.
+//!
+//! The commit stream is fed by the commit subscribers of the node, so it is lossy: a client
+//! falling more than `SUBSCRIBER_CAPACITY` commits behind does not hold consensus back, its
+//! stream ends with a `DATA_LOSS` status telling how many commits it missed, and it has to
+//! subscribe again.
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use consensus_core::CommittedSubDag;
+use futures::{StreamExt, stream::BoxStream};
+use tokio::{
+    net::TcpListener,
+    sync::broadcast::{self, error::RecvError},
+};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Request, Response, Status};
+use tracing::{debug, warn};
+
+use crate::grpc::proto::{
+    CommitSummary, ConsensusStatus, ConsensusStatusRequest, MysticetiService,
+    MysticetiServiceServer, SubscribeCommitsRequest, TransactionRequest, TransactionResponse,
+};
+use crate::validator::node::{NOT_RUNNING_MESSAGE, SharedTransactionClient};
+use crate::validator::stats::NodeStats;
+use crate::validator::verifier::TransactionCheck;
+
+/// Serves the `MysticetiService`: submits transactions to consensus, reports its status and
+/// streams its commits.
+#[derive(Clone)]
+pub struct MysticetiGrpcServer {
+    /// The client submitting transactions, only set while consensus is running.
+    transaction_client: SharedTransactionClient,
+    /// The commit statistics of the node.
+    stats: Arc<NodeStats>,
+    /// The number of transactions submitted through this server.
+    submitted_transactions: Arc<AtomicU64>,
+    /// The commits of the node, streamed to the subscribers.
+    commits: broadcast::Sender<Arc<CommittedSubDag>>,
+    /// The check rejecting the invalid transactions before they are submitted, if any.
+    transaction_check: Option<Arc<dyn TransactionCheck>>,
+}
+
+impl MysticetiGrpcServer {
+    pub(crate) fn new(
+        transaction_client: SharedTransactionClient,
+        stats: Arc<NodeStats>,
+        commits: broadcast::Sender<Arc<CommittedSubDag>>,
+    ) -> Self {
+        Self {
+            transaction_client,
+            stats,
+            submitted_transactions: Arc::new(AtomicU64::new(0)),
+            commits,
+            transaction_check: None,
+        }
+    }
+
+    /// Reject the transactions failing `check` with an `INVALID_ARGUMENT` status, instead of
+    /// submitting them to consensus.
+    pub(crate) fn with_transaction_check(mut self, check: Arc<dyn TransactionCheck>) -> Self {
+        self.transaction_check = Some(check);
+        self
+    }
+
+    /// Serve the gRPC requests on the specified address until the server fails.
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+        self.serve_with_shutdown(addr, std::future::pending()).await
+    }
+
+    /// Serve the gRPC requests on the specified address until the server fails or `shutdown`
+    /// completes, then stop accepting connections and wait for the open ones to close.
+    pub async fn serve_with_shutdown<F>(
+        self,
+        addr: SocketAddr,
+        shutdown: F,
+    ) -> Result<(), tonic::transport::Error>
+    where
+        F: Future<Output = ()>,
+    {
+        tonic::transport::Server::builder()
+            .add_service(MysticetiServiceServer::new(self))
+            .serve_with_shutdown(addr, shutdown)
+            .await
+    }
+
+    /// Serve the gRPC requests on a bound listener until the server fails or `shutdown`
+    /// completes. Binding first lets the caller report a port already in use before serving.
+    pub async fn serve_with_incoming_shutdown<F>(
+        self,
+        listener: TcpListener,
+        shutdown: F,
+    ) -> Result<(), tonic::transport::Error>
+    where
+        F: Future<Output = ()>,
+    {
+        tonic::transport::Server::builder()
+            .add_service(MysticetiServiceServer::new(self))
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+            .await
+    }
+
+    /// Submit a transaction to consensus, returning once it is included in a proposed block.
+    pub async fn submit(&self, transaction: Vec<u8>) -> Result<TransactionResponse, Status> {
+        let checked = self
+            .transaction_check
+            .as_ref()
+            .map(|check| check.check(&transaction));
+        if let Some(Err(reason)) = checked {
+            warn!("Rejected an invalid gRPC transaction: {}", reason);
+            let message = format!("Invalid transaction: {reason}");
+            return Err(Status::invalid_argument(message));
+        }
+        // Not `unavailable`, which clients retry as a transport error.
+        let Some(client) = self.transaction_client.read().clone() else {
+            return Err(Status::failed_precondition(NOT_RUNNING_MESSAGE));
+        };
+        match client.submit(vec![transaction]).await {
+            Ok((block_ref, _status)) => {
+                self.submitted_transactions.fetch_add(1, Ordering::Relaxed);
+                debug!("gRPC transaction included in block {:?}", block_ref);
+                Ok(TransactionResponse {
+                    success: true,
+                    message: "Transaction included in block".to_string(),
+                    block_ref: Some(block_ref.into()),
+                })
+            }
+            Err(e) => {
+                warn!("Failed to submit a gRPC transaction to consensus: {}", e);
+                Ok(TransactionResponse {
+                    success: false,
+                    message: format!("Failed to submit transaction: {e}"),
+                    block_ref: None,
+                })
+            }
+        }
+    }
+
+    /// The status of consensus on this node.
+    pub fn consensus_status(&self) -> ConsensusStatus {
+        ConsensusStatus {
+            is_running: self.transaction_client.read().is_some(),
+            current_round: self.stats.current_round(),
+            total_transactions: self.submitted_transactions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Stream the summaries of the commits of the node, from the next one. If the subscriber
+    /// falls behind and misses commits, the stream ends with a `DATA_LOSS` status.
+    pub fn commit_stream(&self) -> BoxStream<'static, Result<CommitSummary, Status>> {
+        let receiver = Some(self.commits.subscribe());
+        futures::stream::unfold(receiver, |receiver| async move {
+            let mut receiver = receiver?;
+            match receiver.recv().await {
+                Ok(subdag) => Some((Ok(CommitSummary::from(subdag.as_ref())), Some(receiver))),
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("A gRPC commit subscriber missed {} commits", missed);
+                    let status = Status::data_loss(format!(
+                        "Missed {missed} commits by falling behind, subscribe again"
+                    ));
+                    Some((Err(status), None))
+                }
+                Err(RecvError::Closed) => None,
+            }
+        })
+        .boxed()
+    }
+}
+
+#[tonic::async_trait]
+impl MysticetiService for MysticetiGrpcServer {
+    type SubscribeCommitsStream = BoxStream<'static, Result<CommitSummary, Status>>;
+
+    async fn submit_transaction(
+        &self,
+        request: Request<TransactionRequest>,
+    ) -> Result<Response<TransactionResponse>, Status> {
+        self.submit(request.into_inner().transaction)
+            .await
+            .map(Response::new)
+    }
+
+    async fn get_consensus_status(
+        &self,
+        _request: Request<ConsensusStatusRequest>,
+    ) -> Result<Response<ConsensusStatus>, Status> {
+        Ok(Response::new(self.consensus_status()))
+    }
+
+    async fn subscribe_commits(
+        &self,
+        _request: Request<SubscribeCommitsRequest>,
+    ) -> Result<Response<Self::SubscribeCommitsStream>, Status> {
+        Ok(Response::new(self.commit_stream()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+    use consensus_core::CommittedSubDag;
+    use futures::StreamExt;
+    use parking_lot::RwLock;
+    use tokio::sync::broadcast;
+    use tonic::Code;
+
+    use super::MysticetiGrpcServer;
+    use crate::grpc::MysticetiGrpcClient;
+    use crate::validator::verifier::{AcceptAllVerifier, SizeBoundedVerifier};
+    use crate::validator::{handler::test::subdag, stats::NodeStats};
+
+    /// A server of a node whose consensus is not running, streaming the commits of the channel.
+    fn server(capacity: usize) -> (MysticetiGrpcServer, broadcast::Sender<Arc<CommittedSubDag>>) {
+        let (commits, _) = broadcast::channel(capacity);
+        let server = MysticetiGrpcServer::new(
+            Arc::new(RwLock::new(None)),
+            Arc::new(NodeStats::default()),
+            commits.clone(),
+        );
+        (server, commits)
+    }
+
+    /// Serve on an ephemeral port and connect a client.
+    fn serve(server: &MysticetiGrpcServer) -> MysticetiGrpcClient {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+        let serving = server
+            .clone()
+            .serve_with_incoming_shutdown(listener, std::future::pending());
+        tokio::spawn(serving);
+
+        MysticetiGrpcClient::connect_lazy(&format!("http://{addr}"), Duration::from_millis(500))
+            .unwrap()
+            .with_retries(10, Duration::from_millis(10), Duration::from_millis(100))
+    }
+
+    #[tokio::test]
+    async fn report_consensus_status() {
+        let (server, _commits) = server(1);
+        server.stats.record_commit(&subdag(7, &[b"tx"]));
+        let mut client = serve(&server);
+
+        let status = client.status().await.unwrap();
+        assert!(!status.is_running);
+        assert_eq!(status.current_round, 7);
+        assert_eq!(status.total_transactions, 0);
+
+        // Without consensus, submissions are rejected and not counted.
+        let error = client.submit(b"tx".to_vec()).await.unwrap_err();
+        assert_eq!(error.code(), Code::FailedPrecondition);
+        assert_eq!(server.consensus_status().total_transactions, 0);
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_transactions() {
+        let (server, _commits) = server(1);
+        let check = SizeBoundedVerifier::new(AcceptAllVerifier, 4);
+        let server = server.with_transaction_check(Arc::new(check));
+        let mut client = serve(&server);
+
+        // Invalid transactions are rejected before reaching the transaction client.
+        let error = client.submit(b"too large".to_vec()).await.unwrap_err();
+        assert_eq!(error.code(), Code::InvalidArgument);
+        assert!(error.message().contains("Invalid transaction"));
+        let error = client.submit(b"tx".to_vec()).await.unwrap_err();
+        assert_eq!(error.code(), Code::FailedPrecondition);
+        assert_eq!(server.consensus_status().total_transactions, 0);
+    }
+
+    #[tokio::test]
+    async fn stream_commits() {
+        let (server, commits) = server(2);
+        let mut client = serve(&server);
+
+        let mut stream = client.subscribe_commits().await.unwrap();
+        commits.send(Arc::new(subdag(3, &[b"a", b"b"]))).unwrap();
+        let summary = stream.message().await.unwrap().unwrap();
+        assert_eq!(summary.commit_index, 3);
+        assert_eq!(summary.blocks.len(), 1);
+        assert_eq!(summary.blocks[0].round, 3);
+
+        // A subscriber falling behind is told how many commits it missed, then dropped.
+        let mut stream = server.commit_stream();
+        for index in 4..9 {
+            commits.send(Arc::new(subdag(index, &[]))).unwrap();
+        }
+        let error = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(error.code(), Code::DataLoss);
+        assert!(error.message().contains("Missed 3 commits"));
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -2,4 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod abci;
+pub mod client;
+#[cfg(any(test, feature = "testing"))]
+pub mod fuzz;
+pub mod genesis;
+pub mod grpc;
+pub mod reload;
+#[cfg(any(test, feature = "testing"))]
+pub mod selftest;
+pub mod shutdown;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod validator;
@@ -0,0 +1,114 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use tokio::signal::unix::{SignalKind, signal};
+use tracing::info;
+use tracing_subscriber::{EnvFilter, reload};
+
+use crate::validator::NodeConfig;
+
+/// The fields of the node configuration applied without a restart. The other tunables (e.g.
+/// `forward_workers`, `memory_guard`) configure tasks spawned once when the node starts.
+const RELOADABLE_FIELDS: &[&str] = &["log_filter"];
+
+/// The changes between the running configuration of a node and a reloaded one. Consensus
+/// does not support live reconfiguration (the committee is fixed for the lifetime of an
+/// authority), so only the tunables listed in `RELOADABLE_FIELDS` are applied; the other
+/// changes are reported and take effect on the next restart.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigReload {
+    /// The new log filter, if it changed.
+    pub log_filter: Option<Option<String>>,
+    /// The changed fields that require a restart.
+    pub requires_restart: Vec<String>,
+}
+
+impl ConfigReload {
+    /// Compare the running configuration with the reloaded one.
+    pub fn between(running: &NodeConfig, reloaded: &NodeConfig) -> Self {
+        let fields = |config: &NodeConfig| match serde_yaml::to_value(config) {
+            Ok(serde_yaml::Value::Mapping(fields)) => fields,
+            _ => unreachable!("The node config serializes to a mapping"),
+        };
+        let running_fields = fields(running);
+        let requires_restart = fields(reloaded)
+            .into_iter()
+            .filter(|(key, value)| running_fields.get(key) != Some(value))
+            .filter_map(|(key, _)| key.as_str().map(str::to_string))
+            .filter(|key| !RELOADABLE_FIELDS.contains(&key.as_str()))
+            .collect();
+
+        Self {
+            log_filter: (running.log_filter != reloaded.log_filter)
+                .then(|| reloaded.log_filter.clone()),
+            requires_restart,
+        }
+    }
+
+    /// Whether the reloaded configuration is identical to the running one.
+    pub fn is_empty(&self) -> bool {
+        self.log_filter.is_none() && self.requires_restart.is_empty()
+    }
+}
+
+/// Reads and replaces the log filter of the running process, so that operators can raise the
+/// verbosity of a misbehaving node without restarting it.
+pub trait LogFilterControl: Send + Sync {
+    /// The current filter directives.
+    fn current(&self) -> String;
+
+    /// Replace the filter with the specified directives (e.g. `info,consensus_core=debug`),
+    /// keeping the current one if they are invalid.
+    fn set(&self, directives: &str) -> Result<(), String>;
+}
+
+impl<S> LogFilterControl for reload::Handle<EnvFilter, S>
+where
+    S: tracing::Subscriber + 'static,
+{
+    fn current(&self) -> String {
+        self.with_current(|filter| filter.to_string())
+            .unwrap_or_default()
+    }
+
+    fn set(&self, directives: &str) -> Result<(), String> {
+        let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+        self.reload(filter).map_err(|e| e.to_string())
+    }
+}
+
+/// Call `on_hangup` every time the process receives SIGHUP, the conventional request for a
+/// daemon to reload its configuration. Runs until the task is dropped.
+pub async fn on_hangup<F: FnMut()>(mut on_hangup: F) {
+    let mut sighup = signal(SignalKind::hangup()).expect("Failed to register SIGHUP handler");
+    while sighup.recv().await.is_some() {
+        info!("Received SIGHUP, reloading the configuration");
+        on_hangup();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::ConfigReload;
+    use crate::validator::NodeConfig;
+
+    #[test]
+    fn split_reloadable_changes() {
+        let running = NodeConfig::new(0, PathBuf::from("data")).unwrap();
+        assert!(ConfigReload::between(&running, &running).is_empty());
+
+        let mut reloaded = running.clone();
+        reloaded.log_filter = Some("debug".to_string());
+        reloaded.committee.size = 7;
+        reloaded.forward_workers = 4;
+
+        let reload = ConfigReload::between(&running, &reloaded);
+        assert_eq!(reload.log_filter, Some(Some("debug".to_string())));
+        assert_eq!(
+            reload.requires_restart,
+            vec!["committee", "forward_workers"]
+        );
+    }
+}
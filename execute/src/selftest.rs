@@ -0,0 +1,361 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! An end-to-end check of the pipeline: starts an in-process network, submits unique
+//! transactions over HTTP and gRPC, confirms they commit in the same order on every node,
+//! scrapes the metrics of the nodes and tears the network down, reporting the outcome of every
+//! stage. This is the smoke test confirming that an environment works. Enabled by the
+//! `testing` feature.
+
+use std::{
+    collections::BTreeSet,
+    fmt,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::time::{sleep, timeout};
+
+use crate::grpc::MysticetiGrpcClient;
+use crate::testing::TestNetwork;
+use crate::validator::committed::transaction_id;
+
+/// How often the nodes are polled while waiting for the transactions to commit.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long the network has to shut down.
+const TEARDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+/// The metrics every node must expose once it committed.
+const REQUIRED_METRICS: &[&str] = &[
+    "committed_transactions_per_commit",
+    "committed_transactions_by_authority",
+    "time_to_first_commit_seconds",
+    "submission_wait_seconds",
+];
+
+/// The settings of a self-test.
+#[derive(Clone, Debug)]
+pub struct SelfTestSettings {
+    /// The number of validators of the network.
+    pub committee_size: usize,
+    /// The number of transactions to submit.
+    pub transactions: usize,
+    /// How long the transactions have to commit on every node.
+    pub commit_timeout: Duration,
+}
+
+impl Default for SelfTestSettings {
+    fn default() -> Self {
+        Self {
+            committee_size: 4,
+            transactions: 8,
+            commit_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// The outcome of a stage, with a short explanation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StageOutcome {
+    Passed(String),
+    Failed(String),
+    Skipped(String),
+}
+
+/// A stage of the self-test and its outcome.
+#[derive(Clone, Debug)]
+pub struct StageReport {
+    pub stage: &'static str,
+    pub outcome: StageOutcome,
+    pub elapsed: Duration,
+}
+
+/// The outcome of every stage of a self-test, in the order they ran.
+#[derive(Clone, Debug, Default)]
+pub struct SelfTestReport {
+    pub stages: Vec<StageReport>,
+}
+
+impl SelfTestReport {
+    /// Whether no stage failed.
+    pub fn passed(&self) -> bool {
+        !self
+            .stages
+            .iter()
+            .any(|stage| matches!(stage.outcome, StageOutcome::Failed(_)))
+    }
+
+    fn record<T>(
+        &mut self,
+        stage: &'static str,
+        start: Instant,
+        result: Result<(T, String), String>,
+    ) -> Option<T> {
+        let (value, outcome) = match result {
+            Ok((value, message)) => (Some(value), StageOutcome::Passed(message)),
+            Err(message) => (None, StageOutcome::Failed(message)),
+        };
+        self.stages.push(StageReport {
+            stage,
+            outcome,
+            elapsed: start.elapsed(),
+        });
+        value
+    }
+
+    fn skip(&mut self, stage: &'static str, reason: &str) {
+        self.stages.push(StageReport {
+            stage,
+            outcome: StageOutcome::Skipped(reason.to_string()),
+            elapsed: Duration::ZERO,
+        });
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for stage in &self.stages {
+            let (status, message) = match &stage.outcome {
+                StageOutcome::Passed(message) => ("PASS", message),
+                StageOutcome::Failed(message) => ("FAIL", message),
+                StageOutcome::Skipped(message) => ("SKIP", message),
+            };
+            writeln!(
+                f,
+                "{status} {:<8} {:>6.2}s  {message}",
+                stage.stage,
+                stage.elapsed.as_secs_f64()
+            )?;
+        }
+        let verdict = match self.passed() {
+            true => "passed",
+            false => "FAILED",
+        };
+        write!(f, "Self-test {verdict}")
+    }
+}
+
+/// Run every stage of the self-test. A failed stage skips the stages depending on it, but the
+/// network is always torn down.
+pub async fn run_selftest(settings: &SelfTestSettings) -> SelfTestReport {
+    let mut report = SelfTestReport::default();
+
+    let start = Instant::now();
+    let network = TestNetwork::start(settings.committee_size)
+        .await
+        .map(|network| {
+            let message = format!("{} nodes serving RPC", settings.committee_size);
+            (
+                network.with_commit_timeout(settings.commit_timeout),
+                message,
+            )
+        })
+        .map_err(|e| e.to_string());
+    let Some(network) = report.record("start", start, network) else {
+        for stage in ["http", "grpc", "commit", "order", "metrics", "teardown"] {
+            report.skip(stage, "the network did not start");
+        }
+        return report;
+    };
+
+    let start = Instant::now();
+    let submitted = submit(&network, settings.transactions).await;
+    let ids = report.record("http", start, submitted);
+
+    let start = Instant::now();
+    let submitted = submit_grpc(&network).await;
+    let grpc_id = report.record("grpc", start, submitted);
+
+    // The transaction submitted over gRPC must commit like the others.
+    let ids: Vec<_> = ids.unwrap_or_default().into_iter().chain(grpc_id).collect();
+    match ids.is_empty() {
+        false => {
+            let start = Instant::now();
+            let commits = await_commits(&network, &ids, settings.commit_timeout).await;
+            match report.record("commit", start, commits) {
+                Some(commits) => {
+                    let start = Instant::now();
+                    report.record("order", start, check_order(&commits));
+                }
+                None => report.skip("order", "the transactions did not commit"),
+            }
+        }
+        true => {
+            for stage in ["commit", "order"] {
+                report.skip(stage, "no transaction was submitted");
+            }
+        }
+    }
+
+    let start = Instant::now();
+    report.record("metrics", start, check_metrics(&network).await);
+
+    let start = Instant::now();
+    let teardown = match timeout(TEARDOWN_TIMEOUT, network.shutdown()).await {
+        Ok(()) => Ok(((), "all nodes stopped".to_string())),
+        Err(_) => Err(format!(
+            "the nodes did not stop within {TEARDOWN_TIMEOUT:?}"
+        )),
+    };
+    report.record("teardown", start, teardown);
+    report
+}
+
+/// Identifies the run in the transactions, unique across runs so that a transaction is never
+/// mistaken for an earlier one.
+fn run_id() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+}
+
+/// Submit unique transactions over HTTP, spread over the nodes. Returns their ids.
+async fn submit(
+    network: &TestNetwork,
+    transactions: usize,
+) -> Result<(Vec<String>, String), String> {
+    let run = run_id();
+    let mut ids = Vec::with_capacity(transactions);
+    for i in 0..transactions {
+        let transaction = format!("selftest {run} transaction {i}");
+        let id = network
+            .submit(transaction.as_bytes())
+            .await
+            .map_err(|e| format!("transaction {i} was rejected: {e}"))?;
+        ids.push(id);
+    }
+    let message = format!("{transactions} transactions accepted over HTTP");
+    Ok((ids, message))
+}
+
+/// Submit a unique transaction to the gRPC server of the first node, through the gRPC client.
+/// Returns its id.
+async fn submit_grpc(network: &TestNetwork) -> Result<(String, String), String> {
+    let address = network.grpc_url(0);
+    let mut client =
+        MysticetiGrpcClient::connect(&address, MysticetiGrpcClient::DEFAULT_CONNECT_TIMEOUT)
+            .await
+            .map_err(|e| e.to_string())?;
+    let transaction = format!("selftest {} grpc transaction", run_id());
+    let response = client
+        .submit(transaction.clone().into_bytes())
+        .await
+        .map_err(|e| format!("the gRPC call failed: {}", e.message()))?;
+    let Some(block_ref) = response.block_ref.filter(|_| response.success) else {
+        return Err(format!(
+            "the transaction was rejected: {}",
+            response.message
+        ));
+    };
+    let message = format!(
+        "1 transaction included in a block of round {} over gRPC",
+        block_ref.round
+    );
+    Ok((transaction_id(transaction.as_bytes()), message))
+}
+
+/// Poll `/tx_status` on every node until all of them committed every transaction. Returns the
+/// commit index of every transaction on every node.
+async fn await_commits(
+    network: &TestNetwork,
+    ids: &[String],
+    commit_timeout: Duration,
+) -> Result<(Vec<Vec<u32>>, String), String> {
+    let deadline = Instant::now() + commit_timeout;
+    loop {
+        let mut commits = Vec::with_capacity(network.nodes().len());
+        let mut pending = 0;
+        for node in 0..network.nodes().len() {
+            let statuses = network
+                .transaction_status(node, ids)
+                .await
+                .map_err(|e| format!("node {node} did not answer /tx_status: {e}"))?;
+            pending += statuses
+                .iter()
+                .filter(|status| status.commit_index.is_none())
+                .count();
+            commits.push(
+                statuses
+                    .iter()
+                    .map(|status| status.commit_index.unwrap_or_default())
+                    .collect(),
+            );
+        }
+        if pending == 0 {
+            let message = format!("every node committed the {} transactions", ids.len());
+            return Ok((commits, message));
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "{pending} commits missing across the nodes after {commit_timeout:?}"
+            ));
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Check that every node committed every transaction in the same commit.
+fn check_order(commits: &[Vec<u32>]) -> Result<((), String), String> {
+    let Some(reference) = commits.first() else {
+        return Err("no node reported its commits".to_string());
+    };
+    for (node, node_commits) in commits.iter().enumerate().skip(1) {
+        if let Some(i) = (0..reference.len()).find(|i| node_commits[*i] != reference[*i]) {
+            return Err(format!(
+                "transaction {i} committed in commit {} on node 0 but {} on node {node}",
+                reference[i], node_commits[i]
+            ));
+        }
+    }
+    let distinct: BTreeSet<_> = reference.iter().collect();
+    let message = format!(
+        "same commit sequence on {} nodes ({} commits)",
+        commits.len(),
+        distinct.len()
+    );
+    Ok(((), message))
+}
+
+/// Check that every node exposes its metrics on `GET /metrics`.
+async fn check_metrics(network: &TestNetwork) -> Result<((), String), String> {
+    for node in 0..network.nodes().len() {
+        let text = network
+            .metrics(node)
+            .await
+            .map_err(|e| format!("failed to scrape the metrics of node {node}: {e}"))?;
+        if let Some(missing) = REQUIRED_METRICS.iter().find(|name| !text.contains(**name)) {
+            return Err(format!("node {node} does not expose {missing}"));
+        }
+    }
+    let message = format!("{} metrics exposed by every node", REQUIRED_METRICS.len());
+    Ok(((), message))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SelfTestSettings, StageOutcome, check_order, run_selftest};
+
+    #[test]
+    fn detect_diverging_commits() {
+        assert!(check_order(&[vec![3, 3, 4], vec![3, 3, 4]]).is_ok());
+        let error = check_order(&[vec![3, 3, 4], vec![3, 4, 4]]).unwrap_err();
+        assert!(error.contains("transaction 1"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn selftest_passes() {
+        let settings = SelfTestSettings {
+            transactions: 4,
+            ..SelfTestSettings::default()
+        };
+        let report = run_selftest(&settings).await;
+        assert!(report.passed(), "{report}");
+        let stages: Vec<_> = report.stages.iter().map(|stage| stage.stage).collect();
+        assert_eq!(
+            stages,
+            [
+                "start", "http", "grpc", "commit", "order", "metrics", "teardown"
+            ]
+        );
+        assert!(matches!(report.stages[2].outcome, StageOutcome::Passed(_)));
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use tokio::signal::{
+    self,
+    unix::{SignalKind, signal},
+};
+use tracing::info;
+
+/// Wait until the process is asked to stop, either interactively (SIGINT, e.g. Ctrl+C) or by
+/// a supervisor such as Docker or systemd (SIGTERM). Listening to SIGTERM lets `docker stop`
+/// shut the node down cleanly instead of killing it after its grace period.
+pub async fn shutdown_signal() {
+    let mut sigterm = signal(SignalKind::terminate()).expect("Failed to register SIGTERM handler");
+    tokio::select! {
+        result = signal::ctrl_c() => {
+            result.expect("Failed to register SIGINT handler");
+            info!("Received SIGINT, shutting down");
+        }
+        _ = sigterm.recv() => info!("Received SIGTERM, shutting down"),
+    }
+}
@@ -0,0 +1,515 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Utilities to run a real Mysticeti network in the integration tests of downstream crates.
+//! Enabled by the `testing` feature, and in the tests of this crate.
+
+use std::{
+    net::{Ipv4Addr, TcpListener},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use base64::Engine;
+use consensus_config::{Committee, NetworkKeyPair, ProtocolKeyPair, local_committee_and_keys};
+use mysten_metrics::RegistryService;
+use prometheus::Registry;
+use serde::Deserialize;
+use tempfile::TempDir;
+use tokio::time::{Instant, sleep};
+
+use crate::validator::{NodeConfig, ValidatorNode, committed::TransactionStatus};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// How often the network is polled while waiting for the nodes to serve or commit.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long the nodes have to start serving RPC.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A network of validators running in the current process, with in-memory databases and
+/// ephemeral ports so that several networks (e.g. of concurrent tests) do not collide.
+/// Transactions are submitted through the RPC server of the nodes, as a client would.
+pub struct TestNetwork {
+    nodes: Vec<ValidatorNode>,
+    client: reqwest::Client,
+    /// The node receiving the next submission.
+    next_node: AtomicUsize,
+    /// How long `await_commit` waits for a transaction.
+    commit_timeout: Duration,
+    /// Holds the working directories of the nodes until the network is dropped.
+    _working_directory: TempDir,
+}
+
+impl TestNetwork {
+    /// The default time `await_commit` waits for a transaction to commit.
+    pub const DEFAULT_COMMIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Start a network of `committee_size` validators with equal stake.
+    pub async fn start(committee_size: usize) -> Result<Self, Error> {
+        Self::start_with(committee_size, |_| ()).await
+    }
+
+    /// Start a network of `committee_size` validators with equal stake, letting `configure`
+    /// adjust the configuration of every node (e.g. its consensus parameters). The addresses,
+    /// ports, and storage of the nodes are set by the network. The ABCI servers are disabled
+    /// unless `configure` enables them, as they cannot be shut down and would keep their ports
+    /// bound after the network stops.
+    pub async fn start_with<F>(committee_size: usize, configure: F) -> Result<Self, Error>
+    where
+        F: Fn(&mut NodeConfig),
+    {
+        let (committee, keypairs) = local_committee_and_keys(0, vec![1; committee_size]);
+        Self::start_committee(committee, keypairs, configure).await
+    }
+
+    /// Start a validator for every authority of the committee, with the key pairs of the
+    /// authorities.
+    async fn start_committee<F>(
+        committee: Committee,
+        keypairs: Vec<(NetworkKeyPair, ProtocolKeyPair)>,
+        configure: F,
+    ) -> Result<Self, Error>
+    where
+        F: Fn(&mut NodeConfig),
+    {
+        let working_directory = tempfile::tempdir()?;
+        let mut nodes = Vec::with_capacity(committee.size());
+        let started: Result<(), Error> = async {
+            for authority_index in 0..committee.size() as u32 {
+                let mut config = NodeConfig::new(authority_index, working_directory.path().into())?;
+                config.servers.abci = false;
+                configure(&mut config);
+                let config = NodeConfig {
+                    bind_address: Ipv4Addr::LOCALHOST.into(),
+                    rpc_port: ephemeral_port()?,
+                    abci_port: ephemeral_port()?,
+                    grpc_port: ephemeral_port()?,
+                    metrics_port: ephemeral_port()?,
+                    in_memory: true,
+                    ..config
+                };
+                let mut node = ValidatorNode::new(config);
+                node.start(
+                    committee.clone(),
+                    keypairs[authority_index as usize].clone(),
+                    RegistryService::new(Registry::new()),
+                )
+                .await?;
+                nodes.push(node);
+            }
+            Ok(())
+        }
+        .await;
+
+        let network = Self {
+            nodes,
+            client: reqwest::Client::new(),
+            next_node: AtomicUsize::new(0),
+            commit_timeout: Self::DEFAULT_COMMIT_TIMEOUT,
+            _working_directory: working_directory,
+        };
+        let serving = match started {
+            Ok(()) => network.wait_until_serving().await,
+            Err(e) => Err(e),
+        };
+        // Do not leave the nodes already started running when the network fails to start.
+        if let Err(e) = serving {
+            network.shutdown().await;
+            return Err(e);
+        }
+        Ok(network)
+    }
+
+    /// Wait until the RPC server of every node answers, as they are started in the background.
+    async fn wait_until_serving(&self) -> Result<(), Error> {
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        for node in 0..self.nodes.len() {
+            let url = format!("{}/health", self.rpc_url(node));
+            while !self
+                .client
+                .get(&url)
+                .send()
+                .await
+                .is_ok_and(|response| response.status().is_success())
+            {
+                if Instant::now() >= deadline {
+                    return Err(format!("Node {node} did not start serving RPC").into());
+                }
+                sleep(POLL_INTERVAL).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set how long `await_commit` waits for a transaction to commit.
+    pub fn with_commit_timeout(mut self, commit_timeout: Duration) -> Self {
+        self.commit_timeout = commit_timeout;
+        self
+    }
+
+    /// The nodes of the network, by authority index.
+    pub fn nodes(&self) -> &[ValidatorNode] {
+        &self.nodes
+    }
+
+    /// The base url of the RPC server of the specified node.
+    pub fn rpc_url(&self, node: usize) -> String {
+        format!("http://{}", self.nodes[node].config().rpc_address())
+    }
+
+    /// The url of the gRPC server of the specified node.
+    pub fn grpc_url(&self, node: usize) -> String {
+        format!("http://{}", self.nodes[node].config().grpc_address())
+    }
+
+    /// The url of the metrics server of the specified node.
+    pub fn metrics_url(&self, node: usize) -> String {
+        format!(
+            "http://{}/metrics",
+            self.nodes[node].config().metrics_address()
+        )
+    }
+
+    /// Submit a transaction to the next node (in round-robin order). Returns the id of the
+    /// transaction, to pass to `await_commit`.
+    pub async fn submit(&self, transaction: &[u8]) -> Result<String, Error> {
+        let node = self.next_node.fetch_add(1, Ordering::Relaxed) % self.nodes.len();
+        self.submit_to(node, transaction).await
+    }
+
+    /// Submit a transaction to the specified node. Returns the id of the transaction.
+    pub async fn submit_to(&self, node: usize, transaction: &[u8]) -> Result<String, Error> {
+        #[derive(Deserialize)]
+        struct Response {
+            message: String,
+            transaction_id: Option<String>,
+        }
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(transaction);
+        let response: Response = self
+            .client
+            .post(format!("{}/broadcast_tx_async", self.rpc_url(node)))
+            .json(&serde_json::json!({ "transaction": encoded }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        response.transaction_id.ok_or_else(|| {
+            format!("Node {node} rejected the transaction: {}", response.message).into()
+        })
+    }
+
+    /// Wait until every node committed the transaction, and return the index of its commit.
+    /// Fails if the transaction does not commit within the commit timeout.
+    pub async fn await_commit(&self, transaction_id: &str) -> Result<u32, Error> {
+        let deadline = Instant::now() + self.commit_timeout;
+        loop {
+            let commits: Vec<_> = self
+                .nodes
+                .iter()
+                .map(|node| node.committed().status(transaction_id).commit_index)
+                .collect();
+            let all_committed: Option<Vec<_>> = commits.iter().copied().collect();
+            if let Some(&commit_index) = all_committed.as_ref().and_then(|all| all.first()) {
+                return Ok(commit_index);
+            }
+            if Instant::now() >= deadline {
+                let committed = commits.iter().filter(|commit| commit.is_some()).count();
+                return Err(format!(
+                    "Transaction {transaction_id} committed on {committed}/{} nodes after {:?}",
+                    self.nodes.len(),
+                    self.commit_timeout
+                )
+                .into());
+            }
+            sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Look the transactions up on the `/tx_status` endpoint of the specified node.
+    pub async fn transaction_status(
+        &self,
+        node: usize,
+        transaction_ids: &[String],
+    ) -> Result<Vec<TransactionStatus>, Error> {
+        #[derive(Deserialize)]
+        struct Response {
+            transactions: Vec<TransactionStatus>,
+        }
+
+        let response: Response = self
+            .client
+            .post(format!("{}/tx_status", self.rpc_url(node)))
+            .json(&serde_json::json!({ "transaction_ids": transaction_ids }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.transactions)
+    }
+
+    /// Scrape the metrics of the specified node from its metrics server, in the Prometheus
+    /// text format.
+    pub async fn metrics(&self, node: usize) -> Result<String, Error> {
+        Ok(self
+            .client
+            .get(self.metrics_url(node))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?)
+    }
+
+    /// Stop the specified node, closing its RPC server.
+    pub async fn stop_node(&mut self, node: usize) {
+        self.nodes[node].stop().await;
+    }
+
+    /// Stop all the nodes.
+    pub async fn shutdown(mut self) {
+        for node in &mut self.nodes {
+            node.stop().await;
+        }
+    }
+}
+
+/// A port that is free at the time of the call, assigned by the OS.
+fn ephemeral_port() -> std::io::Result<u16> {
+    Ok(TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?
+        .local_addr()?
+        .port())
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+
+    use consensus_config::{committee_and_keys_with_addresses, local_committee_and_keys};
+    use fastcrypto::{ed25519::Ed25519KeyPair, traits::KeyPair};
+    use mysten_metrics::RegistryService;
+    use prometheus::Registry;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{TestNetwork, ephemeral_port};
+    use crate::grpc::proto::{MysticetiServiceClient, TransactionRequest};
+    use crate::validator::verifier::test::signed_transaction;
+    use crate::validator::{NodeConfig, ValidatorNode};
+
+    #[tokio::test]
+    async fn submit_and_commit() {
+        let network = TestNetwork::start(4).await.unwrap();
+        let first = network.submit(b"first transaction").await.unwrap();
+        let second = network.submit(b"second transaction").await.unwrap();
+        assert_ne!(first, second);
+
+        network.await_commit(&first).await.unwrap();
+        network.await_commit(&second).await.unwrap();
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn start_a_committee_addressed_by_hostname() {
+        // The addresses of a local committee (`/ip4/127.0.0.1/udp/<port>`), as a hostname: unlike
+        // `localhost`, it resolves to the IPv4 loopback interface whatever the setup of the host.
+        let (local, _) = local_committee_and_keys(0, vec![1; 4]);
+        let addresses = local
+            .authorities()
+            .map(|(_, authority)| {
+                let address = authority.address.to_string();
+                address.replace("/ip4/", "/dns/").parse().unwrap()
+            })
+            .collect();
+        let (committee, keypairs) = committee_and_keys_with_addresses(0, vec![1; 4], addresses);
+
+        // The nodes resolve the addresses of their peers to reach them over Anemo.
+        let network = TestNetwork::start_committee(committee, keypairs, |_| ())
+            .await
+            .unwrap();
+        let transaction_id = network.submit(b"transaction").await.unwrap();
+        network.await_commit(&transaction_id).await.unwrap();
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn submit_over_grpc() {
+        let network = TestNetwork::start(4).await.unwrap();
+        let mut client = MysticetiServiceClient::connect(network.grpc_url(0))
+            .await
+            .unwrap();
+        let request = TransactionRequest {
+            transaction: b"grpc transaction".to_vec(),
+        };
+        let response = client
+            .submit_transaction(request)
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.success, "{}", response.message);
+
+        // The transaction is included in a block proposed by the node.
+        let block_ref = response.block_ref.unwrap();
+        assert_eq!(block_ref.authority, 0);
+        assert!(block_ref.round > 0);
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_transactions_at_submission() {
+        let network = TestNetwork::start_with(4, |config| config.verifier.signatures = true)
+            .await
+            .unwrap();
+        let error = network.submit(b"unsigned transaction").await.unwrap_err();
+        assert!(error.to_string().contains("Invalid transaction"), "{error}");
+        let mut client = MysticetiServiceClient::connect(network.grpc_url(1))
+            .await
+            .unwrap();
+        let request = TransactionRequest {
+            transaction: b"unsigned transaction".to_vec(),
+        };
+        let status = client.submit_transaction(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        // The invalid transactions never reached consensus, which keeps committing.
+        let keypair = Ed25519KeyPair::generate(&mut StdRng::from_seed([0; 32]));
+        let transaction_id = network
+            .submit(&signed_transaction(&keypair, 0))
+            .await
+            .unwrap();
+        network.await_commit(&transaction_id).await.unwrap();
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn reject_transactions_of_invalid_size_at_submission() {
+        let configure = |config: &mut NodeConfig| config.verifier.max_transaction_size = Some(16);
+        let network = TestNetwork::start_with(4, configure).await.unwrap();
+        for transaction in [&[][..], &[0; 17]] {
+            let error = network.submit(transaction).await.unwrap_err();
+            assert!(error.to_string().contains("Invalid transaction"), "{error}");
+        }
+
+        // Consensus keeps committing the transactions of valid size.
+        let transaction_id = network.submit(&[0; 16]).await.unwrap();
+        network.await_commit(&transaction_id).await.unwrap();
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn reject_transactions_after_stop() {
+        let mut network = TestNetwork::start(4).await.unwrap();
+        network.stop_node(0).await;
+        assert!(!network.nodes()[0].is_running());
+
+        let error = network.submit_to(0, b"transaction").await.unwrap_err();
+        let refused = error
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect());
+        assert!(refused, "{error}");
+        network.submit_to(1, b"transaction").await.unwrap();
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn release_the_rpc_port_on_stop() {
+        let mut network = TestNetwork::start(1).await.unwrap();
+        let address = network.nodes()[0].config().rpc_address();
+        network.stop_node(0).await;
+
+        // A node restarted in the same process can serve on the same port.
+        tokio::net::TcpListener::bind(address).await.unwrap();
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn serve_prometheus_metrics() {
+        let network = TestNetwork::start(1).await.unwrap();
+        let response = reqwest::get(format!("{}/metrics", network.rpc_url(0)))
+            .await
+            .unwrap();
+        let content_type = &response.headers()[reqwest::header::CONTENT_TYPE];
+        assert_eq!(content_type, "text/plain; version=0.0.4");
+
+        // Every sample is a metric name, with optional labels, followed by its value.
+        let body = response.text().await.unwrap();
+        assert!(
+            body.contains("# TYPE submission_queue_capacity gauge"),
+            "{body}"
+        );
+        for sample in body.lines().filter(|line| !line.starts_with('#')) {
+            let (name, value) = sample.rsplit_once(' ').unwrap();
+            assert!(!name.is_empty() && !name.starts_with('{'), "{sample}");
+            value.parse::<f64>().unwrap();
+        }
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn serve_metrics_on_their_own_port() {
+        let mut network = TestNetwork::start_with(1, |config| config.servers.abci = true)
+            .await
+            .unwrap();
+        let response = reqwest::get(network.metrics_url(0)).await.unwrap();
+        assert!(response.status().is_success());
+        let body = response.text().await.unwrap();
+        assert!(body.contains("submission_queue_capacity"), "{body}");
+
+        // The ABCI server, when enabled, accepts connections as well.
+        let abci_address = network.nodes()[0].config().abci_address();
+        tokio::net::TcpStream::connect(abci_address).await.unwrap();
+
+        let metrics_address = network.nodes()[0].config().metrics_address();
+        network.stop_node(0).await;
+        tokio::net::TcpListener::bind(metrics_address)
+            .await
+            .unwrap();
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn disabled_servers_do_not_bind() {
+        let network = TestNetwork::start_with(1, |config| config.servers.metrics = false)
+            .await
+            .unwrap();
+        let config = network.nodes()[0].config();
+        tokio::net::TcpListener::bind(config.abci_address())
+            .await
+            .unwrap();
+        tokio::net::TcpListener::bind(config.metrics_address())
+            .await
+            .unwrap();
+        network.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn fail_to_start_on_a_port_in_use() {
+        let in_use = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let working_directory = tempfile::tempdir().unwrap();
+        let (committee, keypairs) = local_committee_and_keys(0, vec![1]);
+        let mut config = NodeConfig::new(0, working_directory.path().into()).unwrap();
+        config.servers.abci = false;
+        let config = NodeConfig {
+            bind_address: Ipv4Addr::LOCALHOST.into(),
+            rpc_port: ephemeral_port().unwrap(),
+            grpc_port: in_use.local_addr().unwrap().port(),
+            metrics_port: ephemeral_port().unwrap(),
+            in_memory: true,
+            ..config
+        };
+
+        let mut node = ValidatorNode::new(config);
+        let registry_service = RegistryService::new(Registry::new());
+        let error = node
+            .start(committee, keypairs[0].clone(), registry_service)
+            .await
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("Failed to bind the gRPC server"),
+            "{error}"
+        );
+        // The failed start stopped consensus, which was already running.
+        assert!(!node.is_running());
+    }
+}
@@ -1,9 +1,10 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::time::Duration;
+use std::fmt;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use tracing::info;
+use tracing::{info, warn};
 
 pub async fn test_transaction_sending() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     info!("Starting transaction test client...");
@@ -51,7 +52,70 @@ pub async fn test_transaction_sending() -> Result<(), Box<dyn std::error::Error
     Ok(())
 }
 
-pub async fn check_network_health() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// The outcome of the health check of a node.
+pub struct NodeHealth {
+    pub index: usize,
+    pub endpoint: String,
+    pub healthy: bool,
+    /// The HTTP status or the error of the health check.
+    pub status: String,
+    /// How long the node took to answer, if it did.
+    pub latency: Option<Duration>,
+}
+
+/// The health of all the nodes of the network.
+pub struct HealthSummary {
+    pub nodes: Vec<NodeHealth>,
+}
+
+impl HealthSummary {
+    /// The number of healthy nodes.
+    pub fn healthy(&self) -> usize {
+        self.nodes.iter().filter(|node| node.healthy).count()
+    }
+
+    /// The number of unhealthy nodes.
+    pub fn unhealthy(&self) -> usize {
+        self.nodes.len() - self.healthy()
+    }
+
+    /// Whether every node is healthy.
+    pub fn all_healthy(&self) -> bool {
+        self.unhealthy() == 0
+    }
+}
+
+impl fmt::Display for HealthSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<5} {:<24} {:<9} {:>8}  STATUS",
+            "NODE", "ENDPOINT", "HEALTH", "LATENCY"
+        )?;
+        for node in &self.nodes {
+            let latency = node
+                .latency
+                .map(|latency| format!("{} ms", latency.as_millis()))
+                .unwrap_or_else(|| "-".to_string());
+            let health = if node.healthy { "healthy" } else { "UNHEALTHY" };
+            writeln!(
+                f,
+                "{:<5} {:<24} {:<9} {:>8}  {}",
+                node.index, node.endpoint, health, latency, node.status
+            )?;
+        }
+        write!(
+            f,
+            "{} healthy, {} unhealthy",
+            self.healthy(),
+            self.unhealthy()
+        )
+    }
+}
+
+/// Query the `/health` endpoint of every node and summarize the results.
+pub async fn check_network_health()
+-> Result<HealthSummary, Box<dyn std::error::Error + Send + Sync>> {
     info!("Checking network health...");
 
     let endpoints = vec![
@@ -61,23 +125,39 @@ pub async fn check_network_health() -> Result<(), Box<dyn std::error::Error + Se
         "http://127.0.0.1:26660",
     ];
 
+    // Do not let a hung node stall the check.
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()?;
+
+    let mut nodes = Vec::with_capacity(endpoints.len());
     for (i, endpoint) in endpoints.iter().enumerate() {
         let url = format!("{}/health", endpoint);
 
-        let client = reqwest::Client::new();
-        match client.get(&url).send().await {
+        let start = Instant::now();
+        let (healthy, status, latency) = match client.get(&url).send().await {
             Ok(response) => {
-                if response.status().is_success() {
-                    info!("Node {} is healthy", i);
-                } else {
-                    info!("Node {} returned status: {}", i, response.status());
-                }
-            }
-            Err(e) => {
-                info!("Node {} health check failed: {}", i, e);
+                let status = response.status();
+                (
+                    status.is_success(),
+                    status.to_string(),
+                    Some(start.elapsed()),
+                )
             }
+            Err(e) => (false, e.to_string(), None),
+        };
+        match healthy {
+            true => info!("Node {} is healthy", i),
+            false => warn!("Node {} is unhealthy: {}", i, status),
         }
+        nodes.push(NodeHealth {
+            index: i,
+            endpoint: endpoint.to_string(),
+            healthy,
+            status,
+            latency,
+        });
     }
 
-    Ok(())
+    Ok(HealthSummary { nodes })
 }
@@ -48,9 +48,17 @@ async fn main() -> Result<()> {
         }
         Operation::CheckHealth => {
             println!("Checking validator network health...");
-            check_network_health()
+            let summary = check_network_health()
                 .await
                 .map_err(|e| eyre::eyre!("{}", e))?;
+            println!("{summary}");
+            if !summary.all_healthy() {
+                eyre::bail!(
+                    "{} of {} nodes are unhealthy",
+                    summary.unhealthy(),
+                    summary.nodes.len()
+                );
+            }
         }
     }
 
@@ -0,0 +1,185 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Operator endpoints of the RPC server. They are disabled unless the node is configured with
+//! an admin token, and every request must present it as `Authorization: Bearer <token>`.
+
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::reload::LogFilterControl;
+
+#[derive(Deserialize)]
+struct LogLevelRequest {
+    /// The new filter directives, e.g. `info,consensus_core=debug`.
+    filter: String,
+}
+
+#[derive(Serialize)]
+struct LogLevelResponse {
+    success: bool,
+    message: String,
+    /// The filter in effect after the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filter: Option<String>,
+}
+
+impl LogLevelResponse {
+    fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<Self>) {
+        let response = Self {
+            success: false,
+            message: message.into(),
+            filter: None,
+        };
+        (status, Json(response))
+    }
+}
+
+/// Compare two tokens in constant time, so that response times do not leak the admin token.
+fn tokens_match(expected: &str, presented: &str) -> bool {
+    expected.len() == presented.len()
+        && expected
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Check that the request presents the admin token.
+fn authorize(headers: &HeaderMap, token: Option<&str>) -> Result<(), (StatusCode, String)> {
+    let Some(token) = token else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Admin endpoints are disabled (no admin token configured)".to_string(),
+        ));
+    };
+    let presented = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match presented {
+        Some(presented) if tokens_match(token, presented) => Ok(()),
+        _ => Err((
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid admin token".to_string(),
+        )),
+    }
+}
+
+/// The routes of the admin endpoints: `GET /admin/log-level` returns the log filter of the
+/// node and `POST /admin/log-level` replaces it (until the next restart or SIGHUP reload).
+pub fn router(token: Option<String>, log_filter: Option<Arc<dyn LogFilterControl>>) -> Router {
+    let token: Option<Arc<str>> = token.map(Into::into);
+    let get_token = token.clone();
+    let get_log_filter = log_filter.clone();
+    Router::new().route(
+        "/admin/log-level",
+        get(move |headers: HeaderMap| async move {
+            if let Err((status, message)) = authorize(&headers, get_token.as_deref()) {
+                return LogLevelResponse::error(status, message);
+            }
+            let Some(log_filter) = get_log_filter else {
+                let message = "The log filter of this node cannot be changed at runtime";
+                return LogLevelResponse::error(StatusCode::NOT_IMPLEMENTED, message);
+            };
+            let response = LogLevelResponse {
+                success: true,
+                message: "Current log filter".to_string(),
+                filter: Some(log_filter.current()),
+            };
+            (StatusCode::OK, Json(response))
+        })
+        .post(
+            move |headers: HeaderMap, Json(request): Json<LogLevelRequest>| async move {
+                if let Err((status, message)) = authorize(&headers, token.as_deref()) {
+                    warn!("Rejected an admin request: {}", message);
+                    return LogLevelResponse::error(status, message);
+                }
+                let Some(log_filter) = log_filter else {
+                    let message = "The log filter of this node cannot be changed at runtime";
+                    return LogLevelResponse::error(StatusCode::NOT_IMPLEMENTED, message);
+                };
+                if let Err(e) = log_filter.set(&request.filter) {
+                    let message = format!("Invalid log filter '{}': {}", request.filter, e);
+                    return LogLevelResponse::error(StatusCode::BAD_REQUEST, message);
+                }
+                info!("Log filter set to '{}' by an admin request", request.filter);
+                let response = LogLevelResponse {
+                    success: true,
+                    message: "Log filter updated".to_string(),
+                    filter: Some(log_filter.current()),
+                };
+                (StatusCode::OK, Json(response))
+            },
+        ),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use super::{router, tokens_match};
+    use crate::reload::LogFilterControl;
+
+    /// Stores the filter instead of reloading the global subscriber.
+    #[derive(Default)]
+    struct TestLogFilter(Mutex<String>);
+
+    impl LogFilterControl for TestLogFilter {
+        fn current(&self) -> String {
+            self.0.lock().clone()
+        }
+
+        fn set(&self, directives: &str) -> Result<(), String> {
+            if directives.contains(' ') {
+                return Err("invalid directive".to_string());
+            }
+            *self.0.lock() = directives.to_string();
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn set_log_level() {
+        let log_filter = Arc::new(TestLogFilter::default());
+        let app = router(Some("secret".to_string()), Some(log_filter.clone()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/admin/log-level", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = reqwest::Client::new();
+        let set = |token: &'static str, filter: &'static str| {
+            client
+                .post(&url)
+                .bearer_auth(token)
+                .json(&serde_json::json!({ "filter": filter }))
+                .send()
+        };
+
+        let response = set("secret", "info,consensus_core=debug").await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(log_filter.current(), "info,consensus_core=debug");
+
+        assert_eq!(set("wrong", "trace").await.unwrap().status(), 401);
+        assert_eq!(set("secret", "not valid").await.unwrap().status(), 400);
+        assert_eq!(log_filter.current(), "info,consensus_core=debug");
+
+        let response = client.get(&url).bearer_auth("secret").send().await.unwrap();
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["filter"], "info,consensus_core=debug");
+        assert_eq!(client.get(&url).send().await.unwrap().status(), 401);
+
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secre"));
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{fmt, io::Read, str::FromStr};
+
+use serde::Deserialize;
+
+/// The encoding of a request body, from its `Content-Encoding` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentEncoding {
+    Identity,
+    Gzip,
+    Zstd,
+}
+
+impl FromStr for ContentEncoding {
+    type Err = BatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" | "identity" => Ok(Self::Identity),
+            "gzip" | "x-gzip" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd),
+            other => Err(BatchError::Invalid(format!(
+                "Unsupported content encoding '{other}' (expected identity, gzip, or zstd)"
+            ))),
+        }
+    }
+}
+
+impl ContentEncoding {
+    /// Decompress the body. Fails if the decompressed body exceeds `limit` bytes, so that a
+    /// small compressed body cannot exhaust the memory of the node.
+    pub fn decode(&self, body: &[u8], limit: usize) -> Result<Vec<u8>, BatchError> {
+        let invalid = |e: std::io::Error| {
+            BatchError::Invalid(format!("Failed to decompress the request body: {e}"))
+        };
+        let reader: Box<dyn Read + '_> = match self {
+            Self::Identity => Box::new(body),
+            Self::Gzip => Box::new(flate2::read::GzDecoder::new(body)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::new(body).map_err(invalid)?),
+        };
+
+        let mut decoded = Vec::new();
+        reader
+            .take(limit as u64 + 1)
+            .read_to_end(&mut decoded)
+            .map_err(invalid)?;
+        if decoded.len() > limit {
+            return Err(BatchError::TooLarge { limit });
+        }
+        Ok(decoded)
+    }
+}
+
+/// Why a batch request was rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchError {
+    /// The (decompressed) body exceeds the request size limit.
+    TooLarge { limit: usize },
+    /// The body is not a valid batch.
+    Invalid(String),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge { limit } => write!(
+                f,
+                "The decompressed request body exceeds the limit of {limit} bytes"
+            ),
+            Self::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+/// The body of a `/broadcast_tx_batch` request.
+#[derive(Deserialize)]
+struct BatchRequest {
+    /// The base64 encoded transactions.
+    transactions: Vec<String>,
+}
+
+/// Decode the transactions of a `/broadcast_tx_batch` request, decompressing its body first
+/// according to its `Content-Encoding` header (if any).
+pub fn decode_batch(
+    content_encoding: Option<&str>,
+    body: &[u8],
+    limit: usize,
+) -> Result<Vec<Vec<u8>>, BatchError> {
+    let encoding: ContentEncoding = content_encoding.unwrap_or_default().parse()?;
+    let body = encoding.decode(body, limit)?;
+    let request: BatchRequest = serde_json::from_slice(&body)
+        .map_err(|e| BatchError::Invalid(format!("Invalid batch request: {e}")))?;
+
+    request
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(i, transaction)| {
+            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, transaction)
+                .map_err(|e| BatchError::Invalid(format!("Invalid transaction {i}: {e}")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::{BatchError, decode_batch};
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn decode_compressed_batches() {
+        let body = br#"{"transactions":["AQID","BAU="]}"#;
+        let expected = vec![vec![1, 2, 3], vec![4, 5]];
+
+        assert_eq!(decode_batch(None, body, 1024).unwrap(), expected);
+        assert_eq!(
+            decode_batch(Some("gzip"), &gzip(body), 1024).unwrap(),
+            expected
+        );
+        let compressed = zstd::encode_all(&body[..], 0).unwrap();
+        assert_eq!(
+            decode_batch(Some("zstd"), &compressed, 1024).unwrap(),
+            expected
+        );
+
+        assert!(matches!(
+            decode_batch(Some("br"), body, 1024),
+            Err(BatchError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn reject_decompression_bombs() {
+        // A megabyte of zeros compresses to about a kilobyte.
+        let transaction = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            vec![0u8; 1024 * 1024],
+        );
+        let body = format!(r#"{{"transactions":["{transaction}"]}}"#);
+        let compressed = gzip(body.as_bytes());
+        assert!(compressed.len() < 64 * 1024);
+
+        assert_eq!(
+            decode_batch(Some("gzip"), &compressed, 64 * 1024),
+            Err(BatchError::TooLarge { limit: 64 * 1024 })
+        );
+    }
+}
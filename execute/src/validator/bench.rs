@@ -0,0 +1,366 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A micro-benchmark of the transaction verifiers: synthetic batches are fed to a verifier
+//! built as in a node, without consensus or networking, to isolate the CPU cost of
+//! verification when tuning the number of verification threads and the batch size from which
+//! batches are split across them.
+
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use fastcrypto::{
+    ed25519::Ed25519KeyPair,
+    traits::{KeyPair, Signer, ToFromBytes},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::validator::{config::VerifierConfig, verifier::build_verifier};
+
+/// The batch sizes verified by a sweep.
+pub const SWEEP_BATCH_SIZES: &[usize] = &[8, 16, 32, 64, 128, 256, 512, 1024];
+/// The numbers of threads verifying every batch size of a sweep. The first one is the
+/// reference the others are compared to.
+pub const SWEEP_THREADS: &[usize] = &[1, 2, 4, 8];
+
+/// The verifier to benchmark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BenchVerifier {
+    /// Accepts every transaction: the overhead of the verification pipeline alone.
+    AcceptAll,
+    /// Checks the Ed25519 signature of every transaction.
+    Signature,
+}
+
+impl fmt::Display for BenchVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AcceptAll => write!(f, "accept-all"),
+            Self::Signature => write!(f, "signature"),
+        }
+    }
+}
+
+impl FromStr for BenchVerifier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "accept-all" => Ok(Self::AcceptAll),
+            "signature" => Ok(Self::Signature),
+            _ => Err(format!(
+                "Unknown verifier '{s}' (expected accept-all or signature)"
+            )),
+        }
+    }
+}
+
+/// The parameters of a verifier benchmark.
+#[derive(Clone, Debug)]
+pub struct VerifierBenchConfig {
+    pub verifier: BenchVerifier,
+    /// The number of transactions per batch.
+    pub batch_size: usize,
+    /// The number of batches to verify.
+    pub batches: usize,
+    /// The size of the transactions, in bytes.
+    pub transaction_size: usize,
+    /// The number of verification threads, as in the node configuration.
+    pub threads: usize,
+    /// The batch size below which verification runs on a single thread, as in the node
+    /// configuration.
+    pub min_parallel_batch: usize,
+}
+
+/// The outcome of a verifier benchmark.
+#[derive(Clone, Debug)]
+pub struct VerifierBenchReport {
+    /// The number of verified transactions.
+    pub transactions: usize,
+    /// The total time spent verifying.
+    pub elapsed: Duration,
+    /// The time taken to verify each batch, sorted.
+    pub batch_latencies: Vec<Duration>,
+}
+
+impl VerifierBenchReport {
+    /// The number of transactions verified per second.
+    pub fn verifications_per_second(&self) -> f64 {
+        match self.elapsed.is_zero() {
+            true => 0.0,
+            false => self.transactions as f64 / self.elapsed.as_secs_f64(),
+        }
+    }
+
+    /// The time taken to verify a batch at the given percentile (in [0, 100]).
+    pub fn batch_latency(&self, percentile: f64) -> Duration {
+        if self.batch_latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile / 100.0 * (self.batch_latencies.len() - 1) as f64).round();
+        self.batch_latencies[rank as usize]
+    }
+
+    /// The average time taken to verify a single transaction.
+    pub fn transaction_latency(&self) -> Duration {
+        match self.transactions {
+            0 => Duration::ZERO,
+            n => self.elapsed / n as u32,
+        }
+    }
+}
+
+impl fmt::Display for VerifierBenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transactions: {}", self.transactions)?;
+        writeln!(f, "Elapsed: {:.3} s", self.elapsed.as_secs_f64())?;
+        writeln!(
+            f,
+            "Throughput: {:.0} verifications/s",
+            self.verifications_per_second()
+        )?;
+        writeln!(f, "Per transaction: {:?}", self.transaction_latency())?;
+        write!(
+            f,
+            "Per batch: p50 {:?}, p99 {:?}, max {:?}",
+            self.batch_latency(50.0),
+            self.batch_latency(99.0),
+            self.batch_latency(100.0)
+        )
+    }
+}
+
+/// A structured transaction of (at least) `size` bytes, signed by the sender of its header as
+/// the benchmark clients do with `--sign`.
+pub fn signed_transaction(keypair: &Ed25519KeyPair, nonce: u64, size: usize) -> Vec<u8> {
+    // The header (magic, version, kind, payload length, sender), the nonce and the signature.
+    const OVERHEAD: usize = 42 + 16 + 64;
+    let payload_size = size.saturating_sub(OVERHEAD);
+
+    let mut transaction = b"MYST".to_vec();
+    transaction.extend([1, 0]);
+    transaction.extend((payload_size as u32).to_le_bytes());
+    transaction.extend(keypair.public().as_bytes());
+    transaction.extend(nonce.to_le_bytes());
+    transaction.extend(0u64.to_le_bytes());
+    transaction.resize(transaction.len() + payload_size, 7);
+    let signature = keypair.sign(&transaction);
+    transaction.extend(signature.as_bytes());
+    transaction
+}
+
+/// Generate a batch of synthetic transactions valid for the verifier.
+fn synthetic_batch(config: &VerifierBenchConfig, rng: &mut StdRng) -> Vec<Vec<u8>> {
+    match config.verifier {
+        BenchVerifier::AcceptAll => (0..config.batch_size)
+            .map(|_| {
+                let mut transaction = vec![0; config.transaction_size];
+                rng.fill(transaction.as_mut_slice());
+                transaction
+            })
+            .collect(),
+        BenchVerifier::Signature => {
+            let keypair = Ed25519KeyPair::generate(rng);
+            (0..config.batch_size)
+                .map(|nonce| signed_transaction(&keypair, nonce as u64, config.transaction_size))
+                .collect()
+        }
+    }
+}
+
+/// Verify `config.batches` synthetic batches with the selected verifier, one after the other,
+/// and time each of them. Fails if the verifier rejects a batch.
+pub fn run_verifier_bench(config: &VerifierBenchConfig) -> Result<VerifierBenchReport, String> {
+    let verifier = build_verifier(&VerifierConfig {
+        threads: config.threads,
+        signatures: config.verifier == BenchVerifier::Signature,
+        min_parallel_batch: config.min_parallel_batch,
+        ..Default::default()
+    })
+    .map_err(|e| format!("Failed to start the verifier threads: {e}"))?;
+
+    // Generate the batches up front so that only the verification is timed.
+    let mut rng = StdRng::seed_from_u64(0);
+    let batches: Vec<_> = (0..config.batches)
+        .map(|_| synthetic_batch(config, &mut rng))
+        .collect();
+
+    let mut batch_latencies = Vec::with_capacity(batches.len());
+    let start = Instant::now();
+    for (i, batch) in batches.iter().enumerate() {
+        let batch: Vec<&[u8]> = batch.iter().map(|tx| tx.as_slice()).collect();
+        let batch_start = Instant::now();
+        verifier
+            .verify_batch(&batch)
+            .map_err(|e| format!("Batch {i} was rejected: {e:?}"))?;
+        batch_latencies.push(batch_start.elapsed());
+    }
+    let elapsed = start.elapsed();
+
+    batch_latencies.sort();
+    Ok(VerifierBenchReport {
+        transactions: config.batches * config.batch_size,
+        elapsed,
+        batch_latencies,
+    })
+}
+
+/// The throughput of a verifier for every batch size and number of threads of a sweep.
+#[derive(Clone, Debug)]
+pub struct VerifierSweepReport {
+    /// The numbers of threads, the first one being the reference.
+    pub threads: Vec<usize>,
+    /// Every batch size, in increasing order, with the verifications per second for every
+    /// number of threads.
+    pub rows: Vec<(usize, Vec<f64>)>,
+}
+
+impl VerifierSweepReport {
+    /// The smallest batch size from which splitting the batches across threads is faster than
+    /// verifying them on the reference number of threads, for that size and every larger one.
+    /// This is the value of `min_parallel_batch` for the machine. `None` if splitting never
+    /// pays off.
+    pub fn crossover(&self) -> Option<usize> {
+        let faster = |throughputs: &[f64]| throughputs[1..].iter().any(|t| *t > throughputs[0]);
+        let first_faster = match self.rows.iter().rposition(|(_, t)| !faster(t)) {
+            Some(slower) => slower + 1,
+            None => 0,
+        };
+        self.rows
+            .get(first_faster)
+            .map(|(batch_size, _)| *batch_size)
+    }
+}
+
+impl fmt::Display for VerifierSweepReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>10}", "batch")?;
+        for threads in &self.threads {
+            write!(f, " {:>12}", format!("{threads} threads"))?;
+        }
+        writeln!(f)?;
+        for (batch_size, throughputs) in &self.rows {
+            write!(f, "{batch_size:>10}")?;
+            for throughput in throughputs {
+                write!(f, " {throughput:>12.0}")?;
+            }
+            writeln!(f)?;
+        }
+        match self.crossover() {
+            Some(batch_size) => write!(
+                f,
+                "Splitting pays off from batches of {batch_size} transactions (min_parallel_batch)"
+            ),
+            None => write!(f, "Splitting never pays off for these batch sizes"),
+        }
+    }
+}
+
+/// Run the benchmark for every batch size against every number of threads, always splitting
+/// the batches, and report the verifications per second of each run.
+pub fn run_verifier_sweep(
+    config: &VerifierBenchConfig,
+    batch_sizes: &[usize],
+    threads: &[usize],
+) -> Result<VerifierSweepReport, String> {
+    let rows = batch_sizes
+        .iter()
+        .map(|batch_size| {
+            let throughputs = threads
+                .iter()
+                .map(|threads| {
+                    let config = VerifierBenchConfig {
+                        batch_size: *batch_size,
+                        threads: *threads,
+                        min_parallel_batch: 0,
+                        ..config.clone()
+                    };
+                    run_verifier_bench(&config).map(|report| report.verifications_per_second())
+                })
+                .collect::<Result<_, _>>()?;
+            Ok((*batch_size, throughputs))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(VerifierSweepReport {
+        threads: threads.to_vec(),
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        BenchVerifier, VerifierBenchConfig, VerifierSweepReport, run_verifier_bench,
+        run_verifier_sweep,
+    };
+
+    #[test]
+    fn bench_signature_verifier() {
+        let config = VerifierBenchConfig {
+            verifier: "signature".parse().unwrap(),
+            batch_size: 16,
+            batches: 3,
+            transaction_size: 128,
+            threads: 2,
+            min_parallel_batch: 0,
+        };
+        let report = run_verifier_bench(&config).unwrap();
+        assert_eq!(report.transactions, 48);
+        assert_eq!(report.batch_latencies.len(), 3);
+        assert!(report.verifications_per_second() > 0.0);
+        assert!(report.batch_latency(50.0) <= report.batch_latency(100.0));
+
+        assert_eq!(
+            "accept-all".parse::<BenchVerifier>(),
+            Ok(BenchVerifier::AcceptAll)
+        );
+        assert!("replay-guard".parse::<BenchVerifier>().is_err());
+    }
+
+    #[test]
+    fn sweep_batch_sizes_and_threads() {
+        let config = VerifierBenchConfig {
+            verifier: BenchVerifier::AcceptAll,
+            batch_size: 0,
+            batches: 2,
+            transaction_size: 16,
+            threads: 0,
+            min_parallel_batch: 0,
+        };
+        let report = run_verifier_sweep(&config, &[4, 32], &[1, 2]).unwrap();
+        assert_eq!(report.threads, vec![1, 2]);
+        let batch_sizes: Vec<_> = report.rows.iter().map(|(size, _)| *size).collect();
+        assert_eq!(batch_sizes, vec![4, 32]);
+        assert!(
+            report
+                .rows
+                .iter()
+                .all(|(_, throughputs)| throughputs.len() == 2)
+        );
+    }
+
+    #[test]
+    fn find_the_crossover() {
+        let report = |rows: &[(usize, [f64; 3])]| VerifierSweepReport {
+            threads: vec![1, 2, 4],
+            rows: rows.iter().map(|(size, t)| (*size, t.to_vec())).collect(),
+        };
+        // Faster from 64 on, with a noisy run at 16.
+        let sweep = report(&[
+            (16, [10.0, 12.0, 9.0]),
+            (32, [10.0, 8.0, 9.0]),
+            (64, [10.0, 11.0, 9.0]),
+            (128, [10.0, 15.0, 19.0]),
+        ]);
+        assert_eq!(sweep.crossover(), Some(64));
+        assert!(sweep.to_string().contains("from batches of 64"));
+
+        let sweep = report(&[(16, [10.0, 12.0, 13.0])]);
+        assert_eq!(sweep.crossover(), Some(16));
+        let sweep = report(&[(16, [10.0, 12.0, 13.0]), (32, [10.0, 9.0, 8.0])]);
+        assert_eq!(sweep.crossover(), None);
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use consensus_config::DefaultHashFunction;
+use consensus_core::{BlockAPI, CommittedSubDag};
+use fastcrypto::hash::HashFunction;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// The digest identifying a transaction.
+type TransactionDigest = [u8; 32];
+
+/// The id of a transaction: the hex encoding of the digest of its bytes. It is returned to the
+/// client on submission, and can then be looked up on `/tx_status`.
+pub fn transaction_id(transaction: &[u8]) -> String {
+    encode(&digest(transaction))
+}
+
+fn digest(transaction: &[u8]) -> TransactionDigest {
+    DefaultHashFunction::digest(transaction).digest
+}
+
+fn encode(digest: &TransactionDigest) -> String {
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode(id: &str) -> Option<TransactionDigest> {
+    if id.len() != 64 || !id.is_ascii() {
+        return None;
+    }
+    let mut digest = [0u8; 32];
+    for (i, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&id[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// The commit status of a transaction, served on `/tx_status`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionStatus {
+    /// The id of the transaction.
+    pub transaction_id: String,
+    /// The index of the commit including the transaction, if the node committed it.
+    pub commit_index: Option<u32>,
+    /// The wall clock of the node when it processed that commit, in microseconds since the
+    /// epoch. Clients compare it with their submission time to measure the commit latency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub committed_at_us: Option<u64>,
+}
+
+/// Remembers in which commit the most recently committed transactions were included, so that
+/// clients can confirm that the transactions they submitted were not lost.
+pub struct CommittedIndex {
+    /// The maximum number of transactions remembered. The oldest ones are forgotten first.
+    capacity: usize,
+    inner: Mutex<CommittedIndexInner>,
+}
+
+/// Where and when a transaction was committed.
+#[derive(Clone, Copy)]
+struct CommitRecord {
+    commit_index: u32,
+    committed_at_us: u64,
+}
+
+/// How the transactions of a commit count towards goodput.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CommitTally {
+    /// Accepted transactions committed for the first time.
+    pub unique: u64,
+    /// Accepted transactions already committed earlier (retries or replays). Only the
+    /// transactions the index still remembers are detected.
+    pub duplicates: u64,
+    /// Transactions the committee rejected as invalid.
+    pub rejected: u64,
+}
+
+#[derive(Default)]
+struct CommittedIndexInner {
+    commits: HashMap<TransactionDigest, CommitRecord>,
+    order: VecDeque<TransactionDigest>,
+}
+
+impl CommittedIndex {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(CommittedIndexInner::default()),
+        }
+    }
+
+    /// Record the accepted transactions of a committed sub-dag. Returns how its transactions
+    /// count towards goodput; without capacity, duplicates are not detected.
+    pub fn record_commit(&self, subdag: &CommittedSubDag) -> CommitTally {
+        let rejected = subdag
+            .rejected_transactions_by_block
+            .iter()
+            .map(|rejected| rejected.len() as u64)
+            .sum();
+        if self.capacity == 0 {
+            let total: u64 = subdag
+                .blocks
+                .iter()
+                .map(|block| block.transactions().len() as u64)
+                .sum();
+            return CommitTally {
+                unique: total.saturating_sub(rejected),
+                duplicates: 0,
+                rejected,
+            };
+        }
+        let digests: Vec<_> = subdag
+            .blocks
+            .iter()
+            .zip(&subdag.rejected_transactions_by_block)
+            .flat_map(|(block, rejected)| {
+                block
+                    .transactions()
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !rejected.contains(&(*i as u16)))
+                    .map(|(_, tx)| digest(tx.data()))
+            })
+            .collect();
+        let committed_at_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+        let accepted = digests.len() as u64;
+        let duplicates = self.insert(digests, subdag.commit_ref.index, committed_at_us);
+        CommitTally {
+            unique: accepted - duplicates,
+            duplicates,
+            rejected,
+        }
+    }
+
+    /// Remember the commit of the transactions. Returns the number of transactions already
+    /// remembered.
+    fn insert(
+        &self,
+        digests: Vec<TransactionDigest>,
+        commit_index: u32,
+        committed_at_us: u64,
+    ) -> u64 {
+        let record = CommitRecord {
+            commit_index,
+            committed_at_us,
+        };
+        let mut inner = self.inner.lock();
+        let mut duplicates = 0;
+        for digest in digests {
+            // Only remember the first commit of a transaction submitted several times.
+            if inner.commits.contains_key(&digest) {
+                duplicates += 1;
+                continue;
+            }
+            inner.commits.insert(digest, record);
+            inner.order.push_back(digest);
+            if inner.order.len() > self.capacity {
+                let oldest = inner.order.pop_front().expect("The index is not empty");
+                inner.commits.remove(&oldest);
+            }
+        }
+        duplicates
+    }
+
+    /// The commit status of the transaction with the specified id. Malformed ids are reported
+    /// as not committed.
+    pub fn status(&self, transaction_id: &str) -> TransactionStatus {
+        let record = decode(transaction_id)
+            .and_then(|digest| self.inner.lock().commits.get(&digest).copied());
+        TransactionStatus {
+            transaction_id: transaction_id.to_string(),
+            commit_index: record.map(|record| record.commit_index),
+            committed_at_us: record.map(|record| record.committed_at_us),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CommittedIndex, decode, digest, transaction_id};
+
+    #[test]
+    fn transaction_id_roundtrip() {
+        let id = transaction_id(b"transaction");
+        assert_eq!(id.len(), 64);
+        assert_eq!(decode(&id), Some(digest(b"transaction")));
+        assert_eq!(decode("not an id"), None);
+    }
+
+    #[test]
+    fn index_forgets_oldest_transactions() {
+        let index = CommittedIndex::new(2);
+        assert_eq!(index.insert(vec![digest(b"a"), digest(b"b")], 1, 1_000), 0);
+        assert_eq!(index.insert(vec![digest(b"a"), digest(b"c")], 2, 2_000), 1);
+
+        assert_eq!(index.status(&transaction_id(b"a")).commit_index, None);
+        assert_eq!(index.status(&transaction_id(b"b")).commit_index, Some(1));
+        assert_eq!(index.status(&transaction_id(b"c")).commit_index, Some(2));
+        assert_eq!(
+            index.status(&transaction_id(b"c")).committed_at_us,
+            Some(2_000)
+        );
+        assert_eq!(index.status("malformed").commit_index, None);
+    }
+}
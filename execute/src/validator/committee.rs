@@ -0,0 +1,376 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{path::Path, time::Duration};
+
+use base64::Engine;
+use consensus_config::{Committee, NetworkKeyPair, ProtocolKeyPair};
+use fastcrypto::{
+    ed25519::{Ed25519KeyPair, Ed25519PrivateKey},
+    hash::{HashFunction, Sha256},
+    traits::ToFromBytes,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// The number of leading bytes of the public keys included in the committee report.
+const KEY_PREFIX_BYTES: usize = 8;
+/// How long to wait for the committee file when fetching it from a URL.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One committee member, as seen by the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorityInfo {
+    /// The index of the authority in the committee.
+    pub authority_index: u32,
+    /// The voting power of the authority.
+    pub stake: u64,
+    /// The hostname of the authority.
+    pub hostname: String,
+    /// The consensus address of the authority.
+    pub address: String,
+    /// The (truncated) hex prefix of the authority key.
+    pub authority_key: String,
+    /// The (truncated) hex prefix of the protocol key.
+    pub protocol_key: String,
+    /// The (truncated) hex prefix of the network key.
+    pub network_key: String,
+}
+
+/// The committee the node started with, served on `/committee`. Public keys are truncated to
+/// a short prefix: enough to tell committees apart, without exposing the full key material.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitteeReport {
+    /// The epoch of the committee.
+    pub epoch: u64,
+    /// The total stake of the committee.
+    pub total_stake: u64,
+    /// The stake required to form a quorum.
+    pub quorum_threshold: u64,
+    /// The members of the committee, by authority index.
+    pub authorities: Vec<AuthorityInfo>,
+}
+
+impl CommitteeReport {
+    pub fn from_committee(committee: &Committee) -> Self {
+        let authorities = committee
+            .authorities()
+            .map(|(index, authority)| AuthorityInfo {
+                authority_index: index.value() as u32,
+                stake: authority.stake,
+                hostname: authority.hostname.clone(),
+                address: authority.address.to_string(),
+                authority_key: key_prefix(authority.authority_key.to_bytes()),
+                protocol_key: key_prefix(authority.protocol_key.to_bytes()),
+                network_key: key_prefix(&authority.network_key.to_bytes()),
+            })
+            .collect();
+
+        Self {
+            epoch: committee.epoch(),
+            total_stake: committee.total_stake(),
+            quorum_threshold: committee.quorum_threshold(),
+            authorities,
+        }
+    }
+
+    /// The smallest number of authorities whose stake reaches the quorum threshold: fewer
+    /// running nodes cannot commit anything.
+    pub fn min_quorum_size(&self) -> usize {
+        let mut stakes: Vec<_> = self.authorities.iter().map(|a| a.stake).collect();
+        stakes.sort_unstable_by(|a, b| b.cmp(a));
+        let mut stake = 0;
+        for (count, authority_stake) in stakes.into_iter().enumerate() {
+            if stake >= self.quorum_threshold {
+                return count;
+            }
+            stake += authority_stake;
+        }
+        self.authorities.len()
+    }
+
+    /// A one-line summary of the committee and its quorum, e.g. "4 authorities, total stake
+    /// 4, quorum requires stake 3 (at least 3 authorities)".
+    pub fn summary(&self) -> String {
+        format!(
+            "{} authorities, total stake {}, quorum requires stake {} (at least {} authorities)",
+            self.authorities.len(),
+            self.total_stake,
+            self.quorum_threshold,
+            self.min_quorum_size()
+        )
+    }
+}
+
+/// Log the size and quorum of the committee a node starts with, so that operators running
+/// fewer nodes than a quorum immediately see why nothing commits.
+pub fn log_committee(committee: &Committee) {
+    let report = CommitteeReport::from_committee(committee);
+    info!("Committee of epoch {}: {}", report.epoch, report.summary());
+}
+
+/// Load a committee file (yaml, as written by the genesis tool) from an HTTP(S) URL, or from
+/// a local path if `source` is not a URL. If a checksum is expected, the file must hash to it
+/// (SHA-256, in hex), so that every operator pulling the coordinator's committee is sure to
+/// run the same one.
+pub async fn fetch_committee(source: &str, checksum: Option<&str>) -> Result<Committee, String> {
+    let bytes = match source.starts_with("http://") || source.starts_with("https://") {
+        true => {
+            let client = reqwest::Client::builder()
+                .timeout(FETCH_TIMEOUT)
+                .build()
+                .map_err(|e| e.to_string())?;
+            let response = client
+                .get(source)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("Failed to fetch the committee from {source}: {e}"))?;
+            response
+                .bytes()
+                .await
+                .map_err(|e| format!("Failed to fetch the committee from {source}: {e}"))?
+                .to_vec()
+        }
+        false => std::fs::read(source)
+            .map_err(|e| format!("Failed to read the committee from {source}: {e}"))?,
+    };
+    if let Some(expected) = checksum {
+        let actual = sha256_hex(&bytes);
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!(
+                "The committee from {source} has checksum {actual}, expected {expected}"
+            ));
+        }
+    }
+    serde_yaml::from_slice(&bytes).map_err(|e| format!("Invalid committee from {source}: {e}"))
+}
+
+/// The key pair of the node in a loaded committee. Keys are derived as when the node builds
+/// the committee itself, so the committee must have been built that way too: the keys of the
+/// node must match its entry in the committee.
+pub fn committee_keys(
+    committee: &Committee,
+    authority_index: u32,
+) -> Result<(NetworkKeyPair, ProtocolKeyPair), String> {
+    // Fail on an out of range index before deriving the keys.
+    check_node_keys(committee, authority_index, None)?;
+    let (stakes, addresses): (Vec<_>, Vec<_>) = committee
+        .authorities()
+        .map(|(_, authority)| (authority.stake, authority.address.clone()))
+        .unzip();
+    let (_, mut keypairs) =
+        consensus_config::committee_and_keys_with_addresses(committee.epoch(), stakes, addresses);
+    let keypair = keypairs.swap_remove(authority_index as usize);
+    check_node_keys(committee, authority_index, Some(&keypair))?;
+    Ok(keypair)
+}
+
+/// The private keys of a node, as stored in its key file (yaml, with the 32-byte ed25519 keys
+/// in base64).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeKeys {
+    pub network_private_key: String,
+    pub protocol_private_key: String,
+}
+
+/// Load the key pair of the node from its key file. The keys must match the entry of the node
+/// in the committee.
+pub fn load_node_keys(
+    path: &Path,
+    committee: &Committee,
+    authority_index: u32,
+) -> Result<(NetworkKeyPair, ProtocolKeyPair), String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read the keys from {}: {e}", path.display()))?;
+    let keys: NodeKeys = serde_yaml::from_str(&content)
+        .map_err(|e| format!("Invalid keys in {}: {e}", path.display()))?;
+    let keypair = (
+        NetworkKeyPair::new(decode_key_pair("network", &keys.network_private_key)?),
+        ProtocolKeyPair::new(decode_key_pair("protocol", &keys.protocol_private_key)?),
+    );
+    check_node_keys(committee, authority_index, Some(&keypair))?;
+    Ok(keypair)
+}
+
+/// Check that the authority is in the committee and, if its key pair is specified, that the
+/// public keys match its entry.
+fn check_node_keys(
+    committee: &Committee,
+    authority_index: u32,
+    keypair: Option<&(NetworkKeyPair, ProtocolKeyPair)>,
+) -> Result<(), String> {
+    let index = committee
+        .to_authority_index(authority_index as usize)
+        .ok_or_else(|| {
+            format!(
+                "Authority index {authority_index} is out of range for a committee of {}",
+                committee.size()
+            )
+        })?;
+    let Some((network_keypair, protocol_keypair)) = keypair else {
+        return Ok(());
+    };
+    let expected = committee.authority(index);
+    if expected.protocol_key != protocol_keypair.public()
+        || expected.network_key != network_keypair.public()
+    {
+        return Err(format!(
+            "The keys of authority {authority_index} do not match its entry in the committee"
+        ));
+    }
+    Ok(())
+}
+
+/// Decode a base64 ed25519 private key into its key pair.
+fn decode_key_pair(name: &str, encoded: &str) -> Result<Ed25519KeyPair, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("Invalid {name} key: {e}"))?;
+    let private_key =
+        Ed25519PrivateKey::from_bytes(&bytes).map_err(|e| format!("Invalid {name} key: {e}"))?;
+    Ok(Ed25519KeyPair::from(private_key))
+}
+
+/// The SHA-256 digest of the bytes, in hex.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The hex encoding of the first bytes of a public key, followed by an ellipsis.
+fn key_prefix(key: &[u8]) -> String {
+    let prefix: String = key
+        .iter()
+        .take(KEY_PREFIX_BYTES)
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("{prefix}...")
+}
+
+#[cfg(test)]
+mod test {
+    use base64::Engine;
+    use consensus_config::{Committee, NetworkPublicKey, ProtocolPublicKey};
+    use fastcrypto::{
+        ed25519::Ed25519KeyPair,
+        traits::{KeyPair, ToFromBytes},
+    };
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{
+        CommitteeReport, KEY_PREFIX_BYTES, NodeKeys, committee_keys, fetch_committee,
+        load_node_keys, sha256_hex,
+    };
+
+    #[test]
+    fn committee_report_truncates_keys() {
+        let (committee, _) = consensus_config::local_committee_and_keys(3, vec![1, 2, 3, 4]);
+        let report = CommitteeReport::from_committee(&committee);
+
+        assert_eq!(report.epoch, 3);
+        assert_eq!(report.total_stake, 10);
+        assert_eq!(report.quorum_threshold, committee.quorum_threshold());
+        assert_eq!(report.authorities.len(), 4);
+        for (i, authority) in report.authorities.iter().enumerate() {
+            assert_eq!(authority.authority_index, i as u32);
+            assert_eq!(authority.stake, i as u64 + 1);
+            for key in [
+                &authority.authority_key,
+                &authority.protocol_key,
+                &authority.network_key,
+            ] {
+                assert_eq!(key.len(), 2 * KEY_PREFIX_BYTES + "...".len());
+            }
+        }
+    }
+
+    #[test]
+    fn quorum_summary() {
+        let (committee, _) = consensus_config::local_committee_and_keys(0, vec![1; 4]);
+        let report = CommitteeReport::from_committee(&committee);
+        assert_eq!(report.min_quorum_size(), 3);
+        assert_eq!(
+            report.summary(),
+            "4 authorities, total stake 4, quorum requires stake 3 (at least 3 authorities)"
+        );
+
+        // The heaviest authorities reach the quorum first.
+        let (committee, _) = consensus_config::local_committee_and_keys(0, vec![1, 1, 1, 7]);
+        let report = CommitteeReport::from_committee(&committee);
+        assert_eq!(report.min_quorum_size(), 1);
+    }
+
+    #[tokio::test]
+    async fn load_committee_with_checksum() {
+        let addresses = consensus_config::parse_addresses(
+            "/ip4/10.0.0.1/udp/26657,/ip4/10.0.0.2/udp/26657,/ip4/10.0.0.3/udp/26657",
+        )
+        .unwrap();
+        let (committee, _) =
+            consensus_config::committee_and_keys_with_addresses(0, vec![1; 3], addresses);
+        let yaml = serde_yaml::to_string(&committee).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("committee.yaml");
+        std::fs::write(&path, &yaml).unwrap();
+        let path = path.to_str().unwrap();
+
+        let checksum = sha256_hex(yaml.as_bytes());
+        let loaded = fetch_committee(path, Some(&checksum)).await.unwrap();
+        assert_eq!(loaded.size(), 3);
+        let (network_keypair, _) = committee_keys(&loaded, 2).unwrap();
+        assert_eq!(
+            network_keypair.public(),
+            loaded
+                .authority(loaded.to_authority_index(2).unwrap())
+                .network_key
+        );
+        assert!(committee_keys(&loaded, 3).is_err());
+
+        // A tampered committee does not match the checksum.
+        let tampered = yaml.replace("10.0.0.3", "10.6.6.6");
+        std::fs::write(path, tampered).unwrap();
+        let error = fetch_committee(path, Some(&checksum)).await.unwrap_err();
+        assert!(error.contains("checksum"), "{error}");
+        assert!(fetch_committee(path, None).await.is_ok());
+    }
+    #[test]
+    fn load_keys_from_file() {
+        let network = Ed25519KeyPair::generate(&mut StdRng::from_seed([1; 32]));
+        let protocol = Ed25519KeyPair::generate(&mut StdRng::from_seed([2; 32]));
+        let (committee, _) = consensus_config::local_committee_and_keys(0, vec![1; 3]);
+        let mut authorities: Vec<_> = committee
+            .authorities()
+            .map(|(_, authority)| authority.clone())
+            .collect();
+        authorities[1].network_key = NetworkPublicKey::new(network.public().clone());
+        authorities[1].protocol_key = ProtocolPublicKey::new(protocol.public().clone());
+        let committee = Committee::new(0, authorities);
+
+        let encode = |keypair: &Ed25519KeyPair| {
+            base64::engine::general_purpose::STANDARD.encode(keypair.copy().private().as_bytes())
+        };
+        let keys = NodeKeys {
+            network_private_key: encode(&network),
+            protocol_private_key: encode(&protocol),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.yaml");
+        std::fs::write(&path, serde_yaml::to_string(&keys).unwrap()).unwrap();
+
+        let (network_keypair, protocol_keypair) = load_node_keys(&path, &committee, 1).unwrap();
+        assert_eq!(network_keypair.public().into_inner(), *network.public());
+        assert_eq!(
+            protocol_keypair.public(),
+            ProtocolPublicKey::new(protocol.public().clone())
+        );
+
+        // The keys belong to authority 1 only, and cannot be derived.
+        let error = load_node_keys(&path, &committee, 0).unwrap_err();
+        assert!(error.contains("do not match"), "{error}");
+        assert!(committee_keys(&committee, 1).is_err());
+    }
+}
@@ -0,0 +1,1002 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use consensus_config::{
+    Committee, DefaultHashFunction, NetworkKeyPair, Parameters, ProtocolKeyPair, RocksDbCompression,
+};
+use consensus_core::Clock;
+use fastcrypto::hash::HashFunction;
+use serde::{Deserialize, Serialize};
+use sui_protocol_config::{Chain, ProtocolConfig, ProtocolVersion};
+
+use crate::validator::fault::TransactionFault;
+use crate::validator::verifier::DEFAULT_MIN_PARALLEL_BATCH;
+
+/// The clock used by the consensus authority to timestamp blocks.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClockMode {
+    /// The system clock.
+    #[default]
+    System,
+    /// The test clock of consensus. Its drift (in milliseconds) must be zero, since the blocks
+    /// of a node whose clock drifts would be timestamped apart from those of its peers.
+    Test { drift_ms: u64 },
+}
+
+impl ClockMode {
+    /// Build the consensus clock for this mode.
+    pub fn clock(&self) -> Clock {
+        match self {
+            Self::System => Clock::default(),
+            Self::Test { drift_ms } => Clock::new_for_test(*drift_ms),
+        }
+    }
+}
+
+/// How closely the node tracks the fate of the transactions it submits to consensus.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AckMode {
+    /// Fire and forget: only submission errors are reported.
+    None,
+    /// Report the block each transaction is included in.
+    #[default]
+    Submitted,
+    /// Additionally track every transaction until its block is committed (or garbage
+    /// collected). This spawns a task per submission.
+    Committed,
+}
+
+impl FromStr for AckMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "submitted" => Ok(Self::Submitted),
+            "committed" => Ok(Self::Committed),
+            _ => Err(format!(
+                "Unknown ack mode '{s}' (expected none, submitted, or committed)"
+            )),
+        }
+    }
+}
+
+/// The configuration of the transaction verifier of a node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerifierConfig {
+    /// The number of threads used to verify transaction batches.
+    pub threads: usize,
+    /// Verify the Ed25519 signature of every transaction, rejecting the unsigned ones.
+    pub signatures: bool,
+    /// Reject the empty transactions and those larger than this many bytes, if set.
+    pub max_transaction_size: Option<usize>,
+    /// The batch size below which verification runs on a single thread.
+    pub min_parallel_batch: usize,
+}
+
+impl Default for VerifierConfig {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            signatures: false,
+            max_transaction_size: None,
+            min_parallel_batch: DEFAULT_MIN_PARALLEL_BATCH,
+        }
+    }
+}
+
+/// The HTTP settings of the RPC server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Accept HTTP/2 connections (with prior knowledge) besides HTTP/1.1, so that a client
+    /// can multiplex many submissions over a single connection.
+    pub http2: bool,
+    /// Keep HTTP/1.1 connections open between requests.
+    pub keep_alive: bool,
+    /// How often (in seconds) to ping idle HTTP/2 connections. Pings are disabled if unset.
+    pub keep_alive_interval_secs: Option<u64>,
+    /// How long (in seconds) to wait for a ping acknowledgement before closing the connection.
+    pub keep_alive_timeout_secs: u64,
+    /// The maximum number of concurrent HTTP/2 streams per connection.
+    pub max_concurrent_streams: u32,
+    /// Disable Nagle's algorithm on accepted connections.
+    pub tcp_nodelay: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            http2: true,
+            keep_alive: true,
+            keep_alive_interval_secs: Some(30),
+            keep_alive_timeout_secs: 20,
+            max_concurrent_streams: 1024,
+            tcp_nodelay: true,
+        }
+    }
+}
+
+/// Which servers the node runs. Disabled servers do not bind their port, so that a node only
+/// serving some of its interfaces keeps a minimal footprint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServersConfig {
+    /// The HTTP RPC server, through which clients submit transactions.
+    pub rpc: bool,
+    /// The ABCI server, submitting the transactions accepted by its `check_tx` to consensus.
+    pub abci: bool,
+    /// The gRPC server.
+    pub grpc: bool,
+    /// The metrics server, exposing `GET /metrics` (also served by the RPC server).
+    pub metrics: bool,
+}
+
+impl Default for ServersConfig {
+    fn default() -> Self {
+        Self {
+            rpc: true,
+            abci: true,
+            grpc: true,
+            metrics: true,
+        }
+    }
+}
+
+/// Where and how often to post the commits of the transactions submitted through the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// The URL the commit events are posted to.
+    pub url: String,
+    /// The maximum number of events per request.
+    pub max_batch: usize,
+    /// The minimum delay (in milliseconds) between two requests.
+    pub min_interval_ms: u64,
+    /// The maximum number of events waiting to be posted. Further events are dropped.
+    pub queue_capacity: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
+impl WebhookConfig {
+    /// Post to `url` with the default batching and rate limit.
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            max_batch: 100,
+            min_interval_ms: 100,
+            queue_capacity: 10_000,
+        }
+    }
+
+    /// Check that the URL is an HTTP one and that the batches can hold an event.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            return Err(format!(
+                "The commit webhook URL must start with http:// or https:// (got '{}')",
+                self.url
+            ));
+        }
+        if self.max_batch == 0 || self.queue_capacity == 0 {
+            return Err("The commit webhook batch size and queue capacity must be positive".into());
+        }
+        Ok(())
+    }
+}
+
+/// When to shed load because the node runs low on memory.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryGuardConfig {
+    /// Reject new submissions while less than this many MiB of memory are available.
+    pub min_free_mb: u64,
+    /// How often (in milliseconds) to check the available memory.
+    pub check_interval_ms: u64,
+}
+
+impl Default for MemoryGuardConfig {
+    fn default() -> Self {
+        Self::new(512)
+    }
+}
+
+impl MemoryGuardConfig {
+    /// Shed load below `min_free_mb` MiB of available memory, checked every second.
+    pub fn new(min_free_mb: u64) -> Self {
+        Self {
+            min_free_mb,
+            check_interval_ms: 1_000,
+        }
+    }
+
+    /// Check that the threshold and the check interval are positive.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_free_mb == 0 || self.check_interval_ms == 0 {
+            return Err(
+                "The minimum free memory and the memory check interval must be positive".into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Overrides of the consensus parameters. Unset fields keep the default of consensus.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsensusConfig {
+    /// How long to wait for the leader of the parent round before sealing a block.
+    pub leader_timeout_ms: Option<u64>,
+    /// The minimum delay between two rounds.
+    pub min_round_delay_ms: Option<u64>,
+    /// How far in the future received blocks may be timestamped.
+    pub max_forward_time_drift_ms: Option<u64>,
+    /// The maximum number of blocks fetched per block sync request.
+    pub max_blocks_per_sync: Option<usize>,
+    /// The maximum number of blocks fetched per commit sync request.
+    pub max_blocks_per_fetch: Option<usize>,
+    /// How often to probe the highest rounds received by the peers.
+    pub round_prober_interval_ms: Option<u64>,
+    /// The timeout of a round prober request.
+    pub round_prober_request_timeout_ms: Option<u64>,
+    /// The propagation delay (in rounds) above which the node stops proposing.
+    pub propagation_delay_stop_proposal_threshold: Option<u32>,
+    /// The number of rounds of blocks cached per authority.
+    pub dag_state_cached_rounds: Option<u32>,
+    /// The number of authorities the commit syncer fetches from in parallel.
+    pub commit_sync_parallel_fetches: Option<usize>,
+    /// The number of commits fetched per commit sync request.
+    pub commit_sync_batch_size: Option<u32>,
+    /// The number of commit batches fetched ahead of the consensus output.
+    pub commit_sync_batches_ahead: Option<usize>,
+    /// The size of the memtables of the consensus DB, in MiB (between 8 and 4096).
+    pub rocksdb_write_buffer_size_mb: Option<usize>,
+    /// The number of concurrent flushes and compactions of the consensus DB (between 1 and
+    /// 64, ideally at most the number of cores).
+    pub rocksdb_max_background_jobs: Option<usize>,
+    /// The compression of the consensus DB (`none`, `snappy`, `lz4` or `zstd`).
+    pub rocksdb_compression: Option<RocksDbCompression>,
+}
+
+impl ConsensusConfig {
+    /// Override the parameters with the fields that are set.
+    pub fn apply(&self, parameters: &mut Parameters) {
+        let millis = Duration::from_millis;
+        if let Some(value) = self.leader_timeout_ms {
+            parameters.leader_timeout = millis(value);
+        }
+        if let Some(value) = self.min_round_delay_ms {
+            parameters.min_round_delay = millis(value);
+        }
+        if let Some(value) = self.max_forward_time_drift_ms {
+            parameters.max_forward_time_drift = millis(value);
+        }
+        if let Some(value) = self.max_blocks_per_sync {
+            parameters.max_blocks_per_sync = value;
+        }
+        if let Some(value) = self.max_blocks_per_fetch {
+            parameters.max_blocks_per_fetch = value;
+        }
+        if let Some(value) = self.round_prober_interval_ms {
+            parameters.round_prober_interval_ms = value;
+        }
+        if let Some(value) = self.round_prober_request_timeout_ms {
+            parameters.round_prober_request_timeout_ms = value;
+        }
+        if let Some(value) = self.propagation_delay_stop_proposal_threshold {
+            parameters.propagation_delay_stop_proposal_threshold = value;
+        }
+        if let Some(value) = self.dag_state_cached_rounds {
+            parameters.dag_state_cached_rounds = value;
+        }
+        if let Some(value) = self.commit_sync_parallel_fetches {
+            parameters.commit_sync_parallel_fetches = value;
+        }
+        if let Some(value) = self.commit_sync_batch_size {
+            parameters.commit_sync_batch_size = value;
+        }
+        if let Some(value) = self.commit_sync_batches_ahead {
+            parameters.commit_sync_batches_ahead = value;
+        }
+        if let Some(value) = self.rocksdb_write_buffer_size_mb {
+            parameters.rocksdb.write_buffer_size_mb = Some(value);
+        }
+        if let Some(value) = self.rocksdb_max_background_jobs {
+            parameters.rocksdb.max_background_jobs = Some(value);
+        }
+        if let Some(value) = self.rocksdb_compression {
+            parameters.rocksdb.compression = Some(value);
+        }
+    }
+
+    /// Check that the counts and timeouts that must be positive are, and that the RocksDB
+    /// settings are within their safe ranges.
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = [
+            ("leader_timeout_ms", self.leader_timeout_ms),
+            (
+                "max_blocks_per_sync",
+                self.max_blocks_per_sync.map(|v| v as u64),
+            ),
+            (
+                "max_blocks_per_fetch",
+                self.max_blocks_per_fetch.map(|v| v as u64),
+            ),
+            ("round_prober_interval_ms", self.round_prober_interval_ms),
+            (
+                "round_prober_request_timeout_ms",
+                self.round_prober_request_timeout_ms,
+            ),
+            (
+                "commit_sync_parallel_fetches",
+                self.commit_sync_parallel_fetches.map(|v| v as u64),
+            ),
+            (
+                "commit_sync_batch_size",
+                self.commit_sync_batch_size.map(u64::from),
+            ),
+            (
+                "commit_sync_batches_ahead",
+                self.commit_sync_batches_ahead.map(|v| v as u64),
+            ),
+        ];
+        for (name, value) in positive {
+            if value == Some(0) {
+                return Err(format!("The consensus parameter {name} must be positive"));
+            }
+        }
+        let ranges = [
+            (
+                "rocksdb_write_buffer_size_mb",
+                self.rocksdb_write_buffer_size_mb,
+                8..=4096,
+            ),
+            (
+                "rocksdb_max_background_jobs",
+                self.rocksdb_max_background_jobs,
+                1..=64,
+            ),
+        ];
+        for (name, value, range) in ranges {
+            if let Some(value) = value.filter(|value| !range.contains(value)) {
+                return Err(format!(
+                    "The consensus parameter {name} must be between {} and {} (got {value})",
+                    range.start(),
+                    range.end()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The description of the committee the node belongs to. A committee file fetched from `url`
+/// takes precedence over everything else, and addresses over the address template; without
+/// either, the committee uses local ephemeral addresses (or the docker-compose service names
+/// if `docker` is set). Addresses may name hosts: the Anemo network of the validator accepts
+/// `/dns/<hostname>/udp/<port>` and resolves the hostname when it dials the peer, while the
+/// Tonic network also accepts `tcp` and `dns4`/`dns6` and resolves the hostname again on every
+/// reconnection.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitteeConfig {
+    /// The number of authorities in the committee.
+    pub size: usize,
+    /// The consensus addresses of the authorities, one per authority.
+    pub addresses: Vec<String>,
+    /// A template of the consensus addresses, where `{i}` is replaced by the authority
+    /// index and `{port}` by `address_base_port` plus the index.
+    pub address_template: Option<String>,
+    /// The base port substituted for `{port}` in the address template.
+    pub address_base_port: u16,
+    /// Whether to address the authorities by their docker-compose service name.
+    pub docker: bool,
+    /// Load the committee file (yaml) from this HTTP(S) URL, or local path, at startup.
+    pub url: Option<String>,
+    /// The expected SHA-256 checksum (in hex) of the committee file.
+    pub checksum: Option<String>,
+    /// Load the key pair of the node from this yaml file rather than deriving it. The keys
+    /// must match the entry of the node in the committee file.
+    pub keys: Option<PathBuf>,
+}
+
+impl Default for CommitteeConfig {
+    fn default() -> Self {
+        Self {
+            size: 4,
+            addresses: Vec::new(),
+            address_template: None,
+            address_base_port: 26657,
+            docker: false,
+            url: None,
+            checksum: None,
+            keys: None,
+        }
+    }
+}
+
+impl CommitteeConfig {
+    /// Build the committee and the key pairs of its authorities.
+    pub fn committee_and_keys(
+        &self,
+    ) -> Result<
+        (Committee, Vec<(NetworkKeyPair, ProtocolKeyPair)>),
+        Box<dyn std::error::Error + Send + Sync>,
+    > {
+        let addresses = if !self.addresses.is_empty() {
+            Some(consensus_config::parse_addresses(
+                &self.addresses.join(","),
+            )?)
+        } else if let Some(template) = &self.address_template {
+            Some(consensus_config::addresses_from_template(
+                template,
+                self.size,
+                self.address_base_port,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(match addresses {
+            Some(addresses) => {
+                let stakes = vec![1; addresses.len()];
+                consensus_config::committee_and_keys_with_addresses(0, stakes, addresses)
+            }
+            None if self.docker => {
+                consensus_config::docker_committee_and_keys(0, vec![1; self.size])
+            }
+            None => consensus_config::local_committee_and_keys(0, vec![1; self.size]),
+        })
+    }
+}
+
+/// The configuration of a single validator node. Every field has a default so that a
+/// config file only needs to specify what differs from it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeConfig {
+    /// The index of this node in the committee.
+    pub authority_index: u32,
+    /// The directory where the node stores its data.
+    pub working_directory: PathBuf,
+    /// The address on which the node's servers listen.
+    pub bind_address: IpAddr,
+    /// The port of the HTTP RPC server.
+    pub rpc_port: u16,
+    /// The port of the ABCI server.
+    pub abci_port: u16,
+    /// The port of the gRPC server.
+    pub grpc_port: u16,
+    /// The port of the metrics server.
+    pub metrics_port: u16,
+    /// The chain identifier reported to clients.
+    pub chain_id: String,
+    /// The protocol version to run. Defaults to the latest supported version.
+    pub protocol_version: Option<u64>,
+    /// The clock used by consensus.
+    pub clock: ClockMode,
+    /// The transaction verifier settings.
+    pub verifier: VerifierConfig,
+    /// The committee the node belongs to.
+    pub committee: CommitteeConfig,
+    /// The file to which committed sub-dags are journaled, if any.
+    pub commit_journal: Option<PathBuf>,
+    /// The file to which the ids of the committed transactions are appended in commit order,
+    /// to check offline that all nodes committed the same sequence, if any.
+    pub commit_order: Option<PathBuf>,
+    /// The maximum size (in bytes) of the body of an RPC request.
+    pub max_request_bytes: usize,
+    /// How the node tracks the transactions it submits to consensus.
+    pub ack_mode: AckMode,
+    /// The number of tasks concurrently submitting RPC transactions to consensus.
+    pub forward_workers: usize,
+    /// Submit the queued transactions of highest priority (the class byte of structured
+    /// transactions) first, rather than in arrival order.
+    pub prioritize_submissions: bool,
+    /// Keep the consensus DB in memory instead of on disk. The node loses its state on restart.
+    pub in_memory: bool,
+    /// The maximum number of transactions in a proposed block. Defaults to the protocol limit.
+    pub max_transactions_per_block: Option<u64>,
+    /// The number of most recently committed transactions the node remembers, so that clients
+    /// can look them up on `/tx_status`. Zero disables the lookups.
+    pub committed_index_capacity: usize,
+    /// The number of most recent idempotency keys the node remembers, so that client retries
+    /// of a submission are not forwarded twice. Zero disables the deduplication.
+    pub submission_dedup_capacity: usize,
+    /// The number of most recent errors the node remembers, served on `/errors`. Zero only
+    /// counts them.
+    pub error_log_capacity: usize,
+    /// The HTTP settings of the RPC server.
+    pub http: HttpConfig,
+    /// Which servers the node runs (all of them by default).
+    pub servers: ServersConfig,
+    /// The log filter directives (e.g. `info,consensus_core=debug`), overriding `RUST_LOG`.
+    /// Reloaded on SIGHUP.
+    pub log_filter: Option<String>,
+    /// UNSAFE: drop or corrupt a fraction of the transactions received from clients, to test
+    /// how the committee tolerates a faulty-but-alive node.
+    pub unsafe_transaction_fault: Option<TransactionFault>,
+    /// Overrides of the consensus parameters.
+    pub consensus: ConsensusConfig,
+    /// Post the commits of the transactions submitted through the node to a webhook. Off if
+    /// unset.
+    pub commit_webhook: Option<WebhookConfig>,
+    /// Reject new submissions (with 503) while the node runs low on memory. Off if unset.
+    pub memory_guard: Option<MemoryGuardConfig>,
+    /// The bearer token of the admin endpoints (e.g. `/admin/log-level`), which are disabled
+    /// if unset. Never serialized, so that it does not leak into dumped configurations.
+    #[serde(skip_serializing)]
+    pub admin_token: Option<String>,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        Self::new(0, PathBuf::from("validator-node")).expect("The first node has valid ports")
+    }
+}
+
+impl NodeConfig {
+    /// The default RPC port of the first node.
+    pub const DEFAULT_RPC_PORT: u16 = 26657;
+    /// The default ABCI port of the first node.
+    pub const DEFAULT_ABCI_PORT: u16 = 26670;
+    /// The default gRPC port of the first node.
+    pub const DEFAULT_GRPC_PORT: u16 = 50051;
+    /// The default metrics port of the first node.
+    pub const DEFAULT_METRICS_PORT: u16 = 8000;
+    /// The default chain identifier.
+    pub const DEFAULT_CHAIN_ID: &'static str = "mysticeti";
+    /// The default maximum size of the body of an RPC request.
+    pub const DEFAULT_MAX_REQUEST_BYTES: usize = 10 * 1024 * 1024;
+    /// The default number of committed transactions the node remembers.
+    pub const DEFAULT_COMMITTED_INDEX_CAPACITY: usize = 1_000_000;
+    /// The default number of idempotency keys the node remembers.
+    pub const DEFAULT_SUBMISSION_DEDUP_CAPACITY: usize = 100_000;
+    /// The default number of errors the node remembers.
+    pub const DEFAULT_ERROR_LOG_CAPACITY: usize = 16;
+
+    /// Create a new configuration for the specified authority, deriving its ports from the
+    /// default base ports offset by the authority index. Fails if the index is so large that a
+    /// port would overflow.
+    pub fn new(
+        authority_index: u32,
+        working_directory: PathBuf,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let port = |base: u16| {
+            u16::try_from(authority_index)
+                .ok()
+                .and_then(|offset| base.checked_add(offset))
+                .ok_or_else(|| {
+                    format!(
+                        "The port {base} offset by authority index {authority_index} exceeds {}",
+                        u16::MAX
+                    )
+                })
+        };
+        Ok(Self {
+            authority_index,
+            working_directory,
+            bind_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            rpc_port: port(Self::DEFAULT_RPC_PORT)?,
+            abci_port: port(Self::DEFAULT_ABCI_PORT)?,
+            grpc_port: port(Self::DEFAULT_GRPC_PORT)?,
+            metrics_port: port(Self::DEFAULT_METRICS_PORT)?,
+            chain_id: Self::DEFAULT_CHAIN_ID.to_string(),
+            protocol_version: None,
+            clock: ClockMode::default(),
+            verifier: VerifierConfig::default(),
+            committee: CommitteeConfig::default(),
+            commit_journal: None,
+            commit_order: None,
+            max_request_bytes: Self::DEFAULT_MAX_REQUEST_BYTES,
+            ack_mode: AckMode::default(),
+            forward_workers: 1,
+            prioritize_submissions: false,
+            in_memory: false,
+            max_transactions_per_block: None,
+            committed_index_capacity: Self::DEFAULT_COMMITTED_INDEX_CAPACITY,
+            submission_dedup_capacity: Self::DEFAULT_SUBMISSION_DEDUP_CAPACITY,
+            error_log_capacity: Self::DEFAULT_ERROR_LOG_CAPACITY,
+            http: HttpConfig::default(),
+            servers: ServersConfig::default(),
+            log_filter: None,
+            unsafe_transaction_fault: None,
+            consensus: ConsensusConfig::default(),
+            commit_webhook: None,
+            memory_guard: None,
+            admin_token: None,
+        })
+    }
+
+    /// Load a configuration from a yaml file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config '{}': {e}", path.display()))?;
+        let config: Self = serde_yaml::from_str(&content)
+            .map_err(|e| format!("Failed to parse config '{}': {e}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that the configuration is internally consistent.
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Only the servers actually started can conflict.
+        let ports: Vec<_> = [
+            ("rpc", self.rpc_port, self.servers.rpc),
+            ("abci", self.abci_port, self.servers.abci),
+            ("grpc", self.grpc_port, self.servers.grpc),
+            ("metrics", self.metrics_port, self.servers.metrics),
+        ]
+        .into_iter()
+        .filter(|(_, _, enabled)| *enabled)
+        .map(|(name, port, _)| (name, port))
+        .collect();
+        for (i, (name, port)) in ports.iter().enumerate() {
+            if let Some((other, _)) = ports[i + 1..].iter().find(|(_, p)| p == port) {
+                return Err(format!("The {name} and {other} servers both use port {port}").into());
+            }
+        }
+        if self.verifier.threads == 0 {
+            return Err("The verifier needs at least one thread".into());
+        }
+        if self.verifier.max_transaction_size == Some(0) {
+            return Err("The maximum transaction size must be positive".into());
+        }
+        // Generated committees hold the derived keys, never those of a key file.
+        if self.committee.keys.is_some() && self.committee.url.is_none() {
+            return Err("A key file needs a committee file holding its public keys".into());
+        }
+        if self.forward_workers == 0 {
+            return Err("The node needs at least one forwarding worker".into());
+        }
+        if self.max_request_bytes == 0 {
+            return Err("The maximum request size must be positive".into());
+        }
+        if self.max_transactions_per_block == Some(0) {
+            return Err("The maximum number of transactions per block must be positive".into());
+        }
+        if self.http.keep_alive_interval_secs == Some(0) {
+            return Err("The HTTP/2 keep-alive interval must be positive".into());
+        }
+        if self.http.keep_alive_timeout_secs == 0 {
+            return Err("The HTTP/2 keep-alive timeout must be positive".into());
+        }
+        if self.http.max_concurrent_streams == 0 {
+            return Err("The RPC server must accept at least one HTTP/2 stream".into());
+        }
+        if self.chain_id.is_empty() {
+            return Err("The chain id cannot be empty".into());
+        }
+        if let Some(version) = self.protocol_version {
+            let (min, max) = (ProtocolVersion::MIN.as_u64(), ProtocolVersion::MAX.as_u64());
+            if !(min..=max).contains(&version) {
+                return Err(format!(
+                    "Protocol version {version} is not supported (expected {min} to {max})"
+                )
+                .into());
+            }
+        }
+        if matches!(self.clock, ClockMode::Test { drift_ms } if drift_ms != 0) {
+            return Err("The test clock cannot drift from the system clock".into());
+        }
+        if let Some(filter) = &self.log_filter {
+            tracing_subscriber::EnvFilter::try_new(filter)
+                .map_err(|e| format!("Invalid log filter '{filter}': {e}"))?;
+        }
+        if let Some(fault) = &self.unsafe_transaction_fault {
+            fault.validate()?;
+        }
+        self.consensus.validate()?;
+        if let Some(webhook) = &self.commit_webhook {
+            webhook.validate()?;
+        }
+        if let Some(guard) = &self.memory_guard {
+            guard.validate()?;
+        }
+        if let Some(checksum) = &self.committee.checksum {
+            if self.committee.url.is_none() {
+                return Err("A committee checksum requires a committee url".into());
+            }
+            if checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!(
+                    "Invalid committee checksum '{checksum}' (expected SHA-256 hex)"
+                )
+                .into());
+            }
+        }
+        let committee_size = match self.committee.addresses.len() {
+            0 => self.committee.size,
+            n => n,
+        };
+        // The size of a fetched committee is only known once it is loaded.
+        if self.committee.url.is_none() && self.authority_index as usize >= committee_size {
+            return Err(format!(
+                "Authority index {} is out of range for a committee of {committee_size}",
+                self.authority_index
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// The directory where this node stores its data.
+    pub fn node_directory(&self) -> PathBuf {
+        self.working_directory
+            .join(format!("node-{}", self.authority_index))
+    }
+
+    /// The address of the HTTP RPC server.
+    pub fn rpc_address(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.rpc_port)
+    }
+
+    /// The address of the ABCI server.
+    pub fn abci_address(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.abci_port)
+    }
+
+    /// The address of the gRPC server.
+    pub fn grpc_address(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.grpc_port)
+    }
+
+    /// The address of the metrics server.
+    pub fn metrics_address(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.metrics_port)
+    }
+
+    /// The consensus parameters of this node: the consensus defaults with the configured
+    /// overrides, storing the DB in the node directory (or in memory).
+    pub fn consensus_parameters(&self) -> Parameters {
+        let mut parameters = Parameters {
+            db_path: self.node_directory().join("consensus.db"),
+            in_memory_db: self.in_memory,
+            ..Default::default()
+        };
+        self.consensus.apply(&mut parameters);
+        parameters
+    }
+
+    /// The protocol config matching the configured protocol version, with the configured
+    /// block size limit (if any).
+    pub fn protocol_config(&self) -> ProtocolConfig {
+        let mut config = match self.protocol_version {
+            Some(version) => {
+                ProtocolConfig::get_for_version(ProtocolVersion::new(version), Chain::Unknown)
+            }
+            None => ProtocolConfig::get_for_max_version_UNSAFE(),
+        };
+        if let Some(max_transactions) = self.max_transactions_per_block {
+            config.set_consensus_max_num_transactions_in_block_for_testing(max_transactions);
+        }
+        config
+    }
+}
+
+/// The fully-resolved configuration of a node, logged (and optionally dumped) at startup so
+/// that a run can be reproduced.
+#[derive(Clone, Debug, Serialize)]
+pub struct EffectiveConfig {
+    /// The node configuration after applying the config file, flags, and defaults.
+    pub node: NodeConfig,
+    /// The protocol version actually run.
+    pub resolved_protocol_version: u64,
+    /// The number of authorities in the committee.
+    pub committee_size: usize,
+    /// A digest of the committee (addresses, stakes, and public keys).
+    pub committee_checksum: String,
+    /// The environment variables affecting the node. Values of variables that look like
+    /// secrets are redacted.
+    pub environment: BTreeMap<String, String>,
+}
+
+impl EffectiveConfig {
+    /// The prefixes of the environment variables recorded in the effective configuration.
+    const ENVIRONMENT_PREFIXES: &'static [&'static str] = &["MYSTICETI_", "RUST_LOG", "TOKIO_"];
+    /// Variables whose name contains one of these are redacted.
+    const SECRET_MARKERS: &'static [&'static str] = &["SECRET", "TOKEN", "PASSWORD", "PRIVATE"];
+
+    /// Resolve the effective configuration of a node running in the specified committee.
+    pub fn new(node: &NodeConfig, committee: &Committee) -> Self {
+        let committee_bytes =
+            serde_json::to_vec(committee).expect("Committee serialization cannot fail");
+        let committee_checksum = DefaultHashFunction::digest(&committee_bytes)
+            .digest
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        let environment = std::env::vars()
+            .filter(|(name, _)| {
+                Self::ENVIRONMENT_PREFIXES
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+            })
+            .map(|(name, value)| {
+                let secret = Self::SECRET_MARKERS
+                    .iter()
+                    .any(|marker| name.to_uppercase().contains(marker));
+                match secret {
+                    true => (name, "<redacted>".to_string()),
+                    false => (name, value),
+                }
+            })
+            .collect();
+
+        Self {
+            node: node.clone(),
+            resolved_protocol_version: node.protocol_config().version.as_u64(),
+            committee_size: committee.size(),
+            committee_checksum,
+            environment,
+        }
+    }
+
+    /// The effective configuration as yaml.
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(self).expect("Config serialization cannot fail")
+    }
+
+    /// Write the effective configuration to a yaml file.
+    pub fn dump<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_yaml())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::PathBuf, time::Duration};
+
+    use consensus_config::RocksDbCompression;
+    use sui_protocol_config::ProtocolVersion;
+
+    use super::{ClockMode, NodeConfig};
+
+    #[test]
+    fn load_partial_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node.yaml");
+        std::fs::write(
+            &path,
+            "authority_index: 2\nrpc_port: 9000\nchain_id: test\ncommittee:\n  size: 7\n",
+        )
+        .unwrap();
+
+        let config = NodeConfig::load(&path).unwrap();
+        assert_eq!(config.authority_index, 2);
+        assert_eq!(config.rpc_port, 9000);
+        assert_eq!(config.chain_id, "test");
+        assert_eq!(config.verifier.threads, 1);
+        assert_eq!(config.committee.size, 7);
+    }
+
+    #[test]
+    fn load_http_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node.yaml");
+        std::fs::write(
+            &path,
+            "http:\n  http2: false\n  keep_alive_interval_secs: null\n  max_concurrent_streams: 64\n",
+        )
+        .unwrap();
+
+        let config = NodeConfig::load(&path).unwrap();
+        assert!(!config.http.http2);
+        assert!(config.http.keep_alive);
+        assert_eq!(config.http.keep_alive_interval_secs, None);
+        assert_eq!(config.http.max_concurrent_streams, 64);
+
+        std::fs::write(&path, "http:\n  keep_alive_timeout_secs: 0\n").unwrap();
+        assert!(NodeConfig::load(&path).is_err());
+    }
+
+    #[test]
+    fn reject_port_conflicts() {
+        let mut config = NodeConfig::new(1, PathBuf::from("data")).unwrap();
+        assert!(config.validate().is_ok());
+
+        config.abci_port = config.rpc_port;
+        assert!(config.validate().is_err());
+
+        // A disabled server does not bind its port.
+        config.servers.abci = false;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn reject_port_overflows() {
+        let config = NodeConfig::new(1_000, PathBuf::from("data")).unwrap();
+        assert_eq!(config.rpc_port, NodeConfig::DEFAULT_RPC_PORT + 1_000);
+
+        assert!(NodeConfig::new(60_000, PathBuf::from("data")).is_err());
+        assert!(NodeConfig::new(u32::MAX, PathBuf::from("data")).is_err());
+    }
+
+    #[test]
+    fn reject_unsupported_protocol_versions() {
+        let mut config = NodeConfig::new(0, PathBuf::from("data")).unwrap();
+        config.protocol_version = Some(ProtocolVersion::MAX.as_u64());
+        assert!(config.validate().is_ok());
+
+        config.protocol_version = Some(ProtocolVersion::MAX.as_u64() + 1);
+        assert!(config.validate().is_err());
+        config.protocol_version = Some(ProtocolVersion::MIN.as_u64() - 1);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn reject_clock_drift() {
+        let mut config = NodeConfig::new(0, PathBuf::from("data")).unwrap();
+        config.clock = ClockMode::Test { drift_ms: 0 };
+        assert!(config.validate().is_ok());
+
+        config.clock = ClockMode::Test { drift_ms: 500 };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn override_block_size_limit() {
+        let mut config = NodeConfig::new(0, PathBuf::from("data")).unwrap();
+        let default_limit = config.protocol_config().max_num_transactions_in_block();
+
+        config.max_transactions_per_block = Some(default_limit / 2);
+        assert_eq!(
+            config.protocol_config().max_num_transactions_in_block(),
+            default_limit / 2
+        );
+
+        config.max_transactions_per_block = Some(0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn override_consensus_parameters() {
+        let mut config = NodeConfig::new(0, PathBuf::from("data")).unwrap();
+        config.consensus.leader_timeout_ms = Some(1_000);
+        config.consensus.dag_state_cached_rounds = Some(100);
+        let parameters = config.consensus_parameters();
+        assert_eq!(parameters.leader_timeout, Duration::from_secs(1));
+        assert_eq!(parameters.dag_state_cached_rounds, 100);
+        assert_eq!(
+            parameters.db_path,
+            PathBuf::from("data/node-0/consensus.db")
+        );
+
+        config.consensus.commit_sync_batch_size = Some(0);
+        assert!(config.validate().is_err());
+        config.consensus.commit_sync_batch_size = None;
+
+        config.consensus.rocksdb_write_buffer_size_mb = Some(512);
+        config.consensus.rocksdb_compression = Some(RocksDbCompression::Zstd);
+        let parameters = config.consensus_parameters();
+        assert_eq!(parameters.rocksdb.write_buffer_size_mb, Some(512));
+        assert_eq!(
+            parameters.rocksdb.compression,
+            Some(RocksDbCompression::Zstd)
+        );
+        assert_eq!(parameters.rocksdb.max_background_jobs, None);
+
+        config.consensus.rocksdb_max_background_jobs = Some(0);
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Idempotent submissions. A client retrying a failed submission attaches the same idempotency
+//! key to every attempt; the node forwards the first attempt to consensus and answers the
+//! following ones with the acknowledgment of the first, so that a retry never submits the
+//! transaction twice (which would inflate the committed counts).
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+/// The maximum length of an idempotency key, in bytes.
+pub const MAX_KEY_BYTES: usize = 128;
+
+/// What to do with a submission carrying an idempotency key.
+#[derive(Debug)]
+pub enum Claim {
+    /// First attempt: forward the transaction, then `acknowledge` the key. Dropping the claimed
+    /// key without acknowledging it (e.g. when forwarding fails or the request is cancelled)
+    /// releases it, so that a retry is forwarded.
+    First(ClaimedKey),
+    /// An earlier attempt is still being forwarded: the client should retry later.
+    InFlight,
+    /// An earlier attempt was accepted with the specified transaction id.
+    Acknowledged(String),
+}
+
+/// The state of an idempotency key.
+#[derive(Debug)]
+enum KeyState {
+    InFlight,
+    Acknowledged(String),
+}
+
+/// Remembers the idempotency keys of the most recent submissions.
+#[derive(Debug)]
+pub struct SubmissionDedup {
+    /// The maximum number of keys remembered. The oldest ones are forgotten first.
+    capacity: usize,
+    inner: Mutex<SubmissionDedupInner>,
+}
+
+#[derive(Debug, Default)]
+struct SubmissionDedupInner {
+    /// The state of every remembered key, with the sequence number of its claim.
+    keys: HashMap<String, (u64, KeyState)>,
+    /// The remembered keys by sequence number, from the oldest claim.
+    order: BTreeMap<u64, String>,
+    /// The sequence number of the next claim.
+    next_sequence: u64,
+}
+
+impl SubmissionDedup {
+    /// Remember the `capacity` most recent keys. A zero capacity disables deduplication: every
+    /// attempt is forwarded.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(SubmissionDedupInner::default()),
+        }
+    }
+
+    /// Claim the key for an attempt.
+    pub fn claim(self: &Arc<Self>, key: &str) -> Claim {
+        let mut inner = self.inner.lock();
+        match inner.keys.get(key) {
+            Some((_, KeyState::InFlight)) => return Claim::InFlight,
+            Some((_, KeyState::Acknowledged(id))) => return Claim::Acknowledged(id.clone()),
+            None => (),
+        }
+        let sequence = inner.next_sequence;
+        inner.next_sequence += 1;
+        if self.capacity > 0 {
+            inner
+                .keys
+                .insert(key.to_string(), (sequence, KeyState::InFlight));
+            inner.order.insert(sequence, key.to_string());
+            while inner.order.len() > self.capacity {
+                let (_, oldest) = inner.order.pop_first().expect("The keys are not empty");
+                inner.keys.remove(&oldest);
+            }
+        }
+        Claim::First(ClaimedKey {
+            dedup: self.clone(),
+            key: key.to_string(),
+            sequence,
+            acknowledged: false,
+        })
+    }
+
+    /// Forget the in-flight claim of the key with the specified sequence number, if it is
+    /// still remembered. A later claim of the same key is left untouched.
+    fn release(&self, key: &str, sequence: u64) {
+        let mut inner = self.inner.lock();
+        if matches!(inner.keys.get(key), Some((s, KeyState::InFlight)) if *s == sequence) {
+            inner.keys.remove(key);
+            inner.order.remove(&sequence);
+        }
+    }
+}
+
+/// A key claimed by the first attempt of a submission, released when dropped unless the
+/// attempt was acknowledged.
+#[derive(Debug)]
+pub struct ClaimedKey {
+    dedup: Arc<SubmissionDedup>,
+    key: String,
+    /// The sequence number of the claim, telling it apart from later claims of the same key.
+    sequence: u64,
+    acknowledged: bool,
+}
+
+impl ClaimedKey {
+    /// Record that the first attempt was accepted, so that retries get the same transaction id.
+    pub fn acknowledge(mut self, transaction_id: &str) {
+        let mut inner = self.dedup.inner.lock();
+        let claim = inner.keys.get_mut(&self.key);
+        if let Some((_, state)) = claim.filter(|(sequence, _)| *sequence == self.sequence) {
+            *state = KeyState::Acknowledged(transaction_id.to_string());
+        }
+        self.acknowledged = true;
+    }
+}
+
+impl Drop for ClaimedKey {
+    fn drop(&mut self) {
+        if !self.acknowledged {
+            self.dedup.release(&self.key, self.sequence);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::{Claim, ClaimedKey, SubmissionDedup};
+
+    /// Claim the key, expecting the first attempt.
+    fn first(dedup: &Arc<SubmissionDedup>, key: &str) -> ClaimedKey {
+        match dedup.claim(key) {
+            Claim::First(claimed) => claimed,
+            claim => panic!("Expected the first attempt, got {claim:?}"),
+        }
+    }
+
+    #[test]
+    fn deduplicate_retries() {
+        let dedup = Arc::new(SubmissionDedup::new(2));
+        let claimed = first(&dedup, "a");
+        assert!(matches!(dedup.claim("a"), Claim::InFlight));
+        claimed.acknowledge("id-a");
+        assert!(matches!(dedup.claim("a"), Claim::Acknowledged(id) if id == "id-a"));
+
+        // A failed attempt, dropping its claim, can be retried.
+        drop(first(&dedup, "b"));
+        first(&dedup, "b").acknowledge("id-b");
+
+        // The oldest keys are forgotten first.
+        first(&dedup, "c").acknowledge("id-c");
+        first(&dedup, "a");
+        first(&dedup, "b");
+
+        // Without capacity, every attempt is forwarded.
+        let dedup = Arc::new(SubmissionDedup::new(0));
+        first(&dedup, "a");
+        let claimed = first(&dedup, "a");
+        claimed.acknowledge("id-a");
+        first(&dedup, "a");
+    }
+
+    #[test]
+    fn release_only_the_own_claim() {
+        let dedup = Arc::new(SubmissionDedup::new(1));
+        let forgotten = first(&dedup, "a");
+        // Claiming another key forgets the in-flight claim of the first one.
+        first(&dedup, "b").acknowledge("id-b");
+        let claimed = first(&dedup, "a");
+
+        // The forgotten claim does not release the later claim of its key.
+        drop(forgotten);
+        assert!(matches!(dedup.claim("a"), Claim::InFlight));
+        claimed.acknowledge("id-a");
+        assert!(matches!(dedup.claim("a"), Claim::Acknowledged(id) if id == "id-a"));
+    }
+}
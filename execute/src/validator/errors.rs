@@ -0,0 +1,129 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The most recent errors of a node, served on `/errors` so that operators (and the
+//! orchestrator) see why a node degrades without grepping its logs.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// The maximum length of a recorded message, in bytes. Longer messages are truncated.
+const MAX_MESSAGE_BYTES: usize = 512;
+
+/// An error recorded by the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RecordedError {
+    /// The number of errors recorded before this one.
+    pub sequence: u64,
+    /// When the error was recorded, in milliseconds since the epoch.
+    pub unix_time_ms: u64,
+    /// What failed (e.g. `submit`, `journal`).
+    pub source: &'static str,
+    pub message: String,
+}
+
+/// The errors served on `/errors`, the most recent first.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ErrorsReport {
+    /// The number of errors recorded since the node started, including the forgotten ones.
+    pub total: u64,
+    pub errors: Vec<RecordedError>,
+}
+
+/// A ring buffer of the most recent errors. Recording never blocks: each error claims a slot
+/// with an atomic counter, and is dropped in the rare case that slot is being read.
+pub struct ErrorLog {
+    slots: Box<[Mutex<Option<RecordedError>>]>,
+    next: AtomicU64,
+}
+
+impl ErrorLog {
+    /// Remember the `capacity` most recent errors. A zero capacity only counts them.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity).map(|_| Mutex::new(None)).collect(),
+            next: AtomicU64::new(0),
+        }
+    }
+
+    /// Record an error of the specified source.
+    pub fn record(&self, source: &'static str, message: impl ToString) {
+        let sequence = self.next.fetch_add(1, Ordering::Relaxed);
+        if self.slots.is_empty() {
+            return;
+        }
+        let Some(mut slot) = self.slots[(sequence % self.slots.len() as u64) as usize].try_lock()
+        else {
+            return;
+        };
+        // A slower writer may come back around the ring after a more recent error.
+        if slot.as_ref().is_some_and(|error| error.sequence > sequence) {
+            return;
+        }
+        let mut message = message.to_string();
+        if message.len() > MAX_MESSAGE_BYTES {
+            let mut end = MAX_MESSAGE_BYTES;
+            while !message.is_char_boundary(end) {
+                end -= 1;
+            }
+            message.truncate(end);
+        }
+        *slot = Some(RecordedError {
+            sequence,
+            unix_time_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            source,
+            message,
+        });
+    }
+
+    /// Copy the recorded errors, the most recent first.
+    pub fn report(&self) -> ErrorsReport {
+        let mut errors: Vec<_> = self
+            .slots
+            .iter()
+            .filter_map(|slot| slot.lock().clone())
+            .collect();
+        errors.sort_by(|a, b| b.sequence.cmp(&a.sequence));
+        ErrorsReport {
+            total: self.next.load(Ordering::Relaxed),
+            errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ErrorLog, MAX_MESSAGE_BYTES};
+
+    #[test]
+    fn keep_most_recent_errors() {
+        let log = ErrorLog::new(3);
+        for i in 0..5 {
+            log.record("submit", format!("error {i}"));
+        }
+        log.record("journal", format!("x{}", "é".repeat(MAX_MESSAGE_BYTES)));
+
+        let report = log.report();
+        assert_eq!(report.total, 6);
+        let sources: Vec<_> = report.errors.iter().map(|error| error.source).collect();
+        assert_eq!(sources, ["journal", "submit", "submit"]);
+        assert_eq!(report.errors[1].message, "error 4");
+        assert_eq!(report.errors[2].message, "error 3");
+        // Truncated on a character boundary.
+        assert_eq!(report.errors[0].message.len(), MAX_MESSAGE_BYTES - 1);
+
+        // Without capacity, errors are only counted.
+        let log = ErrorLog::new(0);
+        log.record("submit", "timeout");
+        assert_eq!(log.report().total, 1);
+        assert!(log.report().errors.is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// A Byzantine-lite fault: the node silently drops or corrupts a fraction of the transactions
+/// it receives from clients before submitting them to consensus, while still acknowledging
+/// them. Only meant to test how the committee tolerates a faulty-but-alive node; never enable
+/// it on a real deployment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionFault {
+    /// The fraction of the transactions dropped.
+    pub drop_ratio: f64,
+    /// The fraction of the transactions corrupted (one byte flipped).
+    pub corrupt_ratio: f64,
+}
+
+// The ratios are validated to be finite.
+impl Eq for TransactionFault {}
+
+/// What the fault does to a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FaultOutcome {
+    /// The transaction is submitted unchanged.
+    Forwarded(Vec<u8>),
+    /// The transaction is submitted with one byte flipped.
+    Corrupted(Vec<u8>),
+    /// The transaction is silently discarded.
+    Dropped,
+}
+
+impl TransactionFault {
+    /// Whether the fault affects any transaction.
+    pub fn is_active(&self) -> bool {
+        self.drop_ratio > 0.0 || self.corrupt_ratio > 0.0
+    }
+
+    /// Check that the ratios are valid fractions.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, ratio) in [("drop", self.drop_ratio), ("corrupt", self.corrupt_ratio)] {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(format!(
+                    "The {name} ratio must be between 0 and 1 (got {ratio})"
+                ));
+            }
+        }
+        if self.drop_ratio + self.corrupt_ratio > 1.0 {
+            return Err("The drop and corrupt ratios cannot sum to more than 1".into());
+        }
+        Ok(())
+    }
+
+    /// Apply the fault to a transaction, given a uniform sample in `[0, 1)`.
+    pub fn apply(&self, mut transaction: Vec<u8>, sample: f64) -> FaultOutcome {
+        if sample < self.drop_ratio {
+            return FaultOutcome::Dropped;
+        }
+        if sample < self.drop_ratio + self.corrupt_ratio && !transaction.is_empty() {
+            let middle = transaction.len() / 2;
+            transaction[middle] ^= 0xff;
+            return FaultOutcome::Corrupted(transaction);
+        }
+        FaultOutcome::Forwarded(transaction)
+    }
+}
+
+impl FromStr for TransactionFault {
+    type Err = String;
+
+    /// Parse `none` or comma-separated `drop=RATIO` and `corrupt=RATIO` (e.g.
+    /// `drop=0.1,corrupt=0.05`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fault = Self::default();
+        if s == "none" {
+            return Ok(fault);
+        }
+        for part in s.split(',') {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected KEY=RATIO, got '{part}'"))?;
+            let ratio: f64 = value
+                .trim()
+                .parse()
+                .map_err(|e| format!("Invalid ratio '{value}': {e}"))?;
+            match key.trim() {
+                "drop" => fault.drop_ratio = ratio,
+                "corrupt" => fault.corrupt_ratio = ratio,
+                _ => return Err(format!("Unknown fault '{key}' (expected drop or corrupt)")),
+            }
+        }
+        fault.validate()?;
+        Ok(fault)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FaultOutcome, TransactionFault};
+
+    #[test]
+    fn drop_and_corrupt_transactions() {
+        let fault: TransactionFault = "drop=0.25,corrupt=0.25".parse().unwrap();
+        assert!(fault.is_active());
+        assert_eq!(fault.apply(vec![1, 2, 3], 0.1), FaultOutcome::Dropped);
+        match fault.apply(vec![1, 2, 3], 0.3) {
+            FaultOutcome::Corrupted(transaction) => {
+                assert_eq!(transaction.len(), 3);
+                assert_ne!(transaction, vec![1, 2, 3]);
+            }
+            outcome => panic!("Unexpected outcome {outcome:?}"),
+        }
+        assert_eq!(
+            fault.apply(vec![1, 2, 3], 0.9),
+            FaultOutcome::Forwarded(vec![1, 2, 3])
+        );
+
+        assert!(!"none".parse::<TransactionFault>().unwrap().is_active());
+        assert!("drop=0.8,corrupt=0.5".parse::<TransactionFault>().is_err());
+        assert!("delay=0.1".parse::<TransactionFault>().is_err());
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The extension point of applications built on consensus: the node hands every committed
+//! sub-dag to its `CommitHandler`s, which apply it to the state machine of the application.
+//!
+//! Commits are fanned out in two ways, with different guarantees:
+//! - handlers are lossless: every handler receives every commit exactly once, in commit order.
+//!   Each handler has a bounded queue; when it is full, the node waits for the handler to catch
+//!   up (backpressure) rather than dropping commits, which would corrupt a state machine;
+//! - subscribers are lossy: they receive the commits on a broadcast channel, and a subscriber
+//!   falling too far behind misses the oldest commits (its next `recv` reports how many). Fit
+//!   for monitoring and dashboards, never for state.
+
+use std::sync::Arc;
+
+use consensus_core::CommittedSubDag;
+use tokio::{
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+};
+use tracing::error;
+
+/// The number of commits queued for a handler before the node waits for it.
+pub const HANDLER_QUEUE_CAPACITY: usize = 64;
+/// The number of commits buffered for subscribers before the slowest ones miss commits.
+pub const SUBSCRIBER_CAPACITY: usize = 1024;
+
+/// Applies the commits of consensus to a downstream state machine. Every handler is called
+/// for every commit, in commit order, from its own blocking thread, so that a slow handler does
+/// not stall the runtime: handlers run concurrently with each other and with the node, but a
+/// handler more than `HANDLER_QUEUE_CAPACITY` commits behind holds back the delivery to all of
+/// them. Implemented by any `Fn(&CommittedSubDag)` closure.
+pub trait CommitHandler: Send + Sync + 'static {
+    fn on_commit(&self, subdag: &CommittedSubDag);
+}
+
+impl<F> CommitHandler for F
+where
+    F: Fn(&CommittedSubDag) + Send + Sync + 'static,
+{
+    fn on_commit(&self, subdag: &CommittedSubDag) {
+        self(subdag)
+    }
+}
+
+/// Fans the commits out to the handlers (lossless) and the subscribers (lossy).
+pub struct CommitDispatcher {
+    handlers: Vec<mpsc::Sender<Arc<CommittedSubDag>>>,
+    tasks: Vec<JoinHandle<()>>,
+    subscribers: broadcast::Sender<Arc<CommittedSubDag>>,
+}
+
+impl CommitDispatcher {
+    /// Start a blocking thread per handler, and publish the commits to the subscribers of the
+    /// channel.
+    pub fn start(
+        handlers: &[Arc<dyn CommitHandler>],
+        subscribers: broadcast::Sender<Arc<CommittedSubDag>>,
+    ) -> Self {
+        let (handlers, tasks) = handlers
+            .iter()
+            .map(|handler| {
+                let handler = handler.clone();
+                let (sender, mut receiver) =
+                    mpsc::channel::<Arc<CommittedSubDag>>(HANDLER_QUEUE_CAPACITY);
+                let task = tokio::task::spawn_blocking(move || {
+                    while let Some(subdag) = receiver.blocking_recv() {
+                        handler.on_commit(&subdag);
+                    }
+                });
+                (sender, task)
+            })
+            .unzip();
+        Self {
+            handlers,
+            tasks,
+            subscribers,
+        }
+    }
+
+    /// Queue the commit for every handler, waiting for the handlers whose queue is full, then
+    /// publish it to the subscribers.
+    pub async fn dispatch(&self, subdag: CommittedSubDag) {
+        let subdag = Arc::new(subdag);
+        for (i, handler) in self.handlers.iter().enumerate() {
+            if handler.send(subdag.clone()).await.is_err() {
+                error!(
+                    "Commit handler {} stopped, it misses commit {}",
+                    i, subdag.commit_ref.index
+                );
+            }
+        }
+        // Fails only without subscribers.
+        let _ = self.subscribers.send(subdag);
+    }
+
+    /// Wait for the handlers to process the queued commits.
+    pub async fn close(self) {
+        drop(self.handlers);
+        for task in self.tasks {
+            if let Err(e) = task.await {
+                error!("Commit handler failed: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{sync::Arc, time::Duration};
+
+    use consensus_core::{
+        BlockRef, CommitDigest, CommitRef, CommittedSubDag, TestBlock, Transaction, VerifiedBlock,
+    };
+    use parking_lot::Mutex;
+    use tokio::sync::broadcast::{self, error::RecvError};
+
+    use super::{CommitDispatcher, CommitHandler};
+
+    /// A commit of the specified index with a single block holding the transactions.
+    pub(crate) fn subdag(index: u32, transactions: &[&[u8]]) -> CommittedSubDag {
+        let block = TestBlock::new(index, 0)
+            .set_transactions(
+                transactions
+                    .iter()
+                    .map(|tx| Transaction::new(tx.to_vec()))
+                    .collect(),
+            )
+            .build();
+        CommittedSubDag::new(
+            BlockRef::MIN,
+            vec![VerifiedBlock::new_for_test(block)],
+            vec![vec![]],
+            0,
+            CommitRef::new(index, CommitDigest::MIN),
+            vec![],
+        )
+    }
+
+    /// A handler recording the index of every commit it receives.
+    fn recording_handler(delay: Duration) -> (Arc<dyn CommitHandler>, Arc<Mutex<Vec<u32>>>) {
+        let commits = Arc::new(Mutex::new(Vec::new()));
+        let seen = commits.clone();
+        let handler = move |subdag: &CommittedSubDag| {
+            std::thread::sleep(delay);
+            seen.lock().push(subdag.commit_ref.index);
+        };
+        (Arc::new(handler), commits)
+    }
+
+    #[tokio::test]
+    async fn handlers_are_lossless_subscribers_are_lossy() {
+        let (fast, fast_commits) = recording_handler(Duration::ZERO);
+        let (slow, slow_commits) = recording_handler(Duration::from_millis(1));
+        let (subscribers, mut subscriber) = broadcast::channel(2);
+        let dispatcher = CommitDispatcher::start(&[fast, slow], subscribers);
+
+        for index in 1..=100 {
+            dispatcher.dispatch(subdag(index, &[b"tx"])).await;
+        }
+        dispatcher.close().await;
+
+        let expected: Vec<_> = (1..=100).collect();
+        assert_eq!(*fast_commits.lock(), expected);
+        assert_eq!(*slow_commits.lock(), expected);
+
+        // The subscriber did not keep up: it only gets the most recent commits.
+        assert!(matches!(
+            subscriber.recv().await,
+            Err(RecvError::Lagged(98))
+        ));
+        assert_eq!(subscriber.recv().await.unwrap().commit_ref.index, 99);
+        assert_eq!(subscriber.recv().await.unwrap().commit_ref.index, 100);
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use axum::Router;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::conn::auto::Builder,
+    service::TowerToHyperService,
+};
+use tokio::{net::TcpListener, sync::watch};
+use tracing::{debug, warn};
+
+use crate::validator::config::HttpConfig;
+
+/// Build the hyper connection builder matching the HTTP settings of the node.
+fn connection_builder(config: &HttpConfig) -> Builder<TokioExecutor> {
+    let mut builder = Builder::new(TokioExecutor::new());
+    builder
+        .http1()
+        .timer(TokioTimer::new())
+        .keep_alive(config.keep_alive);
+    builder
+        .http2()
+        .timer(TokioTimer::new())
+        .keep_alive_interval(config.keep_alive_interval_secs.map(Duration::from_secs))
+        .keep_alive_timeout(Duration::from_secs(config.keep_alive_timeout_secs))
+        .max_concurrent_streams(config.max_concurrent_streams);
+    match config.http2 {
+        true => builder,
+        false => builder.http1_only(),
+    }
+}
+
+/// Serve the RPC router on the listener. Unlike `axum::serve`, this accepts HTTP/2 (with prior
+/// knowledge) and applies the keep-alive settings of the node. Runs until `shutdown` completes:
+/// the listener is then closed, and the open connections shut down gracefully in the
+/// background, once their in-flight requests complete.
+pub async fn serve<F>(listener: TcpListener, app: Router, config: &HttpConfig, shutdown: F)
+where
+    F: Future<Output = ()>,
+{
+    let builder = connection_builder(config);
+    // The connections are told to close when the sender is dropped, on return.
+    let (_closing, closing) = watch::channel(());
+    tokio::pin!(shutdown);
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = &mut shutdown => break,
+        };
+        let (stream, peer) = match accepted {
+            Ok(connection) => connection,
+            Err(e) => {
+                // Errors such as running out of file descriptors are transient.
+                warn!("Failed to accept RPC connection: {e}");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let nodelay = config.tcp_nodelay.then(|| stream.set_nodelay(true));
+        if let Some(Err(e)) = nodelay {
+            debug!("Failed to set TCP_NODELAY for {peer}: {e}");
+        }
+
+        let builder = builder.clone();
+        let service = TowerToHyperService::new(app.clone());
+        let mut closing = closing.clone();
+        tokio::spawn(async move {
+            let io = TokioIo::new(stream);
+            let connection = builder.serve_connection_with_upgrades(io, service);
+            tokio::pin!(connection);
+            let result = tokio::select! {
+                result = connection.as_mut() => result,
+                _ = closing.changed() => {
+                    connection.as_mut().graceful_shutdown();
+                    connection.await
+                }
+            };
+            if let Err(e) = result {
+                debug!("RPC connection with {peer} closed: {e}");
+            }
+        });
+    }
+    debug!("RPC server stopped accepting connections");
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{Router, routing::get};
+
+    use super::serve;
+    use crate::validator::config::HttpConfig;
+
+    #[tokio::test]
+    async fn serve_http1_and_http2() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new().route("/health", get(|| async { "OK" }));
+        let shutdown = std::future::pending();
+        tokio::spawn(async move { serve(listener, app, &HttpConfig::default(), shutdown).await });
+
+        let url = format!("http://{address}/health");
+        let http1 = reqwest::Client::builder().http1_only().build().unwrap();
+        let response = http1.get(&url).send().await.unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_11);
+        assert_eq!(response.text().await.unwrap(), "OK");
+
+        let http2 = reqwest::Client::builder()
+            .http2_prior_knowledge()
+            .build()
+            .unwrap();
+        let response = http2.get(&url).send().await.unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_2);
+        assert_eq!(response.text().await.unwrap(), "OK");
+    }
+
+    #[tokio::test]
+    async fn release_the_port_on_shutdown() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new().route("/health", get(|| async { "OK" }));
+        let (shutdown, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            let shutdown = async {
+                let _ = stopped.await;
+            };
+            serve(listener, app, &HttpConfig::default(), shutdown).await
+        });
+
+        shutdown.send(()).unwrap();
+        server.await.unwrap();
+        tokio::net::TcpListener::bind(address).await.unwrap();
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A commit journal records the committed sub-dags of a node to an append-only file, so the
+//! exact same commit sequence can later be replayed into the application without running
+//! consensus.
+//!
+//! Every entry is encoded as (all integers little-endian):
+//! `commit_index: u32 | timestamp_ms: u64 | leader_round: u32 | leader_author: u32 |
+//! num_transactions: u32 | (length: u32 | bytes)*`.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+    path::Path,
+};
+
+use consensus_core::{BlockAPI, CommittedSubDag};
+
+/// A committed sub-dag reduced to what the application needs to execute it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// The index of the commit.
+    pub commit_index: u32,
+    /// The timestamp of the commit.
+    pub timestamp_ms: u64,
+    /// The round of the leader of the commit.
+    pub leader_round: u32,
+    /// The authority that proposed the leader of the commit.
+    pub leader_author: u32,
+    /// The accepted transactions of the commit, in commit order.
+    pub transactions: Vec<Vec<u8>>,
+}
+
+impl JournalEntry {
+    /// Extract the journal entry of a committed sub-dag, skipping rejected transactions.
+    pub fn from_subdag(subdag: &CommittedSubDag) -> Self {
+        let transactions = subdag
+            .blocks
+            .iter()
+            .zip(&subdag.rejected_transactions_by_block)
+            .flat_map(|(block, rejected)| {
+                block
+                    .transactions()
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !rejected.contains(&(*i as u16)))
+                    .map(|(_, tx)| tx.data().to_vec())
+            })
+            .collect();
+
+        Self {
+            commit_index: subdag.commit_ref.index,
+            timestamp_ms: subdag.timestamp_ms,
+            leader_round: subdag.leader.round,
+            leader_author: subdag.leader.author.value() as u32,
+            transactions,
+        }
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.commit_index.to_le_bytes())?;
+        writer.write_all(&self.timestamp_ms.to_le_bytes())?;
+        writer.write_all(&self.leader_round.to_le_bytes())?;
+        writer.write_all(&self.leader_author.to_le_bytes())?;
+        writer.write_all(&(self.transactions.len() as u32).to_le_bytes())?;
+        for tx in &self.transactions {
+            writer.write_all(&(tx.len() as u32).to_le_bytes())?;
+            writer.write_all(tx)?;
+        }
+        Ok(())
+    }
+
+    /// Read the next entry. Returns `None` at the end of the journal.
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        let mut commit_index = [0u8; 4];
+        match reader.read_exact(&mut commit_index) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let timestamp_ms = u64::from_le_bytes(read_array(reader)?);
+        let leader_round = u32::from_le_bytes(read_array(reader)?);
+        let leader_author = u32::from_le_bytes(read_array(reader)?);
+        let num_transactions = u32::from_le_bytes(read_array(reader)?);
+        let mut transactions = Vec::with_capacity(num_transactions as usize);
+        for _ in 0..num_transactions {
+            let length = u32::from_le_bytes(read_array(reader)?);
+            let mut tx = vec![0u8; length as usize];
+            reader.read_exact(&mut tx)?;
+            transactions.push(tx);
+        }
+
+        Ok(Some(Self {
+            commit_index: u32::from_le_bytes(commit_index),
+            timestamp_ms,
+            leader_round,
+            leader_author,
+            transactions,
+        }))
+    }
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buffer = [0u8; N];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Appends commits to a journal file.
+pub struct CommitJournal {
+    writer: BufWriter<File>,
+}
+
+impl CommitJournal {
+    /// Open (or create) the journal at the specified path, appending to existing entries.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// Append an entry to the journal and flush it to the file.
+    pub fn append(&mut self, entry: &JournalEntry) -> io::Result<()> {
+        entry.write_to(&mut self.writer)?;
+        self.writer.flush()
+    }
+}
+
+/// Read all entries of a journal file.
+pub fn read_journal<P: AsRef<Path>>(path: P) -> io::Result<Vec<JournalEntry>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+    while let Some(entry) = JournalEntry::read_from(&mut reader)? {
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CommitJournal, JournalEntry, read_journal};
+
+    #[test]
+    fn journal_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("commits.journal");
+
+        let entries: Vec<_> = (1..=3)
+            .map(|i| JournalEntry {
+                commit_index: i,
+                timestamp_ms: 1000 + i as u64,
+                leader_round: i * 2,
+                leader_author: i % 4,
+                transactions: (0..i).map(|j| vec![j as u8; j as usize]).collect(),
+            })
+            .collect();
+
+        let mut journal = CommitJournal::open(&path).unwrap();
+        journal.append(&entries[0]).unwrap();
+        drop(journal);
+
+        // Reopening appends to the existing entries.
+        let mut journal = CommitJournal::open(&path).unwrap();
+        for entry in &entries[1..] {
+            journal.append(entry).unwrap();
+        }
+
+        assert_eq!(read_journal(&path).unwrap(), entries);
+    }
+}
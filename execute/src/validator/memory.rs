@@ -0,0 +1,168 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sheds load when the node runs low on memory: new submissions are rejected (with 503) until
+//! memory frees up, so that an overloaded node stays alive instead of getting OOM-killed.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use tracing::{info, warn};
+
+use crate::validator::{config::MemoryGuardConfig, metrics::NodeMetrics};
+
+/// The margin above the threshold the available memory must reach to stop shedding, so that
+/// the node does not flap around the threshold.
+const RECOVERY_MARGIN: f64 = 0.1;
+
+/// Periodically checks the available memory and tells the RPC server when to shed load.
+pub struct MemoryGuard {
+    shedding: AtomicBool,
+    /// Start shedding below this many available bytes.
+    min_free_bytes: u64,
+    metrics: Arc<NodeMetrics>,
+}
+
+impl MemoryGuard {
+    /// Start the task checking the available memory.
+    pub fn start(config: MemoryGuardConfig, metrics: Arc<NodeMetrics>) -> Arc<Self> {
+        let guard = Arc::new(Self {
+            shedding: AtomicBool::new(false),
+            min_free_bytes: config.min_free_mb * 1024 * 1024,
+            metrics,
+        });
+        let interval = Duration::from_millis(config.check_interval_ms);
+        let task_guard = guard.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match available_memory() {
+                    Some(available) => task_guard.update(available),
+                    None => {
+                        warn!("Cannot read the available memory, disabling the memory guard");
+                        break;
+                    }
+                }
+            }
+        });
+        guard
+    }
+
+    /// Whether new submissions must be rejected. Counts the rejected submission if so.
+    pub fn shed(&self) -> bool {
+        let shedding = self.shedding.load(Ordering::Relaxed);
+        if shedding {
+            self.metrics.shed_submissions.inc();
+        }
+        shedding
+    }
+
+    /// Record the available memory, starting or stopping to shed load accordingly.
+    fn update(&self, available: u64) {
+        self.metrics.available_memory_bytes.set(available as i64);
+        let recovery = self.min_free_bytes as f64 * (1.0 + RECOVERY_MARGIN);
+        let shedding = self.shedding.load(Ordering::Relaxed);
+        if !shedding && available < self.min_free_bytes {
+            warn!(
+                "Only {} MiB of memory available (threshold {} MiB), rejecting new submissions",
+                available >> 20,
+                self.min_free_bytes >> 20
+            );
+            self.shedding.store(true, Ordering::Relaxed);
+            self.metrics.memory_shedding.set(1);
+        } else if shedding && available as f64 >= recovery {
+            info!(
+                "{} MiB of memory available again, accepting submissions",
+                available >> 20
+            );
+            self.shedding.store(false, Ordering::Relaxed);
+            self.metrics.memory_shedding.set(0);
+        }
+    }
+}
+
+/// The memory available to the node, in bytes: the available memory of the machine, or the
+/// headroom under the memory limit of its cgroup (e.g. a docker container) if lower.
+pub fn available_memory() -> Option<u64> {
+    let system = std::fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|meminfo| parse_meminfo(&meminfo));
+    let cgroup = std::fs::read_to_string("/sys/fs/cgroup/memory.max")
+        .ok()
+        .zip(std::fs::read_to_string("/sys/fs/cgroup/memory.current").ok())
+        .and_then(|(max, current)| cgroup_headroom(&max, &current));
+    match (system, cgroup) {
+        (Some(system), Some(cgroup)) => Some(system.min(cgroup)),
+        (system, cgroup) => system.or(cgroup),
+    }
+}
+
+/// Parse the `MemAvailable` line of `/proc/meminfo`, in bytes.
+fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// The bytes left under the cgroup (v2) memory limit, or `None` if it is unlimited.
+fn cgroup_headroom(max: &str, current: &str) -> Option<u64> {
+    let max: u64 = max.trim().parse().ok()?;
+    let current: u64 = current.trim().parse().ok()?;
+    Some(max.saturating_sub(current))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use prometheus::Registry;
+
+    use super::{MemoryGuard, cgroup_headroom, parse_meminfo};
+    use crate::validator::metrics::NodeMetrics;
+
+    #[test]
+    fn read_available_memory() {
+        let meminfo = "MemTotal:       16315276 kB\nMemFree:          712844 kB\n\
+                       MemAvailable:    8123456 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(8_123_456 * 1024));
+        assert_eq!(parse_meminfo("MemTotal: 1 kB\n"), None);
+
+        assert_eq!(
+            cgroup_headroom("1073741824\n", "73741824\n"),
+            Some(1_000_000_000)
+        );
+        assert_eq!(cgroup_headroom("max\n", "73741824\n"), None);
+    }
+
+    #[test]
+    fn shed_below_threshold() {
+        let metrics = Arc::new(NodeMetrics::new(&Registry::new()));
+        let guard = MemoryGuard {
+            shedding: Default::default(),
+            min_free_bytes: 100 << 20,
+            metrics: metrics.clone(),
+        };
+        const MIB: u64 = 1 << 20;
+
+        guard.update(200 * MIB);
+        assert!(!guard.shed());
+        guard.update(99 * MIB);
+        assert!(guard.shed());
+        // Recovering requires some margin above the threshold.
+        guard.update(105 * MIB);
+        assert!(guard.shed());
+        guard.update(120 * MIB);
+        assert!(!guard.shed());
+
+        assert_eq!(metrics.shed_submissions.get(), 2);
+        assert_eq!(metrics.memory_shedding.get(), 0);
+    }
+}
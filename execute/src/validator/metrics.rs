@@ -0,0 +1,227 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{
+    Router,
+    http::{StatusCode, header::CONTENT_TYPE},
+    routing::get,
+};
+use prometheus::{
+    Gauge, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, Registry,
+    register_gauge_with_registry, register_histogram_vec_with_registry,
+    register_histogram_with_registry, register_int_counter_vec_with_registry,
+    register_int_counter_with_registry, register_int_gauge_with_registry,
+};
+
+const COUNT_BUCKETS: &[f64] = &[
+    0.0, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0, 512.0, 1024.0, 2048.0, 4096.0, 8192.0,
+    16384.0, 32768.0, 65536.0,
+];
+
+const LATENCY_SEC_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 5.0, 7.5,
+    10.0, 20.0, 30.0, 60.0,
+];
+
+/// Metrics of the validator node (outside of consensus itself).
+pub struct NodeMetrics {
+    /// Number of transactions waiting in the RPC to consensus submission queue.
+    pub submission_queue_depth: IntGauge,
+    /// Capacity of the RPC to consensus submission queue.
+    pub submission_queue_capacity: IntGauge,
+    /// Number of transactions in each committed sub-dag.
+    pub transactions_per_commit: Histogram,
+    /// Number of blocks in each committed sub-dag.
+    pub blocks_per_commit: Histogram,
+    /// Number of committed transactions, by the authority that proposed them.
+    pub committed_transactions_by_authority: IntCounterVec,
+    /// Number of accepted transactions committed again after an earlier commit (retries or
+    /// replays), among the transactions the node still remembers.
+    pub committed_duplicate_transactions: IntCounter,
+    /// Number of committed transactions the committee rejected as invalid.
+    pub committed_rejected_transactions: IntCounter,
+    /// Number of submission retries answered with the acknowledgment of an earlier attempt.
+    pub deduplicated_submissions: IntCounter,
+    /// Number of tracked submissions, by their final status (only in committed ack mode).
+    pub submission_acks: IntCounterVec,
+    /// Time between the start of the node and its first commit (zero until it commits).
+    pub time_to_first_commit_seconds: Gauge,
+    /// Number of transactions affected by the unsafe transaction fault, by action.
+    pub faulty_transactions: IntCounterVec,
+    /// Number of commit events for the webhook, by outcome (delivered, failed, or dropped).
+    pub webhook_events: IntCounterVec,
+    /// Memory available to the node, as last checked by the memory guard.
+    pub available_memory_bytes: IntGauge,
+    /// Whether the memory guard is rejecting submissions (1) or not (0).
+    pub memory_shedding: IntGauge,
+    /// Number of submissions rejected by the memory guard.
+    pub shed_submissions: IntCounter,
+    /// Time between the acceptance of a transaction by the RPC server and its inclusion in a
+    /// proposed block: the wait in the submission queue.
+    pub submission_wait_seconds: Histogram,
+    /// Time between the inclusion of a submitted transaction in a proposed block and the
+    /// commit of that block.
+    pub propose_to_commit_seconds: Histogram,
+    /// Time between the most recent ancestor of a committed block (from another authority)
+    /// and the block, by the authority that proposed it.
+    pub block_proposal_delay_seconds: HistogramVec,
+    /// Time between a committed leader block and its commit by this node, by the authority
+    /// that proposed the leader.
+    pub leader_commit_delay_seconds: HistogramVec,
+}
+
+impl NodeMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            submission_queue_depth: register_int_gauge_with_registry!(
+                "submission_queue_depth",
+                "Number of transactions waiting in the RPC to consensus submission queue",
+                registry,
+            )
+            .unwrap(),
+            submission_queue_capacity: register_int_gauge_with_registry!(
+                "submission_queue_capacity",
+                "Capacity of the RPC to consensus submission queue",
+                registry,
+            )
+            .unwrap(),
+            transactions_per_commit: register_histogram_with_registry!(
+                "committed_transactions_per_commit",
+                "Number of transactions in each committed sub-dag",
+                COUNT_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            blocks_per_commit: register_histogram_with_registry!(
+                "committed_blocks_per_commit",
+                "Number of blocks in each committed sub-dag",
+                COUNT_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            committed_transactions_by_authority: register_int_counter_vec_with_registry!(
+                "committed_transactions_by_authority",
+                "Number of committed transactions, by the authority that proposed them",
+                &["authority"],
+                registry,
+            )
+            .unwrap(),
+            committed_duplicate_transactions: register_int_counter_with_registry!(
+                "committed_duplicate_transactions",
+                "Number of accepted transactions committed again after an earlier commit",
+                registry,
+            )
+            .unwrap(),
+            committed_rejected_transactions: register_int_counter_with_registry!(
+                "committed_rejected_transactions",
+                "Number of committed transactions the committee rejected as invalid",
+                registry,
+            )
+            .unwrap(),
+            deduplicated_submissions: register_int_counter_with_registry!(
+                "deduplicated_submissions",
+                "Number of submission retries answered with the acknowledgment of an earlier attempt",
+                registry,
+            )
+            .unwrap(),
+            submission_acks: register_int_counter_vec_with_registry!(
+                "submission_acks",
+                "Number of tracked submissions, by their final status",
+                &["status"],
+                registry,
+            )
+            .unwrap(),
+            time_to_first_commit_seconds: register_gauge_with_registry!(
+                "time_to_first_commit_seconds",
+                "Time between the start of the node and its first commit",
+                registry,
+            )
+            .unwrap(),
+            faulty_transactions: register_int_counter_vec_with_registry!(
+                "faulty_transactions",
+                "Number of transactions affected by the unsafe transaction fault, by action",
+                &["action"],
+                registry,
+            )
+            .unwrap(),
+            webhook_events: register_int_counter_vec_with_registry!(
+                "webhook_events",
+                "Number of commit events for the webhook, by outcome",
+                &["outcome"],
+                registry,
+            )
+            .unwrap(),
+            available_memory_bytes: register_int_gauge_with_registry!(
+                "available_memory_bytes",
+                "Memory available to the node, as last checked by the memory guard",
+                registry,
+            )
+            .unwrap(),
+            memory_shedding: register_int_gauge_with_registry!(
+                "memory_shedding",
+                "Whether the memory guard is rejecting submissions",
+                registry,
+            )
+            .unwrap(),
+            shed_submissions: register_int_counter_with_registry!(
+                "shed_submissions",
+                "Number of submissions rejected by the memory guard",
+                registry,
+            )
+            .unwrap(),
+            submission_wait_seconds: register_histogram_with_registry!(
+                "submission_wait_seconds",
+                "Time between the acceptance of a transaction and its inclusion in a proposed block",
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            propose_to_commit_seconds: register_histogram_with_registry!(
+                "propose_to_commit_seconds",
+                "Time between the inclusion of a transaction in a proposed block and its commit",
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            block_proposal_delay_seconds: register_histogram_vec_with_registry!(
+                "block_proposal_delay_seconds",
+                "Time between the most recent ancestor of a committed block and the block, by author",
+                &["authority"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            leader_commit_delay_seconds: register_histogram_vec_with_registry!(
+                "leader_commit_delay_seconds",
+                "Time between a committed leader block and its commit, by leader",
+                &["authority"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// The route exposing the metrics of the registry, in the Prometheus text format: `GET /metrics`.
+/// Served by the RPC server and, on its own port, by the metrics server.
+pub fn router(registry: Registry) -> Router {
+    Router::new().route(
+        "/metrics",
+        get(move || async move {
+            let metrics = registry.gather();
+            match prometheus::TextEncoder::new().encode_to_string(&metrics) {
+                Ok(text) => (
+                    StatusCode::OK,
+                    [(CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+                    text,
+                ),
+                Err(e) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    [(CONTENT_TYPE, "text/plain")],
+                    format!("Failed to encode the metrics: {e}"),
+                ),
+            }
+        }),
+    )
+}
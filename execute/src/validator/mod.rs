@@ -1,8 +1,37 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod admin;
+pub mod batch;
+pub mod bench;
+pub mod committed;
+pub mod committee;
+pub mod config;
+pub mod dedup;
+pub mod errors;
+pub mod fault;
+pub mod handler;
+pub mod http;
+pub mod journal;
+pub mod memory;
+pub mod metrics;
 pub mod network;
 pub mod node;
+pub mod order;
+pub mod peers;
+pub mod priority;
+pub mod stats;
+pub mod structured;
+pub mod timing;
+pub mod verifier;
+pub mod webhook;
 
-pub use network::ValidatorNetwork;
+pub use config::{
+    AckMode, ClockMode, ConsensusConfig, EffectiveConfig, HttpConfig, MemoryGuardConfig,
+    NodeConfig, ServersConfig, VerifierConfig, WebhookConfig,
+};
+pub use fault::TransactionFault;
+pub use handler::CommitHandler;
+pub use network::{ShutdownOrder, ValidatorNetwork};
 pub use node::ValidatorNode;
+pub use verifier::{AcceptAllVerifier, Ed25519SignatureVerifier, ParallelVerifier};
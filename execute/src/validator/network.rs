@@ -1,91 +1,316 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr, time::Duration};
 use tracing::info;
 
-use consensus_config::local_committee_and_keys;
+use consensus_config::{Committee, NetworkKeyPair, ProtocolKeyPair, local_committee_and_keys};
 use mysten_metrics::RegistryService;
 use prometheus::Registry;
 
+use crate::validator::config::NodeConfig;
 use crate::validator::node::ValidatorNode;
 
+/// The order in which `ValidatorNetwork::stop` stops the nodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShutdownOrder {
+    /// Stop the nodes in index order.
+    #[default]
+    Index,
+    /// Stop the proposer of the latest committed leader last, so that the nodes still running
+    /// keep committing for as long as possible.
+    LeaderLast,
+}
+
+impl FromStr for ShutdownOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "index" => Ok(Self::Index),
+            "leader-last" => Ok(Self::LeaderLast),
+            _ => Err(format!(
+                "Unknown shutdown order '{s}' (expected index or leader-last)"
+            )),
+        }
+    }
+}
+
+impl ShutdownOrder {
+    /// The indices of `nodes` nodes in the order in which they are stopped, given the
+    /// proposer of the latest committed leader (if known).
+    pub fn sequence(&self, nodes: usize, leader: Option<usize>) -> Vec<usize> {
+        let mut sequence: Vec<_> = (0..nodes).collect();
+        if let (Self::LeaderLast, Some(leader)) = (self, leader) {
+            sequence.retain(|i| *i != leader);
+            if leader < nodes {
+                sequence.push(leader);
+            }
+        }
+        sequence
+    }
+}
+
 pub struct ValidatorNetwork {
     working_directory: PathBuf,
+    committee_size: usize,
+    in_memory: bool,
+    abci: bool,
     nodes: Vec<ValidatorNode>,
+    shutdown_order: ShutdownOrder,
+    shutdown_grace: Duration,
 }
 
 impl ValidatorNetwork {
-    pub fn new(working_directory: PathBuf) -> Self {
-        Self {
+    /// The default delay between stopping two nodes.
+    pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_millis(200);
+
+    /// Create a network of `committee_size` nodes. The ports of every node are offset by its
+    /// index from the default base ports (e.g. node `i` serves RPC on `26657 + i`), so they must
+    /// all fit in a `u16` and the ranges of two servers must not overlap: RPC and ABCI are only
+    /// 13 ports apart, which caps the committee at 13 nodes when the ABCI servers run.
+    pub fn new(
+        working_directory: PathBuf,
+        committee_size: usize,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if committee_size == 0 {
+            return Err("The committee needs at least one node".into());
+        }
+        let network = Self {
             working_directory,
+            committee_size,
+            in_memory: false,
+            abci: false,
             nodes: Vec::new(),
+            shutdown_order: ShutdownOrder::default(),
+            shutdown_grace: Self::DEFAULT_SHUTDOWN_GRACE,
+        };
+        network.check_ports()?;
+        Ok(network)
+    }
+
+    /// Keep the consensus DB of every node in memory instead of on disk.
+    pub fn with_in_memory(mut self, in_memory: bool) -> Self {
+        self.in_memory = in_memory;
+        self
+    }
+
+    /// Run the ABCI server of every node (disabled by default). The ABCI server cannot be shut
+    /// down, so its port stays bound until the process exits, even after `stop`.
+    pub fn with_abci(
+        mut self,
+        abci: bool,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        self.abci = abci;
+        self.check_ports()?;
+        Ok(self)
+    }
+
+    /// Check that the ports of the servers run by the nodes fit in a `u16` and do not overlap.
+    fn check_ports(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let committee_size = self.committee_size;
+        let mut base_ports = vec![
+            (NodeConfig::DEFAULT_RPC_PORT, "RPC"),
+            (NodeConfig::DEFAULT_GRPC_PORT, "gRPC"),
+            (NodeConfig::DEFAULT_METRICS_PORT, "metrics"),
+        ];
+        if self.abci {
+            base_ports.push((NodeConfig::DEFAULT_ABCI_PORT, "ABCI"));
+        }
+        base_ports.sort();
+        for pair in base_ports.windows(2) {
+            let ((low_port, low_server), (high_port, high_server)) = (pair[0], pair[1]);
+            if low_port as usize + committee_size > high_port as usize {
+                return Err(format!(
+                    "A committee of {committee_size} nodes would serve {low_server} (from {low_port}) and {high_server} (from {high_port}) on the same ports"
+                )
+                .into());
+            }
+        }
+        let (highest_base_port, _) = base_ports[base_ports.len() - 1];
+        if highest_base_port as usize + committee_size - 1 > u16::MAX as usize {
+            return Err(format!(
+                "A committee of {committee_size} nodes needs ports beyond {} (from {highest_base_port})",
+                u16::MAX
+            )
+            .into());
         }
+        Ok(())
+    }
+
+    /// Set the order in which `stop` stops the nodes.
+    pub fn with_shutdown_order(mut self, shutdown_order: ShutdownOrder) -> Self {
+        self.shutdown_order = shutdown_order;
+        self
+    }
+
+    /// Set the delay between stopping two nodes.
+    pub fn with_shutdown_grace(mut self, shutdown_grace: Duration) -> Self {
+        self.shutdown_grace = shutdown_grace;
+        self
     }
 
     pub async fn start(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!(
-            "Starting validator network with 4 nodes in directory: {}",
+            "Starting validator network with {} nodes in directory: {}",
+            self.committee_size,
             self.working_directory.display()
         );
 
         // Create working directory
         std::fs::create_dir_all(&self.working_directory)?;
 
-        // Generate committee and keypairs for 4 nodes
-        let committee_size = 4;
-        let (committee, keypairs) = local_committee_and_keys(0, vec![1; committee_size]);
+        // Generate committee and keypairs
+        let (committee, keypairs) = local_committee_and_keys(0, vec![1; self.committee_size]);
 
-        // Define RPC ports for each node
-        let rpc_ports = [26657, 26658, 26659, 26660];
+        // Start all validator nodes, on the default ports offset by their index. A node failing
+        // to start stops the nodes already started, so that the network can be started again.
+        for i in 0..self.committee_size {
+            if let Err(e) = self.start_node(i, &committee, &keypairs).await {
+                for node in &mut self.nodes {
+                    node.stop().await;
+                }
+                self.nodes.clear();
+                return Err(e);
+            }
+        }
 
-        // Start all 4 validator nodes
-        for (i, rpc_port) in rpc_ports.iter().enumerate().take(committee_size) {
-            let authority_index = i as u32;
+        info!("Validator network started successfully!");
+        info!("RPC endpoints:");
+        for (i, endpoint) in self.get_rpc_endpoints().iter().enumerate() {
+            info!("  Node {}: {}/broadcast_tx_async", i, endpoint);
+        }
 
-            let mut node =
-                ValidatorNode::new(authority_index, self.working_directory.clone(), *rpc_port);
+        Ok(())
+    }
 
-            // Create a unique registry for each node to avoid conflicts
-            let node_registry_service = RegistryService::new(Registry::new());
+    /// Start the node of index `i` and add it to the network.
+    async fn start_node(
+        &mut self,
+        i: usize,
+        committee: &Committee,
+        keypairs: &[(NetworkKeyPair, ProtocolKeyPair)],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let authority_index = i as u32;
 
-            // Start the node
-            node.start(committee.clone(), keypairs.clone(), node_registry_service)
-                .await?;
+        let mut config = NodeConfig {
+            in_memory: self.in_memory,
+            ..NodeConfig::new(authority_index, self.working_directory.clone())?
+        };
+        config.servers.abci = self.abci;
+        let rpc_port = config.rpc_port;
+        let mut node = ValidatorNode::new(config);
 
-            self.nodes.push(node);
+        // Create a unique registry for each node to avoid conflicts
+        let node_registry_service = RegistryService::new(Registry::new());
 
-            info!(
-                "Started validator node {} on RPC port {}",
-                authority_index, rpc_port
-            );
-        }
+        // Start the node
+        node.start(
+            committee.clone(),
+            keypairs[i].clone(),
+            node_registry_service,
+        )
+        .await?;
 
-        info!("Validator network started successfully!");
-        info!("RPC endpoints:");
-        for (i, port) in rpc_ports.iter().enumerate() {
-            info!("  Node {}: http://127.0.0.1:{}/broadcast_tx_async", i, port);
-        }
+        self.nodes.push(node);
 
+        info!(
+            "Started validator node {} on RPC port {}",
+            authority_index, rpc_port
+        );
         Ok(())
     }
 
     pub async fn stop(&mut self) {
         info!("Stopping validator network...");
 
-        for (i, node) in self.nodes.iter_mut().enumerate() {
+        // The node with the most commits has the most recent view of the leader.
+        let leader = self
+            .nodes
+            .iter()
+            .map(|node| node.stats().snapshot())
+            .max_by_key(|stats| stats.commits)
+            .and_then(|stats| stats.last_leader)
+            .map(|leader| leader as usize);
+        let sequence = self.shutdown_order.sequence(self.nodes.len(), leader);
+        info!(
+            "Stopping nodes in order {:?}; the nodes still running may report losing quorum, \
+            which is expected during an intentional shutdown",
+            sequence
+        );
+
+        for (position, i) in sequence.into_iter().enumerate() {
+            if position > 0 {
+                tokio::time::sleep(self.shutdown_grace).await;
+            }
             info!("Stopping node {}", i);
-            node.stop().await;
+            self.nodes[i].stop().await;
         }
 
         info!("Validator network stopped");
     }
 
     pub fn get_rpc_endpoints(&self) -> Vec<String> {
-        let rpc_ports = [26657, 26658, 26659, 26660];
-        rpc_ports
-            .iter()
-            .map(|port| format!("http://127.0.0.1:{}", port))
+        (0..self.committee_size)
+            .map(|i| {
+                let port = NodeConfig::DEFAULT_RPC_PORT + i as u16;
+                format!("http://127.0.0.1:{}", port)
+            })
             .collect()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::{ShutdownOrder, ValidatorNetwork};
+
+    #[test]
+    fn shutdown_sequence() {
+        assert_eq!(ShutdownOrder::Index.sequence(4, Some(1)), vec![0, 1, 2, 3]);
+        assert_eq!(
+            ShutdownOrder::LeaderLast.sequence(4, Some(1)),
+            vec![0, 2, 3, 1]
+        );
+        assert_eq!(
+            ShutdownOrder::LeaderLast.sequence(4, None),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            "leader-last".parse::<ShutdownOrder>(),
+            Ok(ShutdownOrder::LeaderLast)
+        );
+    }
+    #[test]
+    fn size_the_committee() {
+        let network = ValidatorNetwork::new(PathBuf::from(".data"), 7).unwrap();
+        let endpoints = network.get_rpc_endpoints();
+        assert_eq!(endpoints.len(), 7);
+        assert_eq!(endpoints[6], "http://127.0.0.1:26663");
+
+        assert!(ValidatorNetwork::new(PathBuf::from(".data"), 0).is_err());
+        assert!(ValidatorNetwork::new(PathBuf::from(".data"), 20_000).is_err());
+    }
+
+    #[test]
+    fn keep_the_server_ports_apart() {
+        // Node 13 would serve RPC on 26670, the ABCI port of node 0.
+        let network = ValidatorNetwork::new(PathBuf::from(".data"), 13)
+            .unwrap()
+            .with_abci(true)
+            .unwrap();
+        assert_eq!(network.get_rpc_endpoints()[12], "http://127.0.0.1:26669");
+        // Without the ABCI servers, their ports are free for RPC.
+        let network = ValidatorNetwork::new(PathBuf::from(".data"), 14).unwrap();
+        let error = network
+            .with_abci(true)
+            .err()
+            .expect("The RPC and ABCI ports overlap");
+        assert!(
+            error
+                .to_string()
+                .contains("RPC (from 26657) and ABCI (from 26670)")
+        );
+    }
+}
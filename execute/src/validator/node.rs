@@ -1,80 +1,179 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::net::SocketAddr;
-use std::path::PathBuf;
 use std::sync::Arc;
-use tracing::{error, info};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::{sync::oneshot, task::JoinHandle};
+use tracing::{debug, error, info, warn};
 
-use consensus_config::{AuthorityIndex, NetworkKeyPair, Parameters, ProtocolKeyPair};
+use consensus_config::{AuthorityIndex, NetworkKeyPair, ProtocolKeyPair};
 use consensus_core::{
-    Clock, CommitConsumer, ConsensusAuthority, TransactionIndex, TransactionVerifier,
-    ValidationError,
+    BlockAPI, BlockRef, BlockStatus, CommitConsumer, CommittedSubDag, ConsensusAuthority,
+    TransactionClient,
 };
 use mysten_metrics::RegistryService;
-use sui_protocol_config::{ConsensusNetwork, ProtocolConfig};
-// Simple transaction verifier that accepts all transactions
-struct SimpleTransactionVerifier;
+use parking_lot::RwLock;
+use sui_protocol_config::ConsensusNetwork;
 
-impl TransactionVerifier for SimpleTransactionVerifier {
-    fn verify_batch(&self, _batch: &[&[u8]]) -> Result<(), ValidationError> {
-        Ok(())
-    }
+use crate::abci::app::{MysticetiAbciApp, Rejection};
+use crate::grpc::MysticetiGrpcServer;
+use crate::reload::LogFilterControl;
+use crate::validator::admin;
+use crate::validator::batch::{BatchError, decode_batch};
+use crate::validator::committed::{CommittedIndex, TransactionStatus, transaction_id};
+use crate::validator::committee::{CommitteeReport, log_committee};
+use crate::validator::config::{AckMode, NodeConfig};
+use crate::validator::dedup::{self, Claim, SubmissionDedup};
+use crate::validator::errors::ErrorLog;
+use crate::validator::fault::{FaultOutcome, TransactionFault};
+use crate::validator::handler::{CommitDispatcher, CommitHandler, SUBSCRIBER_CAPACITY};
+use crate::validator::journal::{CommitJournal, JournalEntry};
+use crate::validator::memory::MemoryGuard;
+use crate::validator::metrics::NodeMetrics;
+use crate::validator::order::CommitOrderLog;
+use crate::validator::peers::PeersReport;
+use crate::validator::priority::{PriorityReceiver, Submission};
+use crate::validator::stats::NodeStats;
+use crate::validator::timing::ProposalTimer;
+use crate::validator::verifier::{TransactionCheck, build_check, build_verifier};
+use crate::validator::webhook::{CommitWebhook, PendingCommit};
 
-    fn verify_and_vote_batch(
-        &self,
-        _batch: &[&[u8]],
-    ) -> Result<Vec<TransactionIndex>, ValidationError> {
-        Ok(vec![])
-    }
-}
+/// The capacity of the queue between the RPC server and consensus.
+const SUBMISSION_QUEUE_CAPACITY: usize = 1000;
+/// How often the depth of the submission queue is sampled.
+const SUBMISSION_QUEUE_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+/// The fraction of the submission queue capacity above which the node is considered overloaded.
+const SUBMISSION_QUEUE_HIGH_WATER_MARK: f64 = 0.8;
+/// The message returned to clients submitting transactions while consensus is not running.
+pub(crate) const NOT_RUNNING_MESSAGE: &str = "Node not started or already stopped";
+const LOW_MEMORY_MESSAGE: &str = "Node low on memory, retry later";
+
+/// The client submitting transactions to consensus. It is shared with the RPC server, and only
+/// set while consensus is running (between `start` and `stop`), so that requests arriving
+/// outside of that window are rejected rather than crashing the node.
+pub(crate) type SharedTransactionClient = Arc<RwLock<Option<Arc<TransactionClient>>>>;
 
 pub struct ValidatorNode {
     authority_index: AuthorityIndex,
-    working_directory: PathBuf,
-    rpc_port: u16,
-    abci_port: u16,
+    config: NodeConfig,
     consensus_authority: Option<ConsensusAuthority>,
+    transaction_client: SharedTransactionClient,
+    metrics: Option<Arc<NodeMetrics>>,
+    stats: Arc<NodeStats>,
+    committed: Arc<CommittedIndex>,
+    dedup: Arc<SubmissionDedup>,
+    errors: Arc<ErrorLog>,
+    /// The check of the verifier, run on the transactions submitted by clients before they
+    /// reach consensus, where an invalid transaction would get the blocks of the node rejected.
+    transaction_check: Arc<dyn TransactionCheck>,
+    log_filter: Option<Arc<dyn LogFilterControl>>,
+    commit_handlers: Vec<Arc<dyn CommitHandler>>,
+    commit_subscribers: tokio::sync::broadcast::Sender<Arc<CommittedSubDag>>,
+    /// Stops the RPC server, and its task, set while the server runs.
+    rpc_server: Option<(oneshot::Sender<()>, JoinHandle<()>)>,
+    /// Stops the gRPC server, set while the server runs.
+    grpc_shutdown: Option<oneshot::Sender<()>>,
+    /// Stops the metrics server, and its task, set while the server runs.
+    metrics_server: Option<(oneshot::Sender<()>, JoinHandle<()>)>,
 }
 
 impl ValidatorNode {
-    pub fn new(authority_index: u32, working_directory: PathBuf, rpc_port: u16) -> Self {
-        let abci_port = 26670 + authority_index as u16;
+    pub fn new(config: NodeConfig) -> Self {
         Self {
-            authority_index: AuthorityIndex::new_for_test(authority_index),
-            working_directory,
-            rpc_port,
-            abci_port,
+            authority_index: AuthorityIndex::new_for_test(config.authority_index),
+            committed: Arc::new(CommittedIndex::new(config.committed_index_capacity)),
+            dedup: Arc::new(SubmissionDedup::new(config.submission_dedup_capacity)),
+            errors: Arc::new(ErrorLog::new(config.error_log_capacity)),
+            transaction_check: build_check(&config.verifier),
+            config,
             consensus_authority: None,
+            transaction_client: Arc::new(RwLock::new(None)),
+            metrics: None,
+            stats: Arc::new(NodeStats::default()),
+            log_filter: None,
+            commit_handlers: Vec::new(),
+            commit_subscribers: tokio::sync::broadcast::channel(SUBSCRIBER_CAPACITY).0,
+            rpc_server: None,
+            grpc_shutdown: None,
+            metrics_server: None,
         }
     }
 
+    /// Let the admin endpoints read and replace the log filter of the process.
+    pub fn with_log_filter_control(mut self, log_filter: Arc<dyn LogFilterControl>) -> Self {
+        self.log_filter = Some(log_filter);
+        self
+    }
+
+    /// Hand every commit to the specified handler, e.g. the state machine of an application.
+    /// Handlers are lossless: each of them receives every commit exactly once, in commit
+    /// order, and the node waits for handlers falling behind. Can be called several times.
+    pub fn with_commit_handler<H: CommitHandler>(mut self, handler: H) -> Self {
+        self.commit_handlers.push(Arc::new(handler));
+        self
+    }
+
+    /// Subscribe to the commits of the node, from the next one. Subscriptions are lossy: a
+    /// subscriber falling more than `SUBSCRIBER_CAPACITY` commits behind misses the oldest
+    /// ones, so state machines must use a commit handler instead.
+    pub fn subscribe_commits(&self) -> tokio::sync::broadcast::Receiver<Arc<CommittedSubDag>> {
+        self.commit_subscribers.subscribe()
+    }
+
+    /// The configuration of this node.
+    pub fn config(&self) -> &NodeConfig {
+        &self.config
+    }
+
+    /// The commit statistics of this node.
+    pub fn stats(&self) -> Arc<NodeStats> {
+        self.stats.clone()
+    }
+
+    /// The index of the transactions recently committed by this node.
+    pub fn committed(&self) -> Arc<CommittedIndex> {
+        self.committed.clone()
+    }
+
+    /// The most recent errors of this node.
+    pub fn errors(&self) -> Arc<ErrorLog> {
+        self.errors.clone()
+    }
+
+    /// Whether consensus is running, i.e. the node was started and not stopped since.
+    pub fn is_running(&self) -> bool {
+        self.transaction_client.read().is_some()
+    }
+
+    /// Start consensus and the servers of the node, with the key pair of its authority.
     pub async fn start(
         &mut self,
         committee: consensus_config::Committee,
-        keypairs: Vec<(NetworkKeyPair, ProtocolKeyPair)>,
+        keypair: (NetworkKeyPair, ProtocolKeyPair),
         registry_service: RegistryService,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let launched = Instant::now();
         info!(
             "Starting validator node {} on RPC port {} and ABCI port {}",
-            self.authority_index, self.rpc_port, self.abci_port
+            self.authority_index, self.config.rpc_port, self.config.abci_port
         );
+        log_committee(&committee);
 
         // Create node directory
-        let node_dir = self
-            .working_directory
-            .join(format!("node-{}", self.authority_index));
-        std::fs::create_dir_all(&node_dir)?;
-        let db_path = node_dir.join("consensus.db");
+        std::fs::create_dir_all(self.config.node_directory())?;
 
-        // Get keypairs for this node
-        let (network_keypair, protocol_keypair) = &keypairs[self.authority_index.value()];
+        let (network_keypair, protocol_keypair) = &keypair;
 
         // Create parameters
-        let parameters = Parameters {
-            db_path,
-            ..Default::default()
-        };
+        let parameters = self.config.consensus_parameters();
+
+        // Register the node metrics
+        self.metrics = Some(Arc::new(NodeMetrics::new(
+            &registry_service.default_registry(),
+        )));
+
+        let verifier = build_verifier(&self.config.verifier)
+            .map_err(|e| format!("Failed to start the verifier threads: {e}"))?;
 
         // Create commit consumer
         let (commit_consumer, commit_receiver, block_receiver) = CommitConsumer::new(0);
@@ -83,30 +182,32 @@ impl ValidatorNode {
         let consensus_authority = ConsensusAuthority::start(
             ConsensusNetwork::Anemo,
             self.authority_index,
-            committee,
+            committee.clone(),
             parameters,
-            ProtocolConfig::get_for_max_version_UNSAFE(),
+            self.config.protocol_config(),
             protocol_keypair.clone(),
             network_keypair.clone(),
-            Arc::new(Clock::new_for_test(0)),
-            Arc::new(SimpleTransactionVerifier),
+            Arc::new(self.config.clock.clock()),
+            verifier,
             commit_consumer,
             registry_service.default_registry().clone(),
             0, // boot_counter
         )
         .await;
 
+        *self.transaction_client.write() = Some(consensus_authority.transaction_client());
         self.consensus_authority = Some(consensus_authority);
 
         // Start transaction processing and consensus output handling
-        self.start_transaction_processing(commit_receiver, block_receiver)
+        self.start_transaction_processing(commit_receiver, block_receiver, launched)
             .await;
 
-        // Start ABCI server with consensus output sender
-        //self.start_abci_server().await?;
-
-        // Start RPC server
-        self.start_rpc_server().await?;
+        // A server failing to start stops consensus and the servers already started, rather
+        // than leaving the node half running.
+        if let Err(e) = self.start_servers(committee, &registry_service).await {
+            self.stop().await;
+            return Err(e);
+        }
 
         info!(
             "Validator node {} started successfully",
@@ -115,46 +216,147 @@ impl ValidatorNode {
         Ok(())
     }
 
-    async fn start_rpc_server(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        info!("Starting RPC server on port {}", self.rpc_port);
+    /// Start the servers enabled in the configuration.
+    async fn start_servers(
+        &mut self,
+        committee: consensus_config::Committee,
+        registry_service: &RegistryService,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Start the ABCI server
+        match self.config.servers.abci {
+            true => self.start_abci_server()?,
+            false => info!("ABCI server disabled"),
+        }
 
-        // Create a channel to forward transactions from RPC to ABCI
-        let (rpc_tx_sender, mut rpc_tx_receiver) = tokio::sync::mpsc::channel::<Vec<u8>>(1000);
-        let transaction_client = self
-            .consensus_authority
-            .as_ref()
-            .unwrap()
-            .transaction_client();
-
-        // Start transaction forwarding from RPC to consensus
-        tokio::spawn(async move {
-            while let Some(tx_data) = rpc_tx_receiver.recv().await {
-                info!(
-                    "Forwarding transaction from RPC to consensus: {} bytes",
-                    tx_data.len()
-                );
-                // Forward to Mysticeti consensus
-                // Submit transaction to Mysticeti consensus authority using the transaction client
-                match transaction_client.submit(vec![tx_data]).await {
-                    Ok((block_ref, _status_receiver)) => {
-                        info!(
-                            "Transaction submitted successfully to Mysticeti consensus, included in block: {:?}",
-                            block_ref
-                        );
-                    }
-                    Err(e) => {
-                        error!("Failed to submit transaction to Mysticeti consensus: {}", e);
-                    }
-                }
+        // Start the Mysticeti gRPC server
+        match self.config.servers.grpc {
+            true => self.start_grpc_server().await?,
+            false => info!("Mysticeti gRPC server disabled"),
+        }
+
+        // Start RPC server
+        match self.config.servers.rpc {
+            true => {
+                self.start_rpc_server(committee, registry_service.default_registry())
+                    .await?
+            }
+            false => info!("RPC server disabled"),
+        }
+
+        // Start the metrics server
+        match self.config.servers.metrics {
+            true => {
+                self.start_metrics_server(registry_service.default_registry())
+                    .await?
             }
+            false => info!("Metrics server disabled"),
+        }
+
+        Ok(())
+    }
+
+    async fn start_rpc_server(
+        &mut self,
+        committee: consensus_config::Committee,
+        registry: prometheus::Registry,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.transaction_client.read().is_none() {
+            return Err(format!(
+                "Cannot start the RPC server of node {}: {}",
+                self.authority_index, NOT_RUNNING_MESSAGE
+            )
+            .into());
+        }
+        info!("Starting RPC server on port {}", self.config.rpc_port);
+
+        // Create a channel to forward transactions from RPC to ABCI
+        let (rpc_tx_sender, rpc_tx_receiver) =
+            tokio::sync::mpsc::channel::<Submission>(SUBMISSION_QUEUE_CAPACITY);
+
+        // Sample the depth of the submission queue
+        let metrics = self
+            .metrics
+            .clone()
+            .expect("Metrics are registered on start");
+        Self::monitor_submission_queue(rpc_tx_sender.downgrade(), metrics.clone());
+
+        // Start the workers forwarding transactions from RPC to consensus. They share the
+        // receiver so that a slow submission does not hold back the others. Unless the
+        // submissions are prioritized, they are forwarded in arrival order.
+        let max_pending = match self.config.prioritize_submissions {
+            true => SUBMISSION_QUEUE_CAPACITY,
+            false => 1,
+        };
+        let rpc_tx_receiver = Arc::new(tokio::sync::Mutex::new(PriorityReceiver::new(
+            rpc_tx_receiver,
+            max_pending,
+        )));
+        let fault = self
+            .config
+            .unsafe_transaction_fault
+            .filter(TransactionFault::is_active);
+        if let Some(fault) = &fault {
+            warn!(
+                "UNSAFE: dropping {:.1}% and corrupting {:.1}% of the client transactions",
+                fault.drop_ratio * 100.0,
+                fault.corrupt_ratio * 100.0
+            );
+        }
+        let webhook = self.config.commit_webhook.clone().map(|config| {
+            info!(
+                "Posting the commits of submitted transactions to {}",
+                config.url
+            );
+            CommitWebhook::start(config, metrics.clone())
+        });
+        let memory_guard = self.config.memory_guard.clone().map(|config| {
+            info!(
+                "Rejecting submissions below {} MiB of available memory",
+                config.min_free_mb
+            );
+            MemoryGuard::start(config, metrics.clone())
         });
+        let batch_memory_guard = memory_guard.clone();
+        for _ in 0..self.config.forward_workers {
+            Self::spawn_forward_worker(
+                rpc_tx_receiver.clone(),
+                self.transaction_client.clone(),
+                self.config.ack_mode,
+                fault,
+                webhook.clone(),
+                metrics.clone(),
+                self.errors.clone(),
+            );
+        }
 
-        let addr: SocketAddr = format!("0.0.0.0:{}", self.rpc_port).parse()?;
+        let addr = self.config.rpc_address();
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        info!("RPC server listening on {}", addr);
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let stats = self.stats.clone();
+        let committed = self.committed.clone();
+        let dedup = self.dedup.clone();
+        let dedup_metrics = metrics.clone();
+        let errors = self.errors.clone();
+        let batch_errors = self.errors.clone();
+        let transaction_check = self.transaction_check.clone();
+        let batch_transaction_check = self.transaction_check.clone();
+        let node_errors = self.errors.clone();
+        let max_request_bytes = self.config.max_request_bytes;
+        let http_config = self.config.http.clone();
+        let authority_index = self.authority_index;
+        let committee_report = CommitteeReport::from_committee(&committee);
+        let running = self.transaction_client.clone();
+        let batch_running = self.transaction_client.clone();
+        let metrics_registry = registry.clone();
+        let admin_routes = admin::router(self.config.admin_token.clone(), self.log_filter.clone());
 
-        tokio::spawn(async move {
+        let server = tokio::spawn(async move {
             use axum::{
                 Json, Router,
-                http::StatusCode,
+                body::Bytes,
+                extract::{DefaultBodyLimit, rejection::JsonRejection},
+                http::{HeaderMap, StatusCode, header::CONTENT_ENCODING},
                 routing::{get, post},
             };
             use serde::{Deserialize, Serialize};
@@ -162,12 +364,37 @@ impl ValidatorNode {
             #[derive(Deserialize)]
             struct TransactionRequest {
                 transaction: String, // Base64 encoded transaction
+                /// A key identifying the submission across the retries of the client, which
+                /// are then acknowledged without being forwarded again.
+                #[serde(default)]
+                idempotency_key: Option<String>,
             }
 
             #[derive(Serialize)]
             struct TransactionResponse {
                 success: bool,
                 message: String,
+                /// The id to look the transaction up on `/tx_status`, if it was accepted.
+                #[serde(skip_serializing_if = "Option::is_none")]
+                transaction_id: Option<String>,
+            }
+
+            #[derive(Serialize)]
+            struct BatchResponse {
+                success: bool,
+                message: String,
+                /// The ids of the accepted transactions, in the order of the request.
+                transaction_ids: Vec<String>,
+            }
+
+            #[derive(Deserialize)]
+            struct TransactionStatusRequest {
+                transaction_ids: Vec<String>,
+            }
+
+            #[derive(Serialize)]
+            struct TransactionStatusResponse {
+                transactions: Vec<TransactionStatus>,
             }
 
             #[derive(Serialize)]
@@ -176,6 +403,12 @@ impl ValidatorNode {
                 abci_app_version: &'static str,
             }
 
+            #[derive(Serialize)]
+            struct TimeResponse {
+                /// The wall clock of the node, in microseconds since the epoch.
+                unix_time_us: u64,
+            }
+
             #[derive(Deserialize)]
             struct AbciQueryRequest {}
 
@@ -185,45 +418,246 @@ impl ValidatorNode {
                 value: String,
             }
 
+            let batch_sender = rpc_tx_sender.clone();
             let app = Router::new()
                 .route(
                     "/broadcast_tx_async",
-                    post(|Json(payload): Json<TransactionRequest>| async move {
-                        match base64::Engine::decode(
-                            &base64::engine::general_purpose::STANDARD,
-                            &payload.transaction,
-                        ) {
-                            Ok(tx_data) => {
-                                if let Err(e) = rpc_tx_sender.send(tx_data).await {
-                                    error!("Failed to forward transaction to ABCI: {}", e);
+                    post(
+                        move |payload: Result<Json<TransactionRequest>, JsonRejection>| async move {
+                            let payload = match payload {
+                                Ok(Json(payload)) => payload,
+                                Err(rejection)
+                                    if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE =>
+                                {
+                                    warn!(
+                                        "Rejected RPC request larger than {} bytes",
+                                        max_request_bytes
+                                    );
                                     return (
-                                        StatusCode::INTERNAL_SERVER_ERROR,
+                                        StatusCode::PAYLOAD_TOO_LARGE,
                                         Json(TransactionResponse {
                                             success: false,
-                                            message: "Failed to process transaction".to_string(),
+                                            transaction_id: None,
+                                            message: format!(
+                                                "Request body exceeds the limit of {} bytes",
+                                                max_request_bytes
+                                            ),
                                         }),
                                     );
                                 }
-                                (
-                                    StatusCode::OK,
+                                Err(rejection) => {
+                                    return (
+                                        rejection.status(),
+                                        Json(TransactionResponse {
+                                            success: false,
+                                            transaction_id: None,
+                                            message: rejection.body_text(),
+                                        }),
+                                    );
+                                }
+                            };
+                            if running.read().is_none() {
+                                return (
+                                    StatusCode::SERVICE_UNAVAILABLE,
+                                    Json(TransactionResponse {
+                                        success: false,
+                                        transaction_id: None,
+                                        message: NOT_RUNNING_MESSAGE.to_string(),
+                                    }),
+                                );
+                            }
+                            if memory_guard.as_ref().is_some_and(|guard| guard.shed()) {
+                                return (
+                                    StatusCode::SERVICE_UNAVAILABLE,
                                     Json(TransactionResponse {
-                                        success: true,
-                                        message: "Transaction accepted and forwarded to ABCI"
-                                            .to_string(),
+                                        success: false,
+                                        transaction_id: None,
+                                        message: LOW_MEMORY_MESSAGE.to_string(),
+                                    }),
+                                );
+                            }
+                            let key = payload.idempotency_key.as_deref();
+                            if key.is_some_and(|key| key.len() > dedup::MAX_KEY_BYTES) {
+                                return (
+                                    StatusCode::BAD_REQUEST,
+                                    Json(TransactionResponse {
+                                        success: false,
+                                        transaction_id: None,
+                                        message: format!(
+                                            "Idempotency key longer than {} bytes",
+                                            dedup::MAX_KEY_BYTES
+                                        ),
                                     }),
-                                )
+                                );
                             }
-                            Err(e) => {
+                            // Dropping the claimed key releases it, including when the client
+                            // disconnects before the transaction is forwarded.
+                            let claimed = match key.map(|key| dedup.claim(key)) {
+                                Some(Claim::InFlight) => {
+                                    return (
+                                        StatusCode::CONFLICT,
+                                        Json(TransactionResponse {
+                                            success: false,
+                                            transaction_id: None,
+                                            message: "An earlier attempt is in flight, retry later"
+                                                .to_string(),
+                                        }),
+                                    );
+                                }
+                                Some(Claim::Acknowledged(id)) => {
+                                    dedup_metrics.deduplicated_submissions.inc();
+                                    return (
+                                        StatusCode::OK,
+                                        Json(TransactionResponse {
+                                            success: true,
+                                            message: "Transaction accepted and forwarded to ABCI"
+                                                .to_string(),
+                                            transaction_id: Some(id),
+                                        }),
+                                    );
+                                }
+                                Some(Claim::First(claimed)) => Some(claimed),
+                                None => None,
+                            };
+                            let decoded = base64::Engine::decode(
+                                &base64::engine::general_purpose::STANDARD,
+                                &payload.transaction,
+                            )
+                            .map_err(|e| {
                                 error!("Failed to decode transaction: {}", e);
-                                (
+                                "Invalid transaction format".to_string()
+                            });
+                            let checked = decoded.and_then(|tx_data| {
+                                let checked = transaction_check.check(&tx_data);
+                                checked.map(|()| tx_data).map_err(|reason| {
+                                    warn!("Rejected an invalid transaction: {}", reason);
+                                    format!("Invalid transaction: {reason}")
+                                })
+                            });
+                            let response = match checked {
+                                Ok(tx_data) => {
+                                    let id = transaction_id(&tx_data);
+                                    let submission = Submission::new(tx_data);
+                                    if let Err(e) = rpc_tx_sender.send(submission).await {
+                                        error!("Failed to forward transaction to ABCI: {}", e);
+                                        errors.record("forward", e);
+                                        return (
+                                            StatusCode::INTERNAL_SERVER_ERROR,
+                                            Json(TransactionResponse {
+                                                success: false,
+                                                transaction_id: None,
+                                                message: "Failed to process transaction"
+                                                    .to_string(),
+                                            }),
+                                        );
+                                    }
+                                    (
+                                        StatusCode::OK,
+                                        Json(TransactionResponse {
+                                            success: true,
+                                            message: "Transaction accepted and forwarded to ABCI"
+                                                .to_string(),
+                                            transaction_id: Some(id),
+                                        }),
+                                    )
+                                }
+                                Err(message) => (
                                     StatusCode::BAD_REQUEST,
                                     Json(TransactionResponse {
                                         success: false,
-                                        message: "Invalid transaction format".to_string(),
+                                        transaction_id: None,
+                                        message,
                                     }),
-                                )
+                                ),
+                            };
+                            if let (Some(claimed), Some(id)) = (claimed, &response.1.transaction_id)
+                            {
+                                claimed.acknowledge(id);
                             }
+                            response
+                        },
+                    ),
+                )
+                .route(
+                    "/broadcast_tx_batch",
+                    post(move |headers: HeaderMap, body: Bytes| async move {
+                        let reject = |status: StatusCode, message: String| {
+                            (
+                                status,
+                                Json(BatchResponse {
+                                    success: false,
+                                    message,
+                                    transaction_ids: Vec::new(),
+                                }),
+                            )
+                        };
+                        if batch_running.read().is_none() {
+                            let message = NOT_RUNNING_MESSAGE.to_string();
+                            return reject(StatusCode::SERVICE_UNAVAILABLE, message);
+                        }
+                        if batch_memory_guard
+                            .as_ref()
+                            .is_some_and(|guard| guard.shed())
+                        {
+                            let message = LOW_MEMORY_MESSAGE.to_string();
+                            return reject(StatusCode::SERVICE_UNAVAILABLE, message);
+                        }
+                        let content_encoding = match headers.get(CONTENT_ENCODING) {
+                            Some(value) => match value.to_str() {
+                                Ok(value) => Some(value),
+                                Err(_) => {
+                                    let message = "Invalid Content-Encoding header".to_string();
+                                    return reject(StatusCode::BAD_REQUEST, message);
+                                }
+                            },
+                            None => None,
+                        };
+                        let transactions =
+                            match decode_batch(content_encoding, &body, max_request_bytes) {
+                                Ok(transactions) => transactions,
+                                Err(e @ BatchError::TooLarge { .. }) => {
+                                    warn!("Rejected RPC batch: {}", e);
+                                    return reject(StatusCode::PAYLOAD_TOO_LARGE, e.to_string());
+                                }
+                                Err(e) => return reject(StatusCode::BAD_REQUEST, e.to_string()),
+                            };
+                        // Reject the whole batch, as consensus would reject a block with any
+                        // invalid transaction.
+                        let invalid = transactions.iter().enumerate().find_map(|(i, tx_data)| {
+                            batch_transaction_check
+                                .check(tx_data)
+                                .err()
+                                .map(|reason| (i, reason))
+                        });
+                        if let Some((i, reason)) = invalid {
+                            warn!("Rejected an invalid RPC batch: {}", reason);
+                            let message = format!("Invalid transaction {i}: {reason}");
+                            return reject(StatusCode::BAD_REQUEST, message);
                         }
+
+                        let mut transaction_ids = Vec::with_capacity(transactions.len());
+                        for tx_data in transactions {
+                            let id = transaction_id(&tx_data);
+                            let submission = Submission::new(tx_data);
+                            if let Err(e) = batch_sender.send(submission).await {
+                                error!("Failed to forward transaction to ABCI: {}", e);
+                                batch_errors.record("forward", e);
+                                let message = "Failed to process transaction batch".to_string();
+                                return reject(StatusCode::INTERNAL_SERVER_ERROR, message);
+                            }
+                            transaction_ids.push(id);
+                        }
+                        (
+                            StatusCode::OK,
+                            Json(BatchResponse {
+                                success: true,
+                                message: format!(
+                                    "{} transactions accepted and forwarded to ABCI",
+                                    transaction_ids.len()
+                                ),
+                                transaction_ids,
+                            }),
+                        )
                     }),
                 )
                 .route(
@@ -251,16 +685,330 @@ impl ValidatorNode {
                         )
                     }),
                 )
-                .route("/health", get(|| async { "OK" }));
+                .route(
+                    "/stats",
+                    get(move || async move { (StatusCode::OK, Json(stats.snapshot())) }),
+                )
+                .route(
+                    "/errors",
+                    get(move || async move { (StatusCode::OK, Json(node_errors.report())) }),
+                )
+                .route(
+                    "/peers",
+                    get(move || async move {
+                        let report =
+                            PeersReport::from_registry(&committee, authority_index, &registry);
+                        (StatusCode::OK, Json(report))
+                    }),
+                )
+                .route(
+                    "/tx_status",
+                    post(
+                        move |Json(request): Json<TransactionStatusRequest>| async move {
+                            let transactions = request
+                                .transaction_ids
+                                .iter()
+                                .map(|id| committed.status(id))
+                                .collect();
+                            (
+                                StatusCode::OK,
+                                Json(TransactionStatusResponse { transactions }),
+                            )
+                        },
+                    ),
+                )
+                .route(
+                    "/committee",
+                    get(move || async move { (StatusCode::OK, Json(committee_report)) }),
+                )
+                .route(
+                    "/time",
+                    get(|| async {
+                        let unix_time_us = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_micros() as u64;
+                        (StatusCode::OK, Json(TimeResponse { unix_time_us }))
+                    }),
+                )
+                .route("/health", get(|| async { "OK" }))
+                .merge(admin_routes)
+                .merge(crate::validator::metrics::router(metrics_registry))
+                .layer(DefaultBodyLimit::max(max_request_bytes));
 
-            info!("RPC server listening on {}", addr);
-            let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-            axum::serve(listener, app).await.unwrap();
+            // Stop when the node is stopped, or dropped.
+            let shutdown = async {
+                let _ = stopped.await;
+            };
+            crate::validator::http::serve(listener, app, &http_config, shutdown).await;
         });
+        self.rpc_server = Some((shutdown, server));
 
         Ok(())
     }
 
+    /// Start the Mysticeti gRPC server, sharing the transaction client, the statistics and the
+    /// commit subscriptions of the node.
+    async fn start_grpc_server(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let addr = self.config.grpc_address();
+        // Bind before spawning the server, so that a port already in use fails the start.
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| format!("Failed to bind the gRPC server on {addr}: {e}"))?;
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let server = MysticetiGrpcServer::new(
+            self.transaction_client.clone(),
+            self.stats.clone(),
+            self.commit_subscribers.clone(),
+        )
+        .with_transaction_check(self.transaction_check.clone());
+        info!("Mysticeti gRPC server listening on {}", addr);
+        tokio::spawn(async move {
+            let shutdown = async {
+                let _ = stopped.await;
+            };
+            if let Err(e) = server
+                .serve_with_incoming_shutdown(listener, shutdown)
+                .await
+            {
+                error!("Mysticeti gRPC server failed: {}", e);
+            }
+        });
+        self.grpc_shutdown = Some(shutdown);
+        Ok(())
+    }
+
+    /// Start the ABCI server, submitting the transactions accepted by its `check_tx` to
+    /// consensus. The server runs on its own threads and cannot be shut down: its port stays
+    /// bound until the process exits, and its transactions are rejected once the node stopped.
+    fn start_abci_server(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let addr = self.config.abci_address();
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(SUBMISSION_QUEUE_CAPACITY);
+        let check = self.transaction_check.clone();
+        let mut app = MysticetiAbciApp::new(sender)
+            .with_validator(move |tx: &[u8]| check.check(tx).map_err(Rejection::new));
+        // Reject the transactions of the size the verifier rejects with the specific code.
+        if let Some(max_transaction_size) = self.config.verifier.max_transaction_size {
+            app = app.with_max_transaction_size(max_transaction_size);
+        }
+        let server = tendermint_abci::ServerBuilder::default()
+            .bind(addr, app)
+            .map_err(|e| format!("Failed to bind the ABCI server on {addr}: {e}"))?;
+        info!("ABCI server listening on {}", addr);
+        std::thread::Builder::new()
+            .name(format!("abci-server-{}", self.authority_index))
+            .spawn(move || {
+                if let Err(e) = server.listen() {
+                    error!("ABCI server failed: {}", e);
+                }
+            })?;
+
+        let transaction_client = self.transaction_client.clone();
+        tokio::spawn(async move {
+            while let Some(transaction) = receiver.recv().await {
+                let Some(client) = transaction_client.read().clone() else {
+                    warn!("Dropped an ABCI transaction: {}", NOT_RUNNING_MESSAGE);
+                    continue;
+                };
+                // Do not wait for the inclusion of a transaction before submitting the next.
+                tokio::spawn(async move {
+                    if let Err(e) = client.submit(vec![transaction]).await {
+                        warn!("Failed to submit an ABCI transaction to consensus: {}", e);
+                    }
+                });
+            }
+        });
+        Ok(())
+    }
+
+    /// Start the metrics server, exposing the metrics of the registry on `GET /metrics`.
+    async fn start_metrics_server(
+        &mut self,
+        registry: prometheus::Registry,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let addr = self.config.metrics_address();
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        info!("Metrics server listening on {}", addr);
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let app = crate::validator::metrics::router(registry);
+        let http_config = self.config.http.clone();
+        let server = tokio::spawn(async move {
+            let shutdown = async {
+                let _ = stopped.await;
+            };
+            crate::validator::http::serve(listener, app, &http_config, shutdown).await;
+        });
+        self.metrics_server = Some((shutdown, server));
+        Ok(())
+    }
+
+    /// Spawn a task submitting the transactions received from the RPC server to consensus.
+    fn spawn_forward_worker(
+        receiver: Arc<tokio::sync::Mutex<PriorityReceiver>>,
+        transaction_client: SharedTransactionClient,
+        ack_mode: AckMode,
+        fault: Option<TransactionFault>,
+        webhook: Option<Arc<CommitWebhook>>,
+        metrics: Arc<NodeMetrics>,
+        errors: Arc<ErrorLog>,
+    ) {
+        tokio::spawn(async move {
+            loop {
+                // Only hold the lock while waiting for the next transaction.
+                let Some(submission) = receiver.lock().await.recv().await else {
+                    break;
+                };
+                let Submission {
+                    transaction: tx_data,
+                    accepted_at,
+                } = submission;
+                let outcome = match &fault {
+                    Some(fault) => fault.apply(tx_data, rand::random()),
+                    None => FaultOutcome::Forwarded(tx_data),
+                };
+                // The node may have been stopped while the transaction was queued.
+                let Some(client) = transaction_client.read().clone() else {
+                    warn!("Dropping a queued transaction: {}", NOT_RUNNING_MESSAGE);
+                    continue;
+                };
+                let tx_data = match outcome {
+                    FaultOutcome::Forwarded(tx_data) => tx_data,
+                    FaultOutcome::Corrupted(tx_data) => {
+                        metrics
+                            .faulty_transactions
+                            .with_label_values(&["corrupted"])
+                            .inc();
+                        tx_data
+                    }
+                    FaultOutcome::Dropped => {
+                        metrics
+                            .faulty_transactions
+                            .with_label_values(&["dropped"])
+                            .inc();
+                        continue;
+                    }
+                };
+                info!(
+                    "Forwarding transaction from RPC to consensus: {} bytes",
+                    tx_data.len()
+                );
+                // The webhook needs the commit of the transaction, whatever the ack mode.
+                let pending = webhook
+                    .as_ref()
+                    .map(|webhook| PendingCommit::new(webhook.clone(), transaction_id(&tx_data)));
+                // Submit transaction to Mysticeti consensus authority using the transaction client
+                match client.submit(vec![tx_data]).await {
+                    Ok((block_ref, status_receiver)) => {
+                        // The transaction is now part of a proposed block.
+                        let proposed_at = Instant::now();
+                        metrics
+                            .submission_wait_seconds
+                            .observe((proposed_at - accepted_at).as_secs_f64());
+                        if ack_mode == AckMode::Submitted {
+                            info!(
+                                "Transaction submitted successfully to Mysticeti consensus, included in block: {:?}",
+                                block_ref
+                            );
+                        }
+                        Self::track_submission(
+                            block_ref,
+                            status_receiver,
+                            metrics.clone(),
+                            ack_mode == AckMode::Committed,
+                            proposed_at,
+                            pending,
+                            errors.clone(),
+                        );
+                    }
+                    Err(e) => {
+                        error!("Failed to submit transaction to Mysticeti consensus: {}", e);
+                        errors.record("submit", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Wait for the block including a submission to be committed (or garbage collected), then
+    /// record the time from its proposal to its commit, count the outcome (if `count_acks` is
+    /// set), report the commit to the webhook (if `pending` is set) and record the blocks that
+    /// will never be committed.
+    fn track_submission(
+        block_ref: BlockRef,
+        status_receiver: tokio::sync::oneshot::Receiver<BlockStatus>,
+        metrics: Arc<NodeMetrics>,
+        count_acks: bool,
+        proposed_at: Instant,
+        pending: Option<PendingCommit>,
+        errors: Arc<ErrorLog>,
+    ) {
+        tokio::spawn(async move {
+            let status = match status_receiver.await {
+                Ok(BlockStatus::Sequenced(_)) => {
+                    debug!("Transaction committed in block {:?}", block_ref);
+                    metrics
+                        .propose_to_commit_seconds
+                        .observe(proposed_at.elapsed().as_secs_f64());
+                    if let Some(pending) = pending {
+                        pending.committed(&block_ref);
+                    }
+                    "committed"
+                }
+                Ok(BlockStatus::GarbageCollected(_)) => {
+                    warn!(
+                        "Block {:?} was garbage collected, its transactions will not be committed",
+                        block_ref
+                    );
+                    let message = format!("Block {block_ref:?} was garbage collected");
+                    errors.record("garbage_collected", message);
+                    "garbage_collected"
+                }
+                Err(_) => "dropped",
+            };
+            if count_acks {
+                metrics.submission_acks.with_label_values(&[status]).inc();
+            }
+        });
+    }
+
+    /// Periodically export the depth of the submission queue and warn when it crosses the
+    /// high-water mark, an early sign that consensus is falling behind the offered load.
+    fn monitor_submission_queue(
+        sender: tokio::sync::mpsc::WeakSender<Submission>,
+        metrics: Arc<NodeMetrics>,
+    ) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SUBMISSION_QUEUE_SAMPLE_INTERVAL);
+            let mut overloaded = false;
+            loop {
+                interval.tick().await;
+                let Some(sender) = sender.upgrade() else {
+                    break;
+                };
+                let capacity = sender.max_capacity();
+                let depth = capacity - sender.capacity();
+                drop(sender);
+
+                metrics.submission_queue_capacity.set(capacity as i64);
+                metrics.submission_queue_depth.set(depth as i64);
+
+                let above = depth as f64 >= capacity as f64 * SUBMISSION_QUEUE_HIGH_WATER_MARK;
+                if above && !overloaded {
+                    warn!(
+                        "Submission queue crossed the high-water mark: {}/{} transactions",
+                        depth, capacity
+                    );
+                } else if !above && overloaded {
+                    info!(
+                        "Submission queue back below the high-water mark: {}/{} transactions",
+                        depth, capacity
+                    );
+                }
+                overloaded = above;
+            }
+        });
+    }
+
     async fn start_transaction_processing(
         &self,
         mut commit_receiver: mysten_metrics::monitored_mpsc::UnboundedReceiver<
@@ -269,15 +1017,123 @@ impl ValidatorNode {
         mut block_receiver: mysten_metrics::monitored_mpsc::UnboundedReceiver<
             consensus_core::CertifiedBlocksOutput,
         >,
+        launched: Instant,
     ) {
+        let mut journal = match &self.config.commit_journal {
+            Some(path) => match CommitJournal::open(path) {
+                Ok(journal) => {
+                    info!("Journaling commits to {}", path.display());
+                    Some(journal)
+                }
+                Err(e) => {
+                    error!("Failed to open commit journal {}: {}", path.display(), e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let mut order_log = match &self.config.commit_order {
+            Some(path) => match CommitOrderLog::open(path) {
+                Ok(log) => {
+                    info!(
+                        "Logging the commit order to {} (from commit {})",
+                        path.display(),
+                        log.last_commit() + 1
+                    );
+                    Some(log)
+                }
+                Err(e) => {
+                    error!("Failed to open commit order log {}: {}", path.display(), e);
+                    None
+                }
+            },
+            None => None,
+        };
+
         // Process committed sub-dags from Mysticeti consensus
+        let metrics = self
+            .metrics
+            .clone()
+            .expect("Metrics are registered on start");
+        let stats = self.stats.clone();
+        let committed = self.committed.clone();
+        let errors = self.errors.clone();
+        let dispatcher =
+            CommitDispatcher::start(&self.commit_handlers, self.commit_subscribers.clone());
+        let mut proposal_timer = ProposalTimer::default();
         tokio::spawn(async move {
             while let Some(committed_subdag) = commit_receiver.recv().await {
+                let num_transactions: usize = committed_subdag
+                    .blocks
+                    .iter()
+                    .map(|block| block.transactions().len())
+                    .sum();
                 info!(
-                    "Received committed sub-dag from Mysticeti: {} blocks",
-                    committed_subdag.blocks.len()
+                    "Received committed sub-dag from Mysticeti: {} blocks, {} transactions",
+                    committed_subdag.blocks.len(),
+                    num_transactions
                 );
+                metrics
+                    .blocks_per_commit
+                    .observe(committed_subdag.blocks.len() as f64);
+                metrics
+                    .transactions_per_commit
+                    .observe(num_transactions as f64);
+                for block in &committed_subdag.blocks {
+                    metrics
+                        .committed_transactions_by_authority
+                        .with_label_values(&[&block.author().value().to_string()])
+                        .inc_by(block.transactions().len() as u64);
+                }
+                let elapsed = launched.elapsed();
+                if stats.record_first_commit(elapsed) {
+                    let elapsed = elapsed.as_secs_f64();
+                    metrics.time_to_first_commit_seconds.set(elapsed);
+                    info!("First commit {:.2}s after startup", elapsed);
+                }
+                stats.record_commit(&committed_subdag);
+                let now_ms = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                let timings = proposal_timer.record(&committed_subdag, now_ms);
+                for (authority, delay_ms) in timings.proposal_delays_ms {
+                    metrics
+                        .block_proposal_delay_seconds
+                        .with_label_values(&[&authority.to_string()])
+                        .observe(delay_ms as f64 / 1000.0);
+                }
+                if let Some((authority, delay_ms)) = timings.leader_commit_delay_ms {
+                    metrics
+                        .leader_commit_delay_seconds
+                        .with_label_values(&[&authority.to_string()])
+                        .observe(delay_ms as f64 / 1000.0);
+                }
+                let tally = committed.record_commit(&committed_subdag);
+                metrics
+                    .committed_duplicate_transactions
+                    .inc_by(tally.duplicates);
+                metrics
+                    .committed_rejected_transactions
+                    .inc_by(tally.rejected);
+                if let Some(journal) = journal.as_mut() {
+                    let entry = JournalEntry::from_subdag(&committed_subdag);
+                    if let Err(e) = journal.append(&entry) {
+                        error!("Failed to journal commit {}: {}", entry.commit_index, e);
+                        errors.record("journal", e);
+                    }
+                }
+                if let Some(log) = order_log.as_mut() {
+                    if let Err(e) = log.append(&committed_subdag) {
+                        let index = committed_subdag.commit_ref.index;
+                        error!("Failed to log the order of commit {}: {}", index, e);
+                        errors.record("commit_order", e);
+                    }
+                }
+                // Last, so that the commit is journaled before any handler sees it.
+                dispatcher.dispatch(committed_subdag).await;
             }
+            dispatcher.close().await;
         });
 
         // Process certified blocks from Mysticeti consensus
@@ -299,8 +1155,30 @@ impl ValidatorNode {
 
     pub async fn stop(&mut self) {
         info!("Stopping validator node {}", self.authority_index);
+        // Reject new submissions before consensus goes away.
+        self.transaction_client.write().take();
         if let Some(authority) = self.consensus_authority.take() {
             authority.stop().await;
         }
+        // Close the listeners so that the ports can be bound again, e.g. by a restarted node.
+        // The open connections complete their in-flight requests in the background.
+        if let Some(shutdown) = self.grpc_shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some((shutdown, server)) = self.rpc_server.take() {
+            let _ = shutdown.send(());
+            if let Err(e) = server.await {
+                error!("RPC server of node {} failed: {}", self.authority_index, e);
+            }
+        }
+        if let Some((shutdown, server)) = self.metrics_server.take() {
+            let _ = shutdown.send(());
+            if let Err(e) = server.await {
+                error!(
+                    "Metrics server of node {} failed: {}",
+                    self.authority_index, e
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,154 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A commit order log records the ids of the transactions committed by a node, in commit
+//! order, so that an external tool can check that all nodes committed the same transactions
+//! in the same order. Any difference between the logs of two nodes (over the commits both
+//! reached) is a safety violation.
+//!
+//! The log is an append-only text file with one `<commit_index> <transaction_id>` line per
+//! committed transaction, so comparing two nodes is a `diff` away.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use consensus_core::{BlockAPI, CommittedSubDag};
+
+use crate::validator::committed::transaction_id;
+
+/// The number of bytes read from the end of an existing log to find its last commit; longer
+/// than any line.
+const TAIL_BYTES: u64 = 256;
+
+/// Appends the ids of the committed transactions to a file.
+pub struct CommitOrderLog {
+    writer: BufWriter<File>,
+    /// The last commit in the log. Commits replayed by consensus on restart are skipped.
+    last_commit: u32,
+}
+
+impl CommitOrderLog {
+    /// Open (or create) the log at the specified path, appending after its last commit.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let last_commit = last_commit(&mut file)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            last_commit,
+        })
+    }
+
+    /// The last commit in the log (0 if it is empty).
+    pub fn last_commit(&self) -> u32 {
+        self.last_commit
+    }
+
+    /// Append the accepted transactions of a committed sub-dag.
+    pub fn append(&mut self, subdag: &CommittedSubDag) -> io::Result<()> {
+        let ids = subdag
+            .blocks
+            .iter()
+            .zip(&subdag.rejected_transactions_by_block)
+            .flat_map(|(block, rejected)| {
+                block
+                    .transactions()
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !rejected.contains(&(*i as u16)))
+                    .map(|(_, tx)| transaction_id(tx.data()))
+            });
+        self.append_commit(subdag.commit_ref.index, ids)
+    }
+
+    /// Append the ids of the transactions of a commit and flush them to the file. Commits
+    /// already in the log are skipped.
+    pub fn append_commit<I>(&mut self, commit_index: u32, ids: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        if commit_index <= self.last_commit {
+            return Ok(());
+        }
+        for id in ids {
+            writeln!(self.writer, "{commit_index} {id}")?;
+        }
+        self.writer.flush()?;
+        self.last_commit = commit_index;
+        Ok(())
+    }
+}
+
+/// Find the last commit of an existing log from its last line.
+fn last_commit(file: &mut File) -> io::Result<u32> {
+    let length = file.seek(SeekFrom::End(0))?;
+    file.seek(SeekFrom::Start(length.saturating_sub(TAIL_BYTES)))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    let tail = String::from_utf8_lossy(&tail);
+    Ok(tail
+        .lines()
+        .rev()
+        .find_map(|line| line.split_once(' ')?.0.parse().ok())
+        .unwrap_or(0))
+}
+
+/// Read a commit order log as `(commit_index, transaction_id)` pairs.
+pub fn read_commit_order<P: AsRef<Path>>(path: P) -> io::Result<Vec<(u32, String)>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut order = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let entry = line
+            .split_once(' ')
+            .and_then(|(index, id)| Some((index.parse().ok()?, id.to_string())));
+        match entry {
+            Some(entry) => order.push(entry),
+            None => {
+                let message = format!("Malformed commit order line '{line}'");
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+        }
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CommitOrderLog, read_commit_order};
+
+    #[test]
+    fn resume_after_last_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("commit-order.log");
+        let ids = |commit: u32| (0..commit).map(move |i| format!("{commit:02x}{i:062x}"));
+
+        let mut log = CommitOrderLog::open(&path).unwrap();
+        for commit in 1..=3 {
+            log.append_commit(commit, ids(commit)).unwrap();
+        }
+        drop(log);
+
+        // Consensus replays its commits on restart; those already logged are skipped.
+        let mut log = CommitOrderLog::open(&path).unwrap();
+        assert_eq!(log.last_commit(), 3);
+        for commit in 1..=4 {
+            log.append_commit(commit, ids(commit)).unwrap();
+        }
+
+        let order = read_commit_order(&path).unwrap();
+        let expected: Vec<_> = (1..=4)
+            .flat_map(|commit| ids(commit).map(move |id| (commit, id)))
+            .collect();
+        assert_eq!(order, expected);
+    }
+}
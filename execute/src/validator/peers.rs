@@ -0,0 +1,108 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use consensus_config::{AuthorityIndex, Committee};
+use prometheus::Registry;
+use serde::{Deserialize, Serialize};
+
+/// The suffix of the gauge in which the consensus network reports the connection status of
+/// every known peer (labeled by the hostname of the peer).
+const PEER_CONNECTED_METRIC: &str = "quinn_network_peer_connected";
+
+/// The connectivity of a node to one committee member.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerStatus {
+    /// The index of the peer in the committee.
+    pub authority_index: u32,
+    /// The hostname of the peer.
+    pub hostname: String,
+    /// The consensus address of the peer.
+    pub address: String,
+    /// Whether the node currently has a live connection to the peer.
+    pub connected: bool,
+}
+
+/// The connectivity of a node to the rest of the committee, served on `/peers`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeersReport {
+    /// The index of the reporting node.
+    pub authority_index: u32,
+    /// The status of every other committee member.
+    pub peers: Vec<PeerStatus>,
+}
+
+impl PeersReport {
+    /// Derive the connectivity of `own` from the connection metrics the consensus network
+    /// exports to `registry`. Peers the network never reported are considered disconnected.
+    pub fn from_registry(committee: &Committee, own: AuthorityIndex, registry: &Registry) -> Self {
+        let connected: Vec<String> = registry
+            .gather()
+            .iter()
+            .filter(|family| family.get_name().ends_with(PEER_CONNECTED_METRIC))
+            .flat_map(|family| family.get_metric())
+            .filter(|metric| metric.get_gauge().get_value() > 0.0)
+            .flat_map(|metric| metric.get_label())
+            .filter(|label| label.get_name() == "peer_label")
+            .map(|label| label.get_value().to_string())
+            .collect();
+
+        let peers = committee
+            .authorities()
+            .filter(|(index, _)| *index != own)
+            .map(|(index, authority)| PeerStatus {
+                authority_index: index.value() as u32,
+                hostname: authority.hostname.clone(),
+                address: authority.address.to_string(),
+                connected: connected.contains(&authority.hostname),
+            })
+            .collect();
+
+        Self {
+            authority_index: own.value() as u32,
+            peers,
+        }
+    }
+
+    /// The number of committee members the node is connected to.
+    pub fn connected_peers(&self) -> usize {
+        self.peers.iter().filter(|peer| peer.connected).count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use consensus_config::AuthorityIndex;
+    use prometheus::{Registry, register_int_gauge_vec_with_registry};
+
+    use super::PeersReport;
+
+    #[test]
+    fn peers_from_connection_metrics() {
+        let (committee, _) = consensus_config::local_committee_and_keys(0, vec![1; 4]);
+        let registry = Registry::new();
+        let connected = register_int_gauge_vec_with_registry!(
+            "consensus_quinn_network_peer_connected",
+            "The connection status of a peer",
+            &["peer_id", "peer_label"],
+            registry,
+        )
+        .unwrap();
+        let hostname = |i: u32| {
+            committee
+                .authority(AuthorityIndex::new_for_test(i))
+                .hostname
+                .clone()
+        };
+        connected.with_label_values(&["a", &hostname(1)]).set(1);
+        connected.with_label_values(&["b", &hostname(2)]).set(0);
+
+        let report =
+            PeersReport::from_registry(&committee, AuthorityIndex::new_for_test(0), &registry);
+        assert_eq!(report.authority_index, 0);
+        assert_eq!(report.peers.len(), 3);
+        assert_eq!(report.connected_peers(), 1);
+        assert!(report.peers[0].connected);
+        assert!(!report.peers[1].connected);
+        assert!(!report.peers[2].connected);
+    }
+}
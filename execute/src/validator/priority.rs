@@ -0,0 +1,148 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Prioritization of the submission queue. Consensus orders the transactions of a block as
+//! they were submitted and has no notion of priority, so a node can only favor urgent
+//! transactions by submitting them first: the forwarding workers take the queued transaction
+//! of highest priority rather than the oldest one.
+//!
+//! The priority of a structured transaction is the class byte of its header (0 for normal
+//! transactions, higher for more urgent ones). Other transactions have priority 0.
+
+use std::{cmp::Ordering, collections::BinaryHeap, time::Instant};
+
+use tokio::sync::mpsc::{Receiver, error::TryRecvError};
+
+use crate::validator::structured::{MAGIC, PRIORITY_OFFSET};
+
+/// The priority of a transaction.
+pub fn transaction_priority(transaction: &[u8]) -> u8 {
+    match transaction.starts_with(MAGIC) {
+        true => transaction.get(PRIORITY_OFFSET).copied().unwrap_or(0),
+        false => 0,
+    }
+}
+
+/// A transaction accepted by the RPC server, waiting to be submitted to consensus.
+pub struct Submission {
+    pub transaction: Vec<u8>,
+    /// When the RPC server accepted the transaction, to measure how long it waits before
+    /// being proposed.
+    pub accepted_at: Instant,
+}
+
+impl Submission {
+    pub fn new(transaction: Vec<u8>) -> Self {
+        Self {
+            transaction,
+            accepted_at: Instant::now(),
+        }
+    }
+}
+
+/// A queued transaction, ordered by priority then by arrival.
+struct Queued {
+    priority: u8,
+    sequence: u64,
+    submission: Submission,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The heap pops the greatest: the highest priority, then the earliest arrival.
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+/// Receives the submitted transactions highest priority first. Transactions waiting in the
+/// channel are moved to a heap (of at most `max_pending` transactions, so that the channel
+/// still applies backpressure) and served from there.
+pub struct PriorityReceiver {
+    receiver: Receiver<Submission>,
+    pending: BinaryHeap<Queued>,
+    max_pending: usize,
+    sequence: u64,
+}
+
+impl PriorityReceiver {
+    pub fn new(receiver: Receiver<Submission>, max_pending: usize) -> Self {
+        Self {
+            receiver,
+            pending: BinaryHeap::new(),
+            max_pending: max_pending.max(1),
+            sequence: 0,
+        }
+    }
+
+    /// The next transaction to submit, or `None` once the channel is closed and drained.
+    pub async fn recv(&mut self) -> Option<Submission> {
+        if self.pending.is_empty() {
+            let submission = self.receiver.recv().await?;
+            self.push(submission);
+        }
+        while self.pending.len() < self.max_pending {
+            match self.receiver.try_recv() {
+                Ok(submission) => self.push(submission),
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => break,
+            }
+        }
+        self.pending.pop().map(|queued| queued.submission)
+    }
+
+    fn push(&mut self, submission: Submission) {
+        self.pending.push(Queued {
+            priority: transaction_priority(&submission.transaction),
+            sequence: self.sequence,
+            submission,
+        });
+        self.sequence += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PriorityReceiver, Submission, transaction_priority};
+
+    fn transaction(priority: u8, tag: u8) -> Vec<u8> {
+        let mut transaction = b"MYST".to_vec();
+        transaction.extend([1, priority, tag]);
+        transaction
+    }
+
+    #[tokio::test]
+    async fn serve_highest_priority_first() {
+        assert_eq!(transaction_priority(&transaction(3, 0)), 3);
+        assert_eq!(transaction_priority(b"raw transaction"), 0);
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(16);
+        for (priority, tag) in [(0, 1), (1, 2), (0, 3), (2, 4), (1, 5)] {
+            let submission = Submission::new(transaction(priority, tag));
+            sender.send(submission).await.unwrap();
+        }
+        drop(sender);
+
+        let mut receiver = PriorityReceiver::new(receiver, 16);
+        let mut tags = Vec::new();
+        while let Some(submission) = receiver.recv().await {
+            tags.push(submission.transaction[6]);
+        }
+        // Highest priority first, in arrival order within a priority.
+        assert_eq!(tags, vec![4, 2, 5, 1, 3]);
+    }
+}
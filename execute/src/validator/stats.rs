@@ -0,0 +1,102 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use consensus_core::{BlockAPI, CommittedSubDag};
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// Running statistics of the commits observed by a node, served on `/stats`.
+#[derive(Default)]
+pub struct NodeStats {
+    commits: AtomicU64,
+    committed_blocks: AtomicU64,
+    committed_transactions: AtomicU64,
+    current_round: AtomicU64,
+    transactions_by_authority: Mutex<BTreeMap<u32, u64>>,
+    time_to_first_commit: OnceLock<Duration>,
+    last_leader: Mutex<Option<u32>>,
+}
+
+/// A point-in-time copy of the node statistics.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct StatsSnapshot {
+    /// Number of committed sub-dags.
+    pub commits: u64,
+    /// Number of committed blocks.
+    pub committed_blocks: u64,
+    /// Number of committed transactions.
+    pub committed_transactions: u64,
+    /// The highest round of the committed blocks.
+    pub current_round: u64,
+    /// Number of committed transactions, keyed by the authority that proposed them.
+    pub transactions_by_authority: BTreeMap<u32, u64>,
+    /// Time between the start of the node and its first commit (in milliseconds), if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_to_first_commit_ms: Option<u64>,
+    /// The authority that proposed the leader of the latest commit, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_leader: Option<u32>,
+}
+
+impl NodeStats {
+    /// Account for a committed sub-dag.
+    pub fn record_commit(&self, subdag: &CommittedSubDag) {
+        let mut total = 0;
+        let mut round = 0;
+        let mut by_authority = self.transactions_by_authority.lock();
+        for block in &subdag.blocks {
+            round = round.max(block.round() as u64);
+            let num_transactions = block.transactions().len() as u64;
+            *by_authority
+                .entry(block.author().value() as u32)
+                .or_default() += num_transactions;
+            total += num_transactions;
+        }
+        drop(by_authority);
+        *self.last_leader.lock() = Some(subdag.leader.author.value() as u32);
+
+        self.commits.fetch_add(1, Ordering::Relaxed);
+        self.committed_blocks
+            .fetch_add(subdag.blocks.len() as u64, Ordering::Relaxed);
+        self.committed_transactions
+            .fetch_add(total, Ordering::Relaxed);
+        self.current_round.fetch_max(round, Ordering::Relaxed);
+    }
+
+    /// Record the time elapsed between the start of the node and a commit. Returns whether
+    /// this was the first commit (later calls are ignored).
+    pub fn record_first_commit(&self, elapsed: Duration) -> bool {
+        self.time_to_first_commit.set(elapsed).is_ok()
+    }
+
+    /// The highest round of the committed blocks: consensus does not expose the round it is
+    /// proposing in, which is at most a few rounds ahead.
+    pub fn current_round(&self) -> u64 {
+        self.current_round.load(Ordering::Relaxed)
+    }
+
+    /// Copy the current statistics.
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            commits: self.commits.load(Ordering::Relaxed),
+            committed_blocks: self.committed_blocks.load(Ordering::Relaxed),
+            committed_transactions: self.committed_transactions.load(Ordering::Relaxed),
+            current_round: self.current_round(),
+            transactions_by_authority: self.transactions_by_authority.lock().clone(),
+            time_to_first_commit_ms: self
+                .time_to_first_commit
+                .get()
+                .map(|elapsed| elapsed.as_millis() as u64),
+            last_leader: *self.last_leader.lock(),
+        }
+    }
+}
@@ -0,0 +1,17 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The layout of the structured transactions sent by the benchmark clients: a header (magic,
+//! version, class, payload length and sender), a nonce, the payload and, for signed
+//! transactions, the Ed25519 signature of the sender over the rest of the transaction.
+
+use std::ops::Range;
+
+/// The magic bytes opening every structured transaction.
+pub const MAGIC: &[u8] = b"MYST";
+/// The position of the class (priority) byte in the header.
+pub const PRIORITY_OFFSET: usize = 5;
+/// The position of the sender (the public key of the signer) in the header.
+pub const SENDER: Range<usize> = 10..42;
+/// The size of the nonce following the header.
+pub const NONCE_SIZE: usize = 16;
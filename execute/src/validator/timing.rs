@@ -0,0 +1,131 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-authority timings of the committed blocks, to find a slow proposer or leader. Consensus
+//! does not attribute its own proposal timings (e.g. `block_proposal_interval`) to authorities,
+//! so they are derived from the commits, from the timestamps the authorities put in their
+//! blocks:
+//! - the proposal delay of a block is the time between its most recent ancestor from another
+//!   authority and the block itself: how long its author took to propose once it could
+//!   (waiting for the leader and for a quorum of ancestors, then building the block);
+//! - the commit delay of a leader is the time between its block and the moment this node
+//!   commits it: how long the leader waited for the votes certifying it.
+//!
+//! Both mix the clocks of several nodes, so clock skew adds a constant bias per authority.
+
+use std::collections::{HashMap, VecDeque};
+
+use consensus_core::{BlockAPI, BlockRef, CommittedSubDag};
+
+/// The number of committed blocks whose timestamps are remembered to time their descendants.
+const REMEMBERED_BLOCKS: usize = 4096;
+
+/// The timings of the blocks of a commit, in milliseconds, by authority index.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommitTimings {
+    /// The proposal delay of every block whose ancestors are known.
+    pub proposal_delays_ms: Vec<(u32, u64)>,
+    /// The commit delay of the leader of the commit.
+    pub leader_commit_delay_ms: Option<(u32, u64)>,
+}
+
+/// Derives the timings of the committed blocks, remembering the timestamps of the recent
+/// blocks (ancestors are committed before or with their descendants).
+#[derive(Default)]
+pub struct ProposalTimer {
+    timestamps: HashMap<BlockRef, u64>,
+    order: VecDeque<BlockRef>,
+}
+
+impl ProposalTimer {
+    /// The timings of the blocks of a commit, processed by this node at `now_ms`.
+    pub fn record(&mut self, subdag: &CommittedSubDag, now_ms: u64) -> CommitTimings {
+        let mut timings = CommitTimings::default();
+        // Blocks are sorted by round, so ancestors in the same commit come first.
+        for block in &subdag.blocks {
+            let author = block.author().value() as u32;
+            let latest_ancestor = block
+                .ancestors()
+                .iter()
+                .filter(|ancestor| ancestor.author != block.author())
+                .filter_map(|ancestor| self.timestamps.get(ancestor))
+                .max();
+            if let Some(latest_ancestor) = latest_ancestor {
+                let delay = block.timestamp_ms().saturating_sub(*latest_ancestor);
+                timings.proposal_delays_ms.push((author, delay));
+            }
+            if block.reference() == subdag.leader {
+                let delay = now_ms.saturating_sub(block.timestamp_ms());
+                timings.leader_commit_delay_ms = Some((author, delay));
+            }
+            self.remember(block.reference(), block.timestamp_ms());
+        }
+        timings
+    }
+
+    fn remember(&mut self, reference: BlockRef, timestamp_ms: u64) {
+        self.timestamps.insert(reference, timestamp_ms);
+        self.order.push_back(reference);
+        if self.order.len() > REMEMBERED_BLOCKS {
+            let oldest = self.order.pop_front().expect("The blocks are not empty");
+            self.timestamps.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use consensus_core::{
+        BlockAPI, CommitDigest, CommitRef, CommittedSubDag, TestBlock, VerifiedBlock,
+    };
+
+    use super::{CommitTimings, ProposalTimer};
+
+    fn block(
+        round: u32,
+        author: u32,
+        timestamp_ms: u64,
+        ancestors: &[&VerifiedBlock],
+    ) -> VerifiedBlock {
+        VerifiedBlock::new_for_test(
+            TestBlock::new(round, author)
+                .set_timestamp_ms(timestamp_ms)
+                .set_ancestors(ancestors.iter().map(|block| block.reference()).collect())
+                .build(),
+        )
+    }
+
+    fn commit(index: u32, leader: &VerifiedBlock, blocks: Vec<VerifiedBlock>) -> CommittedSubDag {
+        let rejected = vec![vec![]; blocks.len()];
+        CommittedSubDag::new(
+            leader.reference(),
+            blocks,
+            rejected,
+            leader.timestamp_ms(),
+            CommitRef::new(index, CommitDigest::MIN),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn time_proposals_and_leaders() {
+        let a = block(1, 0, 1_000, &[]);
+        let b = block(1, 1, 1_010, &[]);
+        // Authority 2 proposes 300ms after its latest ancestor, then leads round 2.
+        let c = block(2, 2, 1_310, &[&a, &b]);
+        let mut timer = ProposalTimer::default();
+
+        let timings = timer.record(&commit(1, &a, vec![a.clone()]), 1_100);
+        assert_eq!(
+            timings,
+            CommitTimings {
+                proposal_delays_ms: vec![],
+                leader_commit_delay_ms: Some((0, 100)),
+            }
+        );
+
+        let timings = timer.record(&commit(2, &c, vec![b.clone(), c.clone()]), 1_500);
+        assert_eq!(timings.proposal_delays_ms, vec![(2, 300)]);
+        assert_eq!(timings.leader_commit_delay_ms, Some((2, 190)));
+    }
+}
@@ -0,0 +1,368 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use consensus_core::{TransactionIndex, TransactionVerifier, ValidationError};
+use fastcrypto::{
+    ed25519::{ED25519_SIGNATURE_LENGTH, Ed25519PublicKey, Ed25519Signature},
+    traits::{ToFromBytes, VerifyingKey},
+};
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder, prelude::*};
+
+use crate::validator::config::VerifierConfig;
+use crate::validator::structured::{MAGIC, NONCE_SIZE, SENDER};
+
+/// The default batch size below which verification runs on the calling thread. The crossover
+/// depends on the machine and on the cost of the checks: measure it with `validator
+/// bench-verifier --sweep`, which verifies batches of increasing size on an increasing number
+/// of threads, and set `verifier.min_parallel_batch` of the node configuration accordingly.
+pub const DEFAULT_MIN_PARALLEL_BATCH: usize = 64;
+
+/// A check of a single transaction, run by a `ParallelVerifier` on every transaction of the
+/// batches to verify. The error tells why the transaction is invalid.
+pub trait TransactionCheck: Send + Sync {
+    fn check(&self, transaction: &[u8]) -> Result<(), String>;
+}
+
+impl<C: TransactionCheck + ?Sized> TransactionCheck for Arc<C> {
+    fn check(&self, transaction: &[u8]) -> Result<(), String> {
+        (**self).check(transaction)
+    }
+}
+
+/// Transaction verifier that accepts all transactions.
+pub struct AcceptAllVerifier;
+
+impl TransactionVerifier for AcceptAllVerifier {
+    fn verify_batch(&self, _batch: &[&[u8]]) -> Result<(), ValidationError> {
+        Ok(())
+    }
+
+    fn verify_and_vote_batch(
+        &self,
+        _batch: &[&[u8]],
+    ) -> Result<Vec<TransactionIndex>, ValidationError> {
+        Ok(vec![])
+    }
+}
+
+impl TransactionCheck for AcceptAllVerifier {
+    fn check(&self, _transaction: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Verifies the Ed25519 signature of structured transactions (as sent by the benchmark
+/// clients with `--sign`): the sender field of the header holds the public key of the signer,
+/// and the last 64 bytes hold its signature over the rest of the transaction. Any other
+/// transaction is rejected.
+pub struct Ed25519SignatureVerifier;
+
+impl Ed25519SignatureVerifier {
+    /// Check the signature of a single transaction.
+    pub fn verify(transaction: &[u8]) -> Result<(), String> {
+        let minimum = SENDER.end + NONCE_SIZE + ED25519_SIGNATURE_LENGTH;
+        if transaction.len() < minimum || !transaction.starts_with(MAGIC) {
+            return Err("not a structured transaction".to_string());
+        }
+        let public_key = Ed25519PublicKey::from_bytes(&transaction[SENDER])
+            .map_err(|e| format!("invalid sender: {e}"))?;
+        let (message, signature) =
+            transaction.split_at(transaction.len() - ED25519_SIGNATURE_LENGTH);
+        let signature = Ed25519Signature::from_bytes(signature)
+            .map_err(|e| format!("malformed signature: {e}"))?;
+        public_key
+            .verify(message, &signature)
+            .map_err(|_| "invalid signature".to_string())
+    }
+}
+
+impl TransactionCheck for Ed25519SignatureVerifier {
+    fn check(&self, transaction: &[u8]) -> Result<(), String> {
+        Self::verify(transaction)
+    }
+}
+
+/// Rejects empty transactions and transactions larger than a maximum size, then hands the
+/// others to the inner check. The size checks are cheap, so they run before the inner checks
+/// (e.g. signatures) rather than after them.
+pub struct SizeBoundedVerifier<C> {
+    inner: C,
+    max_transaction_size: usize,
+}
+
+impl<C: TransactionCheck> SizeBoundedVerifier<C> {
+    /// Reject the transactions larger than `max_transaction_size` bytes.
+    pub fn new(inner: C, max_transaction_size: usize) -> Self {
+        Self {
+            inner,
+            max_transaction_size,
+        }
+    }
+}
+
+impl<C: TransactionCheck> TransactionCheck for SizeBoundedVerifier<C> {
+    fn check(&self, transaction: &[u8]) -> Result<(), String> {
+        if transaction.is_empty() {
+            return Err("empty transaction".to_string());
+        }
+        if transaction.len() > self.max_transaction_size {
+            return Err(format!(
+                "{} bytes exceed the maximum size of {} bytes",
+                transaction.len(),
+                self.max_transaction_size
+            ));
+        }
+        self.inner.check(transaction)
+    }
+}
+
+/// Build the check of every transaction described by the configuration. The node runs it on
+/// the transactions submitted by its clients, and its verifier on those of the other nodes.
+pub fn build_check(config: &VerifierConfig) -> Arc<dyn TransactionCheck> {
+    match (config.signatures, config.max_transaction_size) {
+        (true, Some(max)) => Arc::new(SizeBoundedVerifier::new(Ed25519SignatureVerifier, max)),
+        (true, None) => Arc::new(Ed25519SignatureVerifier),
+        (false, Some(max)) => Arc::new(SizeBoundedVerifier::new(AcceptAllVerifier, max)),
+        (false, None) => Arc::new(AcceptAllVerifier),
+    }
+}
+
+/// Build the transaction verifier described by the configuration. Fails if its threads cannot
+/// be started.
+pub fn build_verifier(
+    config: &VerifierConfig,
+) -> Result<Arc<dyn TransactionVerifier>, ThreadPoolBuildError> {
+    let verifier = ParallelVerifier::new(build_check(config), config.threads)?
+        .with_min_parallel_batch(config.min_parallel_batch);
+    Ok(Arc::new(verifier))
+}
+
+/// Runs a CPU-bound check on every transaction of a batch, splitting large batches across a
+/// fixed number of threads, started once and shared by every batch. Batches smaller than
+/// `min_parallel_batch` are checked inline, since handing a handful of transactions to other
+/// threads costs more than the checks themselves. Like a sequential verifier, it reports the
+/// first invalid transaction of a batch.
+pub struct ParallelVerifier<C> {
+    check: C,
+    /// The worker threads, unless verification runs on a single (the calling) thread.
+    pool: Option<ThreadPool>,
+    min_parallel_batch: usize,
+}
+
+impl<C: TransactionCheck> ParallelVerifier<C> {
+    /// Create a new parallel verifier using `threads` worker threads (at least one). Fails if
+    /// the threads cannot be started.
+    pub fn new(check: C, threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        let pool = match threads > 1 {
+            true => Some(
+                ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .thread_name(|i| format!("verifier-{i}"))
+                    .build()?,
+            ),
+            false => None,
+        };
+        Ok(Self {
+            check,
+            pool,
+            min_parallel_batch: DEFAULT_MIN_PARALLEL_BATCH,
+        })
+    }
+
+    /// Set the batch size below which verification is not parallelized.
+    pub fn with_min_parallel_batch(mut self, min_parallel_batch: usize) -> Self {
+        self.min_parallel_batch = min_parallel_batch;
+        self
+    }
+
+    /// The worker threads, if the batch is large enough to be split across them.
+    fn pool(&self, batch_len: usize) -> Option<&ThreadPool> {
+        self.pool
+            .as_ref()
+            .filter(|_| batch_len >= self.min_parallel_batch.max(2))
+    }
+}
+
+impl<C: TransactionCheck> TransactionVerifier for ParallelVerifier<C> {
+    fn verify_batch(&self, batch: &[&[u8]]) -> Result<(), ValidationError> {
+        let invalid = |(index, transaction): (usize, &&[u8])| {
+            self.check
+                .check(transaction)
+                .err()
+                .map(|reason| (index, reason))
+        };
+        let first_invalid = match self.pool(batch.len()) {
+            Some(pool) => pool.install(|| batch.par_iter().enumerate().find_map_first(invalid)),
+            None => batch.iter().enumerate().find_map(invalid),
+        };
+        match first_invalid {
+            Some((index, reason)) => Err(ValidationError::InvalidTransaction(format!(
+                "transaction {index}: {reason}"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn verify_and_vote_batch(
+        &self,
+        batch: &[&[u8]],
+    ) -> Result<Vec<TransactionIndex>, ValidationError> {
+        let rejected = |(index, transaction): (usize, &&[u8])| {
+            self.check
+                .check(transaction)
+                .is_err()
+                .then_some(index as TransactionIndex)
+        };
+        Ok(match self.pool(batch.len()) {
+            Some(pool) => {
+                pool.install(|| batch.par_iter().enumerate().filter_map(rejected).collect())
+            }
+            None => batch.iter().enumerate().filter_map(rejected).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use consensus_core::{TransactionVerifier, ValidationError};
+    use fastcrypto::{
+        ed25519::Ed25519KeyPair,
+        traits::{KeyPair, Signer, ToFromBytes},
+    };
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{
+        AcceptAllVerifier, Ed25519SignatureVerifier, ParallelVerifier, SizeBoundedVerifier,
+        TransactionCheck,
+    };
+
+    /// Rejects every empty transaction.
+    struct RejectEmpty;
+
+    impl TransactionCheck for RejectEmpty {
+        fn check(&self, transaction: &[u8]) -> Result<(), String> {
+            match transaction.is_empty() {
+                true => Err("empty".to_string()),
+                false => Ok(()),
+            }
+        }
+    }
+
+    /// Verify the batches with `check`, on the calling thread.
+    fn single_threaded<C: TransactionCheck>(check: C) -> ParallelVerifier<C> {
+        ParallelVerifier::new(check, 1).unwrap()
+    }
+
+    #[test]
+    fn parallel_votes_keep_batch_indices() {
+        let transactions: Vec<Vec<u8>> = (0..100)
+            .map(|i| if i % 7 == 0 { vec![] } else { vec![1] })
+            .collect();
+        let batch: Vec<&[u8]> = transactions.iter().map(|tx| tx.as_slice()).collect();
+
+        let sequential = single_threaded(RejectEmpty)
+            .verify_and_vote_batch(&batch)
+            .unwrap();
+        let verifier = ParallelVerifier::new(RejectEmpty, 4)
+            .unwrap()
+            .with_min_parallel_batch(1);
+        let parallel = verifier.verify_and_vote_batch(&batch).unwrap();
+
+        assert_eq!(sequential, parallel);
+        assert!(verifier.verify_batch(&batch).is_err());
+        assert!(verifier.verify_batch(&batch[1..7]).is_ok());
+    }
+
+    #[test]
+    fn parallel_errors_report_the_first_invalid_transaction() {
+        let mut transactions = vec![vec![1u8]; 100];
+        transactions[70].clear();
+        transactions[95].clear();
+        let batch: Vec<&[u8]> = transactions.iter().map(|tx| tx.as_slice()).collect();
+
+        let verifier = ParallelVerifier::new(RejectEmpty, 4)
+            .unwrap()
+            .with_min_parallel_batch(1);
+        let error = verifier.verify_batch(&batch).unwrap_err();
+        assert!(matches!(
+            error,
+            ValidationError::InvalidTransaction(message) if message == "transaction 70: empty"
+        ));
+    }
+
+    /// A structured transaction (header, nonce, payload) signed by the sender of its header.
+    pub(crate) fn signed_transaction(keypair: &Ed25519KeyPair, nonce: u8) -> Vec<u8> {
+        let mut transaction = b"MYST".to_vec();
+        transaction.extend([1, 0]);
+        transaction.extend(32u32.to_le_bytes());
+        transaction.extend(keypair.public().as_bytes());
+        transaction.extend([nonce; 16]);
+        transaction.extend([7; 32]);
+        let signature = keypair.sign(&transaction);
+        transaction.extend(signature.as_bytes());
+        transaction
+    }
+
+    #[test]
+    fn verify_signatures() {
+        let keypair = Ed25519KeyPair::generate(&mut StdRng::from_seed([0; 32]));
+        let mut transactions: Vec<_> = (0..4)
+            .map(|nonce| signed_transaction(&keypair, nonce))
+            .collect();
+        let batch: Vec<&[u8]> = transactions.iter().map(|tx| tx.as_slice()).collect();
+        let verifier = single_threaded(Ed25519SignatureVerifier);
+        assert!(verifier.verify_batch(&batch).is_ok());
+
+        // Tamper with the payload of one transaction and truncate another.
+        transactions[1][60] ^= 1;
+        transactions[3].truncate(64);
+        let batch: Vec<&[u8]> = transactions.iter().map(|tx| tx.as_slice()).collect();
+        assert!(verifier.verify_batch(&batch).is_err());
+        let rejected = verifier.verify_and_vote_batch(&batch).unwrap();
+        assert_eq!(rejected, vec![1, 3]);
+    }
+
+    #[test]
+    fn reject_empty_and_oversized_transactions() {
+        let verifier = single_threaded(SizeBoundedVerifier::new(AcceptAllVerifier, 4));
+        assert!(verifier.verify_batch(&[b"tx", b"four"]).is_ok());
+
+        let error = verifier.verify_batch(&[b"tx", b"", b"four"]).unwrap_err();
+        assert!(matches!(
+            error,
+            ValidationError::InvalidTransaction(message) if message == "transaction 1: empty transaction"
+        ));
+        let error = verifier.verify_batch(&[b"tx", b"large"]).unwrap_err();
+        assert!(matches!(
+            error,
+            ValidationError::InvalidTransaction(message)
+                if message == "transaction 1: 5 bytes exceed the maximum size of 4 bytes"
+        ));
+
+        let rejected = verifier
+            .verify_and_vote_batch(&[b"", b"tx", b"large", b"four"])
+            .unwrap();
+        assert_eq!(rejected, vec![0, 2]);
+    }
+
+    #[test]
+    fn vote_with_the_inner_check() {
+        let keypair = Ed25519KeyPair::generate(&mut StdRng::from_seed([0; 32]));
+        let verifier = single_threaded(SizeBoundedVerifier::new(Ed25519SignatureVerifier, 200));
+        let transactions = [
+            signed_transaction(&keypair, 0),
+            vec![1; 300],
+            vec![1; 10],
+            signed_transaction(&keypair, 1),
+        ];
+        let batch: Vec<&[u8]> = transactions.iter().map(|tx| tx.as_slice()).collect();
+
+        // The oversized transaction is rejected on its size, the unsigned one by the inner
+        // check, which only sees the transactions of valid size.
+        assert!(verifier.verify_batch(&batch).is_err());
+        let rejected = verifier.verify_and_vote_batch(&batch).unwrap();
+        assert_eq!(rejected, vec![1, 2]);
+    }
+}
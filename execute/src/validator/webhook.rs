@@ -0,0 +1,205 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Notifies an external system of the commits of the transactions submitted through this node,
+//! so that it can react to them without polling `/tx_status`.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use consensus_core::BlockRef;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::validator::{config::WebhookConfig, metrics::NodeMetrics};
+
+/// How long to wait for the endpoint to answer a POST.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The commit of a transaction submitted through this node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitEvent {
+    /// The id of the transaction (as returned on submission).
+    pub tx_id: String,
+    /// The block that included the transaction.
+    pub block_ref: String,
+    /// The time between the submission of the transaction to consensus and its commit.
+    pub latency_ms: u64,
+}
+
+/// The body of a webhook POST.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommitEvents {
+    pub events: Vec<CommitEvent>,
+}
+
+/// Posts the commit events to the configured URL from a background task. Events are batched,
+/// at most one request is in flight and requests are at least `min_interval_ms` apart; events
+/// arriving while the queue is full are dropped rather than slowing the node down.
+pub struct CommitWebhook {
+    sender: mpsc::Sender<CommitEvent>,
+    metrics: Arc<NodeMetrics>,
+}
+
+impl CommitWebhook {
+    /// Start the task posting the events.
+    pub fn start(config: WebhookConfig, metrics: Arc<NodeMetrics>) -> Arc<Self> {
+        let (sender, receiver) = mpsc::channel(config.queue_capacity);
+        tokio::spawn(Self::run(config, receiver, metrics.clone()));
+        Arc::new(Self { sender, metrics })
+    }
+
+    /// Queue an event, or drop it if the endpoint is not keeping up.
+    pub fn notify(&self, event: CommitEvent) {
+        if self.sender.try_send(event).is_err() {
+            self.metrics
+                .webhook_events
+                .with_label_values(&["dropped"])
+                .inc();
+        }
+    }
+
+    async fn run(
+        config: WebhookConfig,
+        mut receiver: mpsc::Receiver<CommitEvent>,
+        metrics: Arc<NodeMetrics>,
+    ) {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("The webhook client configuration is valid");
+        let min_interval = Duration::from_millis(config.min_interval_ms);
+        let mut events = Vec::with_capacity(config.max_batch);
+        while let Some(event) = receiver.recv().await {
+            // Gather the events queued since the last request, up to a batch.
+            events.push(event);
+            while events.len() < config.max_batch {
+                match receiver.try_recv() {
+                    Ok(event) => events.push(event),
+                    Err(_) => break,
+                }
+            }
+
+            let count = events.len() as u64;
+            let body = CommitEvents {
+                events: std::mem::take(&mut events),
+            };
+            let outcome = match client.post(&config.url).json(&body).send().await {
+                Ok(response) if response.status().is_success() => {
+                    debug!("Posted {} commit events to the webhook", count);
+                    "delivered"
+                }
+                Ok(response) => {
+                    warn!(
+                        "The commit webhook rejected {} events: {}",
+                        count,
+                        response.status()
+                    );
+                    "failed"
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to post {} commit events to the webhook: {}",
+                        count, e
+                    );
+                    "failed"
+                }
+            };
+            metrics
+                .webhook_events
+                .with_label_values(&[outcome])
+                .inc_by(count);
+
+            // Events keep queuing up meanwhile, and go out with the next batch.
+            tokio::time::sleep(min_interval).await;
+        }
+    }
+}
+
+/// A transaction submitted to consensus, to be reported to the webhook once committed.
+pub struct PendingCommit {
+    webhook: Arc<CommitWebhook>,
+    tx_id: String,
+    submitted: Instant,
+}
+
+impl PendingCommit {
+    /// Start timing the transaction, right before it is submitted to consensus.
+    pub fn new(webhook: Arc<CommitWebhook>, tx_id: String) -> Self {
+        Self {
+            webhook,
+            tx_id,
+            submitted: Instant::now(),
+        }
+    }
+
+    /// Report the commit of the block including the transaction.
+    pub fn committed(self, block_ref: &BlockRef) {
+        self.webhook.notify(CommitEvent {
+            tx_id: self.tx_id,
+            block_ref: block_ref.to_string(),
+            latency_ms: self.submitted.elapsed().as_millis() as u64,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Arc, time::Duration};
+
+    use axum::{Json, Router, extract::State, routing::post};
+    use parking_lot::Mutex;
+    use prometheus::Registry;
+
+    use super::{CommitEvent, CommitEvents, CommitWebhook};
+    use crate::validator::{config::WebhookConfig, metrics::NodeMetrics};
+
+    #[tokio::test]
+    async fn batch_commit_events() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route(
+                "/commits",
+                post(
+                    |State(received): State<Arc<Mutex<Vec<usize>>>>,
+                     Json(body): Json<CommitEvents>| async move {
+                        received.lock().push(body.events.len());
+                    },
+                ),
+            )
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = WebhookConfig {
+            max_batch: 2,
+            min_interval_ms: 10,
+            ..WebhookConfig::new(format!("http://{address}/commits"))
+        };
+        let metrics = Arc::new(NodeMetrics::new(&Registry::new()));
+        let webhook = CommitWebhook::start(config, metrics.clone());
+        for i in 0..5 {
+            webhook.notify(CommitEvent {
+                tx_id: format!("{i:064x}"),
+                block_ref: "B1(0,digest)".into(),
+                latency_ms: 100,
+            });
+        }
+
+        let delivered = metrics.webhook_events.with_label_values(&["delivered"]);
+        for _ in 0..100 {
+            if delivered.get() == 5 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(delivered.get(), 5);
+        let batches = received.lock().clone();
+        assert_eq!(batches.iter().sum::<usize>(), 5);
+        assert!(batches.iter().all(|&size| size <= 2));
+    }
+}
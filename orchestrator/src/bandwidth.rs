@@ -0,0 +1,380 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Measurement of the network bandwidth consumed by the nodes during a benchmark: the network
+//! counters of their container (local), or the bytes of the UDP (consensus) traffic exchanged
+//! with the other instances, counted by iptables (remote). Locally, the counters also include
+//! the (small) RPC traffic of the clients.
+
+use std::{collections::BTreeMap, net::Ipv4Addr, process::Command, time::Instant};
+
+use serde::Serialize;
+use tokio::runtime::Handle;
+use tracing::warn;
+
+use crate::{
+    client::Instance,
+    ssh::{CommandContext, SshConnectionManager},
+    storage::parse_block_io,
+};
+
+/// The iptables chain counting the traffic sent to the other instances.
+const SENT_CHAIN: &str = "MYSTICETI_PEER_TX";
+/// The iptables chain counting the traffic received from the other instances.
+const RECEIVED_CHAIN: &str = "MYSTICETI_PEER_RX";
+
+/// The cumulative network counters of a node at one point in time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NetworkSnapshot {
+    pub sent_bytes: u64,
+    pub received_bytes: u64,
+}
+
+/// The bytes a node sent and received over the run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct NodeBandwidth {
+    pub sent_bytes: u64,
+    pub received_bytes: u64,
+}
+
+/// The network traffic of every node over the run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct BandwidthUsage {
+    /// The traffic of each node, in node order (`None` if it could not be measured).
+    pub nodes: Vec<Option<NodeBandwidth>>,
+    pub elapsed_s: f64,
+}
+
+impl BandwidthUsage {
+    /// The traffic between two snapshots of the nodes.
+    pub fn between(
+        before: &[Option<NetworkSnapshot>],
+        after: &[Option<NetworkSnapshot>],
+        elapsed_s: f64,
+    ) -> Self {
+        let nodes = before
+            .iter()
+            .zip(after)
+            .map(|(before, after)| {
+                let (before, after) = (before.as_ref()?, after.as_ref()?);
+                // Counters reset if the container restarts; do not count that as traffic.
+                Some(NodeBandwidth {
+                    sent_bytes: after.sent_bytes.saturating_sub(before.sent_bytes),
+                    received_bytes: after.received_bytes.saturating_sub(before.received_bytes),
+                })
+            })
+            .collect();
+        Self { nodes, elapsed_s }
+    }
+
+    /// The bytes sent by all the measured nodes. Every byte exchanged between two nodes is
+    /// sent once, so this is the total traffic between the nodes.
+    pub fn total_sent_bytes(&self) -> u64 {
+        self.nodes
+            .iter()
+            .flatten()
+            .map(|node| node.sent_bytes)
+            .sum()
+    }
+
+    /// The bytes exchanged between the nodes per committed transaction.
+    pub fn bytes_per_transaction(&self, committed_transactions: u64) -> Option<f64> {
+        (committed_transactions > 0)
+            .then(|| self.total_sent_bytes() as f64 / committed_transactions as f64)
+    }
+
+    /// Summarize the traffic into benchmark metadata: the total and per-node rate of the
+    /// traffic, its cost per committed transaction (if known), and the per-node traffic.
+    pub fn to_metadata(&self, committed_transactions: Option<u64>) -> BTreeMap<String, String> {
+        let measured = self.nodes.iter().flatten().count();
+        if measured == 0 || self.elapsed_s <= 0.0 {
+            return BTreeMap::new();
+        }
+        let total = self.total_sent_bytes();
+        let rate = total as f64 / self.elapsed_s;
+
+        let mut metadata: BTreeMap<_, _> = [
+            ("network_bytes_total".into(), total.to_string()),
+            ("network_bytes_per_s".into(), format!("{rate:.0}")),
+            (
+                "network_bytes_per_s_per_node".into(),
+                format!("{:.0}", rate / measured as f64),
+            ),
+            (
+                "network_bandwidth".into(),
+                serde_json::to_string(self).unwrap_or_default(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        if let Some(bytes) = committed_transactions.and_then(|n| self.bytes_per_transaction(n)) {
+            metadata.insert(
+                "network_bytes_per_transaction".into(),
+                format!("{bytes:.1}"),
+            );
+        }
+        metadata
+    }
+}
+
+/// Parse the `NetIO` column of `docker stats`, e.g. `12.3MB / 45.6MB`, into the bytes received
+/// and sent.
+pub fn parse_net_io(net_io: &str) -> Option<NetworkSnapshot> {
+    let (received_bytes, sent_bytes) = parse_block_io(net_io)?;
+    Some(NetworkSnapshot {
+        sent_bytes,
+        received_bytes,
+    })
+}
+
+/// Parse the `key=value` lines printed by `remote_counters_script`.
+pub fn parse_counters(output: &str) -> Option<NetworkSnapshot> {
+    let mut sent = None;
+    let mut received = None;
+    for (key, value) in output.lines().filter_map(|line| line.split_once('=')) {
+        match key.trim() {
+            "sent_bytes" => sent = value.trim().parse().ok(),
+            "received_bytes" => received = value.trim().parse().ok(),
+            _ => (),
+        }
+    }
+    Some(NetworkSnapshot {
+        sent_bytes: sent?,
+        received_bytes: received?,
+    })
+}
+
+/// A shell script (re)installing the iptables rules counting the UDP traffic exchanged with
+/// the specified peers. The counters start from zero.
+pub fn remote_accounting_script(peers: &[Ipv4Addr]) -> String {
+    let mut lines = Vec::new();
+    for (chain, hook, direction) in [
+        (SENT_CHAIN, "OUTPUT", "-d"),
+        (RECEIVED_CHAIN, "INPUT", "-s"),
+    ] {
+        lines.push(format!(
+            "sudo iptables -N {chain} 2>/dev/null || sudo iptables -F {chain}"
+        ));
+        lines.push(format!(
+            "sudo iptables -C {hook} -j {chain} 2>/dev/null || sudo iptables -I {hook} -j {chain}"
+        ));
+        for peer in peers {
+            lines.push(format!(
+                "sudo iptables -A {chain} {direction} {peer} -p udp"
+            ));
+        }
+    }
+    lines.join(" && ")
+}
+
+/// A shell script printing the bytes counted by the rules of `remote_accounting_script` as
+/// `key=value` lines.
+pub fn remote_counters_script() -> String {
+    [("sent_bytes", SENT_CHAIN), ("received_bytes", RECEIVED_CHAIN)]
+        .iter()
+        .map(|(key, chain)| {
+            format!(
+                "sudo iptables -nvxL {chain} | awk 'NR > 2 {{ b += $2 }} END {{ printf \"{key}=%.0f\\n\", b }}'"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" && ")
+}
+
+/// Read the network counters of the containers of a local network.
+fn sample_local(containers: &[String]) -> Vec<Option<NetworkSnapshot>> {
+    let output = Command::new("docker")
+        .args(["stats", "--no-stream", "--format", "{{.Name}} {{.NetIO}}"])
+        .args(containers)
+        .output();
+    let stats = match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Err(e) => {
+            warn!("Failed to read the network counters of the nodes: {e}");
+            return vec![None; containers.len()];
+        }
+    };
+
+    containers
+        .iter()
+        .map(|container| {
+            stats
+                .lines()
+                .filter_map(|line| line.split_once(' '))
+                .find(|(name, _)| name == container)
+                .and_then(|(_, net_io)| parse_net_io(net_io))
+        })
+        .collect()
+}
+
+/// Read the network counters of remote instances over ssh.
+async fn sample_remote(
+    ssh_manager: &SshConnectionManager,
+    instances: &[Instance],
+) -> Vec<Option<NetworkSnapshot>> {
+    let outputs = ssh_manager
+        .execute(
+            instances.to_vec(),
+            remote_counters_script(),
+            CommandContext::default(),
+        )
+        .await;
+    match outputs {
+        Ok(outputs) => outputs
+            .iter()
+            .map(|(stdout, _)| parse_counters(stdout))
+            .collect(),
+        Err(e) => {
+            warn!("Failed to read the network counters of the instances: {e}");
+            vec![None; instances.len()]
+        }
+    }
+}
+
+/// Where the network counters of the nodes are read.
+enum Target {
+    Local(Vec<String>),
+    Remote(SshConnectionManager, Vec<Instance>),
+}
+
+/// Measures the network traffic of the nodes between `start` and `stop`.
+pub struct BandwidthMeter {
+    target: Target,
+    before: Vec<Option<NetworkSnapshot>>,
+    start: Instant,
+}
+
+impl BandwidthMeter {
+    /// Start measuring the docker containers of a local network, in node order.
+    pub async fn start_local(containers: Vec<String>) -> Self {
+        let target = Target::Local(containers);
+        let before = Self::sample(&target).await;
+        Self {
+            target,
+            before,
+            start: Instant::now(),
+        }
+    }
+
+    /// Start measuring the traffic between remote instances, in node order. Installs iptables
+    /// rules counting the UDP traffic between the instances (requires sudo).
+    pub async fn start_remote(ssh_manager: SshConnectionManager, instances: Vec<Instance>) -> Self {
+        let commands = instances.iter().map(|instance| {
+            let peers: Vec<_> = instances
+                .iter()
+                .filter(|peer| peer.main_ip != instance.main_ip)
+                .map(|peer| peer.main_ip)
+                .collect();
+            (instance.clone(), remote_accounting_script(&peers))
+        });
+        if let Err(e) = ssh_manager
+            .execute_per_instance(commands, CommandContext::default())
+            .await
+        {
+            warn!("Failed to install the network accounting rules: {e}");
+        }
+
+        let target = Target::Remote(ssh_manager, instances);
+        let before = Self::sample(&target).await;
+        Self {
+            target,
+            before,
+            start: Instant::now(),
+        }
+    }
+
+    async fn sample(target: &Target) -> Vec<Option<NetworkSnapshot>> {
+        match target {
+            Target::Local(containers) => {
+                // `docker stats` takes a couple of seconds, keep it off the runtime threads.
+                let containers = containers.clone();
+                Handle::current()
+                    .spawn_blocking(move || sample_local(&containers))
+                    .await
+                    .unwrap_or_default()
+            }
+            Target::Remote(ssh_manager, instances) => sample_remote(ssh_manager, instances).await,
+        }
+    }
+
+    /// Read the counters again and return the traffic of every node since the start.
+    pub async fn stop(self) -> BandwidthUsage {
+        let after = Self::sample(&self.target).await;
+        BandwidthUsage::between(&self.before, &after, self.start.elapsed().as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+
+    use super::{
+        BandwidthUsage, NetworkSnapshot, NodeBandwidth, parse_counters, parse_net_io,
+        remote_accounting_script,
+    };
+
+    #[test]
+    fn parse_network_counters() {
+        assert_eq!(
+            parse_net_io("1.5kB / 2MB"),
+            Some(NetworkSnapshot {
+                sent_bytes: 2_000_000,
+                received_bytes: 1_500
+            })
+        );
+        assert_eq!(parse_net_io("--"), None);
+        assert_eq!(
+            parse_counters("sent_bytes=100\nreceived_bytes=42\n"),
+            Some(NetworkSnapshot {
+                sent_bytes: 100,
+                received_bytes: 42
+            })
+        );
+        assert_eq!(parse_counters("sent_bytes=100\n"), None);
+
+        let script = remote_accounting_script(&[Ipv4Addr::new(10, 0, 0, 2)]);
+        assert!(script.contains("-A MYSTICETI_PEER_TX -d 10.0.0.2 -p udp"));
+        assert!(script.contains("-A MYSTICETI_PEER_RX -s 10.0.0.2 -p udp"));
+    }
+
+    #[test]
+    fn bandwidth_per_transaction() {
+        let snapshot = |sent_bytes, received_bytes| {
+            Some(NetworkSnapshot {
+                sent_bytes,
+                received_bytes,
+            })
+        };
+        let before = [snapshot(1_000, 1_000), snapshot(0, 0), None];
+        let after = [
+            snapshot(5_000, 4_000),
+            snapshot(6_000, 7_000),
+            snapshot(1, 1),
+        ];
+        let usage = BandwidthUsage::between(&before, &after, 10.0);
+
+        assert_eq!(
+            usage.nodes,
+            vec![
+                Some(NodeBandwidth {
+                    sent_bytes: 4_000,
+                    received_bytes: 3_000
+                }),
+                Some(NodeBandwidth {
+                    sent_bytes: 6_000,
+                    received_bytes: 7_000
+                }),
+                None
+            ]
+        );
+        assert_eq!(usage.total_sent_bytes(), 10_000);
+        assert_eq!(usage.bytes_per_transaction(100), Some(100.0));
+        assert_eq!(usage.bytes_per_transaction(0), None);
+
+        let metadata = usage.to_metadata(Some(100));
+        assert_eq!(metadata["network_bytes_per_s"], "1000");
+        assert_eq!(metadata["network_bytes_per_s_per_node"], "500");
+        assert_eq!(metadata["network_bytes_per_transaction"], "100.0");
+        assert!(BandwidthUsage::default().to_metadata(None).is_empty());
+    }
+}
@@ -35,6 +35,8 @@ pub trait BenchmarkType:
     + PartialOrd
     + Ord
     + FromStr
+    + Send
+    + Sync
 {
 }
 
@@ -51,6 +53,9 @@ pub struct BenchmarkParameters<T> {
     pub load: usize,
     /// The duration of the benchmark.
     pub duration: Duration,
+    /// The maximum number of transactions per proposal, if overriding the protocol limit.
+    #[serde(default)]
+    pub max_transactions_per_proposal: Option<u64>,
 }
 
 impl<T: BenchmarkType> Default for BenchmarkParameters<T> {
@@ -61,6 +66,7 @@ impl<T: BenchmarkType> Default for BenchmarkParameters<T> {
             faults: FaultsType::default(),
             load: 500,
             duration: Duration::from_secs(60),
+            max_transactions_per_proposal: None,
         }
     }
 }
@@ -71,7 +77,11 @@ impl<T: BenchmarkType> Debug for BenchmarkParameters<T> {
             f,
             "{:?}-{:?}-{}-{}",
             self.benchmark_type, self.faults, self.nodes, self.load
-        )
+        )?;
+        match self.max_transactions_per_proposal {
+            Some(max) => write!(f, "-{max}"),
+            None => Ok(()),
+        }
     }
 }
 
@@ -81,7 +91,11 @@ impl<T> Display for BenchmarkParameters<T> {
             f,
             "{} nodes ({}) - {} tx/s",
             self.nodes, self.faults, self.load
-        )
+        )?;
+        match self.max_transactions_per_proposal {
+            Some(max) => write!(f, " - max {max} tx/proposal"),
+            None => Ok(()),
+        }
     }
 }
 
@@ -100,8 +114,15 @@ impl<T> BenchmarkParameters<T> {
             faults,
             load,
             duration,
+            max_transactions_per_proposal: None,
         }
     }
+
+    /// Override the maximum number of transactions per proposal.
+    pub fn with_max_transactions_per_proposal(mut self, max: Option<u64>) -> Self {
+        self.max_transactions_per_proposal = max;
+        self
+    }
 }
 
 /// The load type to submit to the nodes.
@@ -118,6 +139,29 @@ pub enum LoadType {
         /// The maximum number of iterations before converging on a breaking point.
         max_iterations: usize,
     },
+
+    /// Search for the highest load at which a latency percentile stays within an SLO.
+    LatencySlo {
+        /// The initial load to test.
+        starting_load: usize,
+        /// The maximum number of iterations before converging on the highest load.
+        max_iterations: usize,
+        /// The percentile bounded by the SLO, as a fraction (e.g. 0.99 for p99).
+        percentile: f64,
+        /// The maximum latency at that percentile.
+        max_latency: Duration,
+    },
+}
+
+/// The highest load that met the latency SLO.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SloResult {
+    /// The input load (tx/s).
+    pub load: usize,
+    /// The throughput achieved at that load (tx/s).
+    pub tps: u64,
+    /// The latency at the percentile of the SLO.
+    pub latency: Duration,
 }
 
 /// Generate benchmark parameters (one set of parameters per run).
@@ -133,6 +177,8 @@ pub struct BenchmarkParametersGenerator<T: BenchmarkType> {
     pub faults: FaultsType,
     /// The duration of the benchmark.
     duration: Duration,
+    /// The maximum number of transactions per proposal, if overriding the protocol limit.
+    max_transactions_per_proposal: Option<u64>,
     /// The load of the next benchmark run.
     next_load: Option<usize>,
     /// Temporary hold a lower bound of the breaking point.
@@ -141,6 +187,8 @@ pub struct BenchmarkParametersGenerator<T: BenchmarkType> {
     upper_bound_result: Option<MeasurementsCollection<T>>,
     /// The current number of iterations.
     iterations: usize,
+    /// The highest load found within the latency SLO so far.
+    slo_result: Option<SloResult>,
 }
 
 impl<T: BenchmarkType> Iterator for BenchmarkParametersGenerator<T> {
@@ -156,6 +204,7 @@ impl<T: BenchmarkType> Iterator for BenchmarkParametersGenerator<T> {
                 load,
                 self.duration,
             )
+            .with_max_transactions_per_proposal(self.max_transactions_per_proposal)
         })
     }
 }
@@ -174,7 +223,9 @@ impl<T: BenchmarkType> BenchmarkParametersGenerator<T> {
                     Some(loads.remove(0))
                 }
             }
-            LoadType::Search { starting_load, .. } => Some(*starting_load),
+            LoadType::Search { starting_load, .. } | LoadType::LatencySlo { starting_load, .. } => {
+                Some(*starting_load)
+            }
         };
         Self {
             benchmark_type: T::default(),
@@ -182,10 +233,12 @@ impl<T: BenchmarkType> BenchmarkParametersGenerator<T> {
             load_type,
             faults: FaultsType::default(),
             duration: Self::DEFAULT_DURATION,
+            max_transactions_per_proposal: None,
             next_load,
             lower_bound_result: None,
             upper_bound_result: None,
             iterations: 0,
+            slo_result: None,
         }
     }
 
@@ -207,6 +260,12 @@ impl<T: BenchmarkType> BenchmarkParametersGenerator<T> {
         self
     }
 
+    /// Override the maximum number of transactions per proposal.
+    pub fn with_max_transactions_per_proposal(mut self, max: Option<u64>) -> Self {
+        self.max_transactions_per_proposal = max;
+        self
+    }
+
     /// Detects whether the latest benchmark parameters run the system out of capacity.
     fn out_of_capacity(
         last_result: &MeasurementsCollection<T>,
@@ -277,7 +336,102 @@ impl<T: BenchmarkType> BenchmarkParametersGenerator<T> {
                     }
                 }
             }
+            LoadType::LatencySlo { .. } => self.next_slo_load(result),
+        };
+    }
+
+    /// The latency at the percentile of the SLO, and whether the run met the SLO. A run also
+    /// misses it if its throughput is less than 2/3 of the input rate: the few transactions a
+    /// saturated system commits may still look fast.
+    fn slo_latency(
+        result: &MeasurementsCollection<T>,
+        percentile: f64,
+        max_latency: Duration,
+    ) -> (Duration, bool) {
+        let Some(first_label) = result.labels().next() else {
+            return (Duration::ZERO, false);
+        };
+        let latency = result.aggregate_latency_percentile(first_label, percentile);
+        let load = result.transaction_load() as u64;
+        let sustained = result.aggregate_tps(first_label) >= 2 * load / 3;
+        (latency, sustained && latency <= max_latency)
+    }
+
+    /// Bisect the load between the highest run within the SLO and the lowest run missing it,
+    /// doubling the load until a run misses it (or halving it until one meets it).
+    fn next_slo_load(&mut self, result: MeasurementsCollection<T>) -> Option<usize> {
+        let LoadType::LatencySlo {
+            max_iterations,
+            percentile,
+            max_latency,
+            ..
+        } = &self.load_type
+        else {
+            unreachable!("Only called when searching for a latency SLO");
+        };
+        let (max_iterations, percentile, max_latency) =
+            (*max_iterations, *percentile, *max_latency);
+
+        let load = result.transaction_load();
+        let (latency, within_slo) = Self::slo_latency(&result, percentile, max_latency);
+        if within_slo {
+            if self.slo_result.as_ref().is_none_or(|best| best.load < load) {
+                let tps = result
+                    .labels()
+                    .next()
+                    .map(|label| result.aggregate_tps(label))
+                    .unwrap_or_default();
+                self.slo_result = Some(SloResult { load, tps, latency });
+            }
+            self.lower_bound_result = Some(result);
+        } else {
+            self.upper_bound_result = Some(result);
+        }
+
+        if self.iterations >= max_iterations {
+            return None;
+        }
+        self.iterations += 1;
+        let next = match (&self.lower_bound_result, &self.upper_bound_result) {
+            (_, None) => load * 2,
+            (None, Some(upper)) => upper.transaction_load() / 2,
+            (Some(lower), Some(upper)) => (lower.transaction_load() + upper.transaction_load()) / 2,
         };
+        // Stop once the bounds are adjacent (or no load meets the SLO).
+        let converged = next == 0
+            || self
+                .lower_bound_result
+                .as_ref()
+                .is_some_and(|lower| lower.transaction_load() == next);
+        (!converged).then_some(next)
+    }
+
+    /// A summary of the latency SLO search, if the generator searches for one.
+    pub fn slo_summary(&self) -> Option<String> {
+        let LoadType::LatencySlo {
+            percentile,
+            max_latency,
+            ..
+        } = &self.load_type
+        else {
+            return None;
+        };
+        let percentile = (percentile * 1000.0).round() / 10.0;
+        let slo = format!("p{percentile} <= {} ms", max_latency.as_millis());
+        Some(match &self.slo_result {
+            Some(best) => format!(
+                "{} tx/s at a load of {} tx/s ({slo}: {} ms)",
+                best.tps,
+                best.load,
+                best.latency.as_millis()
+            ),
+            None => format!("no load met {slo}"),
+        })
+    }
+
+    /// The highest load found within the latency SLO, if any.
+    pub fn slo_result(&self) -> Option<&SloResult> {
+        self.slo_result.as_ref()
     }
 }
 
@@ -288,11 +442,24 @@ pub enum NetworkType {
     Remote,
 }
 
+impl NetworkType {
+    /// The name identifying networks of this type in summaries and file names.
+    pub fn default_name(&self) -> &'static str {
+        match self {
+            NetworkType::Local => "local",
+            NetworkType::Remote => "remote",
+        }
+    }
+}
+
 /// Comprehensive benchmark result structure
 #[derive(Debug, Clone, Serialize)]
 pub struct BenchmarkResult<T: BenchmarkType + DeserializeOwned> {
     /// Network type (local or remote)
     pub network_type: NetworkType,
+    /// The name of the network the benchmark ran against, distinguishing networks of the same
+    /// type (e.g., with different protocol parameters)
+    pub network_name: String,
     /// Benchmark parameters
     pub parameters: BenchmarkParameters<T>,
     /// Measurement collection
@@ -301,6 +468,9 @@ pub struct BenchmarkResult<T: BenchmarkType + DeserializeOwned> {
     pub timestamp: DateTime<Utc>,
     /// Additional metadata
     pub metadata: HashMap<String, String>,
+    /// The complete configuration of the run (options, resolved endpoints, and environment),
+    /// so that a saved result can be reproduced. Null if not recorded.
+    pub config: serde_json::Value,
 }
 
 impl<T: BenchmarkType + DeserializeOwned> BenchmarkResult<T> {
@@ -310,20 +480,35 @@ impl<T: BenchmarkType + DeserializeOwned> BenchmarkResult<T> {
         measurements: MeasurementsCollection<T>,
     ) -> Self {
         Self {
+            network_name: network_type.default_name().to_string(),
             network_type,
             parameters,
             measurements,
             timestamp: Utc::now(),
             metadata: HashMap::new(),
+            config: serde_json::Value::Null,
         }
     }
 
+    /// Record the complete configuration of the run with the result.
+    pub fn with_config(mut self, config: serde_json::Value) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set the name of the network the benchmark ran against.
+    pub fn with_network_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.network_name = name.into();
+        self
+    }
+
     /// Print benchmark results to console
     pub fn print_to_console(&self) {
         println!("\n{}", "=".repeat(80));
         println!("BENCHMARK RESULTS");
         println!("{}", "=".repeat(80));
         println!("Network Type: {:?}", self.network_type);
+        println!("Network: {}", self.network_name);
         println!("Timestamp: {}", self.timestamp);
         println!("Parameters: {:?}", self.parameters);
         println!("Duration: {:?}", self.parameters.duration);
@@ -401,12 +586,9 @@ impl<T: BenchmarkType + DeserializeOwned> BenchmarkResult<T> {
         // Create output directory if it doesn't exist
         fs::create_dir_all(output_dir)?;
 
-        // Generate filename based on timestamp and network type
+        // Generate filename based on timestamp and network name
         let timestamp_str = self.timestamp.format("%Y%m%d_%H%M%S");
-        let network_str = match self.network_type {
-            NetworkType::Local => "local",
-            NetworkType::Remote => "remote",
-        };
+        let network_str = &self.network_name;
 
         let filename = format!(
             "benchmark_{}_{}_{}nodes_{}txs.json",
@@ -432,6 +614,7 @@ impl<T: BenchmarkType + DeserializeOwned> BenchmarkResult<T> {
         writeln!(summary_file, "BENCHMARK SUMMARY")?;
         writeln!(summary_file, "{}", "=".repeat(50))?;
         writeln!(summary_file, "Network Type: {:?}", self.network_type)?;
+        writeln!(summary_file, "Network: {}", self.network_name)?;
         writeln!(summary_file, "Timestamp: {}", self.timestamp)?;
         writeln!(summary_file, "Parameters: {:?}", self.parameters)?;
         writeln!(summary_file, "Duration: {:?}", self.parameters.duration)?;
@@ -590,6 +773,7 @@ impl<T: BenchmarkType + DeserializeOwned> BenchmarkRunner<T> {
             working_dir: PathBuf::from("test"),
             results_dir: PathBuf::from("test"),
             logs_dir: PathBuf::from("test"),
+            measurement_sink: Default::default(),
         };
 
         let mut collection = MeasurementsCollection::new(&settings, parameters.clone());
@@ -607,36 +791,20 @@ impl<T: BenchmarkType + DeserializeOwned> BenchmarkRunner<T> {
         println!("COMPREHENSIVE BENCHMARK SUMMARY");
         println!("{}", "=".repeat(80));
 
-        // Group results by network type
-        let mut local_results = Vec::new();
-        let mut remote_results = Vec::new();
-
-        for result in results {
-            match result.network_type {
-                NetworkType::Local => local_results.push(result),
-                NetworkType::Remote => remote_results.push(result),
-            }
-        }
-
-        // Print local network summary
-        if !local_results.is_empty() {
-            println!("\nLOCAL NETWORK RESULTS:");
-            println!("{}", "-".repeat(40));
-            self.print_network_summary(&local_results);
-        }
+        // Group results by network
+        let networks = group_by_network(results);
 
-        // Print remote network summary
-        if !remote_results.is_empty() {
-            println!("\nREMOTE NETWORK RESULTS:");
+        for (name, network_results) in &networks {
+            println!("\n{} NETWORK RESULTS:", name.to_uppercase());
             println!("{}", "-".repeat(40));
-            self.print_network_summary(&remote_results);
+            self.print_network_summary(network_results);
         }
 
-        // Print comparison if both types exist
-        if !local_results.is_empty() && !remote_results.is_empty() {
+        // Print comparison if several networks exist
+        if networks.len() > 1 {
             println!("\nNETWORK COMPARISON:");
             println!("{}", "-".repeat(40));
-            self.print_network_comparison(&local_results, &remote_results);
+            print_network_comparison(&networks);
         }
 
         println!("{}", "=".repeat(80));
@@ -673,65 +841,95 @@ impl<T: BenchmarkType + DeserializeOwned> BenchmarkRunner<T> {
 
         table.printstd();
     }
+}
 
-    /// Print comparison between local and remote networks
-    fn print_network_comparison(
-        &self,
-        local_results: &[&BenchmarkResult<T>],
-        remote_results: &[&BenchmarkResult<T>],
-    ) {
-        // Find comparable benchmarks (same parameters)
-        for local_result in local_results {
-            for remote_result in remote_results {
-                if local_result.parameters.nodes == remote_result.parameters.nodes
-                    && local_result.parameters.load == remote_result.parameters.load
-                {
-                    if let (Some(local_label), Some(remote_label)) = (
-                        local_result.measurements.labels().next(),
-                        remote_result.measurements.labels().next(),
-                    ) {
-                        let local_tps = local_result.measurements.aggregate_tps(local_label);
-                        let remote_tps = remote_result.measurements.aggregate_tps(remote_label);
-                        let local_latency = local_result
-                            .measurements
-                            .aggregate_average_latency(local_label);
-                        let remote_latency = remote_result
-                            .measurements
-                            .aggregate_average_latency(remote_label);
-
-                        let tps_diff = if local_tps > 0 {
-                            ((remote_tps as f64 - local_tps as f64) / local_tps as f64) * 100.0
-                        } else {
-                            0.0
-                        };
-
-                        let latency_diff = if local_latency.as_millis() > 0 {
-                            ((remote_latency.as_millis() as f64 - local_latency.as_millis() as f64)
-                                / local_latency.as_millis() as f64)
-                                * 100.0
-                        } else {
-                            0.0
-                        };
-
-                        println!(
-                            "Comparison for {} nodes, {} tx/s load:",
-                            local_result.parameters.nodes, local_result.parameters.load
-                        );
-                        println!(
-                            "  Throughput: Local {} tx/s, Remote {} tx/s ({}%)",
-                            local_tps, remote_tps, tps_diff
-                        );
-                        println!(
-                            "  Latency: Local {:.2} ms, Remote {:.2} ms ({}%)",
-                            local_latency.as_millis(),
-                            remote_latency.as_millis(),
-                            latency_diff
-                        );
-                        println!();
-                    }
-                }
-            }
+/// Group the results by network, in the order the networks first appear.
+pub fn group_by_network<T: BenchmarkType + DeserializeOwned>(
+    results: &[BenchmarkResult<T>],
+) -> Vec<(String, Vec<&BenchmarkResult<T>>)> {
+    let mut networks: Vec<(String, Vec<&BenchmarkResult<T>>)> = Vec::new();
+    for result in results {
+        match networks
+            .iter_mut()
+            .find(|(name, _)| *name == result.network_name)
+        {
+            Some((_, network_results)) => network_results.push(result),
+            None => networks.push((result.network_name.clone(), vec![result])),
+        }
+    }
+    networks
+}
+
+/// Print the comparison between networks. The first network is the baseline: every other
+/// network is compared against it, for the benchmarks with the same committee size and load.
+pub fn print_network_comparison<T: BenchmarkType + DeserializeOwned>(
+    networks: &[(String, Vec<&BenchmarkResult<T>>)],
+) {
+    let Some(((baseline_name, baseline_results), others)) = networks.split_first() else {
+        return;
+    };
+
+    for baseline in baseline_results {
+        let Some(baseline_label) = baseline.measurements.labels().next() else {
+            continue;
+        };
+        let baseline_tps = baseline.measurements.aggregate_tps(baseline_label);
+        let baseline_latency = baseline
+            .measurements
+            .aggregate_average_latency(baseline_label);
+
+        let comparable: Vec<_> = others
+            .iter()
+            .flat_map(|(name, results)| results.iter().map(move |result| (name, result)))
+            .filter(|(_, result)| {
+                result.parameters.nodes == baseline.parameters.nodes
+                    && result.parameters.load == baseline.parameters.load
+            })
+            .collect();
+        if comparable.is_empty() {
+            continue;
+        }
+
+        println!(
+            "Comparison for {} nodes, {} tx/s load (baseline: {}):",
+            baseline.parameters.nodes, baseline.parameters.load, baseline_name
+        );
+        println!(
+            "  {}: {} tx/s, {:.2} ms",
+            baseline_name,
+            baseline_tps,
+            baseline_latency.as_millis()
+        );
+        for (name, result) in comparable {
+            let Some(label) = result.measurements.labels().next() else {
+                continue;
+            };
+            let tps = result.measurements.aggregate_tps(label);
+            let latency = result.measurements.aggregate_average_latency(label);
+
+            let tps_diff = if baseline_tps > 0 {
+                ((tps as f64 - baseline_tps as f64) / baseline_tps as f64) * 100.0
+            } else {
+                0.0
+            };
+            let latency_diff = if baseline_latency.as_millis() > 0 {
+                ((latency.as_millis() as f64 - baseline_latency.as_millis() as f64)
+                    / baseline_latency.as_millis() as f64)
+                    * 100.0
+            } else {
+                0.0
+            };
+
+            println!(
+                "  {}: {} tx/s ({:+.1}%), {:.2} ms ({:+.1}%)",
+                name,
+                tps,
+                tps_diff,
+                latency.as_millis(),
+                latency_diff
+            );
         }
+        println!();
     }
 }
 
@@ -749,7 +947,7 @@ pub mod test {
 
     use super::{
         BenchmarkParameters, BenchmarkParametersGenerator, BenchmarkResult, BenchmarkRunner,
-        BenchmarkType, LoadType, NetworkType,
+        BenchmarkType, LoadType, NetworkType, group_by_network,
     };
 
     /// Mock benchmark type for unit tests.
@@ -857,6 +1055,56 @@ pub mod test {
         assert!(next_parameters.is_none());
     }
 
+    #[test]
+    fn search_latency_slo() {
+        let settings = Settings::new_for_test();
+        let load = LoadType::LatencySlo {
+            starting_load: 90,
+            max_iterations: 10,
+            percentile: 0.99,
+            max_latency: Duration::from_secs(1),
+        };
+        let mut generator = BenchmarkParametersGenerator::<TestBenchmarkType>::new(4, load);
+
+        // The test measurement sustains 62 tx/s: a load of 90 tx/s meets the SLO.
+        let mut collection = MeasurementsCollection::new(&settings, generator.next().unwrap());
+        let (label, measurement) = Measurement::new_for_test();
+        collection.add(1, label, measurement);
+        generator.register_result(collection);
+        assert_eq!(generator.next().unwrap().load, 180);
+
+        // Runs without measurements miss the SLO: bisect below them.
+        let collection = MeasurementsCollection::new(&settings, generator.next().unwrap());
+        generator.register_result(collection);
+        assert_eq!(generator.next().unwrap().load, 135);
+        let collection = MeasurementsCollection::new(&settings, generator.next().unwrap());
+        generator.register_result(collection);
+        assert_eq!(generator.next().unwrap().load, 112);
+
+        let best = generator.slo_result().unwrap();
+        assert_eq!((best.load, best.tps), (90, 62));
+        assert_eq!(
+            generator.slo_summary().unwrap(),
+            "62 tx/s at a load of 90 tx/s (p99 <= 1000 ms: 0 ms)"
+        );
+
+        // A starting load missing the SLO is halved.
+        let load = LoadType::LatencySlo {
+            starting_load: 100,
+            max_iterations: 10,
+            percentile: 0.99,
+            max_latency: Duration::from_secs(1),
+        };
+        let mut generator = BenchmarkParametersGenerator::<TestBenchmarkType>::new(4, load);
+        let collection = MeasurementsCollection::new(&settings, generator.next().unwrap());
+        generator.register_result(collection);
+        assert_eq!(generator.next().unwrap().load, 50);
+        assert_eq!(
+            generator.slo_summary().unwrap(),
+            "no load met p99 <= 1000 ms"
+        );
+    }
+
     #[test]
     fn benchmark_result_creation() {
         let settings = Settings::new_for_test();
@@ -906,4 +1154,33 @@ pub mod test {
         assert_eq!(deserialized_local, NetworkType::Local);
         assert_eq!(deserialized_remote, NetworkType::Remote);
     }
+
+    #[test]
+    fn group_results_by_network() {
+        let settings = Settings::new_for_test();
+        let parameters = BenchmarkParameters::new(
+            TestBenchmarkType,
+            4,
+            FaultsType::Permanent { faults: 0 },
+            100,
+            Duration::from_secs(60),
+        );
+        let result = |name: &str| {
+            let collection = MeasurementsCollection::new(&settings, parameters.clone());
+            BenchmarkResult::new(NetworkType::Local, parameters.clone(), collection)
+                .with_network_name(name)
+        };
+        let results = vec![result("baseline"), result("candidate"), result("baseline")];
+
+        let networks = group_by_network(&results);
+        let names: Vec<_> = networks.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["baseline", "candidate"]);
+        assert_eq!(networks[0].1.len(), 2);
+        assert_eq!(networks[1].1.len(), 1);
+
+        // Without an explicit name, results are named after their network type.
+        let collection = MeasurementsCollection::new(&settings, parameters.clone());
+        let remote = BenchmarkResult::new(NetworkType::Remote, parameters, collection);
+        assert_eq!(remote.network_name, "remote");
+    }
 }
@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::net::{IpAddr, Ipv4Addr, ToSocketAddrs};
 use std::path::Path;
 use std::{path::PathBuf, time::Duration};
 use std::sync::Arc;
@@ -8,25 +9,48 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 
 use clap::Parser;
-use color_eyre::eyre::Result;
+use serde::Serialize;
+use color_eyre::eyre::{Result, WrapErr};
 use tracing::{info, warn};
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::{EnvFilter, fmt};
 use tokio::signal;
 
 // Import the orchestrator modules
-use orchestrator::benchmark::{BenchmarkParameters, BenchmarkResult, NetworkType};
+use orchestrator::bandwidth::BandwidthMeter;
+use orchestrator::benchmark::{
+    BenchmarkParameters, BenchmarkResult, NetworkType, group_by_network, print_network_comparison,
+};
 use orchestrator::client::Instance;
-use orchestrator::faults::FaultsType;
-use orchestrator::measurement::{Measurement, MeasurementsCollection};
+use orchestrator::environment;
+use orchestrator::faults::{FaultsType, TransactionFault};
+use orchestrator::measurement::{
+    HistogramSummary, Measurement, MeasurementsCollection, ResultsFormat, counter_by_label,
+    counter_value, gauge_value,
+};
+use orchestrator::orchestrator::{
+    BreakerTransition, CircuitBreaker, Compression, Interpolation, LoadDistribution,
+    NetworkNamespace, RateSchedule, SampleWriter, SubmissionLogging, UtilizationController,
+    run_samples_path, skew_metadata,
+};
+use orchestrator::profiling::NodeProfiler;
+use orchestrator::progress::BenchmarkProgress;
 use orchestrator::protocol::mysticeti::MysticetiBenchmarkType;
+use orchestrator::protocol::mysticeti::{
+    BLOCKS_PER_COMMIT, DUPLICATE_TRANSACTIONS, FAULTY_TRANSACTIONS, LEADER_COMMIT_DELAY,
+    PROPOSAL_DELAY, PROPOSE_TO_COMMIT, REJECTED_TRANSACTIONS, SUBMISSION_WAIT,
+    TIME_TO_FIRST_COMMIT, TRANSACTIONS_BY_AUTHORITY, TRANSACTIONS_PER_COMMIT, TransactionFormat,
+};
+use orchestrator::protocol::ProtocolCommands;
 use orchestrator::protocol::mysticeti::MysticetiProtocol;
-use orchestrator::settings::Settings;
-use orchestrator::settings::{CloudProvider, Repository};
+use orchestrator::settings::{Settings, expand_tilde};
+use orchestrator::settings::{CloudProvider, MeasurementSinkSettings, Repository};
+use orchestrator::sink;
 use orchestrator::ssh::SshConnectionManager;
+use orchestrator::storage::{self, StorageMonitor};
 use orchestrator::{LocalNetworkOrchestrator, Orchestrator};
 
-#[derive(Parser, Clone)]
+#[derive(Parser, Clone, Serialize)]
 #[command(
     author,
     version,
@@ -45,6 +69,11 @@ pub struct Opts {
     #[clap(long, default_value = "true")]
     file_output: bool,
 
+    /// The layout of the result files: default, or sui-compatible to compare the measurements
+    /// against upstream consensus benchmarks with the tooling of the Sui orchestrator
+    #[clap(long, default_value = "default")]
+    format: ResultsFormat,
+
     /// The committee size
     #[clap(long, default_value = "4")]
     committee: usize,
@@ -61,6 +90,11 @@ pub struct Opts {
     #[clap(long, default_value = "60")]
     crash_interval: u64,
 
+    /// UNSAFE: make a node silently drop or corrupt a fraction of the transactions it receives,
+    /// written NODE:FAULT (e.g. "1:drop=0.2,corrupt=0.1"). Local networks only
+    #[clap(long, value_name = "NODE:FAULT")]
+    unsafe_transaction_fault: Option<TransactionFault>,
+
     /// The duration of each benchmark in seconds
     #[clap(long, default_value = "180")]
     duration: u64,
@@ -77,6 +111,39 @@ pub struct Opts {
     #[clap(long, default_value = "512")]
     transaction_size: usize,
 
+    /// The layout of the transactions: raw (random bytes, the cheapest baseline), structured
+    /// (header, nonce, payload and signature, as costly to parse as real transactions) or
+    /// signed (structured, with a real signature the nodes verify)
+    #[clap(long, default_value = "raw")]
+    transaction_format: TransactionFormat,
+
+    /// Sign every transaction with an Ed25519 key and have the nodes verify the signatures.
+    /// Every run is preceded by a baseline with unsigned (structured) transactions of the same
+    /// size, to report the throughput cost of the signatures
+    #[clap(long)]
+    sign: bool,
+
+    /// Submit this fraction of the transactions (between 0 and 1) with high priority, have the
+    /// nodes submit the queued transactions of highest priority first, and report the latency
+    /// of each priority. Requires structured (or signed) transactions. Local networks only
+    #[clap(long, default_value = "0")]
+    high_priority_ratio: f64,
+
+    /// The number of distinct transactions encoded before the submission starts; the
+    /// simulator cycles through them, only changing their nonce
+    #[clap(long, default_value = "1024")]
+    pool_size: usize,
+
+    /// How the load is spread across the nodes: equal (round-robin) or weighted (in
+    /// proportion to the stake of the nodes, read from their committee)
+    #[clap(long, default_value = "equal")]
+    distribution: LoadDistribution,
+
+    /// Comma-separated maximum numbers of transactions per proposal to sweep, each run
+    /// against every load (empty keeps the protocol limit)
+    #[clap(long, default_value = "")]
+    max_transactions_per_proposal: String,
+
     /// Network type to benchmark (local or remote)
     #[clap(long, default_value = "local")]
     network_type: String,
@@ -85,10 +152,50 @@ pub struct Opts {
     #[clap(long, default_value = "../docker-compose.yml")]
     docker_compose_path: String,
 
-    /// Wait time for network startup in seconds
+    /// Comma-separated local networks to benchmark side by side, each as NAME or
+    /// NAME:MAX_TRANSACTIONS_PER_PROPOSAL. Every network runs in its own compose project and
+    /// port range, and the first one is the baseline of the comparison
+    #[clap(long, default_value = "")]
+    networks: String,
+
+    /// Maximum time to wait for all nodes to become healthy in seconds
     #[clap(long, default_value = "30")]
     startup_wait: u64,
 
+    /// Delay between two polls of the health of the nodes in milliseconds
+    #[clap(long, default_value = "1000")]
+    readiness_interval_ms: u64,
+
+    /// Warn if the clock of a node is skewed by more than this many milliseconds from the
+    /// orchestrator's, since it corrupts latencies measured across machines
+    #[clap(long, default_value = "10")]
+    max_clock_skew_ms: u64,
+
+    /// Delay between two samples of the database size and disk I/O of the nodes in seconds
+    /// (0 disables the sampling)
+    #[clap(long, default_value = "5")]
+    storage_interval: u64,
+
+    /// Measure the network traffic between the nodes and report its cost per committed
+    /// transaction (remote runs install iptables counters on the instances, which needs sudo)
+    #[clap(long, default_value = "false")]
+    measure_bandwidth: bool,
+
+    /// After each run, restart every node at once on its existing database and report how
+    /// long the committee takes to commit again (bounded by --startup-wait)
+    #[clap(long, default_value = "false")]
+    measure_restart: bool,
+
+    /// Record a CPU profile of every node with perf over each run, downloaded into the
+    /// `profiles` directory of the output directory. Remote networks only; the instances need
+    /// perf and password-less sudo, which is checked before the first run
+    #[clap(long, default_value = "false")]
+    profile_nodes: bool,
+
+    /// The sampling frequency of --profile-nodes, in Hz
+    #[clap(long, value_name = "HZ", default_value = "99")]
+    profile_frequency: u32,
+
     /// Whether to clean up containers after completion
     #[clap(long, default_value = "false")]
     cleanup: bool,
@@ -96,16 +203,198 @@ pub struct Opts {
     /// Whether to perform thorough cleanup (remove volumes and containers completely)
     #[clap(long, default_value = "false")]
     cleanup_thorough: bool,
+
+    /// Abort the run as soon as a node crashes, saving its logs to the output directory
+    #[clap(long, default_value = "false")]
+    fail_fast: bool,
+
+    /// Pause submission when the failure rate over the recent submissions exceeds this
+    /// fraction (between 0 and 1). Disabled if unset
+    #[clap(long)]
+    breaker_threshold: Option<f64>,
+
+    /// How long the circuit breaker pauses submission before probing the network, in
+    /// milliseconds
+    #[clap(long, default_value = "5000")]
+    breaker_cooldown_ms: u64,
+
+    /// The number of recent submissions over which the circuit breaker computes the failure rate
+    #[clap(long, default_value = "100")]
+    breaker_window: usize,
+
+    /// Only log the summary of the transaction simulation, not individual submissions.
+    /// This is the default above 1000 tx/s unless `--log-interval` is set
+    #[clap(long, default_value = "false")]
+    quiet: bool,
+
+    /// The number of submitted transactions between two log lines (defaults to 100)
+    #[clap(long)]
+    log_interval: Option<usize>,
+
+    /// Submit the transactions as compressed batches (gzip or zstd) to save upload bandwidth.
+    /// Local networks only
+    #[clap(long, value_name = "ALGORITHM")]
+    compress: Option<Compression>,
+
+    /// Submit up to this many transactions per request to the batch endpoint, to amortize the
+    /// HTTP overhead of the client. Local networks only
+    #[clap(long, default_value = "1")]
+    client_batch_size: usize,
+
+    /// How long a batch waits for more transactions after its first one, in milliseconds
+    #[clap(long, value_name = "MS", default_value = "10")]
+    client_batch_window: u64,
+
+    /// After the load stops, verify that every transaction acknowledged as committed is
+    /// committed on every node. Local networks only
+    #[clap(long, default_value = "false")]
+    verify_commits: bool,
+
+    /// Maximum time to wait for the submitted transactions to be committed before verifying
+    /// them (or collecting their latency samples), in seconds
+    #[clap(long, default_value = "30")]
+    verify_timeout: u64,
+
+    /// Write the submission time, commit time, and latency of every transaction to this file:
+    /// CSV if its extension is `.csv`, compact binary otherwise. The network and load of each
+    /// run are appended to the file name. Local networks only
+    #[clap(long, value_name = "PATH")]
+    dump_samples: Option<String>,
+
+    /// The fraction of the transactions written by `--dump-samples` (between 0 and 1), to cap
+    /// the size of the file
+    #[clap(long, default_value = "1.0")]
+    sample_fraction: f64,
+
+    /// Instead of holding the load, adjust the submission rate (starting from the load) to
+    /// hold this number of transactions per commit. Local networks only
+    #[clap(long)]
+    target_transactions_per_commit: Option<f64>,
+
+    /// Vary the submission rate over the run according to this schedule file, with one
+    /// `time_offset_secs,target_rate` pair per line. The run lasts `--duration` seconds.
+    /// Local networks only
+    #[clap(long, value_name = "PATH")]
+    rate_schedule: Option<PathBuf>,
+
+    /// How the rate evolves between the points of the schedule (step or linear)
+    #[clap(long, default_value = "step")]
+    schedule_interpolation: Interpolation,
+
+    /// The proportional gain of the utilization controller
+    #[clap(long, default_value = "0.5")]
+    controller_kp: f64,
+
+    /// The integral gain of the utilization controller
+    #[clap(long, default_value = "0.1")]
+    controller_ki: f64,
+
+    /// Delay between two adjustments of the rate by the utilization controller in
+    /// milliseconds
+    #[clap(long, default_value = "1000")]
+    controller_interval_ms: u64,
+
+    /// The maximum submission rate the utilization controller may reach (tx/s)
+    #[clap(long, default_value = "10000")]
+    controller_max_load: usize,
+
+    /// Serve the live benchmark progress as JSON on http://0.0.0.0:<PORT>/progress
+    #[clap(long)]
+    progress_port: Option<u16>,
+
+    /// Write the effective configuration (options, resolved endpoints, and environment) to
+    /// this json file
+    #[clap(long)]
+    dump_config: Option<String>,
+
+    /// Exit with an error if the throughput of any run falls below this percentage of its
+    /// input load, so that CI catches capacity regressions
+    #[clap(long, value_name = "PCT")]
+    min_efficiency: Option<f64>,
+
+    /// Push the measurements as they are collected to this HTTP endpoint, in the InfluxDB
+    /// line protocol. The token (if any) is read from MYSTICETI_INFLUX_TOKEN
+    #[clap(long)]
+    influx_url: Option<String>,
+}
+
+/// A local network benchmarked side by side with others.
+#[derive(Clone, Debug, Default)]
+struct NetworkSpec {
+    /// The namespace isolating the network from the others.
+    namespace: NetworkNamespace,
+    /// The maximum number of transactions per proposal of this network, overriding the sweep.
+    max_transactions: Option<u64>,
+}
+
+/// The IPv4 address of a host, given either as an address or as a DNS name.
+fn resolve_ipv4(host: &str) -> Result<Ipv4Addr> {
+    if let Ok(ip) = host.parse() {
+        return Ok(ip);
+    }
+    let addresses = (host, 0)
+        .to_socket_addrs()
+        .wrap_err(format!("Failed to resolve host {host}"))?;
+    addresses
+        .filter_map(|address| match address.ip() {
+            IpAddr::V4(ip) => Some(ip),
+            IpAddr::V6(_) => None,
+        })
+        .next()
+        .ok_or_else(|| color_eyre::eyre::eyre!("Host {host} has no IPv4 address"))
+}
+
+/// Parse the `--networks` option. Without networks, only the default network is benchmarked.
+fn parse_networks(spec: &str) -> Result<Vec<NetworkSpec>> {
+    let entries: Vec<_> = spec
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    if entries.is_empty() {
+        return Ok(vec![NetworkSpec::default()]);
+    }
+
+    let mut networks: Vec<NetworkSpec> = Vec::new();
+    for (slot, entry) in entries.into_iter().enumerate() {
+        let (name, max_transactions) = match entry.split_once(':') {
+            Some((name, max)) => {
+                let max = max.parse::<u64>().map_err(|e| {
+                    color_eyre::eyre::eyre!("Invalid max transactions per proposal '{max}': {e}")
+                })?;
+                (name, Some(max))
+            }
+            None => (entry, None),
+        };
+        if networks.iter().any(|n| n.namespace.name() == name) {
+            return Err(color_eyre::eyre::eyre!("Duplicate network name '{name}'"));
+        }
+        networks.push(NetworkSpec {
+            namespace: NetworkNamespace::new(name, slot as u16)?,
+            max_transactions,
+        });
+    }
+    Ok(networks)
 }
 
 struct BenchmarkRunner {
     opts: Opts,
     shutdown_signal: Arc<AtomicBool>,
+    progress: Arc<BenchmarkProgress>,
 }
 
 impl BenchmarkRunner {
     fn new(opts: Opts, shutdown_signal: Arc<AtomicBool>) -> Self {
-        Self { opts, shutdown_signal }
+        Self {
+            opts,
+            shutdown_signal,
+            progress: Arc::new(BenchmarkProgress::default()),
+        }
+    }
+
+    /// The delay between two samples of the storage of the nodes, if sampling is enabled.
+    fn storage_interval(&self) -> Option<Duration> {
+        (self.opts.storage_interval > 0).then(|| Duration::from_secs(self.opts.storage_interval))
     }
 
     fn check_shutdown(&self) -> bool {
@@ -156,6 +445,112 @@ impl BenchmarkRunner {
             self.opts.network_type, loads
         );
 
+        // Parse the proposal size limits to sweep (`None` keeps the protocol limit)
+        let mut proposal_limits: Vec<Option<u64>> = self
+            .opts
+            .max_transactions_per_proposal
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| {
+                s.trim().parse::<u64>().map(Some).map_err(|e| {
+                    color_eyre::eyre::eyre!("Invalid max transactions per proposal '{s}': {e}")
+                })
+            })
+            .collect::<Result<_>>()?;
+        if proposal_limits.is_empty() {
+            proposal_limits.push(None);
+        }
+        if (self.opts.target_transactions_per_commit.is_some()
+            || self.opts.verify_commits
+            || self.opts.compress.is_some()
+            || self.opts.dump_samples.is_some()
+            || self.opts.rate_schedule.is_some()
+            || self.opts.sign
+            || self.opts.high_priority_ratio > 0.0
+            || self.opts.measure_restart
+            || self.opts.client_batch_size > 1)
+            && self.opts.network_type.to_lowercase() != "local"
+        {
+            return Err(color_eyre::eyre::eyre!(
+                "Utilization targets, commit verification, compression, latency samples, rate schedules, signed transactions, priorities, restart measurements, and client batching are only supported for local benchmarks"
+            ));
+        }
+        if self.opts.client_batch_size == 0 {
+            return Err(color_eyre::eyre::eyre!(
+                "The client batch size must be at least 1"
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.opts.high_priority_ratio) {
+            return Err(color_eyre::eyre::eyre!(
+                "The high priority ratio must be in [0, 1] (got {})",
+                self.opts.high_priority_ratio
+            ));
+        }
+        if self.opts.high_priority_ratio > 0.0
+            && !self.opts.sign
+            && self.opts.transaction_format == TransactionFormat::Raw
+        {
+            return Err(color_eyre::eyre::eyre!(
+                "Priorities require structured or signed transactions (--transaction-format)"
+            ));
+        }
+        if self.opts.rate_schedule.is_some() && self.opts.target_transactions_per_commit.is_some() {
+            return Err(color_eyre::eyre::eyre!(
+                "A rate schedule cannot be combined with a utilization target"
+            ));
+        }
+        // Fail before starting any network if the schedule is invalid
+        self.rate_schedule()?;
+        if let Some(min) = self
+            .opts
+            .min_efficiency
+            .filter(|min| !(0.0..=100.0).contains(min))
+        {
+            return Err(color_eyre::eyre::eyre!(
+                "The minimum efficiency must be in [0, 100] (got {min})"
+            ));
+        }
+        if !(self.opts.sample_fraction > 0.0 && self.opts.sample_fraction <= 1.0) {
+            return Err(color_eyre::eyre::eyre!(
+                "The sample fraction must be in (0, 1] (got {})",
+                self.opts.sample_fraction
+            ));
+        }
+
+        // Parse the local networks to benchmark side by side
+        let networks = parse_networks(&self.opts.networks)?;
+        if networks.len() > 1 && self.opts.network_type.to_lowercase() != "local" {
+            return Err(color_eyre::eyre::eyre!(
+                "Side by side networks are only supported for local benchmarks"
+            ));
+        }
+        if self.opts.profile_nodes && self.opts.network_type.to_lowercase() != "remote" {
+            return Err(color_eyre::eyre::eyre!(
+                "Node profiling is only supported for remote benchmarks"
+            ));
+        }
+        if self.opts.profile_nodes {
+            // Fail before any run if the instances cannot be profiled
+            let instances = self.create_remote_instances()?;
+            NodeProfiler::check_remote(&self.remote_ssh_manager(), &instances).await?;
+        }
+
+        // Signed runs follow their unsigned baseline
+        let formats = match self.opts.sign {
+            true => vec![TransactionFormat::Structured, TransactionFormat::Signed],
+            false => vec![self.opts.transaction_format],
+        };
+        let runs: Vec<(usize, Option<u64>, TransactionFormat)> = loads
+            .iter()
+            .flat_map(|load| proposal_limits.iter().map(move |limit| (*load, *limit)))
+            .flat_map(|(load, limit)| formats.iter().map(move |format| (load, limit, *format)))
+            .collect();
+
+        // Expose the live progress if requested
+        if let Some(port) = self.opts.progress_port {
+            self.progress.clone().serve(port).await?;
+        }
+
         // Create output directory
         let output_dir = PathBuf::from(&self.opts.output_dir);
         std::fs::create_dir_all(&output_dir)?;
@@ -164,7 +559,7 @@ impl BenchmarkRunner {
         // Run benchmarks for each load
         let mut all_results = Vec::new();
 
-        for (i, load) in loads.iter().enumerate() {
+        for (i, (load, max_transactions, format)) in runs.iter().enumerate() {
             // Check for shutdown signal before starting each benchmark
             if self.check_shutdown() {
                 warn!("Shutdown signal received, stopping benchmarks gracefully...");
@@ -178,13 +573,23 @@ impl BenchmarkRunner {
                 load
             );
 
-            let result = self.run_single_benchmark(*load).await?;
-            all_results.push((*load, result.clone()));
+            self.progress
+                .start_benchmark(*load, Duration::from_secs(self.opts.duration));
+            let mut results = self
+                .run_single_benchmark(*load, *max_transactions, *format, &networks)
+                .await?;
+            if format.is_signed() {
+                for result in &mut results {
+                    Self::compare_to_unsigned(result, &all_results);
+                }
+            }
 
-            // Check for shutdown signal before saving results
-            if self.check_shutdown() {
+            // Save results (even partial ones if a shutdown signal was received meanwhile)
+            let interrupted = self.check_shutdown();
+            if interrupted {
                 warn!("Shutdown signal received during benchmark, saving partial results...");
-                // Save the current result before shutdown
+            }
+            for result in results {
                 if self.opts.file_output {
                     self.save_benchmark_result(i + 1, *load, &result, &output_dir)
                         .await?;
@@ -192,23 +597,47 @@ impl BenchmarkRunner {
                 if self.opts.console_output {
                     self.print_benchmark_result(i + 1, *load, &result);
                 }
-                break;
+                all_results.push((*load, result));
             }
-
-            // Save results
-            if self.opts.file_output {
-                self.save_benchmark_result(i + 1, *load, &result, &output_dir)
-                    .await?;
-            }
-
-            if self.opts.console_output {
-                self.print_benchmark_result(i + 1, *load, &result);
+            if interrupted {
+                break;
             }
         }
 
         // Print summary
         if self.opts.console_output {
             self.print_benchmark_summary(&all_results);
+            if networks.len() > 1 {
+                let results: Vec<_> = all_results.iter().map(|(_, r)| r.clone()).collect();
+                println!("\nNETWORK COMPARISON:");
+                println!("{:-<80}", "");
+                print_network_comparison(&group_by_network(&results));
+            }
+        }
+
+        // Fail if the network could not keep up with the load
+        if let Some(min) = self.opts.min_efficiency {
+            let inefficient: Vec<_> = all_results
+                .iter()
+                .filter_map(|(load, result)| {
+                    let label = result.measurements.labels().next()?;
+                    let efficiency = efficiency(result.measurements.aggregate_tps(label), *load);
+                    (efficiency < min).then(|| {
+                        format!(
+                            "{} at {} tx/s: {:.1}%",
+                            result.network_name, load, efficiency
+                        )
+                    })
+                })
+                .collect();
+            if !inefficient.is_empty() {
+                return Err(color_eyre::eyre::eyre!(
+                    "{} runs fell below the minimum efficiency of {:.1}% ({})",
+                    inefficient.len(),
+                    min,
+                    inefficient.join(", ")
+                ));
+            }
         }
 
         info!("Benchmark completed successfully!");
@@ -238,13 +667,78 @@ impl BenchmarkRunner {
         Ok(())
     }
 
+    /// The rate schedule of the simulation, if any.
+    fn rate_schedule(&self) -> Result<Option<RateSchedule>> {
+        self.opts
+            .rate_schedule
+            .as_ref()
+            .map(|path| RateSchedule::from_file(path, self.opts.schedule_interpolation))
+            .transpose()
+    }
+
+    /// The benchmark type matching the transactions sent by the simulator.
+    fn benchmark_type(&self, format: TransactionFormat) -> MysticetiBenchmarkType {
+        MysticetiBenchmarkType::new(self.opts.transaction_size).with_transaction_format(format)
+    }
+
+    /// Add the throughput change of a signed run over its unsigned baseline (the run with the
+    /// same network, load and proposal limit) to the result metadata.
+    fn compare_to_unsigned(
+        result: &mut BenchmarkResult<MysticetiBenchmarkType>,
+        results: &[(usize, BenchmarkResult<MysticetiBenchmarkType>)],
+    ) {
+        let parameters = &result.parameters;
+        let baseline = results.iter().rev().find(|(_, baseline)| {
+            let format = baseline.parameters.benchmark_type.transaction_format();
+            !format.is_signed()
+                && baseline.network_name == result.network_name
+                && baseline.parameters.load == parameters.load
+                && baseline.parameters.max_transactions_per_proposal
+                    == parameters.max_transactions_per_proposal
+        });
+        let Some((_, baseline)) = baseline else {
+            return;
+        };
+        let label = "default".to_string();
+        let unsigned = baseline.measurements.aggregate_tps(&label);
+        let signed = result.measurements.aggregate_tps(&label);
+        result
+            .metadata
+            .insert("unsigned_throughput".into(), unsigned.to_string());
+        if unsigned > 0 {
+            let change = (signed as f64 - unsigned as f64) / unsigned as f64 * 100.0;
+            result.metadata.insert(
+                "signature_throughput_change_pct".into(),
+                format!("{change:.1}"),
+            );
+        }
+    }
+
+    /// Run the benchmark with the specified load. Local networks run side by side, each one
+    /// producing its own result.
     async fn run_single_benchmark(
         &self,
         load: usize,
-    ) -> Result<BenchmarkResult<MysticetiBenchmarkType>> {
+        max_transactions: Option<u64>,
+        format: TransactionFormat,
+        networks: &[NetworkSpec],
+    ) -> Result<Vec<BenchmarkResult<MysticetiBenchmarkType>>> {
         match self.opts.network_type.to_lowercase().as_str() {
-            "local" => self.run_local_network_benchmark(load).await,
-            "remote" => self.run_remote_network_benchmark(load).await,
+            "local" => {
+                let runs = networks.iter().map(|network| {
+                    self.run_local_network_benchmark(
+                        load,
+                        network.max_transactions.or(max_transactions),
+                        format,
+                        &network.namespace,
+                    )
+                });
+                futures::future::try_join_all(runs).await
+            }
+            "remote" => Ok(vec![
+                self.run_remote_network_benchmark(load, max_transactions, format)
+                    .await?,
+            ]),
             _ => Err(color_eyre::eyre::eyre!("Invalid network type")),
         }
     }
@@ -252,26 +746,82 @@ impl BenchmarkRunner {
     async fn run_local_network_benchmark(
         &self,
         load: usize,
+        max_transactions: Option<u64>,
+        format: TransactionFormat,
+        namespace: &NetworkNamespace,
     ) -> Result<BenchmarkResult<MysticetiBenchmarkType>> {
-        info!("Starting local network benchmark with load: {} tx/s", load);
+        info!(
+            "Starting local network '{}' benchmark with load: {} tx/s",
+            namespace.name(),
+            load
+        );
 
         // Create orchestrator for docker-compose based local network
-        let orchestrator =
-            LocalNetworkOrchestrator::new(PathBuf::from(&self.opts.docker_compose_path))?;
+        let mut orchestrator =
+            LocalNetworkOrchestrator::new(PathBuf::from(&self.opts.docker_compose_path))?
+                .with_namespace(namespace.clone())
+                .with_progress(self.progress.clone())
+                .with_readiness_interval(Duration::from_millis(self.opts.readiness_interval_ms))
+                .with_max_transactions_per_block(max_transactions)
+                .with_transaction_format(format)
+                .with_high_priority_ratio(self.opts.high_priority_ratio)
+                .with_client_batching(
+                    self.opts.client_batch_size,
+                    Duration::from_millis(self.opts.client_batch_window),
+                )
+                .with_pool_size(self.opts.pool_size)
+                .with_distribution(self.opts.distribution)
+                .with_logging(SubmissionLogging::from_flags(
+                    self.opts.quiet,
+                    self.opts.log_interval,
+                ));
+        if self.opts.verify_commits
+            || self.opts.dump_samples.is_some()
+            || self.opts.high_priority_ratio > 0.0
+        {
+            orchestrator = orchestrator.with_transaction_tracking();
+        }
+        if let Some(compression) = self.opts.compress {
+            orchestrator = orchestrator.with_compression(compression);
+        }
+        if let Some(schedule) = self.rate_schedule()? {
+            orchestrator = orchestrator.with_rate_schedule(schedule);
+        }
+        if let Some(fault) = &self.opts.unsafe_transaction_fault {
+            orchestrator = orchestrator.with_transaction_fault(fault.clone());
+        }
+        if self.opts.fail_fast {
+            orchestrator = orchestrator
+                .with_fail_fast(PathBuf::from(&self.opts.output_dir).join("crash-logs"));
+        }
+        if let Some(threshold) = self.opts.breaker_threshold {
+            let cooldown = Duration::from_millis(self.opts.breaker_cooldown_ms);
+            orchestrator = orchestrator.with_circuit_breaker(
+                CircuitBreaker::new(threshold, cooldown).with_window(self.opts.breaker_window),
+            );
+        }
+        if let Some(target) = self.opts.target_transactions_per_commit {
+            orchestrator = orchestrator.with_utilization_controller(
+                UtilizationController::new(target, self.opts.controller_max_load)
+                    .with_gains(self.opts.controller_kp, self.opts.controller_ki)
+                    .with_interval(Duration::from_millis(self.opts.controller_interval_ms)),
+            );
+        }
 
         // Verify docker-compose file exists
         orchestrator.verify_docker_compose()?;
 
         // Create benchmark parameters
         let parameters = BenchmarkParameters::new(
-            MysticetiBenchmarkType::default(),
+            self.benchmark_type(format),
             self.opts.committee,
             FaultsType::Permanent {
                 faults: self.opts.faults,
             },
             load,
             Duration::from_secs(self.opts.duration),
-        );
+        )
+        .with_max_transactions_per_proposal(max_transactions);
 
         // Start the network using docker-compose
         info!("Starting Mysticeti network with docker-compose...");
@@ -279,11 +829,15 @@ impl BenchmarkRunner {
 
         // Wait for network to be ready
         info!("Waiting for network to be ready...");
-        let node_urls = Some(vec![]);
         orchestrator
-            .wait_for_network_ready(self.opts.startup_wait, node_urls)
+            .wait_for_network_ready(self.opts.startup_wait, None)
             .await?;
 
+        // Check the clocks of the nodes are close enough to compare their timestamps
+        let clock_skews = orchestrator
+            .measure_clock_skew(Duration::from_millis(self.opts.max_clock_skew_ms))
+            .await;
+
         // Check network status
         orchestrator.get_network_status()?;
         info!("Network status: {:?}", ());
@@ -308,17 +862,111 @@ impl BenchmarkRunner {
 
         // Calculate total transactions to send
         let total_transactions = load * self.opts.duration as usize;
-        let transaction_size = self.opts.transaction_size;
+        // The payload size must match the size the results are labeled with.
+        let transaction_size = parameters.benchmark_type.transaction_size();
+        assert_eq!(
+            transaction_size, self.opts.transaction_size,
+            "Benchmark type and simulator disagree on the transaction size"
+        );
+
+        // Sample the storage of the nodes while the transactions are submitted
+        let storage_monitor = self.storage_interval().map(|interval| {
+            let containers = (0..self.opts.committee)
+                .map(|i| namespace.container_name(i))
+                .collect();
+            StorageMonitor::start_local(containers, interval)
+        });
+
+        // Measure the traffic between the nodes while the transactions are submitted
+        let bandwidth_meter = match self.opts.measure_bandwidth {
+            true => {
+                let containers = (0..self.opts.committee)
+                    .map(|i| namespace.container_name(i))
+                    .collect();
+                Some(BandwidthMeter::start_local(containers).await)
+            }
+            false => None,
+        };
 
         // Simulate transactions
-        orchestrator
+        let report = orchestrator
             .simulate_transactions(total_transactions, transaction_size, load)
             .await?;
+        let storage_series = match storage_monitor {
+            Some(monitor) => monitor.stop().await,
+            None => Vec::new(),
+        };
+        let bandwidth = match bandwidth_meter {
+            Some(meter) => Some(meter.stop().await),
+            None => None,
+        };
+        let breaker_events = report.breaker_events;
 
         let _benchmark_duration = start_time.elapsed();
 
+        // A node crashing at the very end of the run still invalidates the results
+        orchestrator.check_nodes_alive()?;
+
+        // Make sure no acknowledged transaction was lost
+        let verification = match self.opts.verify_commits {
+            true => Some(
+                orchestrator
+                    .verify_commits(
+                        &report.submitted_transactions,
+                        Duration::from_secs(self.opts.verify_timeout),
+                    )
+                    .await?,
+            ),
+            false => None,
+        };
+
+        // Dump the raw latency of the (sampled) transactions
+        let samples_path = match &self.opts.dump_samples {
+            Some(base) => {
+                let path =
+                    run_samples_path(Path::new(base), namespace.name(), load, max_transactions);
+                let mut writer = SampleWriter::create(&path, self.opts.sample_fraction)
+                    .wrap_err_with(|| format!("Failed to create {}", path.display()))?;
+                let samples = orchestrator
+                    .collect_latency_samples(
+                        &report.submitted_transactions,
+                        &mut writer,
+                        Duration::from_secs(self.opts.verify_timeout),
+                    )
+                    .await?;
+                writer.finish()?;
+                info!("Wrote {} latency samples to {}", samples, path.display());
+                Some(path)
+            }
+            None => None,
+        };
+
+        // Compare the latency of the priorities
+        let priority_latencies = match self.opts.high_priority_ratio > 0.0 {
+            true => Some(
+                orchestrator
+                    .priority_latencies(
+                        &report.submitted_transactions,
+                        Duration::from_secs(self.opts.verify_timeout),
+                    )
+                    .await,
+            ),
+            false => None,
+        };
+
         // Collect metrics from containers
-        orchestrator.collect_metrics().await?;
+        let node_metrics = orchestrator.collect_metrics().await?;
+
+        // Measure the recovery from a restart of the whole committee, once the steady-state
+        // metrics are collected since the restart resets them
+        let restart = match self.opts.measure_restart {
+            true => Some(
+                orchestrator
+                    .restart_network(Duration::from_secs(self.opts.startup_wait))
+                    .await?,
+            ),
+            false => None,
+        };
 
         // Create mock measurements collection for local network
         let settings = self.create_local_settings()?;
@@ -329,9 +977,156 @@ impl BenchmarkRunner {
         let (_, measurement) = Measurement::new_for_test();
 
         measurements.add(0, "default".to_string(), measurement);
+        if let Err(e) = sink::from_settings(&settings).publish(&measurements).await {
+            warn!("Failed to publish measurements: {e}");
+        }
 
         // Create benchmark result
-        let result = BenchmarkResult::new(NetworkType::Local, parameters, measurements);
+        let mut result = BenchmarkResult::new(NetworkType::Local, parameters, measurements)
+            .with_config(effective_config(&self.opts));
+        if *namespace != NetworkNamespace::default() {
+            result = result.with_network_name(namespace.name());
+        }
+        Self::summarize_commits(&mut result, &node_metrics);
+        Self::summarize_goodput(&mut result, &node_metrics);
+        if let Some(fault) = &self.opts.unsafe_transaction_fault {
+            Self::summarize_transaction_fault(&mut result, &node_metrics, fault);
+        }
+        result
+            .metadata
+            .extend(environment::to_metadata(&[environment::capture_local()]));
+        result.metadata.extend(skew_metadata(&clock_skews));
+        result
+            .metadata
+            .extend(storage::to_metadata(&storage_series));
+        if let Some(bandwidth) = &bandwidth {
+            let committed = Self::committed_transactions(&node_metrics);
+            result.metadata.extend(bandwidth.to_metadata(committed));
+        }
+        if let Some(path) = &samples_path {
+            result
+                .metadata
+                .insert("latency_samples".into(), path.display().to_string());
+        }
+        if let Some(latencies) = &priority_latencies {
+            result.metadata.insert(
+                "high_priority_ratio".into(),
+                self.opts.high_priority_ratio.to_string(),
+            );
+            for latency in latencies {
+                let priority = latency.priority;
+                result.metadata.insert(
+                    format!("latency_ms_mean_priority_{priority}"),
+                    format!("{:.1}", latency.mean_ms),
+                );
+                result.metadata.insert(
+                    format!("latency_ms_p50_priority_{priority}"),
+                    format!("{:.1}", latency.p50_ms),
+                );
+            }
+            result
+                .metadata
+                .insert("priority_latency".into(), serde_json::to_string(latencies)?);
+        }
+        if let Some(restart) = &restart {
+            match restart.first_commit_s() {
+                Some(first) => {
+                    result
+                        .metadata
+                        .insert("restart_recovery_s".into(), format!("{first:.2}"));
+                }
+                None => warn!("No node committed after the restart"),
+            }
+            if let Some(all) = restart.all_committed_s() {
+                result
+                    .metadata
+                    .insert("restart_recovery_max_s".into(), format!("{all:.2}"));
+            }
+        }
+        if self.opts.breaker_threshold.is_some() {
+            let openings = breaker_events
+                .iter()
+                .filter(|event| event.transition == BreakerTransition::Opened)
+                .count();
+            result
+                .metadata
+                .insert("circuit_breaker_openings".into(), openings.to_string());
+            result.metadata.insert(
+                "circuit_breaker_events".into(),
+                serde_json::to_string(&breaker_events)?,
+            );
+        }
+        if let Some(verification) = &verification {
+            result.metadata.insert(
+                "verified_acknowledged_transactions".into(),
+                verification.acknowledged.to_string(),
+            );
+            result.metadata.insert(
+                "verified_lost_transactions".into(),
+                verification.missing.len().to_string(),
+            );
+            result.metadata.insert(
+                "commit_verification".into(),
+                serde_json::to_string(verification)?,
+            );
+        }
+        if let Some(target) = self.opts.target_transactions_per_commit {
+            result
+                .metadata
+                .insert("target_transactions_per_commit".into(), target.to_string());
+            if let Some(last) = report.rate_adjustments.last() {
+                result
+                    .metadata
+                    .insert("controlled_rate_final".into(), last.rate.to_string());
+            }
+            result.metadata.insert(
+                "rate_adjustments".into(),
+                serde_json::to_string(&report.rate_adjustments)?,
+            );
+        }
+        if let Some(tracking) = &report.schedule_tracking {
+            result.metadata.insert(
+                "rate_schedule_mean_error".into(),
+                format!("{:.4}", tracking.mean_error()),
+            );
+            result.metadata.insert(
+                "rate_schedule_tracking".into(),
+                serde_json::to_string(&tracking.samples)?,
+            );
+        }
+
+        if self.opts.client_batch_size > 1 {
+            let metadata = [
+                ("client_batch_size", self.opts.client_batch_size.to_string()),
+                (
+                    "client_batch_window_ms",
+                    self.opts.client_batch_window.to_string(),
+                ),
+                (
+                    "client_transaction_rate",
+                    format!("{:.1}", report.transaction_rate),
+                ),
+                ("client_request_rate", format!("{:.1}", report.request_rate)),
+            ];
+            for (key, value) in metadata {
+                result.metadata.insert(key.into(), value);
+            }
+        }
+        if let (Some(compression), Some(stats)) = (self.opts.compress, &report.compression) {
+            let metadata = [
+                ("compression", compression.content_encoding().to_string()),
+                ("uncompressed_bytes", stats.uncompressed_bytes.to_string()),
+                ("compressed_bytes", stats.compressed_bytes.to_string()),
+                ("compression_ratio", format!("{:.2}", stats.ratio())),
+                (
+                    "compression_time_us_mean",
+                    format!("{:.1}", stats.mean_compression_time_us()),
+                ),
+            ];
+            for (key, value) in metadata {
+                result.metadata.insert(key.into(), value);
+            }
+        }
 
         // Cleanup if requested
         if self.opts.cleanup {
@@ -351,6 +1146,8 @@ impl BenchmarkRunner {
     async fn run_remote_network_benchmark(
         &self,
         load: usize,
+        max_transactions: Option<u64>,
+        format: TransactionFormat,
     ) -> Result<BenchmarkResult<MysticetiBenchmarkType>> {
         info!("Starting remote network benchmark with load: {} tx/s", load);
 
@@ -361,12 +1158,46 @@ impl BenchmarkRunner {
         let instances = self.create_remote_instances()?;
 
         // Create SSH connection manager
-        let ssh_manager =
-            SshConnectionManager::new("ubuntu".to_string(), PathBuf::from("~/.ssh/id_rsa"));
+        let ssh_manager = self.remote_ssh_manager();
 
         // Create protocol commands
         let protocol_commands = MysticetiProtocol::new(&settings);
 
+        // Capture the environment of the instances, for the record
+        let environments = environment::capture_remote(&ssh_manager, &instances).await;
+
+        // Sample the storage of the nodes over the run
+        let storage_monitor = self.storage_interval().map(|interval| {
+            StorageMonitor::start_remote(
+                ssh_manager.clone(),
+                instances.clone(),
+                protocol_commands.db_directories(),
+                interval,
+            )
+        });
+
+        // Measure the traffic between the nodes over the run
+        let bandwidth_meter = match self.opts.measure_bandwidth {
+            true => {
+                Some(BandwidthMeter::start_remote(ssh_manager.clone(), instances.clone()).await)
+            }
+            false => None,
+        };
+
+        // Profile the nodes over the run
+        let profiler = match self.opts.profile_nodes {
+            true => Some(
+                NodeProfiler::start_remote(
+                    ssh_manager.clone(),
+                    instances.clone(),
+                    Duration::from_secs(self.opts.duration),
+                    self.opts.profile_frequency,
+                )
+                .await,
+            ),
+            false => None,
+        };
+
         // Create orchestrator
         let orchestrator = Orchestrator::new(
             settings,
@@ -379,31 +1210,308 @@ impl BenchmarkRunner {
 
         // Create benchmark parameters
         let parameters = BenchmarkParameters::new(
-            MysticetiBenchmarkType::default(),
+            self.benchmark_type(format),
             self.opts.committee,
             FaultsType::Permanent {
                 faults: self.opts.faults,
             },
             load,
             Duration::from_secs(self.opts.duration),
-        );
+        )
+        .with_max_transactions_per_proposal(max_transactions);
 
         // Run the benchmark using orchestrator
-        let measurements = orchestrator.run(&parameters).await?;
+        let measurements = orchestrator.run(&parameters).await;
+        let storage_series = match storage_monitor {
+            Some(monitor) => monitor.stop().await,
+            None => Vec::new(),
+        };
+        let bandwidth = match bandwidth_meter {
+            Some(meter) => Some(meter.stop().await),
+            None => None,
+        };
+        let limit = match max_transactions {
+            Some(max) => format!("_{max}maxtx"),
+            None => String::new(),
+        };
+        let profiles_dir = PathBuf::from(&self.opts.output_dir)
+            .join("profiles")
+            .join(format!("{load}txs{limit}"));
+        let profiles = match profiler {
+            Some(profiler) => Some(profiler.stop(&profiles_dir).await),
+            None => None,
+        };
+        let measurements = measurements?;
+        // Every node commits every transaction, the best-scraped node tells how many.
+        let committed = measurements
+            .labels()
+            .map(|label| measurements.aggregate_tps(label))
+            .max()
+            .map(|tps| tps * measurements.benchmark_duration().as_secs());
 
         // Create benchmark result
-        let result = BenchmarkResult::new(NetworkType::Remote, parameters, measurements);
+        let mut result = BenchmarkResult::new(NetworkType::Remote, parameters, measurements)
+            .with_config(effective_config(&self.opts));
+        result
+            .metadata
+            .extend(environment::to_metadata(&environments));
+        result
+            .metadata
+            .extend(storage::to_metadata(&storage_series));
+        if let Some(bandwidth) = &bandwidth {
+            result.metadata.extend(bandwidth.to_metadata(committed));
+        }
+        if let Some(profiles) = &profiles {
+            result
+                .metadata
+                .insert("profiles_dir".into(), profiles_dir.display().to_string());
+            result.metadata.insert(
+                "profiled_nodes".into(),
+                format!("{}/{}", profiles.iter().flatten().count(), profiles.len()),
+            );
+        }
 
         Ok(result)
     }
 
+    /// The number of transactions committed by the most advanced node, from the sum of its
+    /// transactions-per-commit histogram.
+    fn committed_transactions(node_metrics: &[String]) -> Option<u64> {
+        node_metrics
+            .iter()
+            .map(|text| HistogramSummary::from_prometheus(text, TRANSACTIONS_PER_COMMIT))
+            .filter(|summary| summary.count > 0)
+            .map(|summary| summary.sum as u64)
+            .max()
+    }
+
+    /// Add the distribution of transactions and blocks per commit to the result metadata.
+    fn summarize_commits(
+        result: &mut BenchmarkResult<MysticetiBenchmarkType>,
+        node_metrics: &[String],
+    ) {
+        for (metric, key) in [
+            (TRANSACTIONS_PER_COMMIT, "transactions_per_commit"),
+            (BLOCKS_PER_COMMIT, "blocks_per_commit"),
+        ] {
+            let mut summary = HistogramSummary::default();
+            for text in node_metrics {
+                summary.merge(&HistogramSummary::from_prometheus(text, metric));
+            }
+            if summary.count == 0 {
+                continue;
+            }
+            result
+                .metadata
+                .insert(format!("{key}_mean"), format!("{:.2}", summary.mean()));
+            result
+                .metadata
+                .insert(format!("{key}_observations"), summary.count.to_string());
+        }
+
+        // Where the latency comes from: the wait in the submission queue (backpressure) and
+        // the time consensus takes to commit the proposed transactions.
+        for (metric, key) in [
+            (SUBMISSION_WAIT, "submission_wait_s"),
+            (PROPOSE_TO_COMMIT, "propose_to_commit_s"),
+        ] {
+            let mut summary = HistogramSummary::default();
+            for text in node_metrics {
+                summary.merge(&HistogramSummary::from_prometheus(text, metric));
+            }
+            if summary.count > 0 {
+                result
+                    .metadata
+                    .insert(format!("{key}_mean"), format!("{:.4}", summary.mean()));
+            }
+        }
+
+        // Every node observes the same commit sequence, so the most advanced node holds the
+        // most complete per-authority counts.
+        let mut by_authority = std::collections::BTreeMap::<String, f64>::new();
+        for text in node_metrics {
+            for (authority, count) in counter_by_label(text, TRANSACTIONS_BY_AUTHORITY, "authority")
+            {
+                let entry = by_authority.entry(authority).or_default();
+                *entry = entry.max(count);
+            }
+        }
+        for (authority, count) in by_authority {
+            result.metadata.insert(
+                format!("transactions_from_authority_{authority}"),
+                format!("{count:.0}"),
+            );
+        }
+
+        // How long every authority takes to propose and, as leader, to be committed: a slow
+        // authority stands out against the rest of the committee.
+        let mut proposers = Vec::new();
+        let mut committee = HistogramSummary::default();
+        for (metric, key) in [
+            (PROPOSAL_DELAY, "proposal_delay_ms"),
+            (LEADER_COMMIT_DELAY, "leader_commit_delay_ms"),
+        ] {
+            let mut by_authority = std::collections::BTreeMap::<String, HistogramSummary>::new();
+            for text in node_metrics {
+                for (authority, summary) in HistogramSummary::by_label(text, metric, "authority") {
+                    by_authority.entry(authority).or_default().merge(&summary);
+                }
+            }
+            for (authority, summary) in by_authority.into_iter().filter(|(_, s)| s.count > 0) {
+                let mean_ms = summary.mean() * 1000.0;
+                result.metadata.insert(
+                    format!("{key}_mean_authority_{authority}"),
+                    format!("{mean_ms:.1}"),
+                );
+                if metric == PROPOSAL_DELAY {
+                    committee.merge(&summary);
+                    proposers.push((authority, mean_ms));
+                }
+            }
+        }
+        let slowest_proposer = proposers.into_iter().max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((authority, mean_ms)) = slowest_proposer {
+            result.metadata.insert("slowest_proposer".into(), authority);
+            result
+                .metadata
+                .insert("slowest_proposer_delay_ms".into(), format!("{mean_ms:.1}"));
+            result.metadata.insert(
+                "proposal_delay_ms_mean".into(),
+                format!("{:.1}", committee.mean() * 1000.0),
+            );
+        }
+
+        // The bootstrap latency of the network: the first commit of the fastest node, and the
+        // time until every node committed. Nodes that did not commit yet report zero.
+        let bootstrap: Vec<f64> = node_metrics
+            .iter()
+            .filter_map(|text| gauge_value(text, TIME_TO_FIRST_COMMIT))
+            .filter(|seconds| *seconds > 0.0)
+            .collect();
+        if !bootstrap.is_empty() {
+            let first = bootstrap.iter().copied().fold(f64::INFINITY, f64::min);
+            let last = bootstrap.iter().copied().fold(0.0, f64::max);
+            result
+                .metadata
+                .insert("time_to_first_commit_s".into(), format!("{first:.2}"));
+            result
+                .metadata
+                .insert("time_to_first_commit_max_s".into(), format!("{last:.2}"));
+        }
+    }
+
+    /// Add the goodput of the run to the result metadata. The raw throughput counts every
+    /// committed transaction; the goodput only counts the ones committed for the first time and
+    /// accepted as valid, i.e. it excludes retried or replayed duplicates and the transactions
+    /// the committee rejected. Both rates are over the benchmark duration, from the counters of
+    /// the most advanced node.
+    fn summarize_goodput(
+        result: &mut BenchmarkResult<MysticetiBenchmarkType>,
+        node_metrics: &[String],
+    ) {
+        let Some(committed) = Self::committed_transactions(node_metrics) else {
+            return;
+        };
+        let most = |metric| {
+            node_metrics
+                .iter()
+                .filter_map(|text| counter_value(text, metric))
+                .fold(0.0, f64::max) as u64
+        };
+        let duplicates = most(DUPLICATE_TRANSACTIONS);
+        let rejected = most(REJECTED_TRANSACTIONS);
+        let goodput = committed.saturating_sub(duplicates + rejected);
+        let seconds = result.parameters.duration.as_secs_f64().max(1.0);
+
+        for (key, value) in [
+            ("committed_transactions", committed.to_string()),
+            ("duplicate_transactions", duplicates.to_string()),
+            ("rejected_transactions", rejected.to_string()),
+            ("goodput_transactions", goodput.to_string()),
+            (
+                "committed_throughput",
+                format!("{:.0}", committed as f64 / seconds),
+            ),
+            ("goodput", format!("{:.0}", goodput as f64 / seconds)),
+        ] {
+            result.metadata.insert(key.into(), value);
+        }
+        if committed > 0 {
+            result.metadata.insert(
+                "goodput_ratio_pct".into(),
+                format!("{:.1}", 100.0 * goodput as f64 / committed as f64),
+            );
+        }
+    }
+
+    /// Add the impact of the faulty node to the result metadata: how many transactions it
+    /// dropped or corrupted, and how many of its transactions were committed compared to the
+    /// other nodes. Relies on the per-authority counts added by `summarize_commits`.
+    fn summarize_transaction_fault(
+        result: &mut BenchmarkResult<MysticetiBenchmarkType>,
+        node_metrics: &[String],
+        fault: &TransactionFault,
+    ) {
+        result
+            .metadata
+            .insert("transaction_fault_node".into(), fault.node.to_string());
+        result
+            .metadata
+            .insert("transaction_fault".into(), fault.fault.clone());
+
+        // Only the faulty node exposes non-zero counts.
+        let mut affected = std::collections::BTreeMap::<String, f64>::new();
+        for text in node_metrics {
+            for (action, count) in counter_by_label(text, FAULTY_TRANSACTIONS, "action") {
+                *affected.entry(action).or_default() += count;
+            }
+        }
+        for (action, count) in affected {
+            result.metadata.insert(
+                format!("faulty_transactions_{action}"),
+                format!("{count:.0}"),
+            );
+        }
+
+        let committed = |authority: usize| {
+            result
+                .metadata
+                .get(&format!("transactions_from_authority_{authority}"))
+                .and_then(|count| count.parse::<f64>().ok())
+        };
+        let others: Vec<_> = (0..result.parameters.nodes)
+            .filter(|node| *node != fault.node)
+            .filter_map(committed)
+            .collect();
+        let mean = others.iter().sum::<f64>() / others.len().max(1) as f64;
+        if let Some(faulty) = committed(fault.node).filter(|_| mean > 0.0) {
+            result.metadata.insert(
+                "faulty_node_committed_ratio".into(),
+                format!("{:.3}", faulty / mean),
+            );
+        }
+    }
+
+    /// The sink receiving the measurements as they are collected.
+    fn measurement_sink(&self) -> Result<MeasurementSinkSettings> {
+        let Some(url) = &self.opts.influx_url else {
+            return Ok(MeasurementSinkSettings::File);
+        };
+        Ok(MeasurementSinkSettings::Influx {
+            url: url
+                .parse()
+                .wrap_err_with(|| format!("Invalid influx url {url}"))?,
+            token: std::env::var("MYSTICETI_INFLUX_TOKEN").ok(),
+        })
+    }
+
     fn create_local_settings(&self) -> Result<Settings> {
         // Create settings for local network using docker-compose
         let settings = Settings {
             testbed_id: "local-benchmark".to_string(),
             cloud_provider: CloudProvider::Aws,
-            token_file: PathBuf::from("~/.ssh/id_rsa"),
-            ssh_private_key_file: PathBuf::from("~/.ssh/id_rsa"),
+            token_file: expand_tilde("~/.ssh/id_rsa"),
+            ssh_private_key_file: expand_tilde("~/.ssh/id_rsa"),
             ssh_public_key_file: None,
             regions: vec!["local".to_string()],
             specs: "local".to_string(),
@@ -414,6 +1522,7 @@ impl BenchmarkRunner {
             working_dir: PathBuf::from("/tmp/mysticeti-benchmark"),
             results_dir: PathBuf::from(&self.opts.output_dir),
             logs_dir: PathBuf::from(&self.opts.output_dir).join("logs"),
+            measurement_sink: self.measurement_sink()?,
         };
 
         Ok(settings)
@@ -424,8 +1533,8 @@ impl BenchmarkRunner {
         let settings = Settings {
             testbed_id: "remote-benchmark".to_string(),
             cloud_provider: CloudProvider::Aws,
-            token_file: PathBuf::from("~/.ssh/id_rsa"),
-            ssh_private_key_file: PathBuf::from("~/.ssh/id_rsa"),
+            token_file: expand_tilde("~/.ssh/id_rsa"),
+            ssh_private_key_file: expand_tilde("~/.ssh/id_rsa"),
             ssh_public_key_file: None,
             regions: vec!["us-west-1".to_string()],
             specs: "t3.medium".to_string(),
@@ -436,18 +1545,25 @@ impl BenchmarkRunner {
             working_dir: PathBuf::from("/tmp/mysticeti-benchmark"),
             results_dir: PathBuf::from(&self.opts.output_dir),
             logs_dir: PathBuf::from(&self.opts.output_dir).join("logs"),
+            measurement_sink: self.measurement_sink()?,
         };
 
         Ok(settings)
     }
 
+    fn remote_ssh_manager(&self) -> SshConnectionManager {
+        SshConnectionManager::new("ubuntu".to_string(), expand_tilde("~/.ssh/id_rsa"))
+    }
+
     fn create_remote_instances(&self) -> Result<Vec<Instance>> {
         // Create instances from environment variables
         let mut instances = Vec::new();
 
         for i in 0..self.opts.committee {
-            let _host = std::env::var(format!("MYSTICETI_NODE{}_HOST", i))
+            let host = std::env::var(format!("MYSTICETI_NODE{}_HOST", i))
                 .map_err(|_| color_eyre::eyre::eyre!("MYSTICETI_NODE{}_HOST not set", i))?;
+            let main_ip =
+                resolve_ipv4(host.trim()).wrap_err(format!("Invalid MYSTICETI_NODE{}_HOST", i))?;
 
             let _ssh_port = std::env::var(format!("MYSTICETI_NODE{}_SSH_PORT", i))
                 .unwrap_or_else(|_| "22".to_string())
@@ -460,7 +1576,7 @@ impl BenchmarkRunner {
             let instance = Instance {
                 id: format!("remote-node-{}", i),
                 region: "us-west-1".to_string(),
-                main_ip: std::net::Ipv4Addr::new(127, 0, 0, 1), // This should be parsed from host
+                main_ip,
                 tags: vec!["remote".to_string()],
                 specs: "t3.medium".to_string(),
                 status: "running".to_string(),
@@ -478,22 +1594,43 @@ impl BenchmarkRunner {
         result: &BenchmarkResult<MysticetiBenchmarkType>,
         output_dir: &Path,
     ) -> Result<()> {
+        if self.opts.format == ResultsFormat::SuiCompatible {
+            let filepath = result
+                .measurements
+                .save_sui_compatible(output_dir, &"default".to_string())?;
+            info!(
+                "Saved sui-compatible measurements to: {}",
+                filepath.display()
+            );
+            return Ok(());
+        }
+
+        let limit = match result.parameters.max_transactions_per_proposal {
+            Some(max) => format!("_{max}maxtx"),
+            None => String::new(),
+        };
         let filename = format!(
-            "{}_benchmark_{}_{}txs.json",
-            self.opts.network_type, benchmark_num, load
+            "{}_benchmark_{}_{}txs{}.json",
+            result.network_name, benchmark_num, load, limit
         );
         let filepath = output_dir.join(filename);
 
         let json_data = serde_json::json!({
             "network_type": result.network_type,
+            "network": result.network_name,
             "benchmark_number": benchmark_num,
             "parameters": {
                 "nodes": self.opts.committee,
                 "load": load,
                 "duration": self.opts.duration,
                 "transaction_size": self.opts.transaction_size,
+                "transaction_format": result.parameters.benchmark_type.transaction_format(),
+                "pool_size": self.opts.pool_size,
+                "distribution": self.opts.distribution,
+                "max_transactions_per_proposal": result.parameters.max_transactions_per_proposal,
                 "faults": self.opts.faults,
                 "crash_recovery": self.opts.crash_recovery,
+                "unsafe_transaction_fault": self.opts.unsafe_transaction_fault,
                 "crash_interval": self.opts.crash_interval
             },
             "results": {
@@ -504,6 +1641,8 @@ impl BenchmarkRunner {
                 "successful_transactions": result.measurements.transaction_load(),
                 "failed_transactions": 0
             },
+            "metadata": result.metadata,
+            "config": result.config,
             "timestamp": chrono::Utc::now().to_rfc3339()
         });
 
@@ -523,7 +1662,11 @@ impl BenchmarkRunner {
         println!("BENCHMARK RESULT #{}", benchmark_num);
         println!("{}", "=".repeat(60));
         println!("Network Type: {:?}", result.network_type);
+        println!("Network: {}", result.network_name);
         println!("Input Load: {} tx/s", load);
+        if let Some(max) = result.parameters.max_transactions_per_proposal {
+            println!("Max Transactions per Proposal: {}", max);
+        }
         println!("Duration: {}s", result.parameters.duration.as_secs());
         println!();
         println!("RESULTS:");
@@ -536,9 +1679,144 @@ impl BenchmarkRunner {
             println!("  Throughput: {} tx/s", throughput);
             println!("  Average Latency: {:.2} ms", avg_latency.as_millis());
             println!("  Latency Std Dev: {:.2} ms", latency_std_dev.as_millis());
+            println!("  Efficiency: {:.1}%", efficiency(throughput, load));
+        }
+        if let Some(goodput) = result.metadata.get("goodput") {
+            let metadata = |key| result.metadata.get(key).map_or("?", String::as_str);
             println!(
-                "  Efficiency: {:.1}%",
-                (throughput as f64 / load as f64) * 100.0
+                "  Goodput: {} tx/s ({}% of the {} tx/s committed)",
+                goodput,
+                metadata("goodput_ratio_pct"),
+                metadata("committed_throughput")
+            );
+            println!(
+                "    goodput = unique, valid committed transactions per second \
+                 ({} duplicates and {} rejected excluded)",
+                metadata("duplicate_transactions"),
+                metadata("rejected_transactions")
+            );
+        }
+        if let Some(mean) = result.metadata.get("transactions_per_commit_mean") {
+            println!("  Transactions per Commit: {}", mean);
+        }
+        if let Some(mean) = result.metadata.get("blocks_per_commit_mean") {
+            println!("  Blocks per Commit: {}", mean);
+        }
+        if let Some(authority) = result.metadata.get("slowest_proposer") {
+            let metadata = |key| result.metadata.get(key).map_or("?", String::as_str);
+            println!(
+                "  Slowest Proposer: authority {} ({} ms mean proposal delay, committee: {} ms)",
+                authority,
+                metadata("slowest_proposer_delay_ms"),
+                metadata("proposal_delay_ms_mean")
+            );
+        }
+        if let Some(first) = result.metadata.get("time_to_first_commit_s") {
+            let last = result
+                .metadata
+                .get("time_to_first_commit_max_s")
+                .unwrap_or(first);
+            println!("  Time to First Commit: {}s (all nodes: {}s)", first, last);
+        }
+        if let Some(first) = result.metadata.get("restart_recovery_s") {
+            let all = result
+                .metadata
+                .get("restart_recovery_max_s")
+                .map_or("-", String::as_str);
+            println!("  Restart Recovery: {}s (all nodes: {}s)", first, all);
+        }
+        if let Some(openings) = result.metadata.get("circuit_breaker_openings") {
+            println!("  Circuit Breaker Openings: {}", openings);
+        }
+        if let Some(acknowledged) = result.metadata.get("verified_acknowledged_transactions") {
+            println!("  Verified Acknowledged Transactions: {}", acknowledged);
+        }
+        if let Some(lost) = result.metadata.get("verified_lost_transactions") {
+            println!("  Lost Transactions: {}", lost);
+        }
+        if let Some(rate) = result.metadata.get("client_transaction_rate") {
+            let requests = result.metadata.get("client_request_rate");
+            println!(
+                "  Client Rate: {} tx/s in {} requests/s",
+                rate,
+                requests.map_or("?", |requests| requests.as_str())
+            );
+        }
+        if let Some(ratio) = result.metadata.get("compression_ratio") {
+            let time = result.metadata.get("compression_time_us_mean");
+            println!(
+                "  Compression Ratio: {} ({} us per batch)",
+                ratio,
+                time.map_or("?", |time| time.as_str())
+            );
+        }
+        if let Some(change) = result.metadata.get("signature_throughput_change_pct") {
+            let unsigned = result.metadata.get("unsigned_throughput");
+            println!(
+                "  Throughput vs Unsigned: {}% (unsigned: {} tx/s)",
+                change,
+                unsigned.map_or("?", |unsigned| unsigned.as_str())
+            );
+        }
+        if let Some(growth) = result.metadata.get("db_growth_bytes_per_s_mean") {
+            let write = result.metadata.get("disk_write_peak_bytes_per_s");
+            println!(
+                "  DB Growth: {} B/s (peak disk writes: {} B/s)",
+                growth,
+                write.map_or("?", |write| write.as_str())
+            );
+        }
+        if let Some(rate) = result.metadata.get("network_bytes_per_s") {
+            let per_transaction = result.metadata.get("network_bytes_per_transaction");
+            println!(
+                "  Network: {} B/s between nodes ({} B per committed tx)",
+                rate,
+                per_transaction.map_or("?", |bytes| bytes.as_str())
+            );
+        }
+        if let Some(latencies) = result.metadata.get("priority_latency") {
+            let latencies: Vec<serde_json::Value> =
+                serde_json::from_str(latencies).unwrap_or_default();
+            let latencies: Vec<_> = latencies
+                .iter()
+                .map(|latency| {
+                    format!(
+                        "priority {}: {:.1} ms (p50 {:.1} ms)",
+                        latency["priority"],
+                        latency["mean_ms"].as_f64().unwrap_or_default(),
+                        latency["p50_ms"].as_f64().unwrap_or_default()
+                    )
+                })
+                .collect();
+            println!("  Priority Latency: {}", latencies.join(", "));
+        }
+        if let Some(wait) = result.metadata.get("submission_wait_s_mean") {
+            let consensus = result.metadata.get("propose_to_commit_s_mean");
+            println!(
+                "  Latency Breakdown: {} s waiting to be proposed, {} s from proposal to commit",
+                wait,
+                consensus.map_or("?", |consensus| consensus.as_str())
+            );
+        }
+        if let Some(target) = result.metadata.get("target_transactions_per_commit") {
+            println!("  Target Transactions per Commit: {}", target);
+        }
+        if let Some(rate) = result.metadata.get("controlled_rate_final") {
+            println!("  Final Controlled Rate: {} tx/s", rate);
+        }
+        let mut by_authority: Vec<_> = result
+            .metadata
+            .iter()
+            .filter_map(|(key, count)| {
+                key.strip_prefix("transactions_from_authority_")
+                    .map(|authority| (authority, count))
+            })
+            .collect();
+        by_authority.sort();
+        for (authority, count) in by_authority {
+            println!(
+                "  Committed Transactions from Authority {}: {}",
+                authority, count
             );
         }
 
@@ -556,14 +1834,21 @@ impl BenchmarkRunner {
         println!("Committee Size: {}", self.opts.committee);
         println!("Duration: {}s", self.opts.duration);
         println!("Transaction Size: {} bytes", self.opts.transaction_size);
+        println!("Transaction Format: {}", self.opts.transaction_format);
         println!();
 
         println!("RESULTS SUMMARY:");
         println!(
-            "{:<12} {:<12} {:<15} {:<15} {:<12}",
-            "Load (tx/s)", "Throughput", "Avg Latency", "Latency Std", "Efficiency"
+            "{:<12} {:<12} {:<12} {:<12} {:<15} {:<15} {:<12}",
+            "Network",
+            "Load (tx/s)",
+            "Max Tx/Prop",
+            "Throughput",
+            "Avg Latency",
+            "Latency Std",
+            "Efficiency"
         );
-        println!("{:-<80}", "");
+        println!("{:-<93}", "");
 
         for (load, result) in results {
             if let Some(label) = result.measurements.labels().next() {
@@ -571,15 +1856,18 @@ impl BenchmarkRunner {
                 let avg_latency = result.measurements.aggregate_average_latency(label);
                 let latency_std_dev = result.measurements.aggregate_stdev_latency(label);
 
-                let efficiency = if *load > 0 {
-                    (throughput as f64 / *load as f64) * 100.0
-                } else {
-                    0.0
-                };
+                let efficiency = efficiency(throughput, *load);
+
+                let max_transactions = result
+                    .parameters
+                    .max_transactions_per_proposal
+                    .map_or_else(|| "default".to_string(), |max| max.to_string());
 
                 println!(
-                    "{:<12} {:<12} {:<15.2} {:<15.2} {:<12.1}%",
+                    "{:<12} {:<12} {:<12} {:<12} {:<15.2} {:<15.2} {:<12.1}%",
+                    result.network_name,
                     load,
+                    max_transactions,
                     throughput,
                     avg_latency.as_millis(),
                     latency_std_dev.as_millis(),
@@ -588,20 +1876,35 @@ impl BenchmarkRunner {
             }
         }
 
-        println!("{:-<80}", "");
+        println!("{:-<93}", "");
         println!("Total benchmarks run: {}", results.len());
         println!("Output directory: {}", self.opts.output_dir);
         println!("{}", "=".repeat(80));
     }
 }
 
+/// The throughput achieved as a percentage of the input load.
+fn efficiency(throughput: u64, load: usize) -> f64 {
+    match load {
+        0 => 0.0,
+        load => throughput as f64 / load as f64 * 100.0,
+    }
+}
+
 /// Perform Docker cleanup on signal interruption
 async fn cleanup_docker_on_signal(opts: &Opts) {
     if opts.network_type.to_lowercase() == "local" {
         warn!("Performing Docker cleanup due to signal interruption...");
-        
-        // Try to create orchestrator and cleanup
-        if let Ok(orchestrator) = LocalNetworkOrchestrator::new(PathBuf::from(&opts.docker_compose_path)) {
+
+        // Try to create an orchestrator per network and cleanup
+        for network in parse_networks(&opts.networks).unwrap_or_default() {
+            let Ok(orchestrator) =
+                LocalNetworkOrchestrator::new(PathBuf::from(&opts.docker_compose_path))
+            else {
+                warn!("Could not create orchestrator for cleanup");
+                return;
+            };
+            let orchestrator = orchestrator.with_namespace(network.namespace);
             if opts.cleanup_thorough {
                 info!("Performing thorough cleanup of Docker containers and volumes...");
                 if let Err(e) = orchestrator.stop_network_thorough() {
@@ -619,8 +1922,6 @@ async fn cleanup_docker_on_signal(opts: &Opts) {
                     warn!("Failed to stop containers: {}", e);
                 }
             }
-        } else {
-            warn!("Could not create orchestrator for cleanup");
         }
     }
 }
@@ -650,6 +1951,51 @@ async fn setup_signal_handler(shutdown_signal: Arc<AtomicBool>, opts: Opts) {
     }
 }
 
+/// The fully-resolved configuration of a benchmark run. Values of environment variables
+/// that look like secrets are redacted.
+fn effective_config(opts: &Opts) -> serde_json::Value {
+    const SECRET_MARKERS: &[&str] = &["SECRET", "TOKEN", "PASSWORD", "PRIVATE"];
+
+    let environment: std::collections::BTreeMap<_, _> = std::env::vars()
+        .filter(|(name, _)| {
+            name.starts_with("MYSTICETI_") || name == "SSH_TIMEOUT" || name == "RUST_LOG"
+        })
+        .map(|(name, value)| {
+            if SECRET_MARKERS.iter().any(|marker| name.contains(marker)) {
+                (name, "<redacted>".to_string())
+            } else {
+                (name, value)
+            }
+        })
+        .collect();
+
+    let endpoints: Vec<String> = match opts.network_type.to_lowercase().as_str() {
+        "remote" => (0..opts.committee)
+            .map(
+                |i| match std::env::var(format!("MYSTICETI_NODE{}_HOST", i)) {
+                    Ok(host) => format!("http://{}:26657", host),
+                    Err(_) => format!("<MYSTICETI_NODE{}_HOST unset>", i),
+                },
+            )
+            .collect(),
+        _ => parse_networks(&opts.networks)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|network| network.namespace.rpc_urls())
+            .collect(),
+    };
+
+    serde_json::json!({
+        "options": opts,
+        "resolved": {
+            "node_endpoints": endpoints,
+            "ssh_private_key_file": expand_tilde("~/.ssh/id_rsa"),
+        },
+        "environment": environment,
+        "version": env!("CARGO_PKG_VERSION"),
+    })
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Nice colored error messages.
@@ -673,6 +2019,7 @@ async fn main() -> Result<()> {
     println!("  Output directory: {}", opts.output_dir);
     println!("  Console output: {}", opts.console_output);
     println!("  File output: {}", opts.file_output);
+    println!("  Results format: {:?}", opts.format);
     println!("  Committee size: {}", opts.committee);
     println!("  Faults: {}", opts.faults);
     println!("  Crash recovery: {}", opts.crash_recovery);
@@ -680,7 +2027,12 @@ async fn main() -> Result<()> {
     println!("  Duration: {}s", opts.duration);
     println!("  Network type: {}", opts.network_type);
     println!("  Transaction size: {} bytes", opts.transaction_size);
+    println!("  Transaction format: {}", opts.transaction_format);
+    println!("  Sign transactions: {}", opts.sign);
     println!("  Docker compose path: {}", opts.docker_compose_path);
+    if !opts.networks.is_empty() {
+        println!("  Networks: {}", opts.networks);
+    }
     println!("  Startup wait: {}s", opts.startup_wait);
     println!("  Cleanup: {}", opts.cleanup);
     println!();
@@ -705,6 +2057,14 @@ async fn main() -> Result<()> {
         println!();
     }
 
+    // Record the effective configuration
+    let effective_config = serde_json::to_string_pretty(&effective_config(&opts))?;
+    info!("Effective configuration:\n{}", effective_config);
+    if let Some(path) = &opts.dump_config {
+        std::fs::write(path, &effective_config)?;
+        info!("Saved effective configuration to: {}", path);
+    }
+
     let runner = BenchmarkRunner::new(opts.clone(), shutdown_signal.clone());
 
     // Set up signal handler task
@@ -757,3 +2117,21 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+
+    use super::resolve_ipv4;
+
+    #[test]
+    fn resolve_hosts_to_ipv4() {
+        assert_eq!(
+            resolve_ipv4("10.0.0.7").unwrap(),
+            Ipv4Addr::new(10, 0, 0, 7)
+        );
+        assert_eq!(resolve_ipv4("localhost").unwrap(), Ipv4Addr::LOCALHOST);
+        // The `.invalid` top-level domain is reserved to never resolve (RFC 6761).
+        assert!(resolve_ipv4("mysticeti-node.invalid").is_err());
+    }
+}
@@ -4,8 +4,13 @@
 use clap::Parser;
 use color_eyre::eyre::Result;
 use orchestrator::LocalNetworkOrchestrator;
+use orchestrator::orchestrator::{
+    ChaosSchedule, Interpolation, LoadDistribution, RateSchedule, SubmissionLogging,
+};
+use orchestrator::protocol::mysticeti::TransactionFormat;
 
 use std::path::PathBuf;
+use std::time::Duration;
 use tracing::info;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::{EnvFilter, fmt};
@@ -25,14 +30,45 @@ struct Args {
     #[clap(long, default_value = "512")]
     transaction_size: usize,
 
+    /// The layout of the transactions (raw or structured)
+    #[clap(long, default_value = "raw")]
+    transaction_format: TransactionFormat,
+
+    /// The number of distinct pre-encoded transactions to cycle through
+    #[clap(long, default_value = "1024")]
+    pool_size: usize,
+
+    /// How the load is spread across the nodes (equal or weighted by stake)
+    #[clap(long, default_value = "equal")]
+    distribution: LoadDistribution,
+
     /// Transaction rate (tx/s)
     #[clap(long, default_value = "100")]
     transaction_rate: usize,
 
-    /// Wait time for network startup in seconds
+    /// The maximum number of submission requests in flight. A single request at a time caps
+    /// the rate at one transaction per round trip
+    #[clap(long, default_value = "1")]
+    concurrency: usize,
+
+    /// Vary the submission rate according to this schedule file, with one
+    /// `time_offset_secs,target_rate` pair per line. The run lasts as long as it would at
+    /// `--transaction-rate`
+    #[clap(long, value_name = "PATH")]
+    rate_schedule: Option<PathBuf>,
+
+    /// How the rate evolves between the points of the schedule (step or linear)
+    #[clap(long, default_value = "step")]
+    schedule_interpolation: Interpolation,
+
+    /// Maximum time to wait for all nodes to become healthy in seconds
     #[clap(long, default_value = "30")]
     startup_wait: u64,
 
+    /// Delay between two polls of the health of the nodes in milliseconds
+    #[clap(long, default_value = "1000")]
+    readiness_interval_ms: u64,
+
     /// Whether to clean up containers after completion
     #[clap(long, default_value = "false")]
     cleanup: bool,
@@ -40,6 +76,43 @@ struct Args {
     /// Whether to perform thorough cleanup (remove volumes and containers completely)
     #[clap(long, default_value = "false")]
     cleanup_thorough: bool,
+
+    /// Only print the peer connectivity of the running nodes and exit
+    #[clap(long, default_value = "false")]
+    peers: bool,
+
+    /// Only print the most recent errors of the running nodes and exit
+    #[clap(long, default_value = "false")]
+    errors: bool,
+
+    /// Only log the summary of the transaction simulation, not individual submissions.
+    /// This is the default above 1000 tx/s unless `--log-interval` is set
+    #[clap(long, default_value = "false")]
+    quiet: bool,
+
+    /// The number of submitted transactions between two log lines (defaults to 100)
+    #[clap(long)]
+    log_interval: Option<usize>,
+
+    /// Soak the network under steady load at `--transaction-rate` while rotating faults
+    /// through the nodes, then report the throughput around every fault
+    #[clap(long, default_value = "false")]
+    chaos: bool,
+
+    /// The fault schedule of the chaos run, with one `time_offset_secs,action nodes` pair per
+    /// line (e.g. `10,crash 1` or `40,partition 2,3`), repeated until the end of the run.
+    /// Defaults to crashing then partitioning every node in turn
+    #[clap(long, value_name = "PATH")]
+    chaos_schedule: Option<PathBuf>,
+
+    /// The delay between two fault events of the default schedule, and between the last
+    /// event of a schedule file and its next cycle, in seconds
+    #[clap(long, default_value = "30")]
+    chaos_interval_secs: u64,
+
+    /// The length of the chaos run in seconds
+    #[clap(long, default_value = "600")]
+    chaos_duration_secs: u64,
 }
 
 #[tokio::main]
@@ -57,7 +130,38 @@ async fn main() -> Result<()> {
 
     info!("Starting Local Mysticeti Network Orchestrator");
 
-    let orchestrator = LocalNetworkOrchestrator::new(args.docker_compose_path.clone())?;
+    let mut orchestrator = LocalNetworkOrchestrator::new(args.docker_compose_path.clone())?
+        .with_readiness_interval(Duration::from_millis(args.readiness_interval_ms))
+        .with_transaction_format(args.transaction_format)
+        .with_pool_size(args.pool_size)
+        .with_distribution(args.distribution)
+        .with_concurrency(args.concurrency)
+        .with_logging(SubmissionLogging::from_flags(args.quiet, args.log_interval));
+    if let Some(path) = &args.rate_schedule {
+        let schedule = RateSchedule::from_file(path, args.schedule_interpolation)?;
+        orchestrator = orchestrator.with_rate_schedule(schedule);
+    }
+
+    // Probe the connectivity of an already running network
+    if args.peers {
+        let partitioned = orchestrator.probe_peers().await?;
+        if partitioned > 0 {
+            return Err(color_eyre::eyre::eyre!(
+                "{} node(s) are missing connections to the committee",
+                partitioned
+            ));
+        }
+        return Ok(());
+    }
+
+    // Triage the errors of an already running network
+    if args.errors {
+        let failing = orchestrator.probe_errors().await?;
+        if failing > 0 {
+            info!("{} node(s) recorded errors", failing);
+        }
+        return Ok(());
+    }
 
     // Verify docker-compose file exists
     orchestrator.verify_docker_compose()?;
@@ -76,14 +180,37 @@ async fn main() -> Result<()> {
         .wait_for_network_ready(args.startup_wait, node_urls)
         .await?;
 
-    // Simulate transactions
-    orchestrator
-        .simulate_transactions(
-            args.num_transactions,
-            args.transaction_size,
-            args.transaction_rate,
-        )
-        .await?;
+    if args.chaos {
+        // Soak the network under steady load while the schedule faults the nodes
+        let nodes = orchestrator.namespace().rpc_urls().len();
+        let interval = Duration::from_secs(args.chaos_interval_secs);
+        let schedule = match &args.chaos_schedule {
+            Some(path) => ChaosSchedule::from_file(path, nodes, interval)?,
+            None => ChaosSchedule::rotating(nodes, interval)?,
+        };
+        let duration = Duration::from_secs(args.chaos_duration_secs);
+        let num_transactions = args.transaction_rate * args.chaos_duration_secs as usize;
+        let (simulation, timeline) = tokio::join!(
+            orchestrator.simulate_transactions(
+                num_transactions,
+                args.transaction_size,
+                args.transaction_rate,
+            ),
+            orchestrator.run_chaos(&schedule, duration)
+        );
+        let timeline = timeline?;
+        simulation?;
+        info!("Chaos timeline:\n{}", timeline);
+    } else {
+        // Simulate transactions
+        orchestrator
+            .simulate_transactions(
+                args.num_transactions,
+                args.transaction_size,
+                args.transaction_rate,
+            )
+            .await?;
+    }
 
     // Cleanup if requested
     if args.cleanup {
@@ -207,6 +334,8 @@ mod tests {
             "1024",
             "--transaction-rate",
             "200",
+            "--concurrency",
+            "16",
             "--startup-wait",
             "45",
             "--cleanup",
@@ -220,6 +349,7 @@ mod tests {
         assert_eq!(parsed.num_transactions, 500);
         assert_eq!(parsed.transaction_size, 1024);
         assert_eq!(parsed.transaction_rate, 200);
+        assert_eq!(parsed.concurrency, 16);
         assert_eq!(parsed.startup_wait, 45);
         assert_eq!(parsed.cleanup, true);
     }
@@ -236,8 +366,10 @@ mod tests {
         assert_eq!(parsed.num_transactions, 1000);
         assert_eq!(parsed.transaction_size, 512);
         assert_eq!(parsed.transaction_rate, 100);
+        assert_eq!(parsed.concurrency, 1);
         assert_eq!(parsed.startup_wait, 30);
         assert_eq!(parsed.cleanup, false);
+        assert_eq!(parsed.peers, false);
     }
 
     #[test]
@@ -4,7 +4,11 @@
 use clap::Parser;
 use color_eyre::eyre::Result;
 use orchestrator::RemoteNetworkOrchestrator;
+use orchestrator::orchestrator::{LoadDistribution, SubmissionLogging};
+use orchestrator::protocol::mysticeti::TransactionFormat;
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
 use tracing::info;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::{EnvFilter, fmt};
@@ -20,14 +24,39 @@ struct Args {
     #[clap(long, default_value = "512")]
     transaction_size: usize,
 
+    /// The layout of the transactions (raw or structured)
+    #[clap(long, default_value = "raw")]
+    transaction_format: TransactionFormat,
+
+    /// The number of distinct pre-encoded transactions to cycle through
+    #[clap(long, default_value = "1024")]
+    pool_size: usize,
+
+    /// How the load is spread across the nodes (equal or weighted by stake)
+    #[clap(long, default_value = "equal")]
+    distribution: LoadDistribution,
+
     /// Transaction rate (tx/s)
     #[clap(long, default_value = "100")]
     transaction_rate: usize,
 
-    /// Wait time for network startup in seconds
+    /// The maximum number of submission requests in flight. A single request at a time caps
+    /// the rate at one transaction per round trip
+    #[clap(long, default_value = "1")]
+    concurrency: usize,
+
+    /// Maximum time to wait for all nodes to become healthy in seconds
     #[clap(long, default_value = "60")]
     startup_wait: u64,
 
+    /// Delay between two polls of the health of the nodes in milliseconds
+    #[clap(long, default_value = "1000")]
+    readiness_interval_ms: u64,
+
+    /// Warn if the clock of a node is skewed by more than this many milliseconds
+    #[clap(long, default_value = "10")]
+    max_clock_skew_ms: u64,
+
     /// SSH timeout in seconds
     #[clap(long, default_value = "30")]
     ssh_timeout: u64,
@@ -35,6 +64,23 @@ struct Args {
     /// Whether to clean up containers after completion
     #[clap(long, default_value = "false")]
     cleanup: bool,
+
+    /// Upload and run this locally-built validator binary instead of pulling the image
+    #[clap(long)]
+    local_binary: Option<PathBuf>,
+
+    /// Delay between starting two nodes in milliseconds (zero starts them back to back)
+    #[clap(long, default_value = "0")]
+    startup_delay_ms: u64,
+
+    /// Only log the summary of the transaction simulation, not individual submissions.
+    /// This is the default above 1000 tx/s unless `--log-interval` is set
+    #[clap(long, default_value = "false")]
+    quiet: bool,
+
+    /// The number of submitted transactions between two log lines (defaults to 100)
+    #[clap(long)]
+    log_interval: Option<usize>,
 }
 
 #[tokio::main]
@@ -69,19 +115,63 @@ async fn main() -> Result<()> {
         }
     }
 
-    let orchestrator = RemoteNetworkOrchestrator::new()?;
-
-    // Setup Docker on all nodes
-    orchestrator.setup_all_nodes().await?;
+    let mut orchestrator = RemoteNetworkOrchestrator::new()?
+        .with_readiness_interval(Duration::from_millis(args.readiness_interval_ms))
+        .with_transaction_format(args.transaction_format)
+        .with_pool_size(args.pool_size)
+        .with_distribution(args.distribution)
+        .with_concurrency(args.concurrency)
+        .with_startup_delay(Duration::from_millis(args.startup_delay_ms))
+        .with_logging(SubmissionLogging::from_flags(args.quiet, args.log_interval));
+    if let Some(binary) = args.local_binary {
+        if !binary.is_file() {
+            return Err(color_eyre::eyre::eyre!(
+                "Local binary {} does not exist",
+                binary.display()
+            ));
+        }
+        info!("Using local binary {}", binary.display());
+        orchestrator = orchestrator.with_local_binary(binary);
+    } else {
+        // Setup Docker on all nodes
+        orchestrator.setup_all_nodes().await?;
+    }
 
     // Start containers on all nodes
-    orchestrator.start_all_containers().await?;
+    let launch_offsets = orchestrator.start_all_containers().await?;
 
     // Wait for network to be ready
     orchestrator
         .wait_for_network_ready(args.startup_wait)
         .await?;
 
+    // Latencies measured across machines are only meaningful if their clocks agree
+    orchestrator
+        .measure_clock_skew(Duration::from_millis(args.max_clock_skew_ms))
+        .await;
+
+    // Report how long the network took to commit after startup
+    let startup = orchestrator
+        .wait_for_first_commits(&launch_offsets, Duration::from_secs(args.startup_wait))
+        .await;
+    let describe = |seconds: Option<f64>| match seconds {
+        Some(seconds) => format!("{seconds:.2}s"),
+        None => "no commit yet".to_string(),
+    };
+    info!(
+        "Startup delay {}ms: first commit after {}, all nodes committed after {}",
+        startup.startup_delay_ms,
+        describe(startup.first_commit_s()),
+        describe(startup.all_committed_s())
+    );
+    for (i, seconds) in startup.time_to_first_commit_s.iter().enumerate() {
+        info!(
+            "  Node {}: first commit {} after its start",
+            i,
+            describe(*seconds)
+        );
+    }
+
     // Simulate transactions
     orchestrator
         .simulate_transactions(
@@ -106,7 +196,6 @@ async fn main() -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
     use tokio::time::Duration;
 
     // Mock the RemoteNode for testing
@@ -274,6 +363,8 @@ mod tests {
             "1024",
             "--transaction-rate",
             "200",
+            "--concurrency",
+            "16",
             "--startup-wait",
             "90",
             "--ssh-timeout",
@@ -285,9 +376,25 @@ mod tests {
         assert_eq!(parsed.num_transactions, 500);
         assert_eq!(parsed.transaction_size, 1024);
         assert_eq!(parsed.transaction_rate, 200);
+        assert_eq!(parsed.concurrency, 16);
         assert_eq!(parsed.startup_wait, 90);
         assert_eq!(parsed.ssh_timeout, 60);
         assert_eq!(parsed.cleanup, true);
+        assert_eq!(parsed.local_binary, None);
+    }
+
+    #[test]
+    fn test_args_local_binary() {
+        let args = vec![
+            "remote-network",
+            "--local-binary",
+            "target/release/validator",
+        ];
+        let parsed: Args = clap::Parser::try_parse_from(args).unwrap();
+        assert_eq!(
+            parsed.local_binary,
+            Some(PathBuf::from("target/release/validator"))
+        );
     }
 
     #[test]
@@ -0,0 +1,126 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeMap, process::Command};
+
+use tracing::warn;
+
+use crate::{
+    client::Instance,
+    ssh::{CommandContext, SshConnectionManager},
+};
+
+/// The prefix of the environment keys in the metadata of the benchmark results.
+const METADATA_PREFIX: &str = "env_";
+
+/// A shell script printing the runtime environment of a machine as `key=value` lines. It only
+/// relies on tools available on any Linux machine; missing values are printed empty.
+pub const ENVIRONMENT_SCRIPT: &str = r#"
+echo "os=$( (. /etc/os-release && echo "$PRETTY_NAME") 2>/dev/null || uname -s)"
+echo "kernel=$(uname -r)"
+echo "arch=$(uname -m)"
+echo "docker_version=$(docker version --format '{{.Server.Version}}' 2>/dev/null)"
+echo "cpu_model=$(grep -m1 'model name' /proc/cpuinfo 2>/dev/null | cut -d: -f2- | sed 's/^ *//')"
+echo "cpu_count=$(nproc 2>/dev/null)"
+echo "memory_kb=$(awk '/MemTotal/ {print $2}' /proc/meminfo 2>/dev/null)"
+"#;
+
+/// The runtime environment of a machine (OS, kernel, docker version, CPU and memory), keyed
+/// by the names printed by `ENVIRONMENT_SCRIPT`.
+pub type Environment = BTreeMap<String, String>;
+
+/// Parse the output of `ENVIRONMENT_SCRIPT`, skipping the values that could not be collected.
+pub fn parse_environment(output: &str) -> Environment {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+        .collect()
+}
+
+/// Capture the environment of the machine running the orchestrator (and thus the local
+/// networks). Returns an empty environment if the script cannot run.
+pub fn capture_local() -> Environment {
+    match Command::new("sh").args(["-c", ENVIRONMENT_SCRIPT]).output() {
+        Ok(output) => parse_environment(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => {
+            warn!("Failed to capture the local environment: {e}");
+            Environment::new()
+        }
+    }
+}
+
+/// Capture the environment of the specified instances over ssh, adding their specs. Returns
+/// one environment per instance (empty if the instance could not be reached).
+pub async fn capture_remote(
+    ssh_manager: &SshConnectionManager,
+    instances: &[Instance],
+) -> Vec<Environment> {
+    let outputs = ssh_manager
+        .execute(
+            instances.to_vec(),
+            ENVIRONMENT_SCRIPT,
+            CommandContext::default(),
+        )
+        .await;
+    let outputs = match outputs {
+        Ok(outputs) => outputs,
+        Err(e) => {
+            warn!("Failed to capture the environment of the instances: {e}");
+            vec![(String::new(), String::new()); instances.len()]
+        }
+    };
+
+    instances
+        .iter()
+        .zip(outputs)
+        .map(|(instance, (stdout, _))| {
+            let mut environment = parse_environment(&stdout);
+            environment.insert("instance_specs".into(), instance.specs.clone());
+            environment.insert("instance_region".into(), instance.region.clone());
+            environment
+        })
+        .collect()
+}
+
+/// Merge the environments of several machines into benchmark metadata. Values shared by all
+/// machines are stored once; the others list the value of every machine, in order.
+pub fn to_metadata(environments: &[Environment]) -> BTreeMap<String, String> {
+    let mut keys: Vec<_> = environments.iter().flat_map(|env| env.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .map(|key| {
+            let mut values: Vec<_> = environments
+                .iter()
+                .map(|env| env.get(key).map(String::as_str).unwrap_or("unknown"))
+                .collect();
+            if values.windows(2).all(|pair| pair[0] == pair[1]) {
+                values.truncate(1);
+            }
+            (format!("{METADATA_PREFIX}{key}"), values.join(" | "))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_environment, to_metadata};
+
+    #[test]
+    fn environment_metadata() {
+        let first = parse_environment(
+            "os=Ubuntu 22.04.3 LTS\nkernel=6.2.0-1017-aws\ndocker_version=\ncpu_count=8\n",
+        );
+        assert_eq!(first.len(), 3);
+        assert!(!first.contains_key("docker_version"));
+
+        let second = parse_environment("os=Ubuntu 22.04.3 LTS\nkernel=6.5.0-1014-aws\n");
+        let metadata = to_metadata(&[first, second]);
+        assert_eq!(metadata["env_os"], "Ubuntu 22.04.3 LTS");
+        assert_eq!(metadata["env_kernel"], "6.2.0-1017-aws | 6.5.0-1014-aws");
+        assert_eq!(metadata["env_cpu_count"], "8 | unknown");
+    }
+}
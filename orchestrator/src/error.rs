@@ -83,6 +83,17 @@ pub enum MonitorError {
     GrafanaError(String),
 }
 
+pub type SinkResult<T> = Result<T, SinkError>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SinkError {
+    #[error("Failed to save measurements to '{file:?}': {message}")]
+    FileError { file: String, message: String },
+
+    #[error("Failed to push measurements to {url}: {message}")]
+    HttpError { url: Url, message: String },
+}
+
 pub type TestbedResult<T> = Result<T, TestbedError>;
 
 #[derive(thiserror::Error, Debug)]
@@ -3,6 +3,7 @@
 
 use std::{
     fmt::{Debug, Display},
+    str::FromStr,
     time::Duration,
 };
 
@@ -159,13 +160,59 @@ impl CrashRecoverySchedule {
     }
 }
 
+/// A Byzantine-lite fault: a node silently dropping or corrupting a fraction of the
+/// transactions it receives, written `NODE:FAULT` (e.g. `1:drop=0.2,corrupt=0.1`). The fault
+/// itself is passed as is to the node, which validates it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TransactionFault {
+    /// The index of the faulty node.
+    pub node: usize,
+    /// The fault applied by the node.
+    pub fault: String,
+}
+
+impl FromStr for TransactionFault {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (node, fault) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Expected NODE:FAULT, got '{s}'"))?;
+        let node = node
+            .parse()
+            .map_err(|e| format!("Invalid node index '{node}': {e}"))?;
+        if fault.is_empty() {
+            return Err("The fault cannot be empty".into());
+        }
+        Ok(Self {
+            node,
+            fault: fault.to_string(),
+        })
+    }
+}
+
+impl Display for TransactionFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "node {}: {}", self.node, self.fault)
+    }
+}
+
 #[cfg(test)]
 mod faults_tests {
     use std::time::Duration;
 
     use crate::client::Instance;
 
-    use super::{CrashRecoverySchedule, FaultsType};
+    use super::{CrashRecoverySchedule, FaultsType, TransactionFault};
+
+    #[test]
+    fn parse_transaction_fault() {
+        let fault: TransactionFault = "1:drop=0.2,corrupt=0.1".parse().unwrap();
+        assert_eq!(fault.node, 1);
+        assert_eq!(fault.fault, "drop=0.2,corrupt=0.1");
+        assert!("drop=0.2".parse::<TransactionFault>().is_err());
+        assert!("x:drop=0.2".parse::<TransactionFault>().is_err());
+    }
 
     #[test]
     fn crash_recovery_1_fault() {
@@ -1,18 +1,26 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod bandwidth;
 pub mod benchmark;
 pub mod client;
 pub mod display;
+pub mod environment;
 pub mod error;
 pub mod faults;
 pub mod logs;
 pub mod measurement;
+pub mod metrics_diff;
 mod monitor;
 pub mod orchestrator;
+pub mod profiling;
+pub mod progress;
 pub mod protocol;
+pub mod retention;
 pub mod settings;
+pub mod sink;
 pub mod ssh;
+pub mod storage;
 pub mod testbed;
 
 pub use orchestrator::{LocalNetworkOrchestrator, Orchestrator, RemoteNetworkOrchestrator};
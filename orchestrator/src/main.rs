@@ -6,7 +6,7 @@ use std::{str::FromStr, time::Duration};
 use benchmark::{BenchmarkParametersGenerator, LoadType};
 use clap::Parser;
 use client::{ServerProviderClient, aws::AwsClient, vultr::VultrClient};
-use eyre::{Context, Result};
+use eyre::{Context, Result, eyre};
 use faults::FaultsType;
 use measurement::MeasurementsCollection;
 use orchestrator::Orchestrator;
@@ -24,9 +24,11 @@ pub mod error;
 pub mod faults;
 pub mod logs;
 pub mod measurement;
+pub mod metrics_diff;
 mod monitor;
 pub mod orchestrator;
 pub mod protocol;
+pub mod retention;
 pub mod settings;
 pub mod ssh;
 pub mod testbed;
@@ -63,7 +65,8 @@ pub enum Operation {
 
     /// Run a benchmark on the specified testbed.
     Benchmark {
-        /// Transaction size in bytes.
+        /// Transaction size in bytes, optionally followed by the transaction format (e.g.
+        /// `512:structured`).
         #[clap(long, default_value = "512", global = true)]
         benchmark_type: String,
 
@@ -87,6 +90,10 @@ pub enum Operation {
         #[clap(long, value_parser = parse_duration, default_value = "600", global = true)]
         duration: Duration,
 
+        /// The maximum number of transactions per proposal (defaults to the protocol limit).
+        #[clap(long, value_name = "INT", global = true)]
+        max_transactions_per_proposal: Option<u64>,
+
         /// The interval between measurements collection in seconds.
         #[clap(long, value_parser = parse_duration, default_value = "15", global = true)]
         scrape_interval: Duration,
@@ -131,6 +138,56 @@ pub enum Operation {
         #[clap(long, value_name = "FILE")]
         path: String,
     },
+
+    /// Inspect the metrics exposed by the nodes.
+    Metrics {
+        #[clap(subcommand)]
+        action: MetricsAction,
+    },
+
+    /// Manage the benchmark results saved in the results directory.
+    Results {
+        #[clap(subcommand)]
+        action: ResultsAction,
+    },
+}
+
+#[derive(Parser)]
+#[clap(rename_all = "kebab-case")]
+pub enum MetricsAction {
+    /// Print the change of every counter and histogram between two snapshots of the metrics.
+    Diff {
+        /// The first snapshot: the url of a metrics endpoint or the path to a saved snapshot.
+        before: String,
+
+        /// The second snapshot: the url of a metrics endpoint or the path to a saved snapshot.
+        after: String,
+
+        /// The time elapsed between the two snapshots (in seconds), to compute rates.
+        #[clap(long, value_name = "FLOAT")]
+        interval: Option<f64>,
+    },
+}
+
+#[derive(Parser)]
+#[clap(rename_all = "kebab-case")]
+pub enum ResultsAction {
+    /// Remove the old run directories of the results directory. Runs are kept if they are among
+    /// the most recent (`--keep-last`), younger than a maximum age (`--older-than`), or pinned
+    /// with a `KEEP` file.
+    Prune {
+        /// The number of most recent runs to keep.
+        #[clap(long, value_name = "INT")]
+        keep_last: Option<usize>,
+
+        /// Only remove the runs older than this (e.g. `30d`, `12h`).
+        #[clap(long, value_name = "AGE", value_parser = retention::parse_age)]
+        older_than: Option<Duration>,
+
+        /// Only print the runs that would be removed.
+        #[clap(long, action, default_value = "false")]
+        dry_run: bool,
+    },
 }
 
 #[derive(Parser)]
@@ -189,6 +246,22 @@ pub enum Load {
         #[clap(long, value_name = "INT", default_value = "5")]
         max_iterations: usize,
     },
+
+    /// Search for the maximum load that keeps a latency percentile within an SLO.
+    LatencySlo {
+        /// The initial load (in tx/s) to test.
+        #[clap(long, value_name = "INT", default_value = "250")]
+        starting_load: usize,
+        /// The maximum number of iterations before converging on the maximum load.
+        #[clap(long, value_name = "INT", default_value = "8")]
+        max_iterations: usize,
+        /// The latency percentile bounded by the SLO (e.g. 99 for p99).
+        #[clap(long, value_name = "PERCENT", default_value = "99")]
+        percentile: f64,
+        /// The maximum latency (in milliseconds) at that percentile.
+        #[clap(long, value_name = "MS")]
+        max_latency_ms: u64,
+    },
 }
 
 fn parse_duration(arg: &str) -> Result<Duration, std::num::ParseIntError> {
@@ -209,9 +282,19 @@ async fn main() -> Result<()> {
 
     let opts: Opts = Opts::parse();
 
+    // Inspecting metrics does not require a testbed.
+    if let Operation::Metrics { action } = opts.operation {
+        return run_metrics(action).await;
+    }
+
     // Load the settings files.
     let settings = Settings::load(&opts.settings_path).wrap_err("Failed to load settings")?;
 
+    // Managing the results does not require a testbed either.
+    if let Operation::Results { action } = opts.operation {
+        return run_results(&settings, action);
+    }
+
     match &settings.cloud_provider {
         CloudProvider::Aws => {
             // Create the client for the cloud provider.
@@ -274,6 +357,7 @@ async fn run<C: ServerProviderClient>(settings: Settings, client: C, opts: Opts)
             crash_recovery,
             crash_interval,
             duration,
+            max_transactions_per_proposal,
             scrape_interval,
             skip_testbed_update,
             skip_testbed_configuration,
@@ -299,7 +383,7 @@ async fn run<C: ServerProviderClient>(settings: Settings, client: C, opts: Opts)
                 .wrap_err("Failed to load testbed setup commands")?;
 
             let protocol_commands = Protocol::new(&settings);
-            let sui_benchmark_type = BenchmarkType::from_str(&benchmark_type)?;
+            let sui_benchmark_type = BenchmarkType::from_str(&benchmark_type).map_err(|e| eyre!(e))?;
 
             let load = match load_type {
                 Load::FixedLoad { loads } => {
@@ -313,6 +397,24 @@ async fn run<C: ServerProviderClient>(settings: Settings, client: C, opts: Opts)
                     starting_load,
                     max_iterations,
                 },
+                Load::LatencySlo {
+                    starting_load,
+                    max_iterations,
+                    percentile,
+                    max_latency_ms,
+                } => {
+                    if !(percentile > 0.0 && percentile <= 100.0) {
+                        return Err(eyre!(
+                            "The percentile must be in (0, 100] (got {percentile})"
+                        ));
+                    }
+                    LoadType::LatencySlo {
+                        starting_load,
+                        max_iterations,
+                        percentile: percentile / 100.0,
+                        max_latency: Duration::from_millis(max_latency_ms),
+                    }
+                }
             };
 
             let fault_type = if !crash_recovery || faults == 0 {
@@ -327,7 +429,8 @@ async fn run<C: ServerProviderClient>(settings: Settings, client: C, opts: Opts)
             let generator = BenchmarkParametersGenerator::new(committee, load)
                 .with_benchmark_type(sui_benchmark_type)
                 .with_custom_duration(duration)
-                .with_faults(fault_type);
+                .with_faults(fault_type)
+                .with_max_transactions_per_proposal(max_transactions_per_proposal);
 
             Orchestrator::new(
                 settings,
@@ -352,6 +455,57 @@ async fn run<C: ServerProviderClient>(settings: Settings, client: C, opts: Opts)
         Operation::Summarize { path } => {
             MeasurementsCollection::<BenchmarkType>::load(path)?.display_summary()
         }
+
+        Operation::Metrics { .. } => unreachable!("Metrics operations do not need a testbed"),
+        Operation::Results { .. } => unreachable!("Results operations do not need a testbed"),
+    }
+    Ok(())
+}
+
+fn run_results(settings: &Settings, action: ResultsAction) -> Result<()> {
+    match action {
+        // Remove the runs falling outside the retention policy.
+        ResultsAction::Prune {
+            keep_last,
+            older_than,
+            dry_run,
+        } => {
+            let policy = retention::RetentionPolicy {
+                keep_last,
+                older_than,
+            };
+            let report = retention::prune(&settings.results_dir, &policy, dry_run)?;
+            let verb = match dry_run {
+                true => "Would remove",
+                false => "Removed",
+            };
+            for run in &report.pruned {
+                println!("{verb} {}", run.path.display());
+            }
+            println!(
+                "{verb} {} runs ({} MiB), kept {}",
+                report.pruned.len(),
+                report.freed_bytes() >> 20,
+                report.kept
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn run_metrics(action: MetricsAction) -> Result<()> {
+    match action {
+        // Print the change of the metrics between two snapshots.
+        MetricsAction::Diff {
+            before,
+            after,
+            interval,
+        } => {
+            let before = metrics_diff::load_snapshot(&before).await?;
+            let after = metrics_diff::load_snapshot(&after).await?;
+            let deltas = metrics_diff::diff_snapshots(&before, &after)?;
+            metrics_diff::display_diff(&deltas, interval.map(Duration::from_secs_f64));
+        }
     }
     Ok(())
 }
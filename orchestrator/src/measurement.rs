@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     io::BufRead,
     path::{Path, PathBuf},
@@ -141,6 +141,31 @@ impl Measurement {
         Duration::from_secs_f64(stdev)
     }
 
+    /// Compute the latency under which the specified fraction (e.g. 0.99 for p99) of the
+    /// transactions finalized, from the latency buckets. This is the upper bound of the first
+    /// bucket reaching the fraction, so it overestimates the exact percentile by at most the
+    /// bucket width; it is `Duration::MAX` if only the unbounded bucket reaches it.
+    pub fn latency_percentile(&self, fraction: f64) -> Duration {
+        let mut buckets: Vec<_> = self
+            .buckets
+            .iter()
+            .filter_map(|(id, count)| id.parse::<f64>().ok().map(|bound| (bound, *count)))
+            .collect();
+        buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+        // The buckets are cumulative, the last one counts every transaction.
+        let total = buckets.last().map(|(_, count)| *count).unwrap_or_default();
+        if total == 0 {
+            return Duration::ZERO;
+        }
+        let target = (fraction * total as f64).ceil() as usize;
+        buckets
+            .iter()
+            .find(|(_, count)| *count >= target)
+            .filter(|(bound, _)| bound.is_finite())
+            .map(|(bound, _)| Duration::from_secs_f64(*bound))
+            .unwrap_or(Duration::MAX)
+    }
+
     pub fn new_for_test() -> (Label, Self) {
         (
             "owned".to_string(),
@@ -155,9 +180,188 @@ impl Measurement {
     }
 }
 
+/// The count and sum of a prometheus histogram, aggregated over all its labels.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+pub struct HistogramSummary {
+    /// Number of observations.
+    pub count: u64,
+    /// Sum of all observations.
+    pub sum: f64,
+}
+
+impl HistogramSummary {
+    /// Summarize the histogram `metric` from the text exposed by prometheus.
+    pub fn from_prometheus(text: &str, metric: &str) -> Self {
+        let br = std::io::BufReader::new(text.as_bytes());
+        let parsed = Scrape::parse(br.lines()).unwrap();
+
+        let count_metric = format!("{metric}_count");
+        let sum_metric = format!("{metric}_sum");
+        let mut summary = Self::default();
+        for sample in &parsed.samples {
+            let value = match sample.value {
+                prometheus_parse::Value::Untyped(value)
+                | prometheus_parse::Value::Counter(value)
+                | prometheus_parse::Value::Gauge(value) => value,
+                _ => continue,
+            };
+            if sample.metric == count_metric {
+                summary.count += value as u64;
+            } else if sample.metric == sum_metric {
+                summary.sum += value;
+            }
+        }
+        summary
+    }
+
+    /// Summarize the histogram `metric` from the text exposed by prometheus, separately for
+    /// every value of its `label` label.
+    pub fn by_label(text: &str, metric: &str, label: &str) -> BTreeMap<String, Self> {
+        let br = std::io::BufReader::new(text.as_bytes());
+        let parsed = Scrape::parse(br.lines()).unwrap();
+
+        let count_metric = format!("{metric}_count");
+        let sum_metric = format!("{metric}_sum");
+        let mut summaries = BTreeMap::<String, Self>::new();
+        for sample in &parsed.samples {
+            let value = match sample.value {
+                prometheus_parse::Value::Untyped(value)
+                | prometheus_parse::Value::Counter(value)
+                | prometheus_parse::Value::Gauge(value) => value,
+                _ => continue,
+            };
+            let Some(key) = sample.labels.get(label) else {
+                continue;
+            };
+            if sample.metric == count_metric {
+                summaries.entry(key.to_string()).or_default().count += value as u64;
+            } else if sample.metric == sum_metric {
+                summaries.entry(key.to_string()).or_default().sum += value;
+            }
+        }
+        summaries
+    }
+
+    /// Combine the observations of two summaries.
+    pub fn merge(&mut self, other: &Self) {
+        self.count += other.count;
+        self.sum += other.sum;
+    }
+
+    /// The average observation.
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+}
+
+/// The values of the counter `metric` from the text exposed by prometheus, keyed by the
+/// value of its `label` label.
+pub fn counter_by_label(text: &str, metric: &str, label: &str) -> BTreeMap<String, f64> {
+    let br = std::io::BufReader::new(text.as_bytes());
+    let parsed = Scrape::parse(br.lines()).unwrap();
+
+    let mut values = BTreeMap::new();
+    for sample in parsed
+        .samples
+        .iter()
+        .filter(|sample| sample.metric == metric)
+    {
+        let value = match sample.value {
+            prometheus_parse::Value::Untyped(value) | prometheus_parse::Value::Counter(value) => {
+                value
+            }
+            _ => continue,
+        };
+        if let Some(key) = sample.labels.get(label) {
+            *values.entry(key.to_string()).or_default() += value;
+        }
+    }
+    values
+}
+
+/// The value of the counter `metric` from the text exposed by prometheus, summed over its
+/// labels. Returns `None` if the counter is not exposed.
+pub fn counter_value(text: &str, metric: &str) -> Option<f64> {
+    let br = std::io::BufReader::new(text.as_bytes());
+    let parsed = Scrape::parse(br.lines()).unwrap();
+
+    parsed
+        .samples
+        .iter()
+        .filter(|sample| sample.metric == metric)
+        .filter_map(|sample| match sample.value {
+            prometheus_parse::Value::Untyped(value) | prometheus_parse::Value::Counter(value) => {
+                Some(value)
+            }
+            _ => None,
+        })
+        .reduce(|a, b| a + b)
+}
+
+/// The value of the gauge `metric` from the text exposed by prometheus, summed over its
+/// labels. Returns `None` if the gauge is not exposed.
+pub fn gauge_value(text: &str, metric: &str) -> Option<f64> {
+    let br = std::io::BufReader::new(text.as_bytes());
+    let parsed = Scrape::parse(br.lines()).unwrap();
+
+    parsed
+        .samples
+        .iter()
+        .filter(|sample| sample.metric == metric)
+        .filter_map(|sample| match sample.value {
+            prometheus_parse::Value::Untyped(value) | prometheus_parse::Value::Gauge(value) => {
+                Some(value)
+            }
+            _ => None,
+        })
+        .reduce(|a, b| a + b)
+}
+
 /// The identifier of the scrapers collecting the prometheus metrics.
 type ScraperId = usize;
 
+/// The layout of the benchmark result files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResultsFormat {
+    /// The results, parameters, and metadata of the run.
+    #[default]
+    Default,
+    /// The measurements, laid out like the `MeasurementsCollection` of the Sui (narwhal)
+    /// orchestrator.
+    SuiCompatible,
+}
+
+impl std::str::FromStr for ResultsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Self::Default),
+            "sui-compatible" => Ok(Self::SuiCompatible),
+            _ => Err(format!(
+                "Unknown format '{s}' (expected default or sui-compatible)"
+            )),
+        }
+    }
+}
+
+/// The name of the measurement (in the InfluxDB line protocol) holding the benchmark results.
+const MEASUREMENT_NAME: &str = "mysticeti_benchmark";
+
+/// Escape a tag value of the InfluxDB line protocol.
+fn escape_tag(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct MeasurementsCollection<T: crate::benchmark::BenchmarkType> {
     /// The machine / instance type.
@@ -260,6 +464,17 @@ impl<T: crate::benchmark::BenchmarkType> MeasurementsCollection<T> {
             .unwrap_or_default()
     }
 
+    /// Aggregate a latency percentile of multiple data points by merging their latency buckets.
+    pub fn aggregate_latency_percentile(&self, label: &Label, fraction: f64) -> Duration {
+        let mut merged = Measurement::default();
+        for measurement in self.all_measurements(label).iter().filter_map(|x| x.last()) {
+            for (bucket, count) in &measurement.buckets {
+                *merged.buckets.entry(bucket.clone()).or_default() += count;
+            }
+        }
+        merged.latency_percentile(fraction)
+    }
+
     /// Aggregate the stdev latency of multiple data points by taking the max.
     pub fn aggregate_stdev_latency(&self, label: &Label) -> Duration {
         self.all_measurements(label)
@@ -278,6 +493,86 @@ impl<T: crate::benchmark::BenchmarkType> MeasurementsCollection<T> {
         fs::write(file, json).unwrap();
     }
 
+    /// Encode the latest measurement of every label and scraper in the InfluxDB line protocol,
+    /// tagged with the benchmark parameters. The points are stamped with the specified
+    /// wall-clock time (in nanoseconds since the unix epoch).
+    pub fn to_line_protocol(&self, timestamp_ns: u128) -> String {
+        let parameters = format!(
+            "benchmark_type={},nodes={},faults={},load={}",
+            escape_tag(&self.parameters.benchmark_type.to_string()),
+            self.parameters.nodes,
+            escape_tag(&self.parameters.faults.to_string()),
+            self.parameters.load,
+        );
+
+        let mut lines = Vec::new();
+        for (label, scrapers) in &self.data {
+            for (scraper_id, measurements) in scrapers {
+                let Some(measurement) = measurements.last() else {
+                    continue;
+                };
+                lines.push(format!(
+                    "{MEASUREMENT_NAME},{parameters},label={},scraper={scraper_id} \
+                    elapsed_s={},count={}i,tps={}i,avg_latency_ms={},stdev_latency_ms={} \
+                    {timestamp_ns}",
+                    escape_tag(label),
+                    measurement.timestamp.as_secs_f64(),
+                    measurement.count,
+                    measurement.tps(&measurement.timestamp),
+                    measurement.average_latency().as_secs_f64() * 1000.0,
+                    measurement.stdev_latency().as_secs_f64() * 1000.0,
+                ));
+            }
+        }
+        lines.sort();
+        lines.join("\n")
+    }
+
+    /// Serialize the measurements of the specified label in the layout of the
+    /// `MeasurementsCollection` of the Sui (narwhal) orchestrator, so that its tooling and
+    /// dashboards can compare them against upstream consensus benchmarks. That layout predates
+    /// labels (the measurements are keyed by scraper only) and knows none of the parameters we
+    /// added on top of its own.
+    pub fn to_sui_compatible(&self, label: &Label) -> serde_json::Value {
+        let scrapers: BTreeMap<_, _> = self
+            .data
+            .get(label)
+            .map(|scrapers| scrapers.iter().collect())
+            .unwrap_or_default();
+        serde_json::json!({
+            "machine_specs": self.machine_specs,
+            "commit": self.commit,
+            "parameters": {
+                "benchmark_type": self.parameters.benchmark_type,
+                "nodes": self.parameters.nodes,
+                "faults": self.parameters.faults,
+                "load": self.parameters.load,
+                "duration": self.parameters.duration,
+            },
+            "scrapers": scrapers,
+        })
+    }
+
+    /// Save the measurements of the specified label as a json file named and laid out like the
+    /// ones of the Sui (narwhal) orchestrator. Returns the path of the file.
+    pub fn save_sui_compatible<P: AsRef<Path>>(
+        &self,
+        path: P,
+        label: &Label,
+    ) -> std::io::Result<PathBuf> {
+        let json = serde_json::to_string_pretty(&self.to_sui_compatible(label))
+            .expect("Cannot serialize metrics");
+        let file = path.as_ref().join(format!(
+            "measurements-{:?}-{:?}-{}-{}.json",
+            self.parameters.benchmark_type,
+            self.parameters.faults,
+            self.parameters.nodes,
+            self.parameters.load
+        ));
+        fs::write(&file, json)?;
+        Ok(file)
+    }
+
     /// Display a summary of the measurements.
     pub fn display_summary(&self) {
         let mut table = Table::new();
@@ -322,7 +617,114 @@ mod test {
         settings::Settings,
     };
 
-    use super::{BenchmarkParameters, Measurement, MeasurementsCollection};
+    use super::{
+        BenchmarkParameters, HistogramSummary, Measurement, MeasurementsCollection,
+        counter_by_label, counter_value, gauge_value,
+    };
+
+    #[test]
+    fn histogram_summary() {
+        let report = r#"
+            # HELP committed_transactions_per_commit Number of transactions in each committed sub-dag
+            # TYPE committed_transactions_per_commit histogram
+            committed_transactions_per_commit_bucket{le="1"} 2
+            committed_transactions_per_commit_bucket{le="8"} 3
+            committed_transactions_per_commit_bucket{le="+Inf"} 4
+            committed_transactions_per_commit_sum 30
+            committed_transactions_per_commit_count 4
+        "#;
+
+        let mut summary =
+            HistogramSummary::from_prometheus(report, "committed_transactions_per_commit");
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.mean(), 7.5);
+
+        summary.merge(&HistogramSummary { count: 1, sum: 0.0 });
+        assert_eq!(summary.mean(), 6.0);
+        assert_eq!(HistogramSummary::default().mean(), 0.0);
+
+        let report = r#"
+            # TYPE block_proposal_delay_seconds histogram
+            block_proposal_delay_seconds_bucket{authority="0",le="+Inf"} 2
+            block_proposal_delay_seconds_sum{authority="0"} 0.5
+            block_proposal_delay_seconds_count{authority="0"} 2
+            block_proposal_delay_seconds_bucket{authority="3",le="+Inf"} 1
+            block_proposal_delay_seconds_sum{authority="3"} 1.5
+            block_proposal_delay_seconds_count{authority="3"} 1
+        "#;
+        let summaries =
+            HistogramSummary::by_label(report, "block_proposal_delay_seconds", "authority");
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries["0"].mean(), 0.25);
+        assert_eq!(summaries["3"].mean(), 1.5);
+    }
+
+    #[test]
+    fn counter_values_by_label() {
+        let report = r#"
+            # HELP committed_transactions_by_authority Number of committed transactions, by the authority that proposed them
+            # TYPE committed_transactions_by_authority counter
+            committed_transactions_by_authority{authority="0"} 12
+            committed_transactions_by_authority{authority="1"} 30
+            submission_queue_depth 4
+        "#;
+
+        let values = counter_by_label(report, "committed_transactions_by_authority", "authority");
+        assert_eq!(values.len(), 2);
+        assert_eq!(values["0"], 12.0);
+        assert_eq!(values["1"], 30.0);
+        assert_eq!(
+            counter_value(report, "committed_transactions_by_authority"),
+            Some(42.0)
+        );
+        assert_eq!(counter_value(report, "missing_counter"), None);
+    }
+
+    #[test]
+    fn line_protocol() {
+        let settings = Settings::new_for_test();
+        let parameters = BenchmarkParameters::<TestBenchmarkType>::default();
+        let mut aggregator = MeasurementsCollection::new(&settings, parameters);
+        let (label, measurement) = Measurement::new_for_test();
+        aggregator.add(1, label.clone(), Measurement::default());
+        aggregator.add(1, label, measurement);
+        aggregator.add(2, "a,b".into(), Measurement::default());
+
+        let lines = aggregator.to_line_protocol(42);
+        let lines: Vec<_> = lines.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "mysticeti_benchmark,benchmark_type=TestBenchmarkType,nodes=4,faults=no\\ faults,\
+            load=500,label=a\\,b,scraper=2 \
+            elapsed_s=0,count=0i,tps=0i,avg_latency_ms=0,stdev_latency_ms=0 42"
+        );
+        assert!(lines[1].contains(",label=owned,scraper=1 elapsed_s=30,count=1860i,tps=62i,"));
+        assert!(lines[1].ends_with(" 42"));
+    }
+
+    #[test]
+    fn sui_compatible_layout() {
+        let settings = Settings::new_for_test();
+        let parameters = BenchmarkParameters::<TestBenchmarkType> {
+            max_transactions_per_proposal: Some(100),
+            ..Default::default()
+        };
+        let mut aggregator = MeasurementsCollection::new(&settings, parameters);
+        let (label, measurement) = Measurement::new_for_test();
+        aggregator.add(1, label.clone(), measurement);
+        aggregator.add(2, "other".into(), Measurement::default());
+
+        let json = aggregator.to_sui_compatible(&label);
+        assert_eq!(json["parameters"]["nodes"], 4);
+        let parameters = json["parameters"].as_object().unwrap();
+        assert!(!parameters.contains_key("max_transactions_per_proposal"));
+        assert!(json.get("data").is_none());
+        let scrapers = json["scrapers"].as_object().unwrap();
+        assert_eq!(scrapers.len(), 1);
+        assert_eq!(scrapers["1"][0]["count"], 1860);
+        assert_eq!(scrapers["1"][0]["timestamp"]["secs"], 30);
+    }
 
     #[test]
     fn average_latency() {
@@ -337,6 +739,30 @@ mod test {
         assert_eq!(data.average_latency(), Duration::from_millis(20));
     }
 
+    #[test]
+    fn latency_percentile() {
+        let buckets = [("0.1", 50), ("0.5", 90), ("1", 99), ("inf", 100)];
+        let data = Measurement {
+            timestamp: Duration::from_secs(10),
+            buckets: buckets
+                .into_iter()
+                .map(|(bucket, count)| (bucket.to_string(), count))
+                .collect(),
+            sum: Duration::from_secs(20),
+            count: 100,
+            squared_sum: Duration::from_secs(0),
+        };
+
+        assert_eq!(data.latency_percentile(0.5), Duration::from_millis(100));
+        assert_eq!(data.latency_percentile(0.9), Duration::from_millis(500));
+        assert_eq!(data.latency_percentile(0.99), Duration::from_secs(1));
+        assert_eq!(data.latency_percentile(1.0), Duration::MAX);
+        assert_eq!(
+            Measurement::default().latency_percentile(0.99),
+            Duration::ZERO
+        );
+    }
+
     #[test]
     fn stdev_latency() {
         let data = Measurement {
@@ -465,4 +891,16 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn parse_gauge() {
+        let text = "\
+# TYPE time_to_first_commit_seconds gauge
+time_to_first_commit_seconds 2.5
+# TYPE submission_queue_depth gauge
+submission_queue_depth 3
+";
+        assert_eq!(gauge_value(text, "time_to_first_commit_seconds"), Some(2.5));
+        assert_eq!(gauge_value(text, "missing_gauge"), None);
+    }
 }
@@ -0,0 +1,216 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeMap, fs, io::BufRead, time::Duration};
+
+use eyre::{Context, Result, eyre};
+use prettytable::{Table, row};
+use prometheus_parse::Scrape;
+
+/// The change of a counter (or of the count and sum of a histogram) between two snapshots of
+/// the metrics exposed by a node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesDelta {
+    /// The name of the series, including its labels (e.g. `requests_total{route="/tx"}`).
+    pub series: String,
+    /// The value in the first snapshot (zero if the series was not exposed yet).
+    pub before: f64,
+    /// The value in the second snapshot (zero if the series is no longer exposed).
+    pub after: f64,
+}
+
+impl SeriesDelta {
+    /// The absolute change of the series.
+    pub fn delta(&self) -> f64 {
+        self.after - self.before
+    }
+
+    /// The change relative to the first snapshot. Series that were zero (or missing) in the
+    /// first snapshot and then increased have an infinite relative change.
+    pub fn relative_change(&self) -> f64 {
+        match (self.before, self.delta()) {
+            (_, delta) if delta == 0.0 => 0.0,
+            (before, delta) if before == 0.0 => delta.signum() * f64::INFINITY,
+            (before, delta) => delta / before.abs(),
+        }
+    }
+
+    /// The rate of change of the series over the interval between the two snapshots.
+    pub fn rate(&self, interval: Duration) -> Option<f64> {
+        let seconds = interval.as_secs_f64();
+        (seconds > 0.0).then(|| self.delta() / seconds)
+    }
+}
+
+/// Load a snapshot of the metrics exposed by a node, either by scraping it (if the source is
+/// an http url) or from a file.
+pub async fn load_snapshot(source: &str) -> Result<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let response = reqwest::get(source)
+            .await
+            .wrap_err_with(|| format!("Failed to scrape {source}"))?
+            .error_for_status()
+            .wrap_err_with(|| format!("Failed to scrape {source}"))?;
+        response
+            .text()
+            .await
+            .wrap_err_with(|| format!("Failed to read the metrics of {source}"))
+    } else {
+        fs::read_to_string(source).wrap_err_with(|| format!("Failed to read {source}"))
+    }
+}
+
+/// The counters and the histograms' count and sum of a snapshot, keyed by series.
+fn counters(text: &str) -> Result<BTreeMap<String, f64>> {
+    let br = std::io::BufReader::new(text.as_bytes());
+    let parsed =
+        Scrape::parse(br.lines()).map_err(|e| eyre!("Failed to parse the metrics: {e}"))?;
+
+    let mut values = BTreeMap::new();
+    for sample in &parsed.samples {
+        // Histograms expose their count and sum as separate (untyped) samples; the buckets
+        // and gauges are not cumulative so their delta is meaningless.
+        let value = match sample.value {
+            prometheus_parse::Value::Counter(value) | prometheus_parse::Value::Untyped(value) => {
+                value
+            }
+            _ => continue,
+        };
+
+        let mut labels: Vec<_> = sample
+            .labels
+            .iter()
+            .map(|(key, value)| format!("{key}=\"{value}\""))
+            .collect();
+        labels.sort();
+        let series = match labels.is_empty() {
+            true => sample.metric.clone(),
+            false => format!("{}{{{}}}", sample.metric, labels.join(",")),
+        };
+        *values.entry(series).or_default() += value;
+    }
+    Ok(values)
+}
+
+/// Compute the change of every counter and histogram between the two snapshots, sorted by
+/// decreasing relative change.
+pub fn diff_snapshots(before: &str, after: &str) -> Result<Vec<SeriesDelta>> {
+    let mut before = counters(before)?;
+    let after = counters(after)?;
+
+    let mut deltas: Vec<_> = after
+        .into_iter()
+        .map(|(series, after)| SeriesDelta {
+            before: before.remove(&series).unwrap_or_default(),
+            series,
+            after,
+        })
+        .collect();
+    // Series that are no longer exposed (e.g. after a restart).
+    deltas.extend(before.into_iter().map(|(series, before)| SeriesDelta {
+        series,
+        before,
+        after: 0.0,
+    }));
+
+    deltas.sort_by(|a, b| {
+        b.relative_change()
+            .abs()
+            .total_cmp(&a.relative_change().abs())
+            .then_with(|| b.delta().abs().total_cmp(&a.delta().abs()))
+            .then_with(|| a.series.cmp(&b.series))
+    });
+    Ok(deltas)
+}
+
+/// Print the changes as a table, with their rate if the interval between the two snapshots
+/// is known. Unchanged series are omitted.
+pub fn display_diff(deltas: &[SeriesDelta], interval: Option<Duration>) {
+    let mut table = Table::new();
+    table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    table.set_titles(row![bH2->"Metrics diff"]);
+    match interval {
+        Some(_) => table.add_row(
+            row![b->"Series", b->"Before", b->"After", b->"Delta", b->"Change", b->"Rate (/s)"],
+        ),
+        None => table.add_row(row![b->"Series", b->"Before", b->"After", b->"Delta", b->"Change"]),
+    };
+
+    let changed: Vec<_> = deltas.iter().filter(|d| d.delta() != 0.0).collect();
+    for delta in &changed {
+        let change = match delta.relative_change() {
+            change if change.is_infinite() => "new".to_string(),
+            change => format!("{:+.1}%", change * 100.0),
+        };
+        let mut row = row![
+            delta.series,
+            delta.before,
+            delta.after,
+            format!("{:+}", delta.delta()),
+            change
+        ];
+        if let Some(rate) = interval.and_then(|interval| delta.rate(interval)) {
+            row.add_cell(prettytable::Cell::new(&format!("{rate:.2}")));
+        }
+        table.add_row(row);
+    }
+
+    println!();
+    table.printstd();
+    println!("{} of {} series changed", changed.len(), deltas.len());
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::diff_snapshots;
+
+    #[test]
+    fn diff_counters_and_histograms() {
+        let before = "\
+# TYPE requests_total counter
+requests_total{route=\"/tx\"} 100
+requests_total{route=\"/stats\"} 10
+# TYPE latency_s histogram
+latency_s_bucket{le=\"1\"} 4
+latency_s_bucket{le=\"+Inf\"} 5
+latency_s_sum 2.5
+latency_s_count 5
+# TYPE queue_depth gauge
+queue_depth 3
+";
+        let after = "\
+# TYPE requests_total counter
+requests_total{route=\"/tx\"} 150
+requests_total{route=\"/stats\"} 10
+requests_total{route=\"/broadcast_tx_batch\"} 7
+# TYPE latency_s histogram
+latency_s_bucket{le=\"1\"} 8
+latency_s_bucket{le=\"+Inf\"} 20
+latency_s_sum 10
+latency_s_count 20
+# TYPE queue_depth gauge
+queue_depth 9
+";
+        let deltas = diff_snapshots(before, after).unwrap();
+        let series: Vec<_> = deltas.iter().map(|d| d.series.as_str()).collect();
+        assert_eq!(
+            series,
+            vec![
+                "requests_total{route=\"/broadcast_tx_batch\"}",
+                "latency_s_count",
+                "latency_s_sum",
+                "requests_total{route=\"/tx\"}",
+                "requests_total{route=\"/stats\"}",
+            ]
+        );
+
+        let tx = &deltas[3];
+        assert_eq!(tx.delta(), 50.0);
+        assert_eq!(tx.relative_change(), 0.5);
+        assert_eq!(tx.rate(Duration::from_secs(10)), Some(5.0));
+        assert!(deltas[0].relative_change().is_infinite());
+        assert_eq!(deltas[4].relative_change(), 0.0);
+    }
+}
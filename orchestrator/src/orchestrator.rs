@@ -25,13 +25,43 @@ use crate::{
     measurement::{Measurement, MeasurementsCollection},
     protocol::{ProtocolCommands, ProtocolMetrics},
     settings::Settings,
+    sink,
     ssh::{CommandContext, CommandStatus, SshConnectionManager},
 };
 
+mod breaker;
+mod chaos;
+mod clock;
+mod compression;
+mod controller;
+mod distribution;
 mod local;
+mod logging;
+mod pacer;
+mod readiness;
 mod remote;
-pub use local::LocalNetworkOrchestrator;
-pub use remote::RemoteNetworkOrchestrator;
+mod samples;
+mod schedule;
+mod transactions;
+mod verification;
+pub use breaker::{BreakerEvent, BreakerTransition, CircuitBreaker};
+pub use chaos::{ChaosAction, ChaosEvent, ChaosSchedule, ChaosTimeline, FaultImpact};
+pub use clock::{ClockSkew, measure_clock_skew, skew_metadata};
+pub use compression::{Compression, CompressionStats};
+pub use controller::{RateAdjustment, UtilizationController};
+pub use distribution::{LoadDistribution, NodeSelector, fetch_stakes};
+pub use local::{LocalNetworkOrchestrator, NetworkNamespace, SimulationReport};
+pub use logging::SubmissionLogging;
+pub use pacer::Pacer;
+pub use readiness::ReadinessProbe;
+pub use remote::{RemoteNetworkOrchestrator, StartupReport};
+pub use samples::{LatencySample, PriorityLatency, SampleFormat, SampleWriter, run_samples_path};
+pub use schedule::{Interpolation, RateSchedule, ScheduleSample, ScheduleTracking};
+pub use transactions::{TransactionGenerator, TransactionPool};
+pub use verification::{
+    CommitVerification, MissingTransaction, SubmittedTransaction, lookup_commit_times,
+    lookup_transactions,
+};
 
 /// An orchestrator to run benchmarks on a testbed.
 pub struct Orchestrator<P, T> {
@@ -538,6 +568,7 @@ impl<P: ProtocolCommands<T> + ProtocolMetrics, T: BenchmarkType> Orchestrator<P,
         let metrics_commands = self.protocol_commands.clients_metrics_command(clients);
 
         let mut aggregator = MeasurementsCollection::new(&self.settings, parameters.clone());
+        let sink = sink::from_settings::<T>(&self.settings);
         let mut metrics_interval = time::interval(self.scrape_interval);
         metrics_interval.tick().await; // The first tick returns immediately.
 
@@ -564,13 +595,9 @@ impl<P: ProtocolCommands<T> + ProtocolMetrics, T: BenchmarkType> Orchestrator<P,
                         }
                     }
 
-                    let results_directory = &self.settings.results_dir;
-                    let commit = &self.settings.repository.commit;
-                    let path: PathBuf = [results_directory, &format!("results-{commit}").into()]
-                        .iter()
-                        .collect();
-                    fs::create_dir_all(&path).expect("Failed to create log directory");
-                    aggregator.save(path);
+                    if let Err(e) = sink.publish(&aggregator).await {
+                        display::warn(e);
+                    }
 
                     if elapsed > parameters.duration .as_secs() {
                         break;
@@ -725,6 +752,10 @@ impl<P: ProtocolCommands<T> + ProtocolMetrics, T: BenchmarkType> Orchestrator<P,
         }
 
         display::header("Benchmark completed");
+        if let Some(summary) = generator.slo_summary() {
+            display::config("Max throughput within the latency SLO", summary);
+            display::newline();
+        }
         Ok(())
     }
 }
@@ -0,0 +1,163 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::VecDeque, time::Duration};
+
+use serde::Serialize;
+use tokio::time::Instant;
+
+/// A change of state of the circuit breaker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BreakerTransition {
+    /// Submission is paused because too many recent submissions failed.
+    Opened,
+    /// A probe submission succeeded and submission resumed.
+    Closed,
+}
+
+/// A circuit breaker event, recorded with the benchmark results.
+#[derive(Clone, Debug, Serialize)]
+pub struct BreakerEvent {
+    /// The new state of the breaker.
+    pub transition: BreakerTransition,
+    /// The time since the beginning of the simulation (in seconds).
+    pub elapsed_s: f64,
+    /// The failure rate over the rolling window when the event happened.
+    pub failure_rate: f64,
+}
+
+/// Pauses the transaction simulation when the rolling failure rate exceeds a threshold, so a
+/// degraded network is not hammered with more load. After the cooldown, the next submission
+/// is a probe: submission resumes if it succeeds, and pauses again otherwise.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    /// The failure rate (between 0 and 1) above which the breaker opens.
+    threshold: f64,
+    /// How long submission is paused once the breaker opens.
+    cooldown: Duration,
+    /// The number of recent submissions over which the failure rate is computed.
+    window: usize,
+    /// The outcome (success or failure) of the recent submissions.
+    outcomes: VecDeque<bool>,
+    /// Whether the next submission is a probe.
+    probing: bool,
+    /// The time at which the simulation started.
+    start: Instant,
+    /// The open and close events so far.
+    events: Vec<BreakerEvent>,
+}
+
+impl CircuitBreaker {
+    /// The default number of submissions over which the failure rate is computed.
+    pub const DEFAULT_WINDOW: usize = 100;
+
+    pub fn new(threshold: f64, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            window: Self::DEFAULT_WINDOW,
+            outcomes: VecDeque::new(),
+            probing: false,
+            start: Instant::now(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Set the number of submissions over which the failure rate is computed.
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Restart the breaker for a new simulation, forgetting previous outcomes and events.
+    pub fn reset(&mut self) {
+        self.outcomes.clear();
+        self.probing = false;
+        self.start = Instant::now();
+        self.events.clear();
+    }
+
+    /// The failure rate over the rolling window.
+    pub fn failure_rate(&self) -> f64 {
+        match self.outcomes.len() {
+            0 => 0.0,
+            n => self.outcomes.iter().filter(|success| !**success).count() as f64 / n as f64,
+        }
+    }
+
+    /// Record the outcome of a submission. Returns the time to pause submission for, if the
+    /// breaker (re)opened.
+    pub fn record(&mut self, success: bool) -> Option<Duration> {
+        if self.probing {
+            if success {
+                self.probing = false;
+                self.outcomes.clear();
+                self.push_event(BreakerTransition::Closed, 0.0);
+                return None;
+            }
+            self.push_event(BreakerTransition::Opened, 1.0);
+            return Some(self.cooldown);
+        }
+
+        self.outcomes.push_back(success);
+        if self.outcomes.len() > self.window {
+            self.outcomes.pop_front();
+        }
+        let failure_rate = self.failure_rate();
+        if self.outcomes.len() == self.window && failure_rate > self.threshold {
+            self.probing = true;
+            self.push_event(BreakerTransition::Opened, failure_rate);
+            return Some(self.cooldown);
+        }
+        None
+    }
+
+    /// The open and close events since the breaker was created (or last reset).
+    pub fn events(&self) -> &[BreakerEvent] {
+        &self.events
+    }
+
+    fn push_event(&mut self, transition: BreakerTransition, failure_rate: f64) {
+        self.events.push(BreakerEvent {
+            transition,
+            elapsed_s: self.start.elapsed().as_secs_f64(),
+            failure_rate,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{BreakerTransition, CircuitBreaker};
+
+    #[test]
+    fn breaker_opens_and_probes() {
+        let cooldown = Duration::from_secs(5);
+        let mut breaker = CircuitBreaker::new(0.5, cooldown).with_window(4);
+
+        // The failure rate is only evaluated over a full window.
+        assert_eq!(breaker.record(false), None);
+        assert_eq!(breaker.record(false), None);
+        assert_eq!(breaker.record(false), None);
+        assert_eq!(breaker.record(true), None);
+        assert_eq!(breaker.record(false), Some(cooldown));
+
+        // A failed probe pauses again, a successful one resumes submission.
+        assert_eq!(breaker.record(false), Some(cooldown));
+        assert_eq!(breaker.record(true), None);
+        assert_eq!(breaker.failure_rate(), 0.0);
+
+        let transitions: Vec<_> = breaker.events().iter().map(|e| e.transition).collect();
+        assert_eq!(
+            transitions,
+            vec![
+                BreakerTransition::Opened,
+                BreakerTransition::Opened,
+                BreakerTransition::Closed
+            ]
+        );
+    }
+}
@@ -0,0 +1,399 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Soak and chaos runs: a schedule of faults (crashes and partitions) and their recoveries,
+//! repeated for as long as the run lasts while the network is under steady load. The faulted
+//! nodes rotate, so that the run exercises the recovery of every node rather than a single
+//! fault. The timeline of the run compares the committed throughput before and after every
+//! fault event.
+
+use std::{collections::BTreeSet, fmt, path::Path, str::FromStr, time::Duration};
+
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use serde::Serialize;
+
+/// The number of seconds of throughput compared before and after every fault event.
+pub const IMPACT_WINDOW_SECS: u64 = 5;
+
+/// A fault event, applied to the specified nodes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", content = "nodes", rename_all = "lowercase")]
+pub enum ChaosAction {
+    /// Kill the nodes abruptly.
+    Crash(Vec<usize>),
+    /// Restart crashed nodes on their existing state.
+    Recover(Vec<usize>),
+    /// Disconnect the nodes from the network (each node is isolated from all others).
+    Partition(Vec<usize>),
+    /// Reconnect partitioned nodes.
+    Heal(Vec<usize>),
+}
+
+impl ChaosAction {
+    /// The nodes the action applies to.
+    pub fn nodes(&self) -> &[usize] {
+        match self {
+            Self::Crash(nodes)
+            | Self::Recover(nodes)
+            | Self::Partition(nodes)
+            | Self::Heal(nodes) => nodes,
+        }
+    }
+}
+
+impl fmt::Display for ChaosAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self {
+            Self::Crash(_) => "crash",
+            Self::Recover(_) => "recover",
+            Self::Partition(_) => "partition",
+            Self::Heal(_) => "heal",
+        };
+        let nodes: Vec<_> = self.nodes().iter().map(ToString::to_string).collect();
+        write!(f, "{action} {}", nodes.join(","))
+    }
+}
+
+impl FromStr for ChaosAction {
+    type Err = String;
+
+    /// Parse an action such as `crash 1` or `partition 2,3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (action, nodes) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("Invalid fault event '{s}' (expected ACTION NODES)"))?;
+        let nodes = nodes
+            .split(',')
+            .map(|node| {
+                node.trim()
+                    .parse::<usize>()
+                    .map_err(|e| format!("Invalid node '{node}' in '{s}': {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        match action {
+            "crash" => Ok(Self::Crash(nodes)),
+            "recover" => Ok(Self::Recover(nodes)),
+            "partition" => Ok(Self::Partition(nodes)),
+            "heal" => Ok(Self::Heal(nodes)),
+            _ => Err(format!(
+                "Unknown fault action '{action}' (expected crash, recover, partition or heal)"
+            )),
+        }
+    }
+}
+
+/// A cycle of fault events, repeated until the end of the run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChaosSchedule {
+    /// The offsets of the events from the start of the cycle, by increasing offset.
+    events: Vec<(Duration, ChaosAction)>,
+    /// The length of a cycle.
+    period: Duration,
+}
+
+impl ChaosSchedule {
+    /// Make a schedule from the events of a cycle over a network of `nodes` nodes. The offsets
+    /// must be increasing and within the period, every recovery must follow a fault of the
+    /// same node, and every node must be healthy again at the end of the cycle.
+    pub fn new(
+        events: Vec<(Duration, ChaosAction)>,
+        period: Duration,
+        nodes: usize,
+    ) -> Result<Self> {
+        if events.is_empty() {
+            return Err(eyre!("The fault schedule is empty"));
+        }
+        if events.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(eyre!(
+                "The offsets of the fault schedule must be increasing"
+            ));
+        }
+        if events.last().is_some_and(|(offset, _)| *offset >= period) {
+            return Err(eyre!(
+                "The fault events must happen within the cycle of {period:?}"
+            ));
+        }
+
+        let mut crashed = BTreeSet::new();
+        let mut partitioned = BTreeSet::new();
+        for (offset, action) in &events {
+            if let Some(node) = action.nodes().iter().find(|node| **node >= nodes) {
+                return Err(eyre!(
+                    "At {offset:?}: no node {node} in a network of {nodes}"
+                ));
+            }
+            for node in action.nodes() {
+                let consistent = match action {
+                    ChaosAction::Crash(_) | ChaosAction::Partition(_) => {
+                        !crashed.contains(node) && !partitioned.contains(node)
+                    }
+                    ChaosAction::Recover(_) => crashed.remove(node),
+                    ChaosAction::Heal(_) => partitioned.remove(node),
+                };
+                if !consistent {
+                    return Err(eyre!("At {offset:?}: cannot {action} (node {node} state)"));
+                }
+                match action {
+                    ChaosAction::Crash(_) => crashed.insert(*node),
+                    ChaosAction::Partition(_) => partitioned.insert(*node),
+                    ChaosAction::Recover(_) | ChaosAction::Heal(_) => true,
+                };
+            }
+        }
+        if !crashed.is_empty() || !partitioned.is_empty() {
+            return Err(eyre!(
+                "The fault schedule must recover every node by the end of the cycle"
+            ));
+        }
+        Ok(Self { events, period })
+    }
+
+    /// The default rotation: crash and recover every node in turn, then partition and heal
+    /// every pair of consecutive nodes in turn, one event every `interval`.
+    pub fn rotating(nodes: usize, interval: Duration) -> Result<Self> {
+        let mut actions = Vec::new();
+        for node in 0..nodes {
+            actions.push(ChaosAction::Crash(vec![node]));
+            actions.push(ChaosAction::Recover(vec![node]));
+        }
+        if nodes > 2 {
+            for node in 0..nodes {
+                let pair = vec![node, (node + 1) % nodes];
+                actions.push(ChaosAction::Partition(pair.clone()));
+                actions.push(ChaosAction::Heal(pair));
+            }
+        }
+        let events: Vec<_> = actions
+            .into_iter()
+            .enumerate()
+            .map(|(i, action)| (interval * (i as u32 + 1), action))
+            .collect();
+        let period = interval * (events.len() as u32 + 1);
+        Self::new(events, period, nodes)
+    }
+
+    /// Read a schedule file with one `time_offset_secs,action nodes` pair per line (e.g.
+    /// `10,crash 1` or `40,partition 2,3`). Blank lines and lines starting with `#` are
+    /// ignored. The cycle restarts `tail` after its last event.
+    pub fn from_file(path: &Path, nodes: usize, tail: Duration) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read the fault schedule {}", path.display()))?;
+        let events = content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                let (offset, action) = line
+                    .split_once(',')
+                    .ok_or_else(|| eyre!("Line {number}: expected OFFSET_SECS,ACTION NODES"))?;
+                let offset = offset
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|offset| offset.is_finite() && *offset >= 0.0)
+                    .ok_or_else(|| eyre!("Line {number}: invalid offset '{offset}'"))?;
+                let action = action
+                    .parse::<ChaosAction>()
+                    .map_err(|e| eyre!("Line {number}: {e}"))?;
+                Ok((Duration::from_secs_f64(offset), action))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let period = events.last().map_or(tail, |(offset, _)| *offset + tail);
+        Self::new(events, period, nodes)
+    }
+
+    /// The length of a cycle.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// The events of the run, repeating the cycle until `duration`.
+    pub fn events_until(&self, duration: Duration) -> Vec<(Duration, ChaosAction)> {
+        (0..)
+            .map(|cycle| self.period * cycle)
+            .take_while(|start| *start < duration)
+            .flat_map(|start| {
+                self.events
+                    .iter()
+                    .map(move |(offset, action)| (start + *offset, action.clone()))
+            })
+            .filter(|(offset, _)| *offset < duration)
+            .collect()
+    }
+}
+
+/// A fault event of the run, as applied.
+#[derive(Clone, Debug, Serialize)]
+pub struct ChaosEvent {
+    /// When the event was applied, since the start of the run.
+    pub offset_s: f64,
+    pub action: ChaosAction,
+    /// Why the event could not be applied, if it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The fault events of a run and the committed throughput, second by second.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ChaosTimeline {
+    pub events: Vec<ChaosEvent>,
+    /// The number of transactions committed during every second of the run (by the node
+    /// committing the most during that second).
+    pub throughput: Vec<u64>,
+}
+
+/// The committed throughput around a fault event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaultImpact {
+    pub offset_s: f64,
+    pub action: String,
+    /// The mean throughput over the window before the event (tx/s).
+    pub before: f64,
+    /// The mean throughput over the window after the event (tx/s).
+    pub after: f64,
+}
+
+impl FaultImpact {
+    /// The relative change of the throughput after the event, in percent (negative for a dip).
+    pub fn change_pct(&self) -> f64 {
+        match self.before > 0.0 {
+            true => 100.0 * (self.after - self.before) / self.before,
+            false => 0.0,
+        }
+    }
+}
+
+impl ChaosTimeline {
+    /// The throughput over the `window` seconds before and after every event.
+    pub fn impacts(&self, window: u64) -> Vec<FaultImpact> {
+        let mean = |from: usize, to: usize| {
+            let samples =
+                &self.throughput[from.min(self.throughput.len())..to.min(self.throughput.len())];
+            match samples.is_empty() {
+                true => 0.0,
+                false => samples.iter().sum::<u64>() as f64 / samples.len() as f64,
+            }
+        };
+        self.events
+            .iter()
+            .map(|event| {
+                let second = event.offset_s as usize;
+                FaultImpact {
+                    offset_s: event.offset_s,
+                    action: event.action.to_string(),
+                    before: mean(second.saturating_sub(window as usize), second),
+                    after: mean(second + 1, second + 1 + window as usize),
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ChaosTimeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>8}  {:<16} {:>12} {:>12} {:>8}",
+            "time", "event", "before tx/s", "after tx/s", "change"
+        )?;
+        for (impact, event) in self.impacts(IMPACT_WINDOW_SECS).iter().zip(&self.events) {
+            write!(
+                f,
+                "{:>7.1}s  {:<16} {:>12.0} {:>12.0} {:>7.1}%",
+                impact.offset_s,
+                impact.action,
+                impact.before,
+                impact.after,
+                impact.change_pct()
+            )?;
+            match &event.error {
+                Some(error) => writeln!(f, "  FAILED: {error}")?,
+                None => writeln!(f)?,
+            }
+        }
+        let mean = match self.throughput.is_empty() {
+            true => 0.0,
+            false => self.throughput.iter().sum::<u64>() as f64 / self.throughput.len() as f64,
+        };
+        let min = self.throughput.iter().min().copied().unwrap_or_default();
+        write!(
+            f,
+            "{} fault events over {}s, committed {:.0} tx/s on average (minimum {} tx/s)",
+            self.events.len(),
+            self.throughput.len(),
+            mean,
+            min
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::Write, time::Duration};
+
+    use super::{ChaosAction, ChaosEvent, ChaosSchedule, ChaosTimeline};
+
+    #[test]
+    fn parse_fault_schedule() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "# offset_secs,action nodes\n10,crash 1\n20,recover 1\n\n30,partition 2,3\n40,heal 2,3"
+        )
+        .unwrap();
+        let schedule = ChaosSchedule::from_file(file.path(), 4, Duration::from_secs(10)).unwrap();
+        assert_eq!(schedule.period(), Duration::from_secs(50));
+
+        let events = schedule.events_until(Duration::from_secs(75));
+        let offsets: Vec<_> = events.iter().map(|(offset, _)| offset.as_secs()).collect();
+        assert_eq!(offsets, [10, 20, 30, 40, 60, 70]);
+        assert_eq!(events[2].1, ChaosAction::Partition(vec![2, 3]));
+        assert_eq!(events[2].1.to_string(), "partition 2,3");
+
+        let invalid = [
+            vec![(10, "crash 4")],
+            vec![(10, "recover 1")],
+            vec![(10, "crash 1")],
+            vec![(10, "crash 1"), (20, "partition 1"), (30, "recover 1")],
+        ];
+        for events in invalid {
+            let events = events
+                .into_iter()
+                .map(|(offset, action)| (Duration::from_secs(offset), action.parse().unwrap()))
+                .collect();
+            assert!(ChaosSchedule::new(events, Duration::from_secs(60), 4).is_err());
+        }
+        assert!("reboot 1".parse::<ChaosAction>().is_err());
+    }
+
+    #[test]
+    fn rotate_faults() {
+        let schedule = ChaosSchedule::rotating(4, Duration::from_secs(30)).unwrap();
+        let events = schedule.events_until(schedule.period());
+        assert_eq!(events.len(), 16);
+        assert_eq!(
+            events[0],
+            (Duration::from_secs(30), ChaosAction::Crash(vec![0]))
+        );
+        assert_eq!(events[15].1, ChaosAction::Heal(vec![3, 0]));
+    }
+
+    #[test]
+    fn measure_throughput_dips() {
+        let timeline = ChaosTimeline {
+            events: vec![ChaosEvent {
+                offset_s: 5.2,
+                action: ChaosAction::Crash(vec![1]),
+                error: None,
+            }],
+            throughput: vec![100, 100, 100, 100, 100, 100, 40, 60, 80, 100, 100, 100],
+        };
+        let impacts = timeline.impacts(5);
+        assert_eq!(impacts[0].before, 100.0);
+        assert_eq!(impacts[0].after, 76.0);
+        assert_eq!(impacts[0].change_pct(), -24.0);
+        assert!(timeline.to_string().contains("crash 1"));
+    }
+}
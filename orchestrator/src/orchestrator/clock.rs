@@ -0,0 +1,167 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::Client;
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// The number of `/time` queries per node; the one with the shortest round trip is kept as it
+/// bounds the error of the estimate the tightest.
+const SAMPLES: usize = 5;
+
+/// The offset of the wall clock of a node relative to the clock of the orchestrator.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ClockSkew {
+    /// The index of the node.
+    pub node: usize,
+    /// How far the clock of the node is ahead of the orchestrator's (negative if behind), in
+    /// milliseconds.
+    pub skew_ms: f64,
+    /// The round trip of the query the skew was estimated from, in milliseconds. The skew is
+    /// only accurate up to half of it.
+    pub round_trip_ms: f64,
+}
+
+impl ClockSkew {
+    /// Estimate the skew from the time at which the orchestrator sent the query, the time
+    /// reported by the node, and the time at which the orchestrator received the response (all
+    /// in microseconds since the epoch). Assumes the node read its clock halfway through the
+    /// round trip.
+    pub fn estimate(node: usize, sent_us: u64, node_us: u64, received_us: u64) -> Self {
+        let round_trip_us = received_us.saturating_sub(sent_us) as f64;
+        let midpoint_us = sent_us as f64 + round_trip_us / 2.0;
+        Self {
+            node,
+            skew_ms: (node_us as f64 - midpoint_us) / 1000.0,
+            round_trip_ms: round_trip_us / 1000.0,
+        }
+    }
+
+    /// Whether the skew exceeds the threshold.
+    pub fn exceeds(&self, threshold: Duration) -> bool {
+        self.skew_ms.abs() > threshold.as_secs_f64() * 1000.0
+    }
+}
+
+/// The current wall clock of the orchestrator, in microseconds since the epoch.
+pub(crate) fn now_us() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+/// Query the `/time` endpoint of one node and estimate its skew. Returns `None` if the node
+/// cannot be queried (e.g. it predates the endpoint).
+async fn measure_node(client: &Client, node: usize, url: &str) -> Option<ClockSkew> {
+    let mut best: Option<ClockSkew> = None;
+    for _ in 0..SAMPLES {
+        let sent_us = now_us();
+        let response: serde_json::Value = match client.get(format!("{url}/time")).send().await {
+            Ok(response) if response.status().is_success() => response.json().await.ok()?,
+            Ok(response) => {
+                warn!(
+                    "Node {node}: /time responded with status {}",
+                    response.status()
+                );
+                return None;
+            }
+            Err(e) => {
+                warn!("Node {node}: failed to query its clock ({e})");
+                return None;
+            }
+        };
+        let received_us = now_us();
+        let skew = ClockSkew::estimate(
+            node,
+            sent_us,
+            response["unix_time_us"].as_u64()?,
+            received_us,
+        );
+        if best
+            .as_ref()
+            .is_none_or(|best| skew.round_trip_ms < best.round_trip_ms)
+        {
+            best = Some(skew);
+        }
+    }
+    best
+}
+
+/// Measure the clock skew of the nodes (given as index and base rpc url) relative to the
+/// orchestrator, warning about the nodes whose skew exceeds the threshold: latencies measured
+/// across machines are off by the skew. Nodes that cannot be queried are skipped.
+pub async fn measure_clock_skew(
+    client: &Client,
+    nodes: &[(usize, String)],
+    threshold: Duration,
+) -> Vec<ClockSkew> {
+    let mut skews = Vec::new();
+    for (node, url) in nodes {
+        let Some(skew) = measure_node(client, *node, url).await else {
+            continue;
+        };
+        if skew.exceeds(threshold) {
+            warn!(
+                "Node {}: clock skew of {:+.1}ms (threshold {}ms); cross-machine latencies \
+                 will be off by as much",
+                node,
+                skew.skew_ms,
+                threshold.as_millis()
+            );
+        } else {
+            info!(
+                "Node {}: clock skew of {:+.1}ms (±{:.1}ms)",
+                node,
+                skew.skew_ms,
+                skew.round_trip_ms / 2.0
+            );
+        }
+        skews.push(skew);
+    }
+    skews
+}
+
+/// Summarize the skews as benchmark metadata, so that latencies can be interpreted (or
+/// corrected) after the fact.
+pub fn skew_metadata(skews: &[ClockSkew]) -> BTreeMap<String, String> {
+    let mut metadata = BTreeMap::new();
+    let Some(max) = skews.iter().map(|skew| skew.skew_ms.abs()).reduce(f64::max) else {
+        return metadata;
+    };
+    metadata.insert("clock_skew_ms_max".into(), format!("{max:.3}"));
+    metadata.insert(
+        "clock_skew_ms".into(),
+        serde_json::to_string(skews).expect("Skews serialize to json"),
+    );
+    metadata
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{ClockSkew, skew_metadata};
+
+    #[test]
+    fn estimate_clock_skew() {
+        // The node reads its clock 3ms ahead of the midpoint of a 4ms round trip.
+        let ahead = ClockSkew::estimate(0, 1_000_000, 1_005_000, 1_004_000);
+        assert_eq!(ahead.skew_ms, 3.0);
+        assert_eq!(ahead.round_trip_ms, 4.0);
+        assert!(ahead.exceeds(Duration::from_millis(2)));
+        assert!(!ahead.exceeds(Duration::from_millis(5)));
+
+        let behind = ClockSkew::estimate(1, 1_000_000, 990_000, 1_000_000);
+        assert_eq!(behind.skew_ms, -10.0);
+
+        let metadata = skew_metadata(&[ahead, behind]);
+        assert_eq!(metadata["clock_skew_ms_max"], "10.000");
+        assert!(skew_metadata(&[]).is_empty());
+    }
+}
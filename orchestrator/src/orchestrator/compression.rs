@@ -0,0 +1,106 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{io::Write, str::FromStr, time::Instant};
+
+use serde::Serialize;
+
+/// The compression applied to the body of the batches submitted to the nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gzip" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd),
+            _ => Err(format!("Unknown compression '{s}' (expected gzip or zstd)")),
+        }
+    }
+}
+
+impl Compression {
+    /// The value of the `Content-Encoding` header of compressed bodies.
+    pub fn content_encoding(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// Compress the body, accounting for its size and the time spent in `stats`.
+    pub fn compress(&self, body: &[u8], stats: &mut CompressionStats) -> Vec<u8> {
+        let start = Instant::now();
+        let compressed = match self {
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+                encoder
+                    .write_all(body)
+                    .expect("Writing to memory cannot fail");
+                encoder.finish().expect("Writing to memory cannot fail")
+            }
+            Self::Zstd => zstd::encode_all(body, 0).expect("Writing to memory cannot fail"),
+        };
+        stats.bodies += 1;
+        stats.uncompressed_bytes += body.len() as u64;
+        stats.compressed_bytes += compressed.len() as u64;
+        stats.compression_time_s += start.elapsed().as_secs_f64();
+        compressed
+    }
+}
+
+/// The bandwidth saved by compressing the submitted bodies, and the CPU time it cost.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct CompressionStats {
+    /// The number of compressed bodies.
+    pub bodies: u64,
+    /// The total size of the bodies before compression.
+    pub uncompressed_bytes: u64,
+    /// The total size of the bodies after compression.
+    pub compressed_bytes: u64,
+    /// The total time spent compressing (in seconds).
+    pub compression_time_s: f64,
+}
+
+impl CompressionStats {
+    /// The ratio between the uncompressed and compressed sizes.
+    pub fn ratio(&self) -> f64 {
+        match self.compressed_bytes {
+            0 => 1.0,
+            compressed => self.uncompressed_bytes as f64 / compressed as f64,
+        }
+    }
+
+    /// The average time spent compressing a body (in microseconds).
+    pub fn mean_compression_time_us(&self) -> f64 {
+        match self.bodies {
+            0 => 0.0,
+            bodies => self.compression_time_s * 1e6 / bodies as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Compression, CompressionStats};
+
+    #[test]
+    fn compress_bodies() {
+        let body = vec![0u8; 4096];
+        let mut stats = CompressionStats::default();
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let compressed = compression.compress(&body, &mut stats);
+            assert!(compressed.len() < body.len());
+        }
+        assert_eq!(stats.bodies, 2);
+        assert_eq!(stats.uncompressed_bytes, 2 * 4096);
+        assert!(stats.ratio() > 1.0);
+        assert_eq!("zstd".parse::<Compression>(), Ok(Compression::Zstd));
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::measurement::HistogramSummary;
+
+/// One adjustment of the submission rate, recorded with the benchmark results.
+#[derive(Clone, Debug, Serialize)]
+pub struct RateAdjustment {
+    /// The time since the beginning of the simulation (in seconds).
+    pub elapsed_s: f64,
+    /// The mean number of transactions per commit observed since the previous adjustment.
+    pub transactions_per_commit: f64,
+    /// The number of commits per second (per node) observed since the previous adjustment.
+    pub commit_rate: f64,
+    /// The new submission rate (tx/s).
+    pub rate: usize,
+}
+
+/// Adjusts the submission rate so that commits hold a target number of transactions, to
+/// characterize the system at a fixed block fullness rather than at a fixed load.
+///
+/// The controller reads the transactions-per-commit histogram of the nodes. The new rate is the
+/// observed commit rate multiplied by the target utilization, corrected by a
+/// proportional-integral term on the utilization error.
+#[derive(Clone, Debug)]
+pub struct UtilizationController {
+    /// The target number of transactions per commit.
+    target: f64,
+    /// The proportional gain.
+    kp: f64,
+    /// The integral gain (per second).
+    ki: f64,
+    /// How often the rate is adjusted.
+    interval: Duration,
+    /// The bounds of the submission rate (tx/s).
+    min_rate: usize,
+    max_rate: usize,
+    /// The accumulated utilization error.
+    integral: f64,
+    /// The current submission rate (tx/s).
+    rate: usize,
+    /// The histogram (and time) of the previous adjustment.
+    last: Option<(HistogramSummary, Instant)>,
+    /// The time at which the simulation started.
+    start: Instant,
+    /// The adjustments so far.
+    adjustments: Vec<RateAdjustment>,
+}
+
+impl UtilizationController {
+    /// The default proportional gain.
+    pub const DEFAULT_KP: f64 = 0.5;
+    /// The default integral gain.
+    pub const DEFAULT_KI: f64 = 0.1;
+    /// The default delay between two adjustments of the rate.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new(target: f64, max_rate: usize) -> Self {
+        Self {
+            target,
+            kp: Self::DEFAULT_KP,
+            ki: Self::DEFAULT_KI,
+            interval: Self::DEFAULT_INTERVAL,
+            min_rate: 1,
+            max_rate: max_rate.max(1),
+            integral: 0.0,
+            rate: 1,
+            last: None,
+            start: Instant::now(),
+            adjustments: Vec::new(),
+        }
+    }
+
+    /// Set the proportional and integral gains.
+    pub fn with_gains(mut self, kp: f64, ki: f64) -> Self {
+        self.kp = kp;
+        self.ki = ki;
+        self
+    }
+
+    /// Set the delay between two adjustments of the rate.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The delay between two adjustments of the rate.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Restart the controller for a new simulation starting at the specified rate.
+    pub fn reset(&mut self, initial_rate: usize) {
+        self.integral = 0.0;
+        self.rate = initial_rate.clamp(self.min_rate, self.max_rate);
+        self.last = None;
+        self.start = Instant::now();
+        self.adjustments.clear();
+    }
+
+    /// The current submission rate (tx/s).
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// Feed the transactions-per-commit histogram merged over `nodes` nodes, scraped at `now`,
+    /// and return the new submission rate. The rate is unchanged until commits are observed.
+    pub fn update(&mut self, summary: HistogramSummary, nodes: usize, now: Instant) -> usize {
+        let Some((last, last_time)) = self.last.replace((summary, now)) else {
+            return self.rate;
+        };
+
+        let commits = summary.count.saturating_sub(last.count);
+        let elapsed = now.duration_since(last_time).as_secs_f64();
+        if commits == 0 || elapsed <= 0.0 {
+            return self.rate;
+        }
+        let transactions_per_commit = (summary.sum - last.sum).max(0.0) / commits as f64;
+        let commit_rate = commits as f64 / nodes.max(1) as f64 / elapsed;
+
+        // Only accumulate the error while the rate is not saturated (anti-windup).
+        let error = self.target - transactions_per_commit;
+        let saturated = (self.rate == self.max_rate && error > 0.0)
+            || (self.rate == self.min_rate && error < 0.0);
+        if !saturated {
+            self.integral += error * elapsed;
+        }
+        let utilization = self.target + self.kp * error + self.ki * self.integral;
+        let rate = (commit_rate * utilization).round().max(0.0) as usize;
+        self.rate = rate.clamp(self.min_rate, self.max_rate);
+
+        self.adjustments.push(RateAdjustment {
+            elapsed_s: now.duration_since(self.start).as_secs_f64(),
+            transactions_per_commit,
+            commit_rate,
+            rate: self.rate,
+        });
+        self.rate
+    }
+
+    /// The adjustments since the controller was created (or last reset).
+    pub fn adjustments(&self) -> &[RateAdjustment] {
+        &self.adjustments
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use crate::measurement::HistogramSummary;
+
+    use super::UtilizationController;
+
+    #[test]
+    fn rate_tracks_target_utilization() {
+        let mut controller = UtilizationController::new(100.0, 10_000).with_gains(0.5, 0.0);
+        controller.reset(500);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // The first sample only sets the baseline.
+        let mut summary = HistogramSummary::default();
+        assert_eq!(controller.update(summary, 1, at(0)), 500);
+
+        // 10 commits of 50 transactions in one second: commits are half as full as targeted,
+        // so the rate increases beyond the commit rate times the target.
+        summary.merge(&HistogramSummary {
+            count: 10,
+            sum: 500.0,
+        });
+        let rate = controller.update(summary, 1, at(1));
+        assert_eq!(rate, 1_250);
+
+        // Without new commits, the rate is unchanged.
+        assert_eq!(controller.update(summary, 1, at(2)), rate);
+
+        // Overfull commits decrease the rate, within the bounds.
+        summary.merge(&HistogramSummary {
+            count: 10,
+            sum: 3_000.0,
+        });
+        assert_eq!(controller.update(summary, 1, at(3)), 1);
+        assert_eq!(controller.adjustments().len(), 2);
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{fmt, str::FromStr};
+
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+/// How the simulator spreads the load across the nodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadDistribution {
+    /// Round-robin: every node receives the same share of the load.
+    #[default]
+    Equal,
+    /// Every node receives a share of the load proportional to its stake, as clients connecting
+    /// to validators in a stake-weighted network would.
+    Weighted,
+}
+
+impl fmt::Display for LoadDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Equal => write!(f, "equal"),
+            Self::Weighted => write!(f, "weighted"),
+        }
+    }
+}
+
+impl FromStr for LoadDistribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "equal" => Ok(Self::Equal),
+            "weighted" => Ok(Self::Weighted),
+            _ => Err(format!(
+                "Unknown distribution '{s}' (expected equal or weighted)"
+            )),
+        }
+    }
+}
+
+/// Picks the node receiving each transaction. Weighted selection is a smooth weighted
+/// round-robin: it is deterministic and interleaves the nodes, rather than sending bursts of
+/// transactions to the heaviest ones.
+#[derive(Clone, Debug)]
+pub struct NodeSelector {
+    weights: Vec<u64>,
+    current: Vec<i128>,
+}
+
+impl NodeSelector {
+    /// Select the nodes in turn.
+    pub fn equal(nodes: usize) -> Self {
+        Self::weighted(vec![1; nodes]).expect("The equal weights are positive")
+    }
+
+    /// Select every node in proportion to its weight.
+    pub fn weighted(weights: Vec<u64>) -> Result<Self> {
+        if weights.iter().all(|weight| *weight == 0) {
+            return Err(eyre!("At least one node must have a positive stake"));
+        }
+        Ok(Self {
+            current: vec![0; weights.len()],
+            weights,
+        })
+    }
+
+    /// The index of the node receiving the next transaction.
+    pub fn select(&mut self) -> usize {
+        let total: u64 = self.weights.iter().sum();
+        for (current, weight) in self.current.iter_mut().zip(&self.weights) {
+            *current += *weight as i128;
+        }
+        let (node, _) = self
+            .current
+            .iter()
+            .enumerate()
+            .max_by_key(|(node, current)| (**current, std::cmp::Reverse(*node)))
+            .expect("The selector has at least one node");
+        self.current[node] -= total as i128;
+        node
+    }
+}
+
+/// Read the stake of the specified authorities from the `/committee` endpoint of a node,
+/// returned in the order of `authorities`.
+pub async fn fetch_stakes(client: &Client, url: &str, authorities: &[usize]) -> Result<Vec<u64>> {
+    let report: serde_json::Value = client
+        .get(format!("{url}/committee"))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .wrap_err_with(|| format!("Failed to fetch the committee from {url}"))?
+        .json()
+        .await
+        .wrap_err_with(|| format!("Failed to parse the committee of {url}"))?;
+
+    let members = report["authorities"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    authorities
+        .iter()
+        .map(|authority| {
+            members
+                .iter()
+                .find(|member| member["authority_index"].as_u64() == Some(*authority as u64))
+                .and_then(|member| member["stake"].as_u64())
+                .ok_or_else(|| eyre!("Authority {authority} is not in the committee of {url}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LoadDistribution, NodeSelector};
+
+    #[test]
+    fn select_nodes_by_stake() {
+        let mut equal = NodeSelector::equal(4);
+        let picks: Vec<_> = (0..8).map(|_| equal.select()).collect();
+        assert_eq!(picks, vec![0, 1, 2, 3, 0, 1, 2, 3]);
+
+        let mut weighted = NodeSelector::weighted(vec![1, 2, 3, 4]).unwrap();
+        let mut counts = [0; 4];
+        for _ in 0..100 {
+            counts[weighted.select()] += 1;
+        }
+        assert_eq!(counts, [10, 20, 30, 40]);
+
+        // Nodes without stake receive nothing.
+        let mut partial = NodeSelector::weighted(vec![0, 1]).unwrap();
+        assert!((0..10).all(|_| partial.select() == 1));
+        assert!(NodeSelector::weighted(vec![0, 0]).is_err());
+
+        assert_eq!("weighted".parse(), Ok(LoadDistribution::Weighted));
+        assert!("uniform".parse::<LoadDistribution>().is_err());
+    }
+}
@@ -1,17 +1,252 @@
-use base64::Engine;
-use color_eyre::eyre::{Context, Result};
-use reqwest::Client;
+use color_eyre::eyre::{Context, Result, bail};
+use futures::{StreamExt, stream::FuturesUnordered};
+use reqwest::{
+    Client,
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+};
 use serde_json::json;
 use std::{
+    collections::{BTreeSet, HashMap},
+    io::Write,
     path::PathBuf,
     process::{Command, Stdio},
+    sync::Arc,
     time::{Duration, Instant},
 };
 use tokio::time::sleep;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
+
+use super::{
+    BreakerEvent, ChaosAction, ChaosEvent, ChaosSchedule, ChaosTimeline, CircuitBreaker, ClockSkew,
+    CommitVerification, Compression, CompressionStats, LatencySample, LoadDistribution,
+    NodeSelector, Pacer, PriorityLatency, RateAdjustment, RateSchedule, ReadinessProbe,
+    SampleWriter, ScheduleTracking, StartupReport, SubmissionLogging, SubmittedTransaction,
+    TransactionGenerator, TransactionPool, UtilizationController, clock::now_us, fetch_stakes,
+    lookup_commit_times, lookup_transactions, measure_clock_skew,
+};
+use crate::{
+    faults::TransactionFault,
+    measurement::HistogramSummary,
+    progress::BenchmarkProgress,
+    protocol::mysticeti::{TRANSACTIONS_PER_COMMIT, TransactionFormat},
+};
+
+/// The number of consecutive failed submissions to a node after which its container is
+/// checked for a crash.
+const CRASH_CHECK_FAILURES: usize = 3;
+
+/// The number of nodes defined in the docker-compose file.
+const COMPOSE_NODES: usize = 4;
+
+/// The outcome of a transaction simulation.
+#[derive(Clone, Debug, Default)]
+pub struct SimulationReport {
+    /// The open and close events of the circuit breaker, if any.
+    pub breaker_events: Vec<BreakerEvent>,
+    /// The adjustments of the submission rate by the utilization controller, if any.
+    pub rate_adjustments: Vec<RateAdjustment>,
+    /// The transactions accepted by the nodes, if tracked.
+    pub submitted_transactions: Vec<SubmittedTransaction>,
+    /// The size and CPU cost of the compressed bodies, if compressing.
+    pub compression: Option<CompressionStats>,
+    /// How closely the submission rate followed the rate schedule, if any.
+    pub schedule_tracking: Option<ScheduleTracking>,
+    /// The number of transactions accepted per second.
+    pub transaction_rate: f64,
+    /// The number of HTTP requests sent per second, lower than the transaction rate when
+    /// batching.
+    pub request_rate: f64,
+}
+
+/// The response of a node to a submission request.
+enum RequestResult {
+    /// The node accepted the transactions, answering with this body (if tracked).
+    Accepted(serde_json::Value),
+    /// The node rejected the transactions with this status.
+    Rejected(reqwest::StatusCode),
+    /// The request did not reach the node.
+    Failed(reqwest::Error),
+}
+
+/// A completed submission request.
+struct RequestOutcome {
+    /// The node the request was sent to.
+    node: usize,
+    /// The sequence number of the first transaction of the request.
+    first: usize,
+    /// When each transaction of the request was generated.
+    submission_times_us: Vec<u64>,
+    result: RequestResult,
+}
+
+/// Identifies one of several local networks running side by side. The compose project, the
+/// container names, the host ports, the data directory, and the docker subnet of the network
+/// are all derived from it, so that networks with different namespaces do not collide.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkNamespace {
+    /// The compose project name, also prefixing the container names.
+    name: String,
+    /// The slot of the network: the host ports and the subnet are offset by it.
+    slot: u16,
+}
+
+impl Default for NetworkNamespace {
+    fn default() -> Self {
+        Self {
+            name: Self::DEFAULT_NAME.into(),
+            slot: 0,
+        }
+    }
+}
+
+impl NetworkNamespace {
+    /// The name of the network started when no namespace is specified.
+    pub const DEFAULT_NAME: &'static str = "mysticeti";
+    /// The host port of the rpc endpoint of the first node of the first network.
+    pub const BASE_RPC_PORT: u16 = 26657;
+    /// The host port of the abci endpoint of the first node of the first network.
+    pub const BASE_ABCI_PORT: u16 = 26670;
+    /// The distance between the host ports of two consecutive slots.
+    pub const PORT_STRIDE: u16 = 100;
+    /// The maximum number of networks that can run side by side: their subnets must stay
+    /// within the private 172.16.0.0/12 range.
+    pub const MAX_SLOTS: u16 = 12;
+
+    /// Create the namespace of the `slot`-th network. Each network running concurrently
+    /// needs a different slot (and name).
+    pub fn new<S: Into<String>>(name: S, slot: u16) -> Result<Self> {
+        let name = name.into();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return Err(color_eyre::eyre::eyre!(
+                "Invalid network name '{name}': use lowercase letters, digits, '-' and '_'"
+            ));
+        }
+        if slot >= Self::MAX_SLOTS {
+            return Err(color_eyre::eyre::eyre!(
+                "At most {} networks can run side by side",
+                Self::MAX_SLOTS
+            ));
+        }
+        Ok(Self { name, slot })
+    }
+
+    /// The name of the network (and of its compose project).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name of the container running the specified node.
+    pub fn container_name(&self, node: usize) -> String {
+        format!("{}-node{}", self.name, node)
+    }
+
+    /// The host port of the rpc endpoint of the specified node.
+    pub fn rpc_port(&self, node: usize) -> u16 {
+        Self::BASE_RPC_PORT + self.slot * Self::PORT_STRIDE + node as u16
+    }
+
+    /// The url of the rpc endpoint of the specified node.
+    pub fn rpc_url(&self, node: usize) -> String {
+        format!("http://localhost:{}", self.rpc_port(node))
+    }
+
+    /// The urls of the rpc endpoints of all nodes.
+    pub fn rpc_urls(&self) -> Vec<String> {
+        (0..COMPOSE_NODES).map(|i| self.rpc_url(i)).collect()
+    }
+
+    /// The docker network of the nodes, created by compose for the project.
+    pub fn docker_network(&self) -> String {
+        format!("{}_mysticeti-network", self.name)
+    }
+
+    /// The ip of the specified node on the docker network.
+    fn node_ip(&self, node: usize) -> String {
+        format!("172.{}.0.{}", 20 + self.slot, 10 + node)
+    }
+
+    /// The variables interpolated by the docker-compose file to place the network in this
+    /// namespace.
+    pub fn compose_env(&self) -> Vec<(String, String)> {
+        let mut env = vec![
+            ("NETWORK_NAME".to_string(), self.name.clone()),
+            (
+                "NETWORK_SUBNET".to_string(),
+                format!("172.{}.0.0/16", 20 + self.slot),
+            ),
+            (
+                "NETWORK_GATEWAY".to_string(),
+                format!("172.{}.0.1", 20 + self.slot),
+            ),
+        ];
+        // The default network keeps its data where it always did.
+        let data_dir = match self.slot {
+            0 => "./data".to_string(),
+            _ => format!("./data/{}", self.name),
+        };
+        env.push(("DATA_DIR".to_string(), data_dir));
+        for node in 0..COMPOSE_NODES {
+            let abci_port = Self::BASE_ABCI_PORT + self.slot * Self::PORT_STRIDE + node as u16;
+            let peers: Vec<_> = (0..COMPOSE_NODES)
+                .filter(|peer| *peer != node)
+                .map(|peer| format!("{}:26657", self.node_ip(peer)))
+                .collect();
+            env.push((format!("NODE{node}_IP"), self.node_ip(node)));
+            env.push((
+                format!("NODE{node}_RPC_PORT"),
+                self.rpc_port(node).to_string(),
+            ));
+            env.push((format!("NODE{node}_ABCI_PORT"), abci_port.to_string()));
+            env.push((format!("PEER_ADDRESSES_NODE{node}"), peers.join(",")));
+        }
+        env
+    }
+}
 
 pub struct LocalNetworkOrchestrator {
     docker_compose_path: PathBuf,
+    /// The namespace of the network, distinguishing it from other local networks.
+    namespace: NetworkNamespace,
+    /// When set, abort as soon as a node crashes and save its logs to this directory.
+    crash_log_dir: Option<PathBuf>,
+    /// The progress counters updated by the transaction simulation, if any.
+    progress: Option<Arc<BenchmarkProgress>>,
+    /// The delay between two polls of the health of the nodes.
+    readiness_interval: Duration,
+    /// The maximum number of transactions per block, if overriding the protocol limit.
+    max_transactions_per_block: Option<u64>,
+    /// Pauses the transaction simulation when too many submissions fail, if set.
+    circuit_breaker: Option<CircuitBreaker>,
+    /// How much the transaction simulation logs while submitting.
+    logging: SubmissionLogging,
+    /// Adjusts the submission rate to a target number of transactions per commit, if set.
+    utilization_controller: Option<UtilizationController>,
+    /// Varies the submission rate over the run, if set.
+    rate_schedule: Option<RateSchedule>,
+    /// Whether to record the id of every accepted transaction, to later verify its commit.
+    track_transactions: bool,
+    /// The compression of the submitted bodies, if any.
+    compression: Option<Compression>,
+    /// The maximum number of transactions submitted in one request.
+    client_batch_size: usize,
+    /// How long a request waits for more transactions after its first one.
+    client_batch_window: Duration,
+    /// The maximum number of requests in flight.
+    concurrency: usize,
+    /// The layout of the submitted transactions.
+    transaction_format: TransactionFormat,
+    /// The fraction of the transactions submitted with high priority.
+    high_priority_ratio: f64,
+    /// The number of distinct transactions encoded ahead of the submission.
+    pool_size: usize,
+    /// How the load is spread across the nodes.
+    distribution: LoadDistribution,
+    /// The node dropping or corrupting transactions, if any.
+    transaction_fault: Option<TransactionFault>,
 }
 
 impl LocalNetworkOrchestrator {
@@ -26,29 +261,212 @@ impl LocalNetworkOrchestrator {
 
         Ok(Self {
             docker_compose_path,
+            namespace: NetworkNamespace::default(),
+            crash_log_dir: None,
+            progress: None,
+            readiness_interval: ReadinessProbe::DEFAULT_INTERVAL,
+            max_transactions_per_block: None,
+            circuit_breaker: None,
+            logging: SubmissionLogging::default(),
+            utilization_controller: None,
+            rate_schedule: None,
+            track_transactions: false,
+            compression: None,
+            client_batch_size: 1,
+            client_batch_window: Duration::ZERO,
+            concurrency: 1,
+            transaction_format: TransactionFormat::default(),
+            high_priority_ratio: 0.0,
+            pool_size: TransactionPool::DEFAULT_SIZE,
+            distribution: LoadDistribution::default(),
+            transaction_fault: None,
         })
     }
 
-    pub fn verify_docker_compose(&self) -> Result<()> {
-        info!(
-            "Using existing docker-compose.yml at {}",
-            self.docker_compose_path.display()
-        );
-        Ok(())
+    /// Run the network in the specified namespace, so that it can run alongside other local
+    /// networks.
+    pub fn with_namespace(mut self, namespace: NetworkNamespace) -> Self {
+        self.namespace = namespace;
+        self
     }
 
-    pub fn start_network(&self) -> Result<()> {
-        info!("Starting Mysticeti network with docker compose...");
+    /// The namespace of the network.
+    pub fn namespace(&self) -> &NetworkNamespace {
+        &self.namespace
+    }
 
+    /// A `docker compose` command operating on the project of this network.
+    fn compose_command(&self) -> Result<Command> {
         // Get the orchestrator directory (parent of docker-compose.yml)
         let orchestrator_dir = self
             .docker_compose_path
             .parent()
             .ok_or_else(|| color_eyre::eyre::eyre!("Failed to get orchestrator directory"))?;
 
-        let status = Command::new("docker")
+        let mut command = Command::new("docker");
+        command
             .current_dir(orchestrator_dir)
-            .args(["compose", "up", "-d"])
+            .envs(self.namespace.compose_env())
+            .args(["compose", "-p", self.namespace.name()]);
+        Ok(command)
+    }
+
+    /// Set the delay between two polls of the health of the nodes.
+    pub fn with_readiness_interval(mut self, interval: Duration) -> Self {
+        self.readiness_interval = interval;
+        self
+    }
+
+    /// Override the maximum number of transactions per block of the nodes started by
+    /// `start_network`.
+    pub fn with_max_transactions_per_block(mut self, max: Option<u64>) -> Self {
+        self.max_transactions_per_block = max;
+        self
+    }
+
+    /// Pause the transaction simulation whenever the specified circuit breaker opens.
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Set how much the transaction simulation logs while submitting.
+    pub fn with_logging(mut self, logging: SubmissionLogging) -> Self {
+        self.logging = logging;
+        self
+    }
+
+    /// Submit the transactions as compressed batches.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Submit transactions with the specified layout.
+    pub fn with_transaction_format(mut self, transaction_format: TransactionFormat) -> Self {
+        self.transaction_format = transaction_format;
+        self
+    }
+
+    /// Submit the specified fraction of the transactions with high priority, and have the
+    /// nodes submit the queued transactions of highest priority first.
+    pub fn with_high_priority_ratio(mut self, ratio: f64) -> Self {
+        self.high_priority_ratio = ratio;
+        self
+    }
+
+    /// Set the number of distinct transactions encoded before the submission starts. The
+    /// simulation cycles through them, only changing their nonce.
+    pub fn with_pool_size(mut self, pool_size: usize) -> Self {
+        self.pool_size = pool_size;
+        self
+    }
+
+    /// Set how the load is spread across the nodes.
+    pub fn with_distribution(mut self, distribution: LoadDistribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    /// UNSAFE: make a node silently drop or corrupt a fraction of the transactions it
+    /// receives, to test how the committee tolerates a faulty-but-alive node.
+    pub fn with_transaction_fault(mut self, transaction_fault: TransactionFault) -> Self {
+        self.transaction_fault = Some(transaction_fault);
+        self
+    }
+
+    /// Let the specified controller adjust the submission rate to reach its target number of
+    /// transactions per commit. The simulation then runs for as long as it would at the
+    /// initial rate, rather than for a fixed number of transactions.
+    pub fn with_utilization_controller(mut self, controller: UtilizationController) -> Self {
+        self.utilization_controller = Some(controller);
+        self
+    }
+
+    /// Vary the submission rate over the run according to the specified schedule. As with a
+    /// utilization controller, the simulation then runs for as long as it would at the
+    /// initial rate.
+    pub fn with_rate_schedule(mut self, schedule: RateSchedule) -> Self {
+        self.rate_schedule = Some(schedule);
+        self
+    }
+
+    /// Record the id of every transaction accepted during the simulation, so that
+    /// `verify_commits` can later confirm none of them was lost.
+    pub fn with_transaction_tracking(mut self) -> Self {
+        self.track_transactions = true;
+        self
+    }
+
+    /// Report the outcome of every simulated transaction to the specified progress counters.
+    pub fn with_progress(mut self, progress: Arc<BenchmarkProgress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Abort the transaction simulation as soon as a node crashes, saving the logs of the
+    /// crashed node to the specified directory.
+    pub fn with_fail_fast(mut self, crash_log_dir: PathBuf) -> Self {
+        self.crash_log_dir = Some(crash_log_dir);
+        self
+    }
+
+    /// Accumulate up to `size` transactions (for at most `window` after the first one) and
+    /// submit them in a single request to the batch endpoint, to amortize the HTTP overhead.
+    pub fn with_client_batching(mut self, size: usize, window: Duration) -> Self {
+        self.client_batch_size = size.max(1);
+        self.client_batch_window = window;
+        self
+    }
+
+    /// Keep up to `concurrency` submission requests in flight, so that the submission rate
+    /// is not capped by the round trip time of the requests.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn verify_docker_compose(&self) -> Result<()> {
+        info!(
+            "Using existing docker-compose.yml at {}",
+            self.docker_compose_path.display()
+        );
+        Ok(())
+    }
+
+    pub fn start_network(&self) -> Result<()> {
+        info!(
+            "Starting Mysticeti network '{}' with docker compose...",
+            self.namespace.name()
+        );
+
+        // The compose file passes this variable to the nodes, where 0 keeps the protocol limit.
+        let max_transactions = self.max_transactions_per_block.unwrap_or_default();
+        let mut command = self.compose_command()?;
+        command.env("MAX_TRANSACTIONS_PER_BLOCK", max_transactions.to_string());
+        let verify_signatures = self.transaction_format.is_signed();
+        command.env("VERIFY_SIGNATURES", verify_signatures.to_string());
+        let prioritize = self.high_priority_ratio > 0.0;
+        command.env("PRIORITIZE_SUBMISSIONS", prioritize.to_string());
+        if let Some(fault) = &self.transaction_fault {
+            if fault.node >= COMPOSE_NODES {
+                return Err(color_eyre::eyre::eyre!(
+                    "Cannot inject a fault in node {} of a {}-node network",
+                    fault.node,
+                    COMPOSE_NODES
+                ));
+            }
+            warn!(
+                "UNSAFE: node {} applies the fault {}",
+                fault.node, fault.fault
+            );
+            command.env(
+                format!("UNSAFE_TRANSACTION_FAULT_NODE{}", fault.node),
+                &fault.fault,
+            );
+        }
+        let status = command
+            .args(["up", "-d"])
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .status()
@@ -66,17 +484,11 @@ impl LocalNetworkOrchestrator {
     }
 
     pub fn stop_network(&self) -> Result<()> {
-        info!("Stopping Mysticeti network...");
-
-        // Get the orchestrator directory (parent of docker-compose.yml)
-        let orchestrator_dir = self
-            .docker_compose_path
-            .parent()
-            .ok_or_else(|| color_eyre::eyre::eyre!("Failed to get orchestrator directory"))?;
+        info!("Stopping Mysticeti network '{}'...", self.namespace.name());
 
-        let status = Command::new("docker")
-            .current_dir(orchestrator_dir)
-            .args(["compose", "down"])
+        let status = self
+            .compose_command()?
+            .args(["down"])
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .status()
@@ -90,21 +502,71 @@ impl LocalNetworkOrchestrator {
         Ok(())
     }
 
+    /// Stop every node at once and start them again on their existing databases, then poll
+    /// their `/stats` until all of them committed (or the timeout expires). Measures how long
+    /// the committee takes to recover from a total restart, replaying its stored state.
+    pub async fn restart_network(&self, timeout: Duration) -> Result<StartupReport> {
+        info!(
+            "Restarting every node of network '{}'...",
+            self.namespace.name()
+        );
+        for action in ["stop", "start"] {
+            let status = self
+                .compose_command()?
+                .arg(action)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .status()
+                .wrap_err_with(|| format!("Failed to {action} docker compose"))?;
+            if !status.success() {
+                return Err(color_eyre::eyre::eyre!(
+                    "Docker compose {} failed with status: {}",
+                    action,
+                    status
+                ));
+            }
+        }
+
+        // The statistics of the nodes restart with them.
+        let client = Client::new();
+        let deadline = Instant::now() + timeout;
+        let mut time_to_first_commit = vec![None; COMPOSE_NODES];
+        loop {
+            for (i, commit) in time_to_first_commit.iter_mut().enumerate() {
+                if commit.is_some() {
+                    continue;
+                }
+                let url = format!("{}/stats", self.namespace.rpc_url(i));
+                let stats: Option<serde_json::Value> = match client.get(&url).send().await {
+                    Ok(response) => response.json().await.ok(),
+                    Err(_) => None,
+                };
+                *commit = stats
+                    .and_then(|stats| stats["time_to_first_commit_ms"].as_u64())
+                    .map(|ms| ms as f64 / 1000.0);
+            }
+            if time_to_first_commit.iter().all(Option::is_some) || Instant::now() >= deadline {
+                break;
+            }
+            sleep(self.readiness_interval).await;
+        }
+
+        Ok(StartupReport {
+            startup_delay_ms: 0,
+            launch_offsets_s: vec![0.0; COMPOSE_NODES],
+            time_to_first_commit_s: time_to_first_commit,
+        })
+    }
+
     pub fn stop_network_thorough(&self) -> Result<()> {
         info!(
             "Performing thorough cleanup of Mysticeti network (removing volumes and containers)..."
         );
 
-        // Get the orchestrator directory (parent of docker-compose.yml)
-        let orchestrator_dir = self
-            .docker_compose_path
-            .parent()
-            .ok_or_else(|| color_eyre::eyre::eyre!("Failed to get orchestrator directory"))?;
-
         // Stop and remove containers with volumes
-        let status = Command::new("docker")
-            .current_dir(orchestrator_dir)
-            .args(["compose", "down", "-v"])
+        let status = self
+            .compose_command()?
+            .args(["down", "-v"])
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .status()
@@ -123,7 +585,7 @@ impl LocalNetworkOrchestrator {
                 "ls",
                 "-aq",
                 "--filter",
-                "label=com.docker.compose.project=mysticeti",
+                &format!("label=com.docker.compose.project={}", self.namespace.name()),
             ])
             .output()
             .wrap_err("Failed to list project containers")?;
@@ -160,49 +622,21 @@ impl LocalNetworkOrchestrator {
         Ok(())
     }
 
+    /// Poll the health of the nodes until all of them are ready, failing after `timeout`
+    /// seconds. Defaults to the docker-compose nodes of the namespace when no urls are
+    /// specified.
     pub async fn wait_for_network_ready(
         &self,
-        wait_time: u64,
+        timeout: u64,
         node_urls: Option<Vec<String>>,
     ) -> Result<()> {
-        info!("Waiting {} seconds for network to be ready...", wait_time);
-        sleep(Duration::from_secs(wait_time)).await;
-
-        // Check if nodes are responding
-        let client = Client::new();
-        let node_urls = node_urls.unwrap_or_else(|| {
-            vec![
-                "http://localhost:26657".to_string(),
-                "http://localhost:26658".to_string(),
-                "http://localhost:26659".to_string(),
-                "http://localhost:26660".to_string(),
-            ]
-        });
-
-        let mut all_nodes_ready = true;
-        for (i, url) in node_urls.iter().enumerate() {
-            match client.get(format!("{}/health", url)).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        info!("Node {} is ready at {}", i, url);
-                    } else {
-                        warn!("Node {} responded with status: {}", i, response.status());
-                        all_nodes_ready = false;
-                    }
-                }
-                Err(e) => {
-                    warn!("Node {} not ready yet: {}", i, e);
-                    all_nodes_ready = false;
-                }
-            }
-        }
+        let node_urls = node_urls.unwrap_or_else(|| self.namespace.rpc_urls());
+        let nodes: Vec<_> = node_urls.into_iter().enumerate().collect();
 
-        if !all_nodes_ready {
-            warn!("Some nodes are not ready yet. Network may still be initializing.");
-            info!("This is normal during startup. The network will continue to retry connections.");
-        }
-
-        Ok(())
+        ReadinessProbe::new(Duration::from_secs(timeout))
+            .with_interval(self.readiness_interval)
+            .wait(&Client::new(), &nodes)
+            .await
     }
 
     /// Get container logs for debugging
@@ -250,16 +684,42 @@ impl LocalNetworkOrchestrator {
         }
     }
 
+    /// Fail if a node container is no longer running (only in fail-fast mode). The logs of
+    /// the crashed node are saved to the crash log directory.
+    pub fn check_nodes_alive(&self) -> Result<()> {
+        let Some(log_dir) = &self.crash_log_dir else {
+            return Ok(());
+        };
+
+        for i in 0..COMPOSE_NODES {
+            let container_name = self.namespace.container_name(i);
+            if self.is_container_running(&container_name)? {
+                continue;
+            }
+
+            error!("Node {} crashed (container {} exited)", i, container_name);
+            std::fs::create_dir_all(log_dir)?;
+            let log_file = log_dir.join(format!("{}-crash.log", container_name));
+            match self.get_container_logs(&container_name) {
+                Ok(logs) => std::fs::write(&log_file, logs)?,
+                Err(e) => warn!("Failed to collect logs of node {}: {}", i, e),
+            }
+            return Err(color_eyre::eyre::eyre!(
+                "Node {} crashed during the benchmark (logs: {})",
+                i,
+                log_file.display()
+            ));
+        }
+        Ok(())
+    }
+
     /// Get container status for all nodes
     pub fn get_network_status(&self) -> Result<()> {
         info!("Checking network status...");
 
-        let container_names = vec![
-            "mysticeti-node0",
-            "mysticeti-node1",
-            "mysticeti-node2",
-            "mysticeti-node3",
-        ];
+        let container_names: Vec<_> = (0..COMPOSE_NODES)
+            .map(|i| self.namespace.container_name(i))
+            .collect();
 
         for container_name in &container_names {
             match self.is_container_running(container_name) {
@@ -272,86 +732,802 @@ impl LocalNetworkOrchestrator {
         Ok(())
     }
 
+    /// Apply a fault event to the containers of the nodes: a crash kills them (they are not
+    /// restarted by docker), a partition disconnects them from the docker network.
+    pub fn apply_chaos(&self, action: &ChaosAction) -> Result<()> {
+        let network = self.namespace.docker_network();
+        for node in action.nodes() {
+            let container = self.namespace.container_name(*node);
+            let ip = self.namespace.node_ip(*node);
+            let args: Vec<&str> = match action {
+                ChaosAction::Crash(_) => vec!["kill", &container],
+                ChaosAction::Recover(_) => vec!["start", &container],
+                ChaosAction::Partition(_) => vec!["network", "disconnect", &network, &container],
+                ChaosAction::Heal(_) => {
+                    vec!["network", "connect", "--ip", &ip, &network, &container]
+                }
+            };
+            let output = Command::new("docker")
+                .args(&args)
+                .output()
+                .wrap_err_with(|| format!("Failed to run docker {}", args.join(" ")))?;
+            if !output.status.success() {
+                return Err(color_eyre::eyre::eyre!(
+                    "Failed to {} node {}: {}",
+                    action,
+                    node,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The number of transactions committed by a node since it started, if it is reachable.
+    async fn committed_transactions(&self, client: &Client, node: usize) -> Option<u64> {
+        let url = format!("{}/stats", self.namespace.rpc_url(node));
+        let response = client
+            .get(&url)
+            .timeout(Duration::from_millis(500))
+            .send()
+            .await
+            .ok()?;
+        let stats: serde_json::Value = response.json().await.ok()?;
+        stats["committed_transactions"].as_u64()
+    }
+
+    /// Apply the fault schedule to the network for `duration`, logging every event and
+    /// sampling the committed throughput every second. A failed event is recorded and the run
+    /// goes on. The nodes still faulted when the run ends are recovered.
+    pub async fn run_chaos(
+        &self,
+        schedule: &ChaosSchedule,
+        duration: Duration,
+    ) -> Result<ChaosTimeline> {
+        info!(
+            "Running the fault schedule for {}s (cycle of {}s)",
+            duration.as_secs(),
+            schedule.period().as_secs()
+        );
+        let client = Client::new();
+        let mut events = schedule.events_until(duration).into_iter().peekable();
+        let mut timeline = ChaosTimeline::default();
+        let mut crashed = BTreeSet::new();
+        let mut partitioned = BTreeSet::new();
+        let mut committed = Vec::with_capacity(COMPOSE_NODES);
+        for node in 0..COMPOSE_NODES {
+            committed.push(self.committed_transactions(&client, node).await);
+        }
+
+        let start = Instant::now();
+        for second in 1..=duration.as_secs() {
+            let sample_at = Duration::from_secs(second);
+            while events.peek().is_some_and(|(offset, _)| *offset < sample_at) {
+                let (offset, action) = events.next().expect("An event is due");
+                sleep(offset.saturating_sub(start.elapsed())).await;
+                let offset_s = start.elapsed().as_secs_f64();
+                info!("Chaos event at {:.1}s: {}", offset_s, action);
+                let error = match self.apply_chaos(&action) {
+                    Ok(()) => {
+                        for node in action.nodes() {
+                            match &action {
+                                ChaosAction::Crash(_) => crashed.insert(*node),
+                                ChaosAction::Recover(_) => crashed.remove(node),
+                                ChaosAction::Partition(_) => partitioned.insert(*node),
+                                ChaosAction::Heal(_) => partitioned.remove(node),
+                            };
+                        }
+                        None
+                    }
+                    Err(e) => {
+                        warn!("Chaos event '{}' failed: {}", action, e);
+                        Some(e.to_string())
+                    }
+                };
+                timeline.events.push(ChaosEvent {
+                    offset_s,
+                    action,
+                    error,
+                });
+            }
+
+            sleep(sample_at.saturating_sub(start.elapsed())).await;
+            // The throughput of the second is that of the node committing the most: the
+            // faulted nodes do not commit, and the counters restart with the nodes.
+            let mut throughput = 0;
+            for (node, previous) in committed.iter_mut().enumerate() {
+                let current = self.committed_transactions(&client, node).await;
+                if let (Some(previous), Some(current)) = (*previous, current) {
+                    throughput = throughput.max(current.saturating_sub(previous));
+                }
+                *previous = current;
+            }
+            timeline.throughput.push(throughput);
+        }
+
+        let faulted = [
+            ChaosAction::Recover(crashed.into_iter().collect()),
+            ChaosAction::Heal(partitioned.into_iter().collect()),
+        ];
+        for action in faulted {
+            if action.nodes().is_empty() {
+                continue;
+            }
+            let offset_s = start.elapsed().as_secs_f64();
+            info!("Chaos run over, at {:.1}s: {}", offset_s, action);
+            let error = self.apply_chaos(&action).err().map(|e| e.to_string());
+            timeline.events.push(ChaosEvent {
+                offset_s,
+                action,
+                error,
+            });
+        }
+        Ok(timeline)
+    }
+
+    /// Submit transactions to the nodes at the specified rate (tx/s), which must be positive.
+    /// Returns the events of the circuit breaker and the adjustments of the utilization
+    /// controller (if any).
     pub async fn simulate_transactions(
         &self,
         num_transactions: usize,
         transaction_size: usize,
         transaction_rate: usize,
-    ) -> Result<()> {
+    ) -> Result<SimulationReport> {
         info!("Starting transaction simulation...");
         info!(
             "Parameters: {} transactions, {} bytes each, {} tx/s",
             num_transactions, transaction_size, transaction_rate
         );
 
+        if transaction_rate == 0 {
+            bail!("The transaction rate must be positive");
+        }
         let client = Client::new();
-        let delay = Duration::from_millis((1000 / transaction_rate) as u64);
         let mut successful_txs = 0;
         let mut failed_txs = 0;
+        let mut consecutive_failures = [0usize; COMPOSE_NODES];
+        let log_interval = self.logging.interval(transaction_rate);
+        if log_interval.is_none() {
+            info!("Per-transaction logging disabled, only the summary is logged");
+        }
+        let mut breaker = self.circuit_breaker.clone();
+        if let Some(breaker) = &mut breaker {
+            breaker.reset();
+        }
+        let mut controller = self.utilization_controller.clone();
+        if let Some(controller) = &mut controller {
+            controller.reset(transaction_rate);
+        }
+        // With a controller or a schedule, run for as long as the simulation would at the
+        // initial rate.
+        let schedule = self.rate_schedule.as_ref();
+        let timed = controller.is_some() || schedule.is_some();
+        let planned_duration =
+            Duration::from_secs_f64(num_transactions as f64 / transaction_rate as f64);
+        let limit = match timed {
+            true => usize::MAX,
+            false => num_transactions,
+        };
+        let mut accepted_at = Vec::new();
         let start_time = Instant::now();
+        let mut next_adjustment = start_time;
+        let mut pacer = Pacer::new(transaction_rate as f64, tokio::time::Instant::now());
 
-        // Generate random transaction data
-        let tx_data = vec![0u8; transaction_size];
-
-        for i in 0..num_transactions {
-            // Round-robin between nodes
-            let node_port = 26657 + (i % 4) as u16;
-            let url = format!("http://localhost:{}/broadcast_tx_async", node_port);
-            let payload = json!({
-                "transaction": base64::engine::general_purpose::STANDARD.encode(&tx_data)
-            });
+        // Every transaction is unique (across runs too), so that its id identifies it.
+        let generator = TransactionGenerator::new(self.transaction_format, transaction_size)?
+            .with_high_priority_ratio(self.high_priority_ratio)?;
+        let mut pool = TransactionPool::new(generator, self.pool_size);
+        let mut selector = self.node_selector(&client).await?;
+        let mut submitted_transactions = Vec::new();
+        let mut compression_stats = CompressionStats::default();
 
-            match client.post(&url).json(&payload).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        successful_txs += 1;
-                        if i % 100 == 0 {
-                            info!("Submitted transaction {} to port {}", i, node_port);
+        // Up to `concurrency` requests are in flight, each in its own task. Their outcomes are
+        // recorded here, in completion order.
+        let mut in_flight = FuturesUnordered::new();
+        let mut requests = 0;
+        let mut next_liveness_check = transaction_rate;
+        let mut i = 0;
+        loop {
+            let finished = i >= limit || (timed && start_time.elapsed() >= planned_duration);
+            if in_flight.len() >= self.concurrency || (finished && !in_flight.is_empty()) {
+                let RequestOutcome {
+                    node,
+                    first,
+                    submission_times_us,
+                    result,
+                } = in_flight
+                    .next()
+                    .await
+                    .expect("Requests are in flight")
+                    .wrap_err("Submission task failed")?;
+                let transactions = submission_times_us.len();
+                let node_port = self.namespace.rpc_port(node);
+                let success = match result {
+                    RequestResult::Accepted(body) => {
+                        consecutive_failures[node] = 0;
+                        successful_txs += transactions;
+                        if schedule.is_some() {
+                            let elapsed = start_time.elapsed();
+                            accepted_at.extend(std::iter::repeat_n(elapsed, transactions));
+                        }
+                        if self.track_transactions {
+                            for (k, submitted_at_us) in submission_times_us.into_iter().enumerate()
+                            {
+                                let id = body["transaction_ids"][k]
+                                    .as_str()
+                                    .or(body["transaction_id"].as_str());
+                                let sequence = (first + k) as u64;
+                                match id {
+                                    Some(id) => submitted_transactions.push(SubmittedTransaction {
+                                        node,
+                                        transaction_id: id.to_string(),
+                                        submitted_at_us,
+                                        priority: pool.priority(sequence),
+                                    }),
+                                    None => warn!("Node {} did not return a transaction id", node),
+                                }
+                            }
+                        }
+                        if let Some(progress) = &self.progress {
+                            for _ in 0..transactions {
+                                progress.record_success();
+                            }
+                        }
+                        if log_interval.is_some_and(|interval| {
+                            (first..first + transactions).any(|k| k % interval == 0)
+                        }) {
+                            info!("Submitted transaction {} to port {}", first, node_port);
                         }
-                    } else {
-                        failed_txs += 1;
-                        warn!(
-                            "Transaction {} failed with status: {}",
-                            i,
-                            response.status()
-                        );
+                        true
                     }
+                    RequestResult::Rejected(status) => {
+                        consecutive_failures[node] = 0;
+                        failed_txs += transactions;
+                        if let Some(progress) = &self.progress {
+                            for _ in 0..transactions {
+                                progress.record_failure();
+                            }
+                        }
+                        if log_interval.is_some() {
+                            warn!("Transaction {} failed with status: {}", first, status);
+                        }
+                        false
+                    }
+                    RequestResult::Failed(e) => {
+                        failed_txs += transactions;
+                        if let Some(progress) = &self.progress {
+                            for _ in 0..transactions {
+                                progress.record_failure();
+                            }
+                        }
+                        if log_interval.is_some() {
+                            warn!("Transaction {} failed: {}", first, e);
+                        }
+                        consecutive_failures[node] += 1;
+                        if consecutive_failures[node] >= CRASH_CHECK_FAILURES {
+                            self.check_nodes_alive()?;
+                        }
+                        false
+                    }
+                };
+
+                // Back off while the network is failing
+                if let Some(pause) = breaker.as_mut().and_then(|b| b.record(success)) {
+                    warn!(
+                        "Circuit breaker open, pausing submission for {:.1}s",
+                        pause.as_secs_f64()
+                    );
+                    sleep(pause).await;
+                    pacer.resume(tokio::time::Instant::now());
                 }
-                Err(e) => {
-                    failed_txs += 1;
-                    warn!("Transaction {} failed: {}", i, e);
+                continue;
+            }
+            if finished {
+                break;
+            }
+
+            // Periodically make sure no node crashed
+            if i >= next_liveness_check {
+                self.check_nodes_alive()?;
+                next_liveness_check = i + transaction_rate;
+            }
+
+            // Periodically adjust the rate to the observed utilization of the commits
+            let due = Instant::now() >= next_adjustment;
+            if let Some(controller) = controller.as_mut().filter(|_| due) {
+                let mut summary = HistogramSummary::default();
+                let metrics = self.scrape_metrics(&client).await;
+                for text in &metrics {
+                    summary.merge(&HistogramSummary::from_prometheus(
+                        text,
+                        TRANSACTIONS_PER_COMMIT,
+                    ));
+                }
+                let rate = controller.update(summary, metrics.len(), tokio::time::Instant::now());
+                pacer.set_rate(rate as f64);
+                next_adjustment = Instant::now() + controller.interval();
+            }
+
+            // Follow the rate of the schedule
+            if let Some(schedule) = schedule {
+                pacer.set_rate(schedule.rate_at(start_time.elapsed()));
+            }
+
+            // Accumulate the transactions of the request at the submission rate, until the
+            // batch is full or its window closes. Their latency includes the wait.
+            let first = i;
+            let window_start = Instant::now();
+            let mut batch = Vec::new();
+            let mut submission_times_us = Vec::new();
+            loop {
+                tokio::time::sleep_until(pacer.next_due(tokio::time::Instant::now())).await;
+                batch.push(pool.transaction(i as u64).to_string());
+                submission_times_us.push(now_us());
+                i += 1;
+                let closed = batch.len() >= self.client_batch_size
+                    || window_start.elapsed() >= self.client_batch_window
+                    || (timed && start_time.elapsed() >= planned_duration);
+                if closed || i >= limit {
+                    break;
                 }
             }
 
-            // Rate limiting
-            sleep(delay).await;
+            let node = selector.select();
+            let node_port = self.namespace.rpc_port(node);
+            let url = |endpoint: &str| format!("http://localhost:{}/{}", node_port, endpoint);
+            let request = match (self.compression, batch.len()) {
+                // Compressed bodies go through the batch endpoint, even with a single
+                // transaction.
+                (Some(compression), _) => {
+                    let body = json!({ "transactions": batch }).to_string();
+                    client
+                        .post(url("broadcast_tx_batch"))
+                        .header(CONTENT_TYPE, "application/json")
+                        .header(CONTENT_ENCODING, compression.content_encoding())
+                        .body(compression.compress(body.as_bytes(), &mut compression_stats))
+                }
+                (None, 1) => client
+                    .post(url("broadcast_tx_async"))
+                    .json(&json!({ "transaction": batch[0] })),
+                (None, _) => client
+                    .post(url("broadcast_tx_batch"))
+                    .json(&json!({ "transactions": batch })),
+            };
+
+            requests += 1;
+            let track_transactions = self.track_transactions;
+            in_flight.push(tokio::spawn(async move {
+                let result = match request.send().await {
+                    Ok(response) if response.status().is_success() => {
+                        let body = match track_transactions {
+                            true => response.json().await.unwrap_or_default(),
+                            false => serde_json::Value::Null,
+                        };
+                        RequestResult::Accepted(body)
+                    }
+                    Ok(response) => RequestResult::Rejected(response.status()),
+                    Err(e) => RequestResult::Failed(e),
+                };
+                RequestOutcome {
+                    node,
+                    first,
+                    submission_times_us,
+                    result,
+                }
+            }));
         }
 
         let duration = start_time.elapsed();
         let actual_rate = successful_txs as f64 / duration.as_secs_f64();
+        let request_rate = requests as f64 / duration.as_secs_f64();
 
         info!("Transaction simulation completed!");
         info!("Duration: {:.2}s", duration.as_secs_f64());
         info!("Successful transactions: {}", successful_txs);
         info!("Failed transactions: {}", failed_txs);
         info!("Actual rate: {:.2} tx/s", actual_rate);
+        info!("Request rate: {:.2} requests/s", request_rate);
+        let schedule_tracking =
+            schedule.map(|schedule| ScheduleTracking::new(schedule, &accepted_at, duration));
+        if let Some(tracking) = &schedule_tracking {
+            info!(
+                "Mean deviation from the rate schedule: {:.1}%",
+                tracking.mean_error() * 100.0
+            );
+        }
 
-        Ok(())
+        Ok(SimulationReport {
+            breaker_events: breaker
+                .map(|breaker| breaker.events().to_vec())
+                .unwrap_or_default(),
+            rate_adjustments: controller
+                .map(|controller| controller.adjustments().to_vec())
+                .unwrap_or_default(),
+            submitted_transactions,
+            compression: self.compression.map(|_| compression_stats),
+            schedule_tracking,
+            transaction_rate: actual_rate,
+            request_rate,
+        })
     }
 
-    /// Collect metrics from containers (placeholder for future implementation)
-    pub async fn collect_metrics(&self) -> Result<()> {
-        info!("Collecting metrics from containers...");
+    /// Verify that no transaction acknowledged as committed was lost. A transaction is
+    /// acknowledged once the node it was submitted to commits it (waiting up to `timeout` for
+    /// in-flight transactions to be committed); every node must then hold every acknowledged
+    /// transaction in its committed state.
+    pub async fn verify_commits(
+        &self,
+        submitted: &[SubmittedTransaction],
+        timeout: Duration,
+    ) -> Result<CommitVerification> {
+        info!(
+            "Verifying the commit of {} submitted transactions...",
+            submitted.len()
+        );
+        let client = Client::new();
+        let mut verification = CommitVerification {
+            submitted: submitted.len(),
+            ..Default::default()
+        };
+
+        // Wait for the nodes to acknowledge the commit of the transactions submitted to them
+        let mut pending: Vec<Vec<String>> = vec![Vec::new(); COMPOSE_NODES];
+        for transaction in submitted {
+            pending[transaction.node].push(transaction.transaction_id.clone());
+        }
+        let mut acknowledged = Vec::new();
+        let deadline = Instant::now() + timeout;
+        loop {
+            for (node, ids) in pending.iter_mut().enumerate() {
+                if ids.is_empty() {
+                    continue;
+                }
+                let url = self.namespace.rpc_url(node);
+                match lookup_transactions(&client, &url, ids).await {
+                    Ok(statuses) => ids.retain(|id| match statuses.get(id) {
+                        Some(Some(_)) => {
+                            acknowledged.push(id.clone());
+                            false
+                        }
+                        _ => true,
+                    }),
+                    Err(e) => warn!("Failed to look up transactions on node {}: {}", node, e),
+                }
+            }
+            if pending.iter().all(|ids| ids.is_empty()) || Instant::now() >= deadline {
+                break;
+            }
+            sleep(self.readiness_interval).await;
+        }
+        verification.acknowledged = acknowledged.len();
+        let unacknowledged: usize = pending.iter().map(|ids| ids.len()).sum();
+        if unacknowledged > 0 {
+            warn!(
+                "{} transactions were not acknowledged as committed within {:.1}s",
+                unacknowledged,
+                timeout.as_secs_f64()
+            );
+        }
+
+        // Every node must hold every acknowledged transaction
+        for node in 0..COMPOSE_NODES {
+            let url = self.namespace.rpc_url(node);
+            let statuses = match lookup_transactions(&client, &url, &acknowledged).await {
+                Ok(statuses) => Some(statuses),
+                Err(e) => {
+                    warn!("Failed to look up transactions on node {}: {}", node, e);
+                    None
+                }
+            };
+            verification.check_node(node, &acknowledged, statuses.as_ref());
+        }
 
-        // TODO: Implement actual metrics collection from containers
-        // This could involve:
-        // 1. Executing commands inside containers to get metrics
-        // 2. Reading log files from containers
-        // 3. Using container monitoring APIs
+        match verification.is_consistent() {
+            true => info!(
+                "All {} acknowledged transactions are committed on every node",
+                verification.acknowledged
+            ),
+            false => {
+                for missing in &verification.missing {
+                    error!(
+                        "Node {} lost acknowledged transaction {}",
+                        missing.node, missing.transaction_id
+                    );
+                }
+                for node in &verification.unreachable {
+                    error!("Node {} could not be verified", node);
+                }
+            }
+        }
+        Ok(verification)
+    }
 
-        // For now, just check container status
+    /// Write the latency of the sampled transactions: the time between their submission and
+    /// their commit by the node they were submitted to (waiting up to `timeout` for in-flight
+    /// transactions to be committed). Transactions that are not committed in time are skipped.
+    /// Returns the number of samples written.
+    pub async fn collect_latency_samples<W: Write>(
+        &self,
+        submitted: &[SubmittedTransaction],
+        writer: &mut SampleWriter<W>,
+        timeout: Duration,
+    ) -> Result<usize> {
+        let sampled: Vec<_> = submitted.iter().filter(|_| writer.sample()).collect();
+        info!(
+            "Collecting the latency of {} sampled transactions (out of {})...",
+            sampled.len(),
+            submitted.len()
+        );
+        // Write the samples in submission order
+        for (_, sample) in self.commit_latencies(&sampled, timeout).await {
+            writer.write(&sample)?;
+        }
+        Ok(writer.written())
+    }
+
+    /// Summarize the commit latency of the submitted transactions by priority (waiting up to
+    /// `timeout` for in-flight transactions to be committed).
+    pub async fn priority_latencies(
+        &self,
+        submitted: &[SubmittedTransaction],
+        timeout: Duration,
+    ) -> Vec<PriorityLatency> {
+        info!(
+            "Collecting the latency of {} transactions by priority...",
+            submitted.len()
+        );
+        let transactions: Vec<_> = submitted.iter().collect();
+        let samples = self.commit_latencies(&transactions, timeout).await;
+        PriorityLatency::from_samples(&samples)
+    }
+
+    /// Wait (up to `timeout`) for the nodes the transactions were submitted to to commit them,
+    /// and return the priority and latency sample of every committed transaction, in
+    /// submission order. Transactions that are not committed in time are skipped.
+    async fn commit_latencies(
+        &self,
+        transactions: &[&SubmittedTransaction],
+        timeout: Duration,
+    ) -> Vec<(u8, LatencySample)> {
+        let client = Client::new();
+        let mut pending: Vec<Vec<String>> = vec![Vec::new(); COMPOSE_NODES];
+        for transaction in transactions {
+            pending[transaction.node].push(transaction.transaction_id.clone());
+        }
+        let mut commit_times: Vec<HashMap<String, u64>> = vec![HashMap::new(); COMPOSE_NODES];
+        let deadline = Instant::now() + timeout;
+        loop {
+            for (node, ids) in pending.iter_mut().enumerate() {
+                if ids.is_empty() {
+                    continue;
+                }
+                let url = self.namespace.rpc_url(node);
+                match lookup_commit_times(&client, &url, ids).await {
+                    Ok(times) => {
+                        ids.retain(|id| !times.contains_key(id));
+                        commit_times[node].extend(times);
+                    }
+                    Err(e) => warn!("Failed to look up transactions on node {}: {}", node, e),
+                }
+            }
+            if pending.iter().all(|ids| ids.is_empty()) || Instant::now() >= deadline {
+                break;
+            }
+            sleep(self.readiness_interval).await;
+        }
+
+        let uncommitted: usize = pending.iter().map(|ids| ids.len()).sum();
+        if uncommitted > 0 {
+            warn!(
+                "Skipped {} transactions not committed within {:.1}s",
+                uncommitted,
+                timeout.as_secs_f64()
+            );
+        }
+        transactions
+            .iter()
+            .filter_map(|transaction| {
+                let committed_at_us = commit_times[transaction.node]
+                    .get(&transaction.transaction_id)
+                    .copied()?;
+                let sample = LatencySample {
+                    submitted_at_us: transaction.submitted_at_us,
+                    committed_at_us,
+                };
+                Some((transaction.priority, sample))
+            })
+            .collect()
+    }
+
+    /// Make the selector spreading the load across the nodes. Weighted distributions read the
+    /// stake of the nodes from the committee of the first one.
+    async fn node_selector(&self, client: &Client) -> Result<NodeSelector> {
+        match self.distribution {
+            LoadDistribution::Equal => Ok(NodeSelector::equal(COMPOSE_NODES)),
+            LoadDistribution::Weighted => {
+                let authorities: Vec<_> = (0..COMPOSE_NODES).collect();
+                let stakes = fetch_stakes(client, &self.namespace.rpc_url(0), &authorities).await?;
+                info!("Distributing the load by stake: {:?}", stakes);
+                NodeSelector::weighted(stakes)
+            }
+        }
+    }
+
+    /// Compare the wall clock of every node with the orchestrator's, warning about the nodes
+    /// whose skew exceeds the threshold.
+    pub async fn measure_clock_skew(&self, threshold: Duration) -> Vec<ClockSkew> {
+        let nodes: Vec<_> = self.namespace.rpc_urls().into_iter().enumerate().collect();
+        measure_clock_skew(&Client::new(), &nodes, threshold).await
+    }
+
+    /// Query the `/peers` endpoint of every node and print which committee members each node
+    /// is connected to. Returns the number of nodes missing at least one connection.
+    pub async fn probe_peers(&self) -> Result<usize> {
+        let client = Client::new();
+        let mut partitioned = 0;
+        for i in 0..COMPOSE_NODES {
+            let url = format!("{}/peers", self.namespace.rpc_url(i));
+            let report: serde_json::Value = match client.get(&url).send().await {
+                Ok(response) if response.status().is_success() => response.json().await?,
+                Ok(response) => {
+                    println!(
+                        "Node {}: /peers responded with status {}",
+                        i,
+                        response.status()
+                    );
+                    partitioned += 1;
+                    continue;
+                }
+                Err(e) => {
+                    println!("Node {}: unreachable ({})", i, e);
+                    partitioned += 1;
+                    continue;
+                }
+            };
+
+            let peers = report["peers"].as_array().cloned().unwrap_or_default();
+            let (connected, disconnected): (Vec<_>, Vec<_>) = peers
+                .iter()
+                .partition(|peer| peer["connected"].as_bool().unwrap_or(false));
+            println!(
+                "Node {}: connected to {}/{} peers",
+                i,
+                connected.len(),
+                peers.len()
+            );
+            for peer in disconnected {
+                println!(
+                    "  no connection to node {} ({} at {})",
+                    peer["authority_index"], peer["hostname"], peer["address"]
+                );
+            }
+            if connected.len() < peers.len() {
+                partitioned += 1;
+            }
+        }
+        Ok(partitioned)
+    }
+
+    /// Query the `/errors` endpoint of every node and print its most recent errors. Returns
+    /// the number of nodes that recorded at least one error.
+    pub async fn probe_errors(&self) -> Result<usize> {
+        let client = Client::new();
+        let mut failing = 0;
+        for i in 0..COMPOSE_NODES {
+            let url = format!("{}/errors", self.namespace.rpc_url(i));
+            let report: serde_json::Value = match client.get(&url).send().await {
+                Ok(response) if response.status().is_success() => response.json().await?,
+                Ok(response) => {
+                    println!(
+                        "Node {}: /errors responded with status {}",
+                        i,
+                        response.status()
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    println!("Node {}: unreachable ({})", i, e);
+                    continue;
+                }
+            };
+
+            let total = report["total"].as_u64().unwrap_or(0);
+            let errors = report["errors"].as_array().cloned().unwrap_or_default();
+            let describe = |error: &serde_json::Value| {
+                format!(
+                    "{}: {}",
+                    error["source"].as_str().unwrap_or_default(),
+                    error["message"].as_str().unwrap_or_default()
+                )
+            };
+            match errors.first() {
+                Some(last) => println!(
+                    "Node {}: {} errors, last error: {}",
+                    i,
+                    total,
+                    describe(last)
+                ),
+                None => println!("Node {}: {} errors", i, total),
+            }
+            for error in errors.iter().skip(1) {
+                println!("  {}", describe(error));
+            }
+            if total > 0 {
+                failing += 1;
+            }
+        }
+        Ok(failing)
+    }
+
+    /// Collect the prometheus metrics exposed by every node. Nodes whose metrics cannot be
+    /// scraped are skipped.
+    pub async fn collect_metrics(&self) -> Result<Vec<String>> {
+        info!("Collecting metrics from containers...");
         self.get_network_status()?;
 
-        Ok(())
+        Ok(self.scrape_metrics(&Client::new()).await)
+    }
+
+    /// Scrape the prometheus metrics of every node, skipping the nodes that cannot be scraped.
+    async fn scrape_metrics(&self, client: &Client) -> Vec<String> {
+        let mut metrics = Vec::new();
+        for i in 0..COMPOSE_NODES {
+            let url = format!("{}/metrics", self.namespace.rpc_url(i));
+            match client.get(&url).send().await {
+                Ok(response) if response.status().is_success() => match response.text().await {
+                    Ok(text) => metrics.push(text),
+                    Err(e) => warn!("Failed to read metrics of node {}: {}", i, e),
+                },
+                Ok(response) => {
+                    warn!(
+                        "Node {} metrics responded with status: {}",
+                        i,
+                        response.status()
+                    )
+                }
+                Err(e) => warn!("Failed to scrape metrics of node {}: {}", i, e),
+            }
+        }
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NetworkNamespace;
+
+    #[test]
+    fn default_namespace_matches_compose_defaults() {
+        let namespace = NetworkNamespace::default();
+        assert_eq!(namespace.container_name(0), "mysticeti-node0");
+        assert_eq!(namespace.rpc_url(3), "http://localhost:26660");
+
+        let env = namespace.compose_env();
+        let get = |key: &str| env.iter().find(|(k, _)| k == key).unwrap().1.clone();
+        assert_eq!(get("NETWORK_SUBNET"), "172.20.0.0/16");
+        assert_eq!(get("DATA_DIR"), "./data");
+        assert_eq!(
+            get("PEER_ADDRESSES_NODE0"),
+            "172.20.0.11:26657,172.20.0.12:26657,172.20.0.13:26657"
+        );
+    }
+
+    #[test]
+    fn namespaces_do_not_collide() {
+        let a = NetworkNamespace::new("baseline", 0).unwrap();
+        let b = NetworkNamespace::new("candidate", 1).unwrap();
+        assert_ne!(a.container_name(0), b.container_name(0));
+        assert_eq!(b.rpc_port(0), 26757);
+        assert!(a.rpc_urls().iter().all(|url| !b.rpc_urls().contains(url)));
+
+        let env = b.compose_env();
+        let get = |key: &str| env.iter().find(|(k, _)| k == key).unwrap().1.clone();
+        assert_eq!(get("NODE1_IP"), "172.21.0.11");
+        assert_eq!(get("NODE1_ABCI_PORT"), "26771");
+        assert_eq!(get("DATA_DIR"), "./data/candidate");
+
+        assert!(NetworkNamespace::new("Not Valid", 2).is_err());
+        assert!(NetworkNamespace::new("late", NetworkNamespace::MAX_SLOTS).is_err());
     }
 }
@@ -0,0 +1,63 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// How much the transaction simulators log while submitting. Logging every submission at
+/// high rates produces enough output to throttle the run itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubmissionLogging {
+    /// Log every `DEFAULT_INTERVAL`-th submission, unless the rate exceeds `QUIET_RATE`.
+    #[default]
+    Auto,
+    /// Log every n-th submission (and every failure).
+    Every(usize),
+    /// Only log the final summary.
+    Quiet,
+}
+
+impl SubmissionLogging {
+    /// The default number of submissions between two log lines.
+    pub const DEFAULT_INTERVAL: usize = 100;
+    /// The rate (tx/s) above which the simulators only log the final summary by default.
+    pub const QUIET_RATE: usize = 1_000;
+
+    /// The logging mode selected by the command line flags.
+    pub fn from_flags(quiet: bool, interval: Option<usize>) -> Self {
+        match (quiet, interval) {
+            (true, _) => Self::Quiet,
+            (false, Some(interval)) => Self::Every(interval.max(1)),
+            (false, None) => Self::Auto,
+        }
+    }
+
+    /// The number of submissions between two log lines at the specified rate, or `None` if
+    /// only the final summary is logged.
+    pub fn interval(&self, transaction_rate: usize) -> Option<usize> {
+        match self {
+            Self::Auto if transaction_rate > Self::QUIET_RATE => None,
+            Self::Auto => Some(Self::DEFAULT_INTERVAL),
+            Self::Every(interval) => Some(*interval),
+            Self::Quiet => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SubmissionLogging;
+
+    #[test]
+    fn resolve_interval() {
+        let auto = SubmissionLogging::from_flags(false, None);
+        assert_eq!(
+            auto.interval(100),
+            Some(SubmissionLogging::DEFAULT_INTERVAL)
+        );
+        assert_eq!(auto.interval(10_000), None);
+
+        let every = SubmissionLogging::from_flags(false, Some(10));
+        assert_eq!(every.interval(10_000), Some(10));
+
+        let quiet = SubmissionLogging::from_flags(true, Some(10));
+        assert_eq!(quiet.interval(100), None);
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// How late the pacer lets the submission fall behind before it stops catching up.
+const MAX_LATENESS: Duration = Duration::from_secs(1);
+
+/// Paces the submission of transactions at a target rate. Each transaction is due one
+/// interval after the previous one rather than one interval after it was sent, so the time
+/// spent sending the requests (and the coarse timer) does not slow the rate down: a late
+/// transaction is sent right away and the next ones catch up. After a stall longer than
+/// `MAX_LATENESS`, the missed transactions are skipped rather than sent in a burst.
+#[derive(Clone, Debug)]
+pub struct Pacer {
+    /// The time between two transactions.
+    interval: Duration,
+    /// The time at which the next transaction is due.
+    next: Instant,
+}
+
+impl Pacer {
+    /// Pace at `rate` tx/s, from `now`. The rate must be positive.
+    pub fn new(rate: f64, now: Instant) -> Self {
+        Self {
+            interval: Self::interval(rate),
+            next: now,
+        }
+    }
+
+    /// Change the rate (tx/s), from the next transaction on. The rate must be positive.
+    pub fn set_rate(&mut self, rate: f64) {
+        self.interval = Self::interval(rate);
+    }
+
+    /// Resume pacing from `now` after a deliberate pause, without catching up.
+    pub fn resume(&mut self, now: Instant) {
+        self.next = now;
+    }
+
+    /// The time at which the next transaction is due, scheduling the one after it.
+    pub fn next_due(&mut self, now: Instant) -> Instant {
+        let earliest = now.checked_sub(MAX_LATENESS).unwrap_or(now);
+        let due = self.next.max(earliest);
+        self.next = due + self.interval;
+        due
+    }
+
+    fn interval(rate: f64) -> Duration {
+        assert!(rate > 0.0, "The transaction rate must be positive");
+        Duration::from_secs_f64(1.0 / rate)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use super::Pacer;
+
+    #[test]
+    fn pace_at_the_target_rate() {
+        let start = Instant::now();
+        let mut pacer = Pacer::new(1000.0, start);
+        assert_eq!(pacer.next_due(start), start);
+        assert_eq!(pacer.next_due(start), start + Duration::from_millis(1));
+
+        // A slow request does not shift the schedule: the late transaction is due right away.
+        let late = start + Duration::from_millis(5);
+        assert_eq!(pacer.next_due(late), start + Duration::from_millis(2));
+        assert_eq!(pacer.next_due(late), start + Duration::from_millis(3));
+
+        pacer.set_rate(100.0);
+        assert_eq!(pacer.next_due(late), start + Duration::from_millis(4));
+        assert_eq!(pacer.next_due(late), start + Duration::from_millis(14));
+    }
+
+    #[test]
+    fn do_not_burst_after_a_stall() {
+        let start = Instant::now();
+        let mut pacer = Pacer::new(10.0, start);
+        pacer.next_due(start);
+
+        // Only the last second of a long stall is made up for.
+        let stalled = start + Duration::from_secs(10);
+        assert_eq!(pacer.next_due(stalled), start + Duration::from_secs(9));
+
+        pacer.resume(stalled);
+        assert_eq!(pacer.next_due(stalled), stalled);
+        assert_eq!(
+            pacer.next_due(stalled),
+            stalled + Duration::from_millis(100)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use color_eyre::eyre::Result;
+use reqwest::Client;
+use tokio::time::{Instant, sleep};
+use tracing::{debug, info};
+
+/// Polls the `/health` endpoint of nodes until all of them are healthy or a timeout expires.
+/// By default a node is only ready once it also committed, which requires the committee to
+/// have formed a quorum: healthy nodes may not be able to commit yet. Shared by the local and
+/// remote orchestrators.
+#[derive(Clone, Copy, Debug)]
+pub struct ReadinessProbe {
+    /// The maximum time to wait for all nodes to become healthy.
+    timeout: Duration,
+    /// The delay between two polls of the nodes not yet healthy.
+    interval: Duration,
+    /// Whether nodes must have committed (according to their `/stats`) to be ready.
+    require_commit: bool,
+}
+
+impl ReadinessProbe {
+    /// The default delay between two polls.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+    /// The maximum time to wait for the answer to a single health check.
+    const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            interval: Self::DEFAULT_INTERVAL,
+            require_commit: true,
+        }
+    }
+
+    /// Set the delay between two polls.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set whether nodes must have committed to be ready. Disable it to only wait for the nodes
+    /// to be up, for instance when fewer than a quorum of them run.
+    pub fn with_commit_check(mut self, require_commit: bool) -> Self {
+        self.require_commit = require_commit;
+        self
+    }
+
+    /// Wait until every node (identified by its index and base url) answers its health check.
+    /// Fails with the list of unhealthy nodes if the timeout expires first.
+    pub async fn wait(&self, client: &Client, nodes: &[(usize, String)]) -> Result<()> {
+        info!(
+            "Waiting up to {}s for {} nodes to be ready...",
+            self.timeout.as_secs(),
+            nodes.len()
+        );
+        let deadline = Instant::now() + self.timeout;
+        let mut pending: Vec<_> = nodes.iter().collect();
+
+        loop {
+            let mut failures = Vec::new();
+            for node in pending {
+                let (index, url) = node;
+                match self.probe(client, url).await {
+                    Ok(()) => info!("Node {} is ready at {}", index, url),
+                    Err(reason) => {
+                        debug!("Node {} not ready yet: {}", index, reason);
+                        failures.push((node, reason));
+                    }
+                }
+            }
+
+            if failures.is_empty() {
+                info!("All nodes are ready");
+                return Ok(());
+            }
+            if Instant::now() + self.interval > deadline {
+                let unhealthy: Vec<_> = failures
+                    .iter()
+                    .map(|((index, url), reason)| format!("node {} at {} ({})", index, url, reason))
+                    .collect();
+                return Err(color_eyre::eyre::eyre!(
+                    "Nodes not ready after {}s: {}",
+                    self.timeout.as_secs(),
+                    unhealthy.join(", ")
+                ));
+            }
+
+            pending = failures.into_iter().map(|(node, _)| node).collect();
+            sleep(self.interval).await;
+        }
+    }
+
+    /// Check the health of a single node and, if required, that it committed.
+    async fn probe(&self, client: &Client, url: &str) -> Result<(), String> {
+        match client
+            .get(format!("{}/health", url))
+            .timeout(Self::PROBE_TIMEOUT)
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => (),
+            Ok(response) => return Err(format!("status {}", response.status())),
+            Err(e) => return Err(e.to_string()),
+        }
+        match self.require_commit {
+            true => Self::probe_commits(client, url).await,
+            false => Ok(()),
+        }
+    }
+
+    /// Check that a single node committed at least once.
+    async fn probe_commits(client: &Client, url: &str) -> Result<(), String> {
+        let response = client
+            .get(format!("{}/stats", url))
+            .timeout(Self::PROBE_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("stats status {}", response.status()));
+        }
+        let stats: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+        let committed = stats["commits"].as_u64().is_some_and(|commits| commits > 0)
+            || stats["time_to_first_commit_ms"].is_u64();
+        match committed {
+            true => Ok(()),
+            false => Err("healthy but no commit yet (quorum not formed)".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::Duration,
+    };
+
+    use axum::{Json, Router, routing::get};
+    use reqwest::Client;
+    use serde_json::json;
+
+    use super::ReadinessProbe;
+
+    #[tokio::test]
+    async fn readiness_timeout_names_unhealthy_nodes() {
+        // Nothing listens on port 1.
+        let nodes = vec![(3, "http://127.0.0.1:1".to_string())];
+        let probe = ReadinessProbe::new(Duration::from_millis(300))
+            .with_interval(Duration::from_millis(100));
+
+        let error = probe.wait(&Client::new(), &nodes).await.unwrap_err();
+        assert!(error.to_string().contains("node 3 at http://127.0.0.1:1"));
+
+        // There is nothing to wait for without nodes.
+        assert!(probe.wait(&Client::new(), &[]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn readiness_waits_for_first_commit() {
+        let commits = Arc::new(AtomicU64::new(0));
+        let stats = commits.clone();
+        let stats =
+            move || async move { Json(json!({ "commits": stats.load(Ordering::Relaxed) })) };
+        let app = Router::new()
+            .route("/health", get(|| async { "OK" }))
+            .route("/stats", get(stats));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let nodes = vec![(0, url)];
+        let probe = ReadinessProbe::new(Duration::from_millis(300))
+            .with_interval(Duration::from_millis(100));
+        let error = probe.wait(&Client::new(), &nodes).await.unwrap_err();
+        assert!(error.to_string().contains("no commit yet"));
+        assert!(
+            probe
+                .with_commit_check(false)
+                .wait(&Client::new(), &nodes)
+                .await
+                .is_ok()
+        );
+
+        commits.store(1, Ordering::Relaxed);
+        assert!(probe.wait(&Client::new(), &nodes).await.is_ok());
+    }
+}
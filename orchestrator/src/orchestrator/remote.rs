@@ -1,16 +1,68 @@
-use base64::Engine;
-use color_eyre::eyre::{Context, Result};
+use color_eyre::eyre::{Context, Result, bail};
+use futures::{StreamExt, stream::FuturesUnordered};
 use reqwest::Client;
+use serde::Serialize;
 use serde_json::json;
 use shell_escape::escape;
 use std::{
     env,
-    path::PathBuf,
+    path::{Path, PathBuf},
     time::{Duration, Instant},
 };
 use tokio::time::sleep;
 use tracing::{info, warn};
 
+use super::{
+    BreakerEvent, CircuitBreaker, ClockSkew, LoadDistribution, NodeSelector, Pacer, ReadinessProbe,
+    SubmissionLogging, TransactionGenerator, TransactionPool, fetch_stakes, measure_clock_skew,
+};
+use crate::{protocol::mysticeti::TransactionFormat, settings::expand_tilde};
+
+/// Where an uploaded validator binary is installed on the remote nodes.
+const REMOTE_BINARY_PATH: &str = "~/mysticeti-bin/validator";
+/// The file holding the pid of a validator started from an uploaded binary.
+const REMOTE_PID_FILE: &str = "~/mysticeti-data/validator.pid";
+
+/// How long the network took to commit after startup, to compare staggered and simultaneous
+/// startups.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StartupReport {
+    /// The delay between starting two nodes (in milliseconds).
+    pub startup_delay_ms: u64,
+    /// When every node started, relative to the first one (in seconds).
+    pub launch_offsets_s: Vec<f64>,
+    /// The time between the start of every node and its first commit (in seconds), if it
+    /// committed.
+    pub time_to_first_commit_s: Vec<Option<f64>>,
+}
+
+impl StartupReport {
+    /// The time between the start of every node and its first commit, relative to the start
+    /// of the first node.
+    fn first_commit_offsets(&self) -> impl Iterator<Item = Option<f64>> + '_ {
+        self.launch_offsets_s
+            .iter()
+            .zip(&self.time_to_first_commit_s)
+            .map(|(launch, commit)| commit.map(|commit| launch + commit))
+    }
+
+    /// The time between the start of the first node and the first commit of any node.
+    pub fn first_commit_s(&self) -> Option<f64> {
+        self.first_commit_offsets()
+            .flatten()
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// The time between the start of the first node and the moment every node committed, if
+    /// they all did.
+    pub fn all_committed_s(&self) -> Option<f64> {
+        self.first_commit_offsets()
+            .try_fold(0.0, |latest: f64, commit| {
+                commit.map(|commit| latest.max(commit))
+            })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RemoteNode {
     host: String,
@@ -37,10 +89,17 @@ impl RemoteNode {
         let ssh_user = env::var(format!("MYSTICETI_NODE{}_SSH_USER", index))
             .unwrap_or_else(|_| "ubuntu".to_string());
 
-        let ssh_key_path = PathBuf::from(
+        let ssh_key_path = expand_tilde(
             env::var(format!("MYSTICETI_NODE{}_SSH_KEY", index))
                 .unwrap_or_else(|_| "~/.ssh/id_rsa".to_string()),
         );
+        if !ssh_key_path.exists() {
+            warn!(
+                "SSH key {} of node {} not found, relying on the ssh agent",
+                ssh_key_path.display(),
+                index
+            );
+        }
 
         let rpc_port = 26657;
         let abci_port = 26670 + index as u16;
@@ -72,11 +131,44 @@ impl RemoteNode {
             safe_cmd,
         )
     }
+
+    fn scp_command(&self, source: &Path, destination: &str) -> String {
+        format!(
+            "scp -i {} -P {} \
+                -o StrictHostKeyChecking=accept-new \
+                -o ConnectTimeout={} {} {}@{}:{}",
+            self.ssh_key_path.display(),
+            self.port,
+            env::var("SSH_TIMEOUT").unwrap_or_else(|_| "30".into()),
+            escape(source.display().to_string().into()),
+            self.ssh_user,
+            self.host,
+            destination,
+        )
+    }
 }
 
 pub struct RemoteNetworkOrchestrator {
     pub nodes: Vec<RemoteNode>,
     pub client: Client,
+    /// A locally-built validator binary to upload and run instead of pulling the image.
+    local_binary: Option<PathBuf>,
+    /// The delay between two polls of the health of the nodes.
+    readiness_interval: Duration,
+    /// Pauses the transaction simulation when too many submissions fail, if set.
+    circuit_breaker: Option<CircuitBreaker>,
+    /// How much the transaction simulation logs while submitting.
+    logging: SubmissionLogging,
+    /// The delay between starting two nodes.
+    startup_delay: Duration,
+    /// The layout of the submitted transactions.
+    transaction_format: TransactionFormat,
+    /// The number of distinct transactions encoded ahead of the submission.
+    pool_size: usize,
+    /// How the load is spread across the nodes.
+    distribution: LoadDistribution,
+    /// The maximum number of requests in flight.
+    concurrency: usize,
 }
 
 impl RemoteNetworkOrchestrator {
@@ -106,9 +198,167 @@ impl RemoteNetworkOrchestrator {
         Ok(Self {
             nodes,
             client: Client::new(),
+            local_binary: None,
+            readiness_interval: ReadinessProbe::DEFAULT_INTERVAL,
+            circuit_breaker: None,
+            logging: SubmissionLogging::default(),
+            startup_delay: Duration::ZERO,
+            transaction_format: TransactionFormat::default(),
+            pool_size: TransactionPool::DEFAULT_SIZE,
+            distribution: LoadDistribution::default(),
+            concurrency: 1,
         })
     }
 
+    /// Set the delay between two polls of the health of the nodes.
+    pub fn with_readiness_interval(mut self, interval: Duration) -> Self {
+        self.readiness_interval = interval;
+        self
+    }
+
+    /// Pause the transaction simulation whenever the specified circuit breaker opens.
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Set how much the transaction simulation logs while submitting.
+    pub fn with_logging(mut self, logging: SubmissionLogging) -> Self {
+        self.logging = logging;
+        self
+    }
+
+    /// Submit transactions with the specified layout.
+    pub fn with_transaction_format(mut self, transaction_format: TransactionFormat) -> Self {
+        self.transaction_format = transaction_format;
+        self
+    }
+
+    /// Set the number of distinct transactions encoded before the submission starts. The
+    /// simulation cycles through them, only changing their nonce.
+    pub fn with_pool_size(mut self, pool_size: usize) -> Self {
+        self.pool_size = pool_size;
+        self
+    }
+
+    /// Set how the load is spread across the nodes.
+    pub fn with_distribution(mut self, distribution: LoadDistribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    /// Keep up to `concurrency` submission requests in flight, so that the submission rate
+    /// is not capped by the round trip time of the requests.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Wait for the specified delay between starting two nodes, so that they come up in
+    /// sequence rather than all at once.
+    pub fn with_startup_delay(mut self, startup_delay: Duration) -> Self {
+        self.startup_delay = startup_delay;
+        self
+    }
+
+    /// Upload and run the specified locally-built validator binary on every node instead of
+    /// pulling the published image. The binary must be built for the nodes' architecture.
+    pub fn with_local_binary(mut self, local_binary: PathBuf) -> Self {
+        self.local_binary = Some(local_binary);
+        self
+    }
+
+    fn run_shell(command: &str) -> Result<std::process::ExitStatus> {
+        std::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .status()
+            .wrap_err(format!("Failed to execute: {}", command))
+    }
+
+    async fn upload_binary(&self, node: &RemoteNode, binary: &Path) -> Result<()> {
+        info!(
+            "Uploading {} to node {} ({})",
+            binary.display(),
+            node.authority_index,
+            node.host
+        );
+
+        let mkdir_cmd = node.ssh_command("mkdir -p ~/mysticeti-bin ~/mysticeti-data");
+        if !Self::run_shell(&mkdir_cmd)?.success() {
+            return Err(color_eyre::eyre::eyre!(
+                "Failed to create directories on node {}",
+                node.authority_index
+            ));
+        }
+
+        let scp_cmd = node.scp_command(binary, REMOTE_BINARY_PATH);
+        if !Self::run_shell(&scp_cmd)?.success() {
+            return Err(color_eyre::eyre::eyre!(
+                "Failed to upload binary to node {}",
+                node.authority_index
+            ));
+        }
+
+        let chmod_cmd = node.ssh_command(&format!("chmod +x {REMOTE_BINARY_PATH}"));
+        if !Self::run_shell(&chmod_cmd)?.success() {
+            warn!(
+                "Failed to make binary executable on node {}",
+                node.authority_index
+            );
+        }
+        Ok(())
+    }
+
+    async fn start_mysticeti_binary(&self, node: &RemoteNode, binary: &Path) -> Result<()> {
+        self.upload_binary(node, binary).await?;
+
+        info!(
+            "Starting uploaded Mysticeti binary on node {} ({})",
+            node.authority_index, node.host
+        );
+        let run_cmd = format!(
+            "RUST_LOG=info nohup {REMOTE_BINARY_PATH} \
+             --authority-index {} \
+             --rpc-port {} \
+             --abci-port {} \
+             --working-directory ~/mysticeti-data \
+             --verify-signatures {} \
+             > ~/mysticeti-data/validator.log 2>&1 & echo $! > {REMOTE_PID_FILE}",
+            node.authority_index,
+            node.rpc_port,
+            node.abci_port,
+            self.transaction_format.is_signed(),
+        );
+        let ssh_cmd = node.ssh_command(&run_cmd);
+        if !Self::run_shell(&ssh_cmd)?.success() {
+            return Err(color_eyre::eyre::eyre!(
+                "Failed to start Mysticeti binary on node {}",
+                node.authority_index
+            ));
+        }
+
+        info!("Mysticeti binary started on node {}", node.authority_index);
+        Ok(())
+    }
+
+    async fn stop_mysticeti_binary(&self, node: &RemoteNode) -> Result<()> {
+        info!(
+            "Stopping Mysticeti binary on node {} ({})",
+            node.authority_index, node.host
+        );
+
+        let stop_cmd = node.ssh_command(&format!(
+            "kill $(cat {REMOTE_PID_FILE}) && rm {REMOTE_PID_FILE}"
+        ));
+        if !Self::run_shell(&stop_cmd)?.success() {
+            warn!("Failed to stop binary on node {}", node.authority_index);
+        } else {
+            info!("Mysticeti binary stopped on node {}", node.authority_index);
+        }
+        Ok(())
+    }
+
     async fn setup_docker_on_node(&self, node: &RemoteNode) -> Result<()> {
         info!(
             "Setting up Docker on node {} ({})",
@@ -169,6 +419,10 @@ impl RemoteNetworkOrchestrator {
     }
 
     async fn start_mysticeti_container(&self, node: &RemoteNode) -> Result<()> {
+        if let Some(binary) = &self.local_binary {
+            return self.start_mysticeti_binary(node, binary).await;
+        }
+
         info!(
             "Starting Mysticeti container on node {} ({})",
             node.authority_index, node.host
@@ -214,13 +468,15 @@ impl RemoteNetworkOrchestrator {
              --authority-index {} \
              --rpc-port 26657 \
              --abci-port {} \
-             --working-directory /app/data",
+             --working-directory /app/data \
+             --verify-signatures {}",
             node.authority_index,
             node.rpc_port,
             node.abci_port,
             node.abci_port,
             node.authority_index,
-            node.abci_port
+            node.abci_port,
+            self.transaction_format.is_signed()
         );
 
         let ssh_cmd = node.ssh_command(&container_cmd);
@@ -245,6 +501,10 @@ impl RemoteNetworkOrchestrator {
     }
 
     async fn stop_mysticeti_container(&self, node: &RemoteNode) -> Result<()> {
+        if self.local_binary.is_some() {
+            return self.stop_mysticeti_binary(node).await;
+        }
+
         info!(
             "Stopping Mysticeti container on node {} ({})",
             node.authority_index, node.host
@@ -273,90 +533,167 @@ impl RemoteNetworkOrchestrator {
         Ok(())
     }
 
-    pub async fn wait_for_network_ready(&self, wait_time: u64) -> Result<()> {
-        info!("Waiting {} seconds for network to be ready...", wait_time);
-        sleep(Duration::from_secs(wait_time)).await;
+    /// Poll the health of the nodes until all of them are ready, failing after `timeout`
+    /// seconds.
+    pub async fn wait_for_network_ready(&self, timeout: u64) -> Result<()> {
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                (
+                    node.authority_index as usize,
+                    format!("http://{}:{}", node.host, node.rpc_port),
+                )
+            })
+            .collect();
+
+        ReadinessProbe::new(Duration::from_secs(timeout))
+            .with_interval(self.readiness_interval)
+            .wait(&self.client, &nodes)
+            .await
+    }
 
-        // Check if nodes are responding
-        for node in &self.nodes {
-            let url = format!("http://{}:{}/health", node.host, node.rpc_port);
-            match self.client.get(&url).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        info!("Node {} is ready at {}", node.authority_index, url);
-                    } else {
-                        warn!(
-                            "Node {} responded with status: {}",
-                            node.authority_index,
-                            response.status()
-                        );
-                    }
-                }
-                Err(e) => {
-                    warn!("Node {} not ready yet: {}", node.authority_index, e);
-                }
+    /// Make the selector spreading the load across the nodes. Weighted distributions read the
+    /// stake of the nodes from the committee of the first one.
+    async fn node_selector(&self) -> Result<NodeSelector> {
+        match self.distribution {
+            LoadDistribution::Equal => Ok(NodeSelector::equal(self.nodes.len())),
+            LoadDistribution::Weighted => {
+                let node = &self.nodes[0];
+                let url = format!("http://{}:{}", node.host, node.rpc_port);
+                let authorities: Vec<_> = self
+                    .nodes
+                    .iter()
+                    .map(|node| node.authority_index as usize)
+                    .collect();
+                let stakes = fetch_stakes(&self.client, &url, &authorities).await?;
+                info!("Distributing the load by stake: {:?}", stakes);
+                NodeSelector::weighted(stakes)
             }
         }
+    }
 
-        Ok(())
+    /// Compare the wall clock of every node with the orchestrator's, warning about the nodes
+    /// whose skew exceeds the threshold.
+    pub async fn measure_clock_skew(&self, threshold: Duration) -> Vec<ClockSkew> {
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                (
+                    node.authority_index as usize,
+                    format!("http://{}:{}", node.host, node.rpc_port),
+                )
+            })
+            .collect();
+        measure_clock_skew(&self.client, &nodes, threshold).await
     }
 
+    /// Submit transactions to the nodes at the specified rate (tx/s), which must be positive.
+    /// Returns the events of the circuit breaker (if any).
     pub async fn simulate_transactions(
         &self,
         num_transactions: usize,
         transaction_size: usize,
         transaction_rate: usize,
-    ) -> Result<()> {
+    ) -> Result<Vec<BreakerEvent>> {
         info!("Starting transaction simulation...");
         info!(
             "Parameters: {} transactions, {} bytes each, {} tx/s",
             num_transactions, transaction_size, transaction_rate
         );
 
-        let delay = Duration::from_millis((1000 / transaction_rate) as u64);
+        if transaction_rate == 0 {
+            bail!("The transaction rate must be positive");
+        }
         let mut successful_txs = 0;
         let mut failed_txs = 0;
+        let log_interval = self.logging.interval(transaction_rate);
+        if log_interval.is_none() {
+            info!("Per-transaction logging disabled, only the summary is logged");
+        }
+        let mut breaker = self.circuit_breaker.clone();
+        if let Some(breaker) = &mut breaker {
+            breaker.reset();
+        }
         let start_time = Instant::now();
-
-        // Generate random transaction data
-        let tx_data = vec![0u8; transaction_size];
-
-        for i in 0..num_transactions {
-            // Round-robin between nodes
-            let node = &self.nodes[i % self.nodes.len()];
-            let url = format!("http://{}:{}/broadcast_tx_async", node.host, node.rpc_port);
-
-            let payload = json!({
-                "transaction": base64::engine::general_purpose::STANDARD.encode(&tx_data)
-            });
-
-            match self.client.post(&url).json(&payload).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
+        let mut pacer = Pacer::new(transaction_rate as f64, tokio::time::Instant::now());
+
+        let generator = TransactionGenerator::new(self.transaction_format, transaction_size)?;
+        let mut pool = TransactionPool::new(generator, self.pool_size);
+        let mut selector = self.node_selector().await?;
+
+        // Up to `concurrency` requests are in flight, each in its own task. Their outcomes are
+        // recorded here, in completion order.
+        let mut in_flight = FuturesUnordered::new();
+        let mut i = 0;
+        loop {
+            let finished = i >= num_transactions;
+            if in_flight.len() >= self.concurrency || (finished && !in_flight.is_empty()) {
+                let (sequence, node, result) = in_flight
+                    .next()
+                    .await
+                    .expect("Requests are in flight")
+                    .wrap_err("Submission task failed")?;
+                let node = &self.nodes[node];
+                let success = match result {
+                    Ok(status) if status.is_success() => {
                         successful_txs += 1;
-                        if i % 100 == 0 {
+                        if log_interval.is_some_and(|interval| sequence % interval == 0) {
                             info!(
                                 "Submitted transaction {} to node {} ({})",
-                                i, node.authority_index, node.host
+                                sequence, node.authority_index, node.host
                             );
                         }
-                    } else {
+                        true
+                    }
+                    Ok(status) => {
                         failed_txs += 1;
-                        warn!(
-                            "Transaction {} failed with status: {}",
-                            i,
-                            response.status()
-                        );
+                        if log_interval.is_some() {
+                            warn!("Transaction {} failed with status: {}", sequence, status);
+                        }
+                        false
                     }
+                    Err(e) => {
+                        failed_txs += 1;
+                        if log_interval.is_some() {
+                            warn!("Transaction {} failed: {}", sequence, e);
+                        }
+                        false
+                    }
+                };
+
+                // Back off while the network is failing
+                if let Some(pause) = breaker.as_mut().and_then(|b| b.record(success)) {
+                    warn!(
+                        "Circuit breaker open, pausing submission for {:.1}s",
+                        pause.as_secs_f64()
+                    );
+                    sleep(pause).await;
+                    pacer.resume(tokio::time::Instant::now());
                 }
-                Err(e) => {
-                    failed_txs += 1;
-                    warn!("Transaction {} failed: {}", i, e);
-                }
+                continue;
+            }
+            if finished {
+                break;
             }
 
             // Rate limiting
-            sleep(delay).await;
+            tokio::time::sleep_until(pacer.next_due(tokio::time::Instant::now())).await;
+
+            let node = selector.select();
+            let url = format!(
+                "http://{}:{}/broadcast_tx_async",
+                self.nodes[node].host, self.nodes[node].rpc_port
+            );
+            let request = self.client.post(&url).json(&json!({
+                "transaction": pool.transaction(i as u64)
+            }));
+            in_flight.push(tokio::spawn(async move {
+                let result = request.send().await.map(|response| response.status());
+                (i, node, result)
+            }));
+            i += 1;
         }
 
         let duration = start_time.elapsed();
@@ -368,7 +705,9 @@ impl RemoteNetworkOrchestrator {
         info!("Failed transactions: {}", failed_txs);
         info!("Actual rate: {:.2} tx/s", actual_rate);
 
-        Ok(())
+        Ok(breaker
+            .map(|breaker| breaker.events().to_vec())
+            .unwrap_or_default())
     }
 
     pub async fn setup_all_nodes(&self) -> Result<()> {
@@ -382,15 +721,63 @@ impl RemoteNetworkOrchestrator {
         Ok(())
     }
 
-    pub async fn start_all_containers(&self) -> Result<()> {
+    /// Start the nodes one after the other, waiting for the startup delay in between. Returns
+    /// when every node started, relative to the first one.
+    pub async fn start_all_containers(&self) -> Result<Vec<Duration>> {
         info!("Starting Mysticeti containers on all nodes...");
 
-        for node in &self.nodes {
+        let start = Instant::now();
+        let mut launch_offsets = Vec::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            if i > 0 && !self.startup_delay.is_zero() {
+                info!(
+                    "Waiting {}ms before starting the next node",
+                    self.startup_delay.as_millis()
+                );
+                sleep(self.startup_delay).await;
+            }
             self.start_mysticeti_container(node).await?;
+            launch_offsets.push(start.elapsed());
         }
 
         info!("All containers started");
-        Ok(())
+        Ok(launch_offsets)
+    }
+
+    /// Poll the `/stats` endpoint of the nodes until all of them committed (or the timeout
+    /// expires), and report how long they took to commit after their start.
+    pub async fn wait_for_first_commits(
+        &self,
+        launch_offsets: &[Duration],
+        timeout: Duration,
+    ) -> StartupReport {
+        let deadline = Instant::now() + timeout;
+        let mut time_to_first_commit = vec![None; self.nodes.len()];
+        loop {
+            for (node, commit) in self.nodes.iter().zip(time_to_first_commit.iter_mut()) {
+                if commit.is_some() {
+                    continue;
+                }
+                let url = format!("http://{}:{}/stats", node.host, node.rpc_port);
+                let stats: Option<serde_json::Value> = match self.client.get(&url).send().await {
+                    Ok(response) => response.json().await.ok(),
+                    Err(_) => None,
+                };
+                *commit = stats
+                    .and_then(|stats| stats["time_to_first_commit_ms"].as_u64())
+                    .map(|ms| ms as f64 / 1000.0);
+            }
+            if time_to_first_commit.iter().all(Option::is_some) || Instant::now() >= deadline {
+                break;
+            }
+            sleep(self.readiness_interval).await;
+        }
+
+        StartupReport {
+            startup_delay_ms: self.startup_delay.as_millis() as u64,
+            launch_offsets_s: launch_offsets.iter().map(Duration::as_secs_f64).collect(),
+            time_to_first_commit_s: time_to_first_commit,
+        }
     }
 
     pub async fn stop_all_containers(&self) -> Result<()> {
@@ -404,3 +791,22 @@ impl RemoteNetworkOrchestrator {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::StartupReport;
+
+    #[test]
+    fn startup_report() {
+        let mut report = StartupReport {
+            startup_delay_ms: 1000,
+            launch_offsets_s: vec![0.0, 1.0, 2.0, 3.0],
+            time_to_first_commit_s: vec![Some(4.0), Some(2.5), None, Some(1.5)],
+        };
+        assert_eq!(report.first_commit_s(), Some(3.5));
+        assert_eq!(report.all_committed_s(), None);
+
+        report.time_to_first_commit_s[2] = Some(1.0);
+        assert_eq!(report.all_committed_s(), Some(4.5));
+    }
+}
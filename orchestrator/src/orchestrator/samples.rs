@@ -0,0 +1,262 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Raw per-transaction latency samples, for building histograms and CDFs outside of the
+//! orchestrator. Samples are written as CSV (`.csv` files) or in a compact binary layout
+//! (any other extension), with one record per transaction:
+//!
+//! `submitted_at_us: u64 | committed_at_us: u64 | latency_us: u64` (little-endian)
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+/// The size of a binary record, in bytes.
+pub const BINARY_RECORD_SIZE: usize = 24;
+
+/// The submission and commit time of a transaction, in microseconds since the epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencySample {
+    /// When the orchestrator submitted the transaction.
+    pub submitted_at_us: u64,
+    /// When the node the transaction was submitted to committed it.
+    pub committed_at_us: u64,
+}
+
+impl LatencySample {
+    /// The commit latency, in microseconds (zero if the clocks place the commit first).
+    pub fn latency_us(&self) -> u64 {
+        self.committed_at_us.saturating_sub(self.submitted_at_us)
+    }
+}
+
+/// The commit latency of the transactions of one priority.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PriorityLatency {
+    pub priority: u8,
+    /// The number of committed transactions of this priority.
+    pub transactions: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl PriorityLatency {
+    /// Summarize the latency of the samples, grouped by the priority of their transaction
+    /// (from the lowest priority to the highest).
+    pub fn from_samples(samples: &[(u8, LatencySample)]) -> Vec<Self> {
+        let mut latencies: BTreeMap<u8, Vec<u64>> = BTreeMap::new();
+        for (priority, sample) in samples {
+            latencies
+                .entry(*priority)
+                .or_default()
+                .push(sample.latency_us());
+        }
+        latencies
+            .into_iter()
+            .map(|(priority, mut latencies)| {
+                latencies.sort_unstable();
+                let percentile = |p: f64| {
+                    let rank = (p / 100.0 * (latencies.len() - 1) as f64).round() as usize;
+                    latencies[rank] as f64 / 1000.0
+                };
+                let total_us: u64 = latencies.iter().sum();
+                Self {
+                    priority,
+                    transactions: latencies.len(),
+                    mean_ms: total_us as f64 / latencies.len() as f64 / 1000.0,
+                    p50_ms: percentile(50.0),
+                    p99_ms: percentile(99.0),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The layout of the samples file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleFormat {
+    Csv,
+    Binary,
+}
+
+impl SampleFormat {
+    /// The format matching the extension of the file.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => Self::Csv,
+            _ => Self::Binary,
+        }
+    }
+}
+
+/// Appends the sampled transactions to a file, through a buffer so that millions of samples
+/// cost a handful of writes. Each transaction is kept with probability `fraction`, to cap the
+/// size of the file.
+pub struct SampleWriter<W: Write> {
+    writer: W,
+    format: SampleFormat,
+    fraction: f64,
+    written: usize,
+}
+
+impl SampleWriter<BufWriter<File>> {
+    /// Create (or truncate) the samples file at `path`.
+    pub fn create(path: &Path, fraction: f64) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Self::new(
+            BufWriter::new(file),
+            SampleFormat::from_path(path),
+            fraction,
+        )
+    }
+}
+
+impl<W: Write> SampleWriter<W> {
+    /// Write the samples to `writer`, keeping each transaction with probability `fraction`.
+    pub fn new(mut writer: W, format: SampleFormat, fraction: f64) -> io::Result<Self> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("The sampling fraction must be in (0, 1] (got {fraction})"),
+            ));
+        }
+        if format == SampleFormat::Csv {
+            writeln!(writer, "submitted_at_us,committed_at_us,latency_us")?;
+        }
+        Ok(Self {
+            writer,
+            format,
+            fraction,
+            written: 0,
+        })
+    }
+
+    /// Whether the next transaction is sampled. Draw it before looking the transaction up, so
+    /// that unsampled transactions cost nothing.
+    pub fn sample(&self) -> bool {
+        self.fraction >= 1.0 || rand::random::<f64>() < self.fraction
+    }
+
+    /// Append a sample.
+    pub fn write(&mut self, sample: &LatencySample) -> io::Result<()> {
+        let latency_us = sample.latency_us();
+        match self.format {
+            SampleFormat::Csv => writeln!(
+                self.writer,
+                "{},{},{}",
+                sample.submitted_at_us, sample.committed_at_us, latency_us
+            )?,
+            SampleFormat::Binary => {
+                let mut record = [0u8; BINARY_RECORD_SIZE];
+                record[..8].copy_from_slice(&sample.submitted_at_us.to_le_bytes());
+                record[8..16].copy_from_slice(&sample.committed_at_us.to_le_bytes());
+                record[16..].copy_from_slice(&latency_us.to_le_bytes());
+                self.writer.write_all(&record)?;
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// The number of samples written so far.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Flush the buffered samples and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// The path of the samples of one run: the load (and proposal limit) and the network are
+/// appended to the file name so that the runs of a sweep do not overwrite each other.
+pub fn run_samples_path(base: &Path, network: &str, load: usize, limit: Option<u64>) -> PathBuf {
+    let stem = base
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("samples");
+    let limit = limit.map(|max| format!("_{max}maxtx")).unwrap_or_default();
+    let mut name = format!("{stem}_{network}_{load}txs{limit}");
+    if let Some(extension) = base.extension().and_then(|extension| extension.to_str()) {
+        name = format!("{name}.{extension}");
+    }
+    base.with_file_name(name)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{
+        BINARY_RECORD_SIZE, LatencySample, PriorityLatency, SampleFormat, SampleWriter,
+        run_samples_path,
+    };
+
+    #[test]
+    fn write_samples() {
+        let sample = LatencySample {
+            submitted_at_us: 1_000,
+            committed_at_us: 251_000,
+        };
+
+        let mut csv = SampleWriter::new(Vec::new(), SampleFormat::Csv, 1.0).unwrap();
+        assert!(csv.sample());
+        csv.write(&sample).unwrap();
+        let csv = String::from_utf8(csv.finish().unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "submitted_at_us,committed_at_us,latency_us\n1000,251000,250000\n"
+        );
+
+        let mut binary = SampleWriter::new(Vec::new(), SampleFormat::Binary, 0.5).unwrap();
+        binary.write(&sample).unwrap();
+        binary.write(&sample).unwrap();
+        assert_eq!(binary.written(), 2);
+        let bytes = binary.finish().unwrap();
+        assert_eq!(bytes.len(), 2 * BINARY_RECORD_SIZE);
+        assert_eq!(bytes[16..24], 250_000u64.to_le_bytes());
+
+        assert!(SampleWriter::new(Vec::new(), SampleFormat::Csv, 0.0).is_err());
+        assert_eq!(
+            SampleFormat::from_path(Path::new("out/samples.CSV")),
+            SampleFormat::Csv
+        );
+        assert_eq!(
+            run_samples_path(Path::new("out/samples.bin"), "mysticeti", 100, Some(50)),
+            Path::new("out/samples_mysticeti_100txs_50maxtx.bin")
+        );
+    }
+
+    #[test]
+    fn latency_by_priority() {
+        let sample = |latency_ms: u64| LatencySample {
+            submitted_at_us: 0,
+            committed_at_us: latency_ms * 1000,
+        };
+        let samples = [
+            (0, sample(300)),
+            (1, sample(100)),
+            (0, sample(500)),
+            (1, sample(140)),
+            (0, sample(400)),
+        ];
+        let summary = PriorityLatency::from_samples(&samples);
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].priority, 0);
+        assert_eq!(summary[0].transactions, 3);
+        assert_eq!(summary[0].mean_ms, 400.0);
+        assert_eq!(summary[0].p50_ms, 400.0);
+        assert_eq!(summary[1].priority, 1);
+        assert_eq!(summary[1].mean_ms, 120.0);
+        assert_eq!(summary[1].p99_ms, 140.0);
+        assert!(PriorityLatency::from_samples(&[]).is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{fmt, path::Path, str::FromStr, time::Duration};
+
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use serde::Serialize;
+
+/// How the rate evolves between two points of a schedule.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interpolation {
+    /// The rate jumps to the rate of each point at its offset.
+    #[default]
+    Step,
+    /// The rate moves linearly from the rate of one point to the rate of the next.
+    Linear,
+}
+
+impl fmt::Display for Interpolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Step => write!(f, "step"),
+            Self::Linear => write!(f, "linear"),
+        }
+    }
+}
+
+impl FromStr for Interpolation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "step" => Ok(Self::Step),
+            "linear" => Ok(Self::Linear),
+            _ => Err(format!(
+                "Unknown interpolation '{s}' (expected step or linear)"
+            )),
+        }
+    }
+}
+
+/// A target submission rate varying over the run, to reproduce a traffic pattern (e.g. a
+/// diurnal cycle or a sudden spike) rather than a constant load.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateSchedule {
+    /// The offsets from the start of the run (in seconds) and the target rates (tx/s), by
+    /// increasing offset.
+    points: Vec<(f64, f64)>,
+    interpolation: Interpolation,
+}
+
+impl RateSchedule {
+    /// Make a schedule from `(offset_secs, rate)` points. The offsets must be increasing and
+    /// the rates positive; the rate of the first point holds before it, and the rate of the
+    /// last point holds after it.
+    pub fn new(points: Vec<(f64, f64)>, interpolation: Interpolation) -> Result<Self> {
+        if points.is_empty() {
+            return Err(eyre!("The rate schedule is empty"));
+        }
+        for (offset, rate) in &points {
+            if !offset.is_finite() || *offset < 0.0 {
+                return Err(eyre!("Invalid offset {offset}s in the rate schedule"));
+            }
+            if !rate.is_finite() || *rate <= 0.0 {
+                return Err(eyre!("Invalid rate {rate} tx/s in the rate schedule"));
+            }
+        }
+        if points.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(eyre!("The offsets of the rate schedule must be increasing"));
+        }
+        Ok(Self {
+            points,
+            interpolation,
+        })
+    }
+
+    /// Read a schedule file with one `time_offset_secs,target_rate` pair per line. Blank lines
+    /// and lines starting with `#` are ignored.
+    pub fn from_file(path: &Path, interpolation: Interpolation) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read the rate schedule {}", path.display()))?;
+        let points = content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                let (offset, rate) = line
+                    .split_once(',')
+                    .ok_or_else(|| eyre!("Line {number}: expected OFFSET_SECS,RATE"))?;
+                let offset = offset
+                    .trim()
+                    .parse::<f64>()
+                    .wrap_err_with(|| format!("Line {number}: invalid offset '{offset}'"))?;
+                let rate = rate
+                    .trim()
+                    .parse::<f64>()
+                    .wrap_err_with(|| format!("Line {number}: invalid rate '{rate}'"))?;
+                Ok((offset, rate))
+            })
+            .collect::<Result<_>>()?;
+        Self::new(points, interpolation)
+    }
+
+    /// The target rate (tx/s) at the specified time since the start of the run.
+    pub fn rate_at(&self, elapsed: Duration) -> f64 {
+        let elapsed = elapsed.as_secs_f64();
+        let next = self
+            .points
+            .partition_point(|(offset, _)| *offset <= elapsed);
+        if next == 0 {
+            return self.points[0].1;
+        }
+        let (offset, rate) = self.points[next - 1];
+        match (self.interpolation, self.points.get(next)) {
+            (Interpolation::Linear, Some((next_offset, next_rate))) => {
+                let progress = (elapsed - offset) / (next_offset - offset);
+                rate + (next_rate - rate) * progress
+            }
+            _ => rate,
+        }
+    }
+}
+
+/// The rate targeted and achieved over one second of the run.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ScheduleSample {
+    /// The start of the second, since the start of the run.
+    pub offset_s: u64,
+    /// The target rate in the middle of the second (tx/s).
+    pub target_rate: f64,
+    /// The number of transactions accepted during the second.
+    pub achieved_rate: f64,
+}
+
+/// Compares the rate achieved by the simulation with the schedule it followed, second by
+/// second.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ScheduleTracking {
+    pub samples: Vec<ScheduleSample>,
+}
+
+impl ScheduleTracking {
+    /// Count the transactions accepted at the specified times since the start of the run
+    /// (which lasted `duration`) against the target of the schedule.
+    pub fn new(schedule: &RateSchedule, accepted: &[Duration], duration: Duration) -> Self {
+        let seconds = duration.as_secs() as usize;
+        let mut counts = vec![0usize; seconds];
+        for elapsed in accepted {
+            if let Some(count) = counts.get_mut(elapsed.as_secs() as usize) {
+                *count += 1;
+            }
+        }
+        let samples = counts
+            .into_iter()
+            .enumerate()
+            .map(|(second, count)| ScheduleSample {
+                offset_s: second as u64,
+                target_rate: schedule.rate_at(Duration::from_secs_f64(second as f64 + 0.5)),
+                achieved_rate: count as f64,
+            })
+            .collect();
+        Self { samples }
+    }
+
+    /// The mean relative deviation of the achieved rate from the target, over every full
+    /// second of the run (0 means the schedule was followed exactly).
+    pub fn mean_error(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self
+            .samples
+            .iter()
+            .map(|sample| (sample.achieved_rate - sample.target_rate).abs() / sample.target_rate)
+            .sum();
+        total / self.samples.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::Write, time::Duration};
+
+    use super::{Interpolation, RateSchedule, ScheduleTracking};
+
+    #[test]
+    fn follow_rate_schedule() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "# offset_secs,rate\n0,100\n10, 300\n\n20,200").unwrap();
+
+        let step = RateSchedule::from_file(file.path(), Interpolation::Step).unwrap();
+        assert_eq!(step.rate_at(Duration::from_secs(5)), 100.0);
+        assert_eq!(step.rate_at(Duration::from_secs(10)), 300.0);
+        assert_eq!(step.rate_at(Duration::from_secs(60)), 200.0);
+
+        let linear = RateSchedule::from_file(file.path(), Interpolation::Linear).unwrap();
+        assert_eq!(linear.rate_at(Duration::from_secs(5)), 200.0);
+        assert_eq!(linear.rate_at(Duration::from_secs(15)), 250.0);
+        assert_eq!(linear.rate_at(Duration::from_secs(60)), 200.0);
+
+        assert!(RateSchedule::new(vec![(5.0, 10.0), (1.0, 10.0)], Interpolation::Step).is_err());
+        assert!(RateSchedule::new(vec![(0.0, 0.0)], Interpolation::Step).is_err());
+        assert!("cubic".parse::<Interpolation>().is_err());
+    }
+
+    #[test]
+    fn track_achieved_rate() {
+        let schedule = RateSchedule::new(vec![(0.0, 4.0)], Interpolation::Step).unwrap();
+        // Four transactions in the first second, two in the second.
+        let accepted: Vec<_> = [0.1, 0.3, 0.5, 0.7, 1.2, 1.8]
+            .into_iter()
+            .map(Duration::from_secs_f64)
+            .collect();
+        let tracking = ScheduleTracking::new(&schedule, &accepted, Duration::from_secs(2));
+        assert_eq!(tracking.samples.len(), 2);
+        assert_eq!(tracking.samples[1].achieved_rate, 2.0);
+        assert_eq!(tracking.mean_error(), 0.25);
+    }
+}
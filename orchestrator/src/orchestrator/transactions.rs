@@ -0,0 +1,376 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ops::Range;
+
+use base64::Engine;
+use color_eyre::eyre::{Result, eyre};
+use fastcrypto::{
+    ed25519::{Ed25519KeyPair, Ed25519PrivateKey},
+    traits::{KeyPair, Signer, ToFromBytes},
+};
+use rand::Rng;
+
+use crate::protocol::mysticeti::TransactionFormat;
+
+// The layout of the structured transactions, as read by the nodes in
+// `execute::validator::structured`. The orchestrator does not depend on the node crate, hence
+// the copy.
+
+/// The magic bytes opening every structured transaction.
+const MAGIC: &[u8; 4] = b"MYST";
+/// The version of the structured transaction layout.
+const VERSION: u8 = 1;
+/// The size of the sender address.
+const SENDER_SIZE: usize = 32;
+/// The size of the header: magic, version, kind, payload length and sender.
+const HEADER_SIZE: usize = MAGIC.len() + 1 + 1 + 4 + SENDER_SIZE;
+/// The size of the nonce: the run nonce followed by the sequence number.
+const NONCE_SIZE: usize = 16;
+/// The size of an Ed25519 signature.
+const SIGNATURE_SIZE: usize = 64;
+/// The position of the kind byte in the header, which the nodes read as the priority of the
+/// transaction.
+const PRIORITY_OFFSET: usize = 5;
+
+/// Generates the (unique) transactions submitted by the simulators, in the layout of the
+/// benchmark type.
+pub struct TransactionGenerator {
+    format: TransactionFormat,
+    /// Distinguishes the transactions of this run from those of previous runs.
+    nonce: u64,
+    /// The last generated transaction; only the varying bytes are rewritten.
+    buffer: Vec<u8>,
+    /// The key signing the transactions (only for signed transactions).
+    keypair: Option<Ed25519KeyPair>,
+    /// The fraction of the transactions marked as high priority.
+    high_priority_ratio: f64,
+}
+
+impl TransactionGenerator {
+    /// The smallest structured transaction: a header, a nonce, and a signature.
+    pub const MIN_STRUCTURED_SIZE: usize = HEADER_SIZE + NONCE_SIZE + SIGNATURE_SIZE;
+    /// The priority of the urgent transactions; the others have priority 0.
+    pub const HIGH_PRIORITY: u8 = 1;
+
+    /// Make a generator of transactions of the specified format and size (in bytes).
+    pub fn new(format: TransactionFormat, transaction_size: usize) -> Result<Self> {
+        let mut buffer = vec![0u8; transaction_size];
+        let mut keypair = None;
+        if format != TransactionFormat::Raw {
+            if transaction_size < Self::MIN_STRUCTURED_SIZE {
+                return Err(eyre!(
+                    "Structured transactions are at least {} bytes (got {transaction_size})",
+                    Self::MIN_STRUCTURED_SIZE
+                ));
+            }
+            let payload_size = transaction_size - Self::MIN_STRUCTURED_SIZE;
+            let mut rng = rand::rng();
+            buffer[..MAGIC.len()].copy_from_slice(MAGIC);
+            buffer[4] = VERSION;
+            // The kind of transaction; a plain transfer of normal priority.
+            buffer[PRIORITY_OFFSET] = 0;
+            buffer[6..10].copy_from_slice(&(payload_size as u32).to_le_bytes());
+            match format {
+                TransactionFormat::Signed => {
+                    let seed: [u8; 32] = rng.random();
+                    let private_key = Ed25519PrivateKey::from_bytes(&seed)
+                        .expect("Any 32 bytes are a valid ed25519 private key");
+                    let signer = Ed25519KeyPair::from(private_key);
+                    buffer[10..HEADER_SIZE].copy_from_slice(signer.public().as_bytes());
+                    keypair = Some(signer);
+                }
+                _ => rng.fill(&mut buffer[10..HEADER_SIZE]),
+            }
+            // Random rather than zeroed payloads, so that they do not compress unrealistically.
+            let payload_start = HEADER_SIZE + NONCE_SIZE;
+            rng.fill(&mut buffer[payload_start..payload_start + payload_size]);
+        }
+
+        Ok(Self {
+            format,
+            nonce: rand::random(),
+            buffer,
+            keypair,
+            high_priority_ratio: 0.0,
+        })
+    }
+
+    /// Mark the specified fraction (in [0, 1]) of the transactions as high priority, spread
+    /// pseudo-randomly over the sequence numbers. Raw transactions have no priority.
+    pub fn with_high_priority_ratio(mut self, ratio: f64) -> Result<Self> {
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(eyre!(
+                "The high priority ratio must be in [0, 1] (got {ratio})"
+            ));
+        }
+        if ratio > 0.0 && self.format == TransactionFormat::Raw {
+            return Err(eyre!("Raw transactions have no priority"));
+        }
+        self.high_priority_ratio = ratio;
+        Ok(self)
+    }
+
+    /// The priority of the transaction with the specified sequence number.
+    pub fn priority(&self, sequence: u64) -> u8 {
+        let draw = splitmix64(sequence) as f64 / u64::MAX as f64;
+        match draw < self.high_priority_ratio {
+            true => Self::HIGH_PRIORITY,
+            false => 0,
+        }
+    }
+
+    /// Whether the transactions have different priorities.
+    fn mixes_priorities(&self) -> bool {
+        self.high_priority_ratio > 0.0 && self.format != TransactionFormat::Raw
+    }
+
+    /// The offset of the bytes making each transaction unique (the run nonce followed by the
+    /// sequence number).
+    fn nonce_offset(&self) -> usize {
+        match self.format {
+            TransactionFormat::Raw => 0,
+            TransactionFormat::Structured | TransactionFormat::Signed => HEADER_SIZE,
+        }
+    }
+
+    /// The transaction with the specified sequence number.
+    pub fn transaction(&mut self, sequence: u64) -> &[u8] {
+        let offset = self.nonce_offset();
+        write_nonce(&mut self.buffer[offset..], self.nonce, sequence);
+        if self.mixes_priorities() {
+            self.buffer[PRIORITY_OFFSET] = self.priority(sequence);
+        }
+        match self.format {
+            TransactionFormat::Raw => (),
+            TransactionFormat::Structured => {
+                // A signature-sized blob that differs for every transaction, like a real one.
+                let start = self.buffer.len() - SIGNATURE_SIZE;
+                let mut state = self.nonce ^ sequence.rotate_left(32);
+                for chunk in self.buffer[start..].chunks_mut(8) {
+                    state = splitmix64(state);
+                    chunk.copy_from_slice(&state.to_le_bytes()[..chunk.len()]);
+                }
+            }
+            TransactionFormat::Signed => {
+                if let Some(keypair) = &self.keypair {
+                    sign(keypair, &mut self.buffer);
+                }
+            }
+        }
+        &self.buffer
+    }
+}
+
+/// A pool of distinct transactions encoded (in base64) ahead of the submission, so that the
+/// simulators do not spend their time encoding: every submission cycles to the next
+/// transaction of the pool and only re-encodes its nonce.
+pub struct TransactionPool {
+    generator: TransactionGenerator,
+    /// The transactions of the pool.
+    transactions: Vec<Vec<u8>>,
+    /// The encoded transactions of the pool.
+    encoded: Vec<String>,
+    /// The bytes re-encoded on every submission: the nonce, widened to a multiple of 3 bytes
+    /// so that it maps to whole base64 characters.
+    window: Range<usize>,
+}
+
+impl TransactionPool {
+    /// The default number of transactions in the pool.
+    pub const DEFAULT_SIZE: usize = 1024;
+
+    /// Pre-generate and encode `size` (at least one) transactions of the generator.
+    pub fn new(mut generator: TransactionGenerator, size: usize) -> Self {
+        let transactions: Vec<_> = (0..size.max(1) as u64)
+            .map(|sequence| generator.transaction(sequence).to_vec())
+            .collect();
+        let encoded = transactions
+            .iter()
+            .map(|transaction| base64::engine::general_purpose::STANDARD.encode(transaction))
+            .collect();
+
+        let length = transactions[0].len();
+        // The priority byte (in the header) varies too when the priorities are mixed.
+        let first = match generator.mixes_priorities() {
+            true => PRIORITY_OFFSET,
+            false => generator.nonce_offset(),
+        };
+        let start = first.min(length) / 3 * 3;
+        let end = (generator.nonce_offset() + NONCE_SIZE).div_ceil(3) * 3;
+        // Short transactions end with a padded base64 group, so they are re-encoded entirely,
+        // and so are signed ones since their signature covers the nonce.
+        let window = match end <= length && !generator.format.is_signed() {
+            true => start..end,
+            false => start..length,
+        };
+
+        Self {
+            generator,
+            transactions,
+            encoded,
+            window,
+        }
+    }
+
+    /// The encoded transaction with the specified sequence number.
+    pub fn transaction(&mut self, sequence: u64) -> &str {
+        let entry = (sequence % self.transactions.len() as u64) as usize;
+        let transaction = &mut self.transactions[entry];
+        let offset = self.generator.nonce_offset();
+        write_nonce(&mut transaction[offset..], self.generator.nonce, sequence);
+        if self.generator.mixes_priorities() {
+            transaction[PRIORITY_OFFSET] = self.generator.priority(sequence);
+        }
+        if let Some(keypair) = &self.generator.keypair {
+            sign(keypair, transaction);
+        }
+
+        let window = &transaction[self.window.clone()];
+        let encoded_window = base64::engine::general_purpose::STANDARD.encode(window);
+        let start = self.window.start / 3 * 4;
+        let encoded = &mut self.encoded[entry];
+        encoded.replace_range(start..start + encoded_window.len(), &encoded_window);
+        encoded
+    }
+
+    /// The priority of the transaction with the specified sequence number.
+    pub fn priority(&self, sequence: u64) -> u8 {
+        match self.generator.mixes_priorities() {
+            true => self.generator.priority(sequence),
+            false => 0,
+        }
+    }
+}
+
+/// Write the run nonce followed by the sequence number at the start of the slice, truncated
+/// if the slice is shorter.
+fn write_nonce(slice: &mut [u8], nonce: u64, sequence: u64) {
+    let unique = [nonce.to_le_bytes(), sequence.to_le_bytes()].concat();
+    let length = unique.len().min(slice.len());
+    slice[..length].copy_from_slice(&unique[..length]);
+}
+
+/// Sign the transaction, overwriting its last bytes with the signature of the rest.
+fn sign(keypair: &Ed25519KeyPair, transaction: &mut [u8]) {
+    let (message, signature) = transaction.split_at_mut(transaction.len() - SIGNATURE_SIZE);
+    signature.copy_from_slice(keypair.sign(message).as_bytes());
+}
+
+/// A cheap pseudo-random step, enough to make the signatures look random.
+fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use base64::Engine;
+    use fastcrypto::{
+        ed25519::{Ed25519PublicKey, Ed25519Signature},
+        traits::{ToFromBytes, VerifyingKey},
+    };
+
+    use super::{
+        HEADER_SIZE, MAGIC, NONCE_SIZE, PRIORITY_OFFSET, SIGNATURE_SIZE, TransactionGenerator,
+        TransactionPool,
+    };
+    use crate::protocol::mysticeti::TransactionFormat;
+
+    #[test]
+    fn generate_structured_transactions() {
+        let mut generator = TransactionGenerator::new(TransactionFormat::Structured, 512).unwrap();
+        let first = generator.transaction(0).to_vec();
+        let second = generator.transaction(1).to_vec();
+
+        assert_eq!(first.len(), 512);
+        assert_eq!(&first[..MAGIC.len()], MAGIC);
+        let payload_size = u32::from_le_bytes(first[6..10].try_into().unwrap()) as usize;
+        assert_eq!(
+            payload_size,
+            512 - TransactionGenerator::MIN_STRUCTURED_SIZE
+        );
+        // Only the nonce and the signature change between transactions.
+        assert_ne!(first, second);
+        assert_eq!(first[..HEADER_SIZE], second[..HEADER_SIZE]);
+        assert_ne!(first[448..], second[448..]);
+        assert_ne!(
+            first[HEADER_SIZE..HEADER_SIZE + NONCE_SIZE],
+            second[HEADER_SIZE..HEADER_SIZE + NONCE_SIZE]
+        );
+
+        assert!(TransactionGenerator::new(TransactionFormat::Structured, 64).is_err());
+        let mut raw = TransactionGenerator::new(TransactionFormat::Raw, 8).unwrap();
+        assert_eq!(raw.transaction(0).len(), 8);
+    }
+
+    #[test]
+    fn pool_matches_generator() {
+        for (format, size) in [
+            (TransactionFormat::Raw, 512),
+            (TransactionFormat::Raw, 8),
+            (TransactionFormat::Structured, 512),
+        ] {
+            let generator = TransactionGenerator::new(format, size).unwrap();
+            let mut pool = TransactionPool::new(generator, 4);
+            let mut seen = std::collections::HashSet::new();
+            for sequence in 0..12 {
+                let encoded = pool.transaction(sequence).to_string();
+                let decoded = base64::engine::general_purpose::STANDARD
+                    .decode(&encoded)
+                    .unwrap();
+                assert_eq!(decoded.len(), size);
+                assert_eq!(decoded, pool.transactions[(sequence % 4) as usize]);
+                // Transactions shorter than the nonce cannot all be unique.
+                assert!(seen.insert(encoded) || size < NONCE_SIZE);
+            }
+        }
+    }
+
+    #[test]
+    fn signed_transactions_verify() {
+        let generator = TransactionGenerator::new(TransactionFormat::Signed, 256).unwrap();
+        let mut pool = TransactionPool::new(generator, 2);
+        for sequence in 0..5 {
+            let transaction = base64::engine::general_purpose::STANDARD
+                .decode(pool.transaction(sequence))
+                .unwrap();
+            let public_key = Ed25519PublicKey::from_bytes(&transaction[10..HEADER_SIZE]).unwrap();
+            let (message, signature) = transaction.split_at(transaction.len() - SIGNATURE_SIZE);
+            let signature = Ed25519Signature::from_bytes(signature).unwrap();
+            assert!(public_key.verify(message, &signature).is_ok());
+        }
+    }
+
+    #[test]
+    fn mix_priorities() {
+        let generator = TransactionGenerator::new(TransactionFormat::Structured, 256)
+            .unwrap()
+            .with_high_priority_ratio(0.25)
+            .unwrap();
+        let mut pool = TransactionPool::new(generator, 8);
+        let mut high = 0;
+        for sequence in 0..4000 {
+            let transaction = base64::engine::general_purpose::STANDARD
+                .decode(pool.transaction(sequence))
+                .unwrap();
+            let priority = pool.priority(sequence);
+            assert_eq!(transaction[PRIORITY_OFFSET], priority);
+            if priority == TransactionGenerator::HIGH_PRIORITY {
+                high += 1;
+            }
+        }
+        // Close to the requested ratio.
+        assert!(
+            (800..1200).contains(&high),
+            "{high} high priority transactions"
+        );
+
+        let raw = TransactionGenerator::new(TransactionFormat::Raw, 64).unwrap();
+        assert!(raw.with_high_priority_ratio(0.5).is_err());
+        let structured = TransactionGenerator::new(TransactionFormat::Structured, 256).unwrap();
+        assert!(structured.with_high_priority_ratio(1.5).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use color_eyre::eyre::Result;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of transactions looked up in a single request.
+const LOOKUP_BATCH_SIZE: usize = 1_000;
+
+/// A transaction accepted by a node during the simulation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubmittedTransaction {
+    /// The node the transaction was submitted to.
+    pub node: usize,
+    /// The id returned by the node.
+    pub transaction_id: String,
+    /// The wall clock of the orchestrator when it submitted the transaction, in microseconds
+    /// since the epoch.
+    pub submitted_at_us: u64,
+    /// The priority of the transaction (0 unless the simulation mixes priorities).
+    pub priority: u8,
+}
+
+/// A transaction the node it was submitted to acknowledged as committed, but that is missing
+/// from the committed state of another node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MissingTransaction {
+    /// The node missing the transaction.
+    pub node: usize,
+    /// The id of the transaction.
+    pub transaction_id: String,
+}
+
+/// The outcome of the verification that no acknowledged transaction was lost.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CommitVerification {
+    /// The number of transactions accepted by the nodes.
+    pub submitted: usize,
+    /// The number of transactions the node they were submitted to committed.
+    pub acknowledged: usize,
+    /// The acknowledged transactions missing from the committed state of a node.
+    pub missing: Vec<MissingTransaction>,
+    /// The nodes that could not be queried.
+    pub unreachable: Vec<usize>,
+}
+
+impl CommitVerification {
+    /// Whether every node could be queried and holds every acknowledged transaction.
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.unreachable.is_empty()
+    }
+
+    /// Check the commit status reported by `node` for the acknowledged transactions, or record
+    /// the node as unreachable.
+    pub fn check_node(
+        &mut self,
+        node: usize,
+        acknowledged: &[String],
+        statuses: Option<&HashMap<String, Option<u32>>>,
+    ) {
+        let Some(statuses) = statuses else {
+            self.unreachable.push(node);
+            return;
+        };
+        for id in acknowledged {
+            if statuses.get(id).copied().flatten().is_none() {
+                self.missing.push(MissingTransaction {
+                    node,
+                    transaction_id: id.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TransactionStatusRequest<'a> {
+    transaction_ids: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct TransactionStatus {
+    transaction_id: String,
+    commit_index: Option<u32>,
+    #[serde(default)]
+    committed_at_us: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct TransactionStatusResponse {
+    transactions: Vec<TransactionStatus>,
+}
+
+/// Query the `/tx_status` endpoint of the node at `url` for the commit index of every
+/// transaction (if committed).
+pub async fn lookup_transactions(
+    client: &Client,
+    url: &str,
+    transaction_ids: &[String],
+) -> Result<HashMap<String, Option<u32>>> {
+    Ok(fetch_statuses(client, url, transaction_ids)
+        .await?
+        .into_iter()
+        .map(|status| (status.transaction_id, status.commit_index))
+        .collect())
+}
+
+/// Query the `/tx_status` endpoint of the node at `url` for the time at which it committed
+/// every transaction, in microseconds since the epoch. Transactions that are not committed
+/// (or that the node forgot) are left out.
+pub async fn lookup_commit_times(
+    client: &Client,
+    url: &str,
+    transaction_ids: &[String],
+) -> Result<HashMap<String, u64>> {
+    Ok(fetch_statuses(client, url, transaction_ids)
+        .await?
+        .into_iter()
+        .filter_map(|status| Some((status.transaction_id, status.committed_at_us?)))
+        .collect())
+}
+
+async fn fetch_statuses(
+    client: &Client,
+    url: &str,
+    transaction_ids: &[String],
+) -> Result<Vec<TransactionStatus>> {
+    let mut statuses = Vec::with_capacity(transaction_ids.len());
+    for batch in transaction_ids.chunks(LOOKUP_BATCH_SIZE) {
+        let response: TransactionStatusResponse = client
+            .post(format!("{url}/tx_status"))
+            .json(&TransactionStatusRequest {
+                transaction_ids: batch,
+            })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        statuses.extend(response.transactions);
+    }
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{CommitVerification, MissingTransaction};
+
+    #[test]
+    fn detects_lost_transactions() {
+        let acknowledged = vec!["a".to_string(), "b".to_string()];
+        let mut verification = CommitVerification {
+            submitted: 3,
+            acknowledged: acknowledged.len(),
+            ..Default::default()
+        };
+
+        let complete = HashMap::from([("a".to_string(), Some(1)), ("b".to_string(), Some(2))]);
+        let partial = HashMap::from([("a".to_string(), Some(1)), ("b".to_string(), None)]);
+        verification.check_node(0, &acknowledged, Some(&complete));
+        assert!(verification.is_consistent());
+
+        verification.check_node(1, &acknowledged, Some(&partial));
+        verification.check_node(2, &acknowledged, None);
+        assert!(!verification.is_consistent());
+        assert_eq!(
+            verification.missing,
+            vec![MissingTransaction {
+                node: 1,
+                transaction_id: "b".into()
+            }]
+        );
+        assert_eq!(verification.unreachable, vec![2]);
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! CPU profiles of the remote nodes under load. `perf` samples the stacks of every node over
+//! the benchmark window; the samples are then exported with `perf script` (the input of the
+//! usual flame graph tools, e.g. `stackcollapse-perf.pl`) and downloaded into the results
+//! directory. Profiling needs `perf` and password-less sudo on the instances, which the
+//! pre-flight check verifies before anything runs.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use eyre::{Result, eyre};
+use tracing::{info, warn};
+
+use crate::{
+    client::Instance,
+    ssh::{CommandContext, CommandStatus, SshConnectionManager},
+};
+
+/// The name of the node process on the instances.
+const NODE_PROCESS: &str = "mysticeti";
+/// The id of the background command recording the profile.
+const PROFILE_COMMAND_ID: &str = "profile";
+/// Where the samples are recorded on the instances (relative to the home directory).
+const REMOTE_SAMPLES: &str = "mysticeti-profile.data";
+/// Where the exported stacks are written on the instances (relative to the home directory).
+const REMOTE_STACKS: &str = "mysticeti-profile.txt";
+
+/// A shell script printing, for every tool profiling needs, whether the instance has it.
+fn preflight_script() -> String {
+    [
+        ("perf", "command -v perf > /dev/null"),
+        ("sudo", "sudo -n true 2> /dev/null"),
+    ]
+    .iter()
+    .map(|(tool, check)| format!("({check} && echo {tool}=ok || echo {tool}=missing)"))
+    .collect::<Vec<_>>()
+    .join("; ")
+}
+
+/// The tools reported missing by `preflight_script`. Tools not reported at all are missing too.
+fn missing_tools(output: &str) -> Vec<&'static str> {
+    ["perf", "sudo"]
+        .into_iter()
+        .filter(|tool| {
+            !output
+                .lines()
+                .any(|line| line.trim() == format!("{tool}=ok"))
+        })
+        .collect()
+}
+
+/// A shell script sampling the stacks of the node at the specified frequency for `duration`.
+fn record_script(duration: Duration, frequency_hz: u32) -> String {
+    format!(
+        "sudo perf record -F {frequency_hz} -g -p $(pidof -s {NODE_PROCESS}) \
+         -o {REMOTE_SAMPLES} -- sleep {}",
+        duration.as_secs().max(1)
+    )
+}
+
+/// A shell script exporting the recorded samples as text stacks.
+fn export_script() -> String {
+    format!("sudo perf script -f -i {REMOTE_SAMPLES} > {REMOTE_STACKS}")
+}
+
+/// Records CPU profiles of the nodes of remote instances between `start_remote` and `stop`.
+pub struct NodeProfiler {
+    ssh_manager: SshConnectionManager,
+    instances: Vec<Instance>,
+}
+
+impl NodeProfiler {
+    /// The default sampling frequency, off the round numbers to avoid sampling in lockstep
+    /// with periodic work.
+    pub const DEFAULT_FREQUENCY_HZ: u32 = 99;
+
+    /// Check that every instance has the tools profiling needs. Fails listing the instances
+    /// missing some.
+    pub async fn check_remote(
+        ssh_manager: &SshConnectionManager,
+        instances: &[Instance],
+    ) -> Result<()> {
+        let outputs = ssh_manager
+            .execute(
+                instances.to_vec(),
+                preflight_script(),
+                CommandContext::default(),
+            )
+            .await
+            .map_err(|e| eyre!("Failed to check the profiling tools of the instances: {e}"))?;
+        let failures: Vec<_> = instances
+            .iter()
+            .zip(&outputs)
+            .filter_map(|(instance, (stdout, _))| {
+                let missing = missing_tools(stdout);
+                (!missing.is_empty()).then(|| format!("{} ({})", instance.id, missing.join(", ")))
+            })
+            .collect();
+        if !failures.is_empty() {
+            return Err(eyre!(
+                "Profiling needs perf and password-less sudo, missing on: {}",
+                failures.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Start sampling the nodes of the instances at the specified frequency, for `duration`
+    /// (the benchmark window).
+    pub async fn start_remote(
+        ssh_manager: SshConnectionManager,
+        instances: Vec<Instance>,
+        duration: Duration,
+        frequency_hz: u32,
+    ) -> Self {
+        let context = CommandContext::new().run_background(PROFILE_COMMAND_ID.into());
+        if let Err(e) = ssh_manager
+            .execute(
+                instances.clone(),
+                record_script(duration, frequency_hz),
+                context,
+            )
+            .await
+        {
+            warn!("Failed to start profiling the nodes: {e}");
+        }
+        Self {
+            ssh_manager,
+            instances,
+        }
+    }
+
+    /// Wait for the recordings to end, then download the profile of every node into the
+    /// directory as `node-<i>.perf.txt`. Returns the path of every profile, in node order
+    /// (`None` if it could not be downloaded).
+    pub async fn stop(self, directory: &Path) -> Vec<Option<PathBuf>> {
+        let mut profiles = vec![None; self.instances.len()];
+        if let Err(e) = fs::create_dir_all(directory) {
+            warn!("Failed to create {}: {e}", directory.display());
+            return profiles;
+        }
+        if let Err(e) = self
+            .ssh_manager
+            .wait_for_command(
+                self.instances.clone(),
+                PROFILE_COMMAND_ID,
+                CommandStatus::Terminated,
+            )
+            .await
+        {
+            warn!("Failed to wait for the profiles of the nodes: {e}");
+        }
+        if let Err(e) = self
+            .ssh_manager
+            .execute(
+                self.instances.clone(),
+                export_script(),
+                CommandContext::default(),
+            )
+            .await
+        {
+            warn!("Failed to export the profiles of the nodes: {e}");
+        }
+
+        // Transfers are sequential, the ssh library does not reliably transfer in parallel.
+        for (i, (instance, profile)) in self.instances.iter().zip(&mut profiles).enumerate() {
+            let stacks = match self.ssh_manager.connect(instance.ssh_address()).await {
+                Ok(connection) => connection.download(REMOTE_STACKS),
+                Err(e) => Err(e),
+            };
+            let path = directory.join(format!("node-{i}.perf.txt"));
+            match stacks.map(|stacks| fs::write(&path, stacks)) {
+                Ok(Ok(())) => *profile = Some(path),
+                Ok(Err(e)) => warn!("Failed to write {}: {e}", path.display()),
+                Err(e) => warn!("Failed to download the profile of node {i}: {e}"),
+            }
+        }
+        info!(
+            "Downloaded {}/{} node profiles into {}",
+            profiles.iter().flatten().count(),
+            profiles.len(),
+            directory.display()
+        );
+        profiles
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{missing_tools, preflight_script, record_script};
+
+    #[test]
+    fn profiling_scripts() {
+        assert_eq!(missing_tools("perf=ok\nsudo=ok\n"), Vec::<&str>::new());
+        assert_eq!(missing_tools("perf=missing\nsudo=ok\n"), vec!["perf"]);
+        assert_eq!(missing_tools(""), vec!["perf", "sudo"]);
+        assert!(preflight_script().contains("echo perf=missing"));
+
+        let script = record_script(Duration::from_secs(60), 99);
+        assert!(script.contains("-F 99 -g -p $(pidof -s mysticeti)"));
+        assert!(script.ends_with("-- sleep 60"));
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{Json, Router, routing::get};
+use color_eyre::eyre::{Context, Result};
+use serde::Serialize;
+use tracing::{error, info};
+
+/// The live progress of a benchmark run, shared between the transaction simulator and the
+/// progress endpoint.
+#[derive(Default)]
+pub struct BenchmarkProgress {
+    /// The load (tx/s) of the current benchmark.
+    load: AtomicUsize,
+    /// The duration of the current benchmark (in seconds).
+    duration: AtomicU64,
+    /// When the current benchmark started.
+    started: Mutex<Option<Instant>>,
+    /// Number of transactions accepted by the nodes during the current benchmark.
+    successful: AtomicU64,
+    /// Number of transactions rejected or lost during the current benchmark.
+    failed: AtomicU64,
+}
+
+/// A point-in-time copy of the benchmark progress.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ProgressSnapshot {
+    /// The load (tx/s) of the current benchmark.
+    pub load: usize,
+    /// Seconds elapsed since the start of the current benchmark.
+    pub elapsed_s: f64,
+    /// Seconds remaining until the end of the current benchmark.
+    pub remaining_s: f64,
+    /// The rate at which transactions are accepted by the nodes (tx/s).
+    pub achieved_tps: f64,
+    /// Number of transactions accepted by the nodes.
+    pub successful: u64,
+    /// Number of transactions rejected or lost.
+    pub failed: u64,
+}
+
+impl BenchmarkProgress {
+    /// Reset the progress for a new benchmark.
+    pub fn start_benchmark(&self, load: usize, duration: Duration) {
+        self.load.store(load, Ordering::Relaxed);
+        self.duration.store(duration.as_secs(), Ordering::Relaxed);
+        self.successful.store(0, Ordering::Relaxed);
+        self.failed.store(0, Ordering::Relaxed);
+        *self.started.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Account for a transaction accepted by a node.
+    pub fn record_success(&self) {
+        self.successful.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Account for a transaction that could not be submitted.
+    pub fn record_failure(&self) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Copy the current progress.
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        let elapsed = self
+            .started
+            .lock()
+            .unwrap()
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+        self.snapshot_at(elapsed)
+    }
+
+    fn snapshot_at(&self, elapsed: Duration) -> ProgressSnapshot {
+        let duration = Duration::from_secs(self.duration.load(Ordering::Relaxed));
+        let successful = self.successful.load(Ordering::Relaxed);
+        let achieved_tps = if elapsed.is_zero() {
+            0.0
+        } else {
+            successful as f64 / elapsed.as_secs_f64()
+        };
+        ProgressSnapshot {
+            load: self.load.load(Ordering::Relaxed),
+            elapsed_s: elapsed.as_secs_f64(),
+            remaining_s: duration.saturating_sub(elapsed).as_secs_f64(),
+            achieved_tps,
+            successful,
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Serve the progress as JSON on `GET /progress` of the specified local port.
+    pub async fn serve(self: Arc<Self>, port: u16) -> Result<()> {
+        let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+        let listener = tokio::net::TcpListener::bind(address)
+            .await
+            .wrap_err(format!("Failed to bind progress endpoint on {address}"))?;
+        let app = Router::new().route(
+            "/progress",
+            get(move || async move { Json(self.snapshot()) }),
+        );
+
+        info!("Serving benchmark progress on http://{}/progress", address);
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                error!("Progress endpoint failed: {}", e);
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::BenchmarkProgress;
+
+    #[test]
+    fn progress_snapshot() {
+        let progress = BenchmarkProgress::default();
+        progress.start_benchmark(100, Duration::from_secs(60));
+        for _ in 0..200 {
+            progress.record_success();
+        }
+        progress.record_failure();
+
+        let snapshot = progress.snapshot_at(Duration::from_secs(10));
+        assert_eq!(snapshot.load, 100);
+        assert_eq!(snapshot.remaining_s, 50.0);
+        assert_eq!(snapshot.achieved_tps, 20.0);
+        assert_eq!(snapshot.successful, 200);
+        assert_eq!(snapshot.failed, 1);
+
+        // Restarting resets the counters.
+        progress.start_benchmark(200, Duration::from_secs(60));
+        assert_eq!(progress.snapshot_at(Duration::ZERO).successful, 0);
+    }
+}
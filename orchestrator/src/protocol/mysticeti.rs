@@ -24,6 +24,33 @@ use super::{config::PrivateConfig, ProtocolCommands, ProtocolMetrics};
 const CARGO_FLAGS: &str = "--release";
 const RUST_FLAGS: &str = "RUSTFLAGS=-C\\ target-cpu=native";
 const METRICS_ROUTE: &str = "/metrics";
+/// The port of the metrics server of the first node (`NodeConfig::DEFAULT_METRICS_PORT` of the
+/// validator); each node listens on this port plus its authority index.
+const METRICS_PORT: u16 = 8000;
+
+/// The name of the node histogram of the number of transactions per commit.
+pub const TRANSACTIONS_PER_COMMIT: &str = "committed_transactions_per_commit";
+/// The name of the node histogram of the number of blocks per commit.
+pub const BLOCKS_PER_COMMIT: &str = "committed_blocks_per_commit";
+/// The name of the node counter of committed transactions by proposing authority.
+pub const TRANSACTIONS_BY_AUTHORITY: &str = "committed_transactions_by_authority";
+/// The name of the node counter of accepted transactions committed again after a first commit.
+pub const DUPLICATE_TRANSACTIONS: &str = "committed_duplicate_transactions";
+/// The name of the node counter of committed transactions rejected as invalid.
+pub const REJECTED_TRANSACTIONS: &str = "committed_rejected_transactions";
+/// The name of the node histogram of the proposal delay of committed blocks, by author.
+pub const PROPOSAL_DELAY: &str = "block_proposal_delay_seconds";
+/// The name of the node histogram of the time between a leader block and its commit, by leader.
+pub const LEADER_COMMIT_DELAY: &str = "leader_commit_delay_seconds";
+/// The name of the node gauge of the time between its start and its first commit.
+pub const TIME_TO_FIRST_COMMIT: &str = "time_to_first_commit_seconds";
+/// The name of the node counter of transactions dropped or corrupted by an unsafe fault.
+pub const FAULTY_TRANSACTIONS: &str = "faulty_transactions";
+/// The name of the node histogram of the time submitted transactions wait to be proposed.
+pub const SUBMISSION_WAIT: &str = "submission_wait_seconds";
+/// The name of the node histogram of the time between the proposal and the commit of the
+/// submitted transactions.
+pub const PROPOSE_TO_COMMIT: &str = "propose_to_commit_seconds";
 // The type of benchmarks supported by Mysticeti.
 // Note that all transactions are interpreted as both owned and shared.
 
@@ -31,21 +58,105 @@ const METRICS_ROUTE: &str = "/metrics";
 pub struct MysticetiBenchmarkType {
     // The transaction size in bytes.
     transaction_size: usize,
+    // The layout of the transactions.
+    #[serde(default)]
+    transaction_format: TransactionFormat,
+}
+
+impl MysticetiBenchmarkType {
+    /// Create a new benchmark type sending transactions of the specified size (in bytes).
+    pub fn new(transaction_size: usize) -> Self {
+        Self {
+            transaction_size,
+            transaction_format: TransactionFormat::default(),
+        }
+    }
+
+    /// Set the layout of the transactions.
+    pub fn with_transaction_format(mut self, transaction_format: TransactionFormat) -> Self {
+        self.transaction_format = transaction_format;
+        self
+    }
+
+    /// The transaction size in bytes.
+    pub fn transaction_size(&self) -> usize {
+        self.transaction_size
+    }
+
+    /// The layout of the transactions.
+    pub fn transaction_format(&self) -> TransactionFormat {
+        self.transaction_format
+    }
 }
 
 impl Display for MysticetiBenchmarkType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}B transactions", self.transaction_size)
+        match self.transaction_format {
+            TransactionFormat::Raw => write!(f, "{}B transactions", self.transaction_size),
+            format => write!(f, "{}B {format} transactions", self.transaction_size),
+        }
     }
 }
 
 impl FromStr for MysticetiBenchmarkType {
-    type Err = std::num::ParseIntError;
+    type Err = String;
+
+    /// Parse `SIZE` or `SIZE:FORMAT` (e.g. `512:structured`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (size, format) = s.split_once(':').unwrap_or((s, "raw"));
+        let transaction_size = size
+            .parse::<usize>()
+            .map_err(|e| format!("Invalid transaction size '{size}': {e}"))?;
+        Ok(Self::new(transaction_size).with_transaction_format(format.parse()?))
+    }
+}
+
+/// The layout of the transactions submitted by the benchmark clients.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionFormat {
+    /// Opaque bytes: the cheapest transactions to generate and process.
+    #[default]
+    Raw,
+    /// Bytes laid out like a real transaction (header, nonce, payload and signature), so that
+    /// parsing and verifying them costs about as much as real ones.
+    Structured,
+    /// Structured transactions whose sender is an Ed25519 public key and whose signature is
+    /// real, so that the nodes can verify it (see `--verify-signatures` of the validator).
+    Signed,
+}
+
+impl TransactionFormat {
+    /// Whether the nodes must verify the signature of the transactions.
+    pub fn is_signed(&self) -> bool {
+        *self == Self::Signed
+    }
+}
+
+impl Display for TransactionFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Raw => write!(f, "raw"),
+            Self::Structured => write!(f, "structured"),
+            Self::Signed => write!(f, "signed"),
+        }
+    }
+}
+
+impl FromStr for TransactionFormat {
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self {
-            transaction_size: s.parse::<usize>()?,
-        })
+        match s {
+            "raw" => Ok(Self::Raw),
+            "structured" => Ok(Self::Structured),
+            "signed" => Ok(Self::Signed),
+            _ => Err(format!(
+                "Unknown transaction format '{s}' (expected raw, structured or signed)"
+            )),
+        }
     }
 }
 
@@ -134,6 +245,10 @@ impl ProtocolCommands<MysticetiBenchmarkType> for MysticetiProtocol {
                 .collect();
 
                 let env = env::var("ENV").unwrap_or_default();
+                let max_transactions = parameters
+                    .max_transactions_per_proposal
+                    .map(|max| format!("--max-transactions-per-block {max}"))
+                    .unwrap_or_default();
                 let run = [
                     &env,
                     &format!("{RUST_FLAGS} cargo run {CARGO_FLAGS} --bin mysticeti --"),
@@ -147,11 +262,13 @@ impl ProtocolCommands<MysticetiBenchmarkType> for MysticetiProtocol {
                         parameters_path.display(),
                         private_configs_path.display()
                     ),
+                    &max_transactions,
                 ]
                 .join(" ");
                 let tps = format!("export TPS={}", parameters.load / parameters.nodes);
                 let tx_size = format!("export TRANSACTION_SIZE={}", parameters.benchmark_type.transaction_size);
-                let command = ["#!/bin/bash -e", "source $HOME/.cargo/env", &tps, &tx_size, &run].join("\\n");
+                let tx_format = format!("export TRANSACTION_FORMAT={}", parameters.benchmark_type.transaction_format);
+                let command = ["#!/bin/bash -e", "source $HOME/.cargo/env", &tps, &tx_size, &tx_format, &run].join("\\n");
                 let command = format!("echo -e '{command}' > mysticeti-start.sh && chmod +x mysticeti-start.sh && ./mysticeti-start.sh");
 
                 (instance, command)
@@ -196,8 +313,7 @@ impl ProtocolMetrics for MysticetiProtocol {
             .into_iter()
             .enumerate()
             .map(|(i, instance)| {
-                // Use a default metrics port pattern: 8000 + instance index
-                let metrics_port = 8000 + i as u16;
+                let metrics_port = METRICS_PORT + i as u16;
                 let main_ip = instance.main_ip;
                 (
                     instance,
@@ -215,3 +331,30 @@ impl ProtocolMetrics for MysticetiProtocol {
         self.nodes_metrics_path(instances)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{client::Instance, protocol::ProtocolMetrics, settings::Settings};
+
+    use super::MysticetiProtocol;
+
+    #[test]
+    fn scrape_the_metrics_servers_of_the_nodes() {
+        let protocol = MysticetiProtocol::new(&Settings::new_for_test());
+        let instances = (0..3).map(|i| Instance::new_for_test(i.to_string()));
+
+        let paths: Vec<_> = protocol
+            .nodes_metrics_path(instances)
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "http://127.0.0.1:8000/metrics",
+                "http://127.0.0.1:8001/metrics",
+                "http://127.0.0.1:8002/metrics",
+            ]
+        );
+    }
+}
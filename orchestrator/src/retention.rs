@@ -0,0 +1,273 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Retention of the benchmark results. Every run saves its measurements in a directory of the
+//! results directory (`results-<commit>`); benchmark machines accumulate them until the disk
+//! fills up. Pruning removes the run directories that fall outside a retention policy, never
+//! touching anything else: other files and directories, symbolic links, and runs pinned with
+//! a `KEEP` file are left alone.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use eyre::{Context, Result, eyre};
+
+/// The prefix of the name of the run directories.
+pub const RUN_PREFIX: &str = "results-";
+/// Runs holding a file of this name are never pruned.
+pub const KEEP_MARKER: &str = "KEEP";
+
+/// A run directory of the results directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunDirectory {
+    pub path: PathBuf,
+    /// The last time a file of the run was modified.
+    pub modified: SystemTime,
+    /// The total size of the files of the run, in bytes.
+    pub size_bytes: u64,
+    /// Whether the run is pinned with a `KEEP` file.
+    pub pinned: bool,
+}
+
+/// Which runs to keep: the most recent ones and those younger than a maximum age. A run is
+/// pruned only if it is outside every limit set.
+#[derive(Clone, Debug, Default)]
+pub struct RetentionPolicy {
+    /// Keep this many of the most recent runs.
+    pub keep_last: Option<usize>,
+    /// Keep the runs modified more recently than this.
+    pub older_than: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    /// The runs to prune among the specified runs (sorted from the most recent).
+    pub fn select<'a>(&self, runs: &'a [RunDirectory], now: SystemTime) -> Vec<&'a RunDirectory> {
+        let keep_last = self.keep_last.unwrap_or(0);
+        runs.iter()
+            .enumerate()
+            .filter(|(i, run)| !run.pinned && *i >= keep_last)
+            .filter(|(_, run)| match self.older_than {
+                Some(max_age) => now.duration_since(run.modified).unwrap_or_default() > max_age,
+                None => true,
+            })
+            .map(|(_, run)| run)
+            .collect()
+    }
+}
+
+/// The runs removed (or that would be removed, in a dry run) by pruning.
+#[derive(Clone, Debug, Default)]
+pub struct PruneReport {
+    pub pruned: Vec<RunDirectory>,
+    /// The number of runs kept.
+    pub kept: usize,
+}
+
+impl PruneReport {
+    /// The disk space freed by pruning, in bytes.
+    pub fn freed_bytes(&self) -> u64 {
+        self.pruned.iter().map(|run| run.size_bytes).sum()
+    }
+}
+
+/// Parse an age such as `30d`, `12h`, `45m` or `90s` (seconds without a unit).
+pub fn parse_age(arg: &str) -> Result<Duration, String> {
+    let arg = arg.trim();
+    let (value, unit) = match arg.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => arg.split_at(index),
+        None => (arg, "s"),
+    };
+    let invalid = || format!("Invalid age '{arg}' (expected e.g. 30d, 12h, 45m or 90s)");
+    let value: u64 = value.parse().map_err(|_| invalid())?;
+    let unit_seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return Err(format!("Unknown unit '{unit}' in age '{arg}'")),
+    };
+    let seconds = value.checked_mul(unit_seconds).ok_or_else(invalid)?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// List the run directories of the results directory, from the most recent.
+pub fn list_runs(results_dir: &Path) -> Result<Vec<RunDirectory>> {
+    let entries = fs::read_dir(results_dir)
+        .wrap_err_with(|| format!("Failed to read {}", results_dir.display()))?;
+    let mut runs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        // Symbolic links may point outside of the results directory.
+        let is_directory = entry.file_type()?.is_dir();
+        if !is_directory || !name.to_string_lossy().starts_with(RUN_PREFIX) {
+            continue;
+        }
+        let path = entry.path();
+        let (modified, size_bytes) = scan(&path)?;
+        // Empty runs date from the creation of their directory.
+        let modified = match modified {
+            Some(modified) => modified,
+            None => entry.metadata()?.modified()?,
+        };
+        runs.push(RunDirectory {
+            pinned: path.join(KEEP_MARKER).exists(),
+            path,
+            modified,
+            size_bytes,
+        });
+    }
+    runs.sort_by(|a, b| b.modified.cmp(&a.modified).then(b.path.cmp(&a.path)));
+    Ok(runs)
+}
+
+/// The last modification time (if any file) and total size of the files under the directory.
+fn scan(directory: &Path) -> Result<(Option<SystemTime>, u64)> {
+    let mut modified = None;
+    let mut size = 0;
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let (entry_modified, entry_size) = match entry.file_type()?.is_dir() {
+            true => scan(&entry.path())?,
+            false => (Some(metadata.modified()?), metadata.len()),
+        };
+        modified = modified.max(entry_modified);
+        size += entry_size;
+    }
+    Ok((modified, size))
+}
+
+/// Remove the run directories of the results directory falling outside the policy (or only
+/// report them, in a dry run).
+pub fn prune(results_dir: &Path, policy: &RetentionPolicy, dry_run: bool) -> Result<PruneReport> {
+    if policy.keep_last.is_none() && policy.older_than.is_none() {
+        return Err(eyre!(
+            "Specify at least one of --keep-last and --older-than"
+        ));
+    }
+    let root = results_dir
+        .canonicalize()
+        .wrap_err_with(|| format!("Failed to resolve {}", results_dir.display()))?;
+    let runs = list_runs(&root)?;
+    let pruned: Vec<_> = policy
+        .select(&runs, SystemTime::now())
+        .into_iter()
+        .cloned()
+        .collect();
+
+    for run in &pruned {
+        // Never remove anything but a direct child of the results directory.
+        let path = run.path.canonicalize()?;
+        if path.parent() != Some(root.as_path()) {
+            return Err(eyre!(
+                "Refusing to remove {} (outside of {})",
+                path.display(),
+                root.display()
+            ));
+        }
+        if !dry_run {
+            fs::remove_dir_all(&path)
+                .wrap_err_with(|| format!("Failed to remove {}", path.display()))?;
+        }
+    }
+    Ok(PruneReport {
+        kept: runs.len() - pruned.len(),
+        pruned,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs::{self, File},
+        time::{Duration, SystemTime},
+    };
+
+    use super::{KEEP_MARKER, RetentionPolicy, list_runs, parse_age, prune};
+
+    #[test]
+    fn prune_old_runs() {
+        let results = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        // From the oldest to the most recent, the last one pinned.
+        for (i, name) in ["results-a", "results-b", "results-c", "results-d"]
+            .iter()
+            .enumerate()
+        {
+            let run = results.path().join(name);
+            fs::create_dir(&run).unwrap();
+            let mut files = vec![run.join("measurements.json")];
+            if i == 3 {
+                files.push(run.join(KEEP_MARKER));
+            }
+            for file in files {
+                fs::write(&file, "{}").unwrap();
+                let age = Duration::from_secs(3600 * (4 - i as u64));
+                File::options()
+                    .write(true)
+                    .open(&file)
+                    .unwrap()
+                    .set_modified(now - age)
+                    .unwrap();
+            }
+        }
+        // Not a run directory.
+        fs::create_dir(results.path().join("plots")).unwrap();
+
+        let runs = list_runs(results.path()).unwrap();
+        let names: Vec<_> = runs
+            .iter()
+            .map(|run| run.path.file_name().unwrap())
+            .collect();
+        assert_eq!(names, ["results-d", "results-c", "results-b", "results-a"]);
+        assert!(runs[0].pinned);
+        assert_eq!(runs[1].size_bytes, 2);
+
+        // Age limits only prune the old runs.
+        let policy = RetentionPolicy {
+            keep_last: None,
+            older_than: Some(Duration::from_secs(150 * 60)),
+        };
+        assert_eq!(policy.select(&runs, now).len(), 2);
+
+        // A dry run removes nothing.
+        let policy = RetentionPolicy {
+            keep_last: Some(2),
+            older_than: None,
+        };
+        let report = prune(results.path(), &policy, true).unwrap();
+        assert_eq!(report.pruned.len(), 2);
+        assert_eq!(report.freed_bytes(), 4);
+        assert_eq!(list_runs(results.path()).unwrap().len(), 4);
+
+        // The most recent and the pinned runs survive, and so does everything else.
+        let report = prune(results.path(), &policy, false).unwrap();
+        assert_eq!(report.kept, 2);
+        assert!(!results.path().join("results-a").exists());
+        assert!(results.path().join("results-c").exists());
+        assert!(results.path().join("plots").exists());
+
+        assert!(prune(results.path(), &RetentionPolicy::default(), true).is_err());
+    }
+
+    #[test]
+    fn parse_ages() {
+        assert_eq!(parse_age("30d"), Ok(Duration::from_secs(30 * 86400)));
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 3600)));
+        assert_eq!(parse_age("90"), Ok(Duration::from_secs(90)));
+        assert!(parse_age("3y").is_err());
+        assert!(parse_age("d").is_err());
+        assert_eq!(
+            parse_age(&format!("{}w", u64::MAX / 86400)),
+            Err(format!(
+                "Invalid age '{}w' (expected e.g. 30d, 12h, 45m or 90s)",
+                u64::MAX / 86400
+            ))
+        );
+    }
+}
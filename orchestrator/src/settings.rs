@@ -48,6 +48,33 @@ pub enum CloudProvider {
     Vultr,
 }
 
+fn parse_sink_url<'de, D>(deserializer: D) -> Result<Url, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: &str = Deserialize::deserialize(deserializer)?;
+    Url::parse(s).map_err(D::Error::custom)
+}
+
+/// Where to publish the benchmark measurements as they are collected.
+#[derive(Deserialize, Clone, Default, Debug)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum MeasurementSinkSettings {
+    /// Save the measurements as json files in the results directory.
+    #[default]
+    File,
+    /// Push the measurements to an HTTP endpoint accepting the InfluxDB line protocol, such
+    /// as the InfluxDB write API or the `/write` endpoint of VictoriaMetrics.
+    Influx {
+        /// The write endpoint, including its query parameters (bucket, precision, ...).
+        #[serde(deserialize_with = "parse_sink_url")]
+        url: Url,
+        /// The token sent in the `Authorization` header, if any.
+        #[serde(default)]
+        token: Option<String>,
+    },
+}
+
 /// The testbed settings. Those are topically specified in a file.
 #[derive(Deserialize, Clone)]
 pub struct Settings {
@@ -79,6 +106,20 @@ pub struct Settings {
     /// The directory (on the local machine) where to download logs files from the instances.
     #[serde(default = "default_logs_dir")]
     pub logs_dir: PathBuf,
+    /// Where to publish the measurements as they are collected. Defaults to saving them as
+    /// json files in the results directory.
+    #[serde(default)]
+    pub measurement_sink: MeasurementSinkSettings,
+}
+
+/// Expand a leading `~` to the home directory of the current user. Rust file operations
+/// treat `~` literally (unlike the shell), so local paths must be expanded before use.
+pub fn expand_tilde<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(relative), Some(home)) => home.join(relative),
+        _ => path.to_path_buf(),
+    }
 }
 
 fn default_working_dir() -> PathBuf {
@@ -102,7 +143,12 @@ impl Settings {
         let reader = || -> Result<Self, std::io::Error> {
             let data = fs::read(path.clone())?;
             let data = resolve_env(std::str::from_utf8(&data).unwrap());
-            let settings: Settings = serde_json::from_slice(data.as_bytes())?;
+            let mut settings: Settings = serde_json::from_slice(data.as_bytes())?;
+
+            // Only expand local paths: the working directory lives on the remote instances.
+            settings.token_file = expand_tilde(&settings.token_file);
+            settings.ssh_private_key_file = expand_tilde(&settings.ssh_private_key_file);
+            settings.ssh_public_key_file = settings.ssh_public_key_file.as_ref().map(expand_tilde);
 
             fs::create_dir_all(&settings.results_dir)?;
             fs::create_dir_all(&settings.logs_dir)?;
@@ -194,6 +240,7 @@ impl Settings {
             working_dir: "/path/to/working_dir".into(),
             results_dir: "results".into(),
             logs_dir: "logs".into(),
+            measurement_sink: MeasurementSinkSettings::File,
         }
     }
 }
@@ -213,9 +260,11 @@ fn resolve_env(s: &str) -> String {
 
 #[cfg(test)]
 mod test {
+    use std::path::PathBuf;
+
     use reqwest::Url;
 
-    use crate::settings::Settings;
+    use crate::settings::{Settings, expand_tilde};
 
     #[test]
     fn repository_name() {
@@ -223,4 +272,13 @@ mod test {
         settings.repository.url = Url::parse("https://example.com/author/name").unwrap();
         assert_eq!(settings.repository_name(), "name");
     }
+
+    #[test]
+    fn tilde_expansion() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_tilde("~/.ssh/id_rsa"), home.join(".ssh/id_rsa"));
+        assert_eq!(expand_tilde("~"), home);
+        assert_eq!(expand_tilde("/etc/ssh/key"), PathBuf::from("/etc/ssh/key"));
+        assert_eq!(expand_tilde("~user/key"), PathBuf::from("~user/key"));
+    }
 }
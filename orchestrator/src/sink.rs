@@ -0,0 +1,165 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use reqwest::{Client, Url, header};
+
+use crate::{
+    benchmark::BenchmarkType,
+    error::{SinkError, SinkResult},
+    measurement::MeasurementsCollection,
+    settings::{MeasurementSinkSettings, Settings},
+};
+
+/// A destination for the benchmark measurements, receiving them as they are collected.
+#[async_trait]
+pub trait MeasurementSink<T: BenchmarkType>: Send + Sync {
+    /// Publish the measurements collected so far. This function is called after every scrape.
+    async fn publish(&self, measurements: &MeasurementsCollection<T>) -> SinkResult<()>;
+}
+
+/// Make the sink described by the settings.
+pub fn from_settings<T: BenchmarkType>(settings: &Settings) -> Box<dyn MeasurementSink<T>> {
+    match &settings.measurement_sink {
+        MeasurementSinkSettings::File => {
+            let commit = &settings.repository.commit;
+            let directory = settings.results_dir.join(format!("results-{commit}"));
+            Box::new(FileSink::new(directory))
+        }
+        MeasurementSinkSettings::Influx { url, token } => {
+            Box::new(InfluxSink::new(url.clone(), token.clone()))
+        }
+    }
+}
+
+/// Save the measurements as a json file (overwritten at every scrape).
+pub struct FileSink {
+    /// The directory holding the measurements files.
+    directory: PathBuf,
+}
+
+impl FileSink {
+    /// Make a new sink saving the measurements in the specified directory.
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+}
+
+#[async_trait]
+impl<T: BenchmarkType> MeasurementSink<T> for FileSink {
+    async fn publish(&self, measurements: &MeasurementsCollection<T>) -> SinkResult<()> {
+        fs::create_dir_all(&self.directory).map_err(|e| SinkError::FileError {
+            file: self.directory.display().to_string(),
+            message: e.to_string(),
+        })?;
+        measurements.save(&self.directory);
+        Ok(())
+    }
+}
+
+/// Push the latest measurements to an HTTP endpoint in the InfluxDB line protocol.
+pub struct InfluxSink {
+    /// The client sending the requests.
+    client: Client,
+    /// The write endpoint.
+    url: Url,
+    /// The token authenticating the requests, if any.
+    token: Option<String>,
+}
+
+impl InfluxSink {
+    /// Make a new sink pushing the measurements to the specified endpoint.
+    pub fn new(url: Url, token: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+            token,
+        }
+    }
+}
+
+#[async_trait]
+impl<T: BenchmarkType> MeasurementSink<T> for InfluxSink {
+    async fn publish(&self, measurements: &MeasurementsCollection<T>) -> SinkResult<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let body = measurements.to_line_protocol(now);
+        if body.is_empty() {
+            return Ok(());
+        }
+
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(body);
+        if let Some(token) = &self.token {
+            request = request.header(header::AUTHORIZATION, format!("Token {token}"));
+        }
+
+        let error = |message: String| SinkError::HttpError {
+            url: self.url.clone(),
+            message,
+        };
+        let response = request.send().await.map_err(|e| error(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(error(format!("{status}: {body}")));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{Router, body::Bytes, http::HeaderMap, routing::post};
+    use tokio::sync::mpsc;
+
+    use crate::{
+        benchmark::{BenchmarkParameters, test::TestBenchmarkType},
+        measurement::{Measurement, MeasurementsCollection},
+        settings::Settings,
+    };
+
+    use super::{InfluxSink, MeasurementSink};
+
+    #[tokio::test]
+    async fn influx_sink_pushes_line_protocol() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/write",
+            post(move |headers: HeaderMap, body: Bytes| async move {
+                let token = headers[axum::http::header::AUTHORIZATION].to_str().unwrap();
+                sender.send((token.to_string(), body)).unwrap();
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let settings = Settings::new_for_test();
+        let parameters = BenchmarkParameters::<TestBenchmarkType>::default();
+        let mut measurements = MeasurementsCollection::new(&settings, parameters);
+        let (label, measurement) = Measurement::new_for_test();
+        measurements.add(0, label, measurement);
+
+        let url = format!("http://{address}/write").parse().unwrap();
+        let sink = InfluxSink::new(url, Some("secret".into()));
+        sink.publish(&measurements).await.unwrap();
+
+        let (token, body) = receiver.recv().await.unwrap();
+        assert_eq!(token, "Token secret");
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.starts_with("mysticeti_benchmark,"));
+        assert!(body.contains("label=owned,scraper=0 "));
+    }
+}
@@ -18,6 +18,7 @@ use crate::{
     client::Instance,
     ensure,
     error::{SshError, SshResult},
+    settings::expand_tilde,
 };
 
 #[derive(PartialEq, Eq)]
@@ -115,7 +116,7 @@ impl SshConnectionManager {
     pub fn new(username: String, private_key_file: PathBuf) -> Self {
         Self {
             username,
-            private_key_file,
+            private_key_file: expand_tilde(private_key_file),
             timeout: None,
             retries: 0,
         }
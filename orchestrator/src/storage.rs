@@ -0,0 +1,422 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sampling of the storage cost of the nodes during a benchmark: the size of their consensus
+//! database and the disk I/O of their container (local) or machine (remote).
+
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio::{runtime::Handle, sync::oneshot, task::JoinHandle};
+use tracing::warn;
+
+use crate::{
+    client::Instance,
+    ssh::{CommandContext, SshConnectionManager},
+};
+
+/// The cumulative storage counters of a node at one point in time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StorageSnapshot {
+    /// The size of the consensus database on disk.
+    pub db_bytes: u64,
+    /// The bytes read from disk since the container or machine started.
+    pub read_bytes: u64,
+    /// The bytes written to disk since the container or machine started.
+    pub write_bytes: u64,
+}
+
+/// A snapshot taken `elapsed_s` seconds after the start of the sampling.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StorageSample {
+    pub elapsed_s: f64,
+    pub db_bytes: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// The storage samples of one node over the run.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StorageSeries {
+    pub samples: Vec<StorageSample>,
+}
+
+impl StorageSeries {
+    fn push(&mut self, elapsed: Duration, snapshot: StorageSnapshot) {
+        self.samples.push(StorageSample {
+            elapsed_s: elapsed.as_secs_f64(),
+            db_bytes: snapshot.db_bytes,
+            read_bytes: snapshot.read_bytes,
+            write_bytes: snapshot.write_bytes,
+        });
+    }
+
+    /// The size of the database at the last sample.
+    pub fn final_db_bytes(&self) -> Option<u64> {
+        self.samples.last().map(|sample| sample.db_bytes)
+    }
+
+    /// The average growth of the database over the run, in bytes per second.
+    pub fn db_growth_rate(&self) -> Option<f64> {
+        let (first, last) = (self.samples.first()?, self.samples.last()?);
+        let elapsed = last.elapsed_s - first.elapsed_s;
+        (elapsed > 0.0).then(|| (last.db_bytes as f64 - first.db_bytes as f64) / elapsed)
+    }
+
+    /// The highest read rate between two consecutive samples, in bytes per second.
+    pub fn peak_read_rate(&self) -> f64 {
+        self.peak_rate(|sample| sample.read_bytes)
+    }
+
+    /// The highest write rate between two consecutive samples, in bytes per second.
+    pub fn peak_write_rate(&self) -> f64 {
+        self.peak_rate(|sample| sample.write_bytes)
+    }
+
+    fn peak_rate(&self, counter: impl Fn(&StorageSample) -> u64) -> f64 {
+        self.samples
+            .windows(2)
+            .filter(|pair| pair[1].elapsed_s > pair[0].elapsed_s)
+            .map(|pair| {
+                // Counters reset if the container restarts; do not count that as I/O.
+                let bytes = counter(&pair[1]).saturating_sub(counter(&pair[0]));
+                bytes as f64 / (pair[1].elapsed_s - pair[0].elapsed_s)
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Parse a size printed by docker, e.g. `0B`, `4.1kB`, `12.3MB` or `1.5GiB`.
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (value, unit) = size.split_at(split);
+    let value: f64 = value.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "" | "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((value * multiplier).round() as u64)
+}
+
+/// Parse the `BlockIO` column of `docker stats`, e.g. `12.3MB / 45.6MB`, into the bytes read
+/// and written.
+pub fn parse_block_io(block_io: &str) -> Option<(u64, u64)> {
+    let (read, write) = block_io.split_once('/')?;
+    Some((parse_size(read)?, parse_size(write)?))
+}
+
+/// Parse the `key=value` lines printed by `remote_storage_script`.
+pub fn parse_snapshot(output: &str) -> Option<StorageSnapshot> {
+    let mut snapshot = StorageSnapshot::default();
+    let mut io = false;
+    for (key, value) in output.lines().filter_map(|line| line.split_once('=')) {
+        let value = value.trim();
+        match key.trim() {
+            // The database does not exist until the node starts.
+            "db_bytes" => snapshot.db_bytes = value.parse().unwrap_or(0),
+            "read_bytes" => {
+                snapshot.read_bytes = value.parse().ok()?;
+                io = true;
+            }
+            "write_bytes" => snapshot.write_bytes = value.parse().ok()?,
+            _ => (),
+        }
+    }
+    io.then_some(snapshot)
+}
+
+/// A shell script printing the size of the specified database directories and the disk I/O
+/// of the machine (summed over its whole disks, from `/proc/diskstats`) as `key=value` lines.
+pub fn remote_storage_script(db_directories: &[PathBuf]) -> String {
+    let directories: Vec<_> = db_directories
+        .iter()
+        .map(|directory| directory.display().to_string())
+        .collect();
+    format!(
+        r#"
+echo "db_bytes=$(du -scb {} 2>/dev/null | tail -1 | cut -f1)"
+awk '$3 ~ /^(sd[a-z]+|vd[a-z]+|xvd[a-z]+|nvme[0-9]+n[0-9]+)$/ {{ r += $6; w += $10 }}
+    END {{ printf "read_bytes=%.0f\nwrite_bytes=%.0f\n", r * 512, w * 512 }}' /proc/diskstats
+"#,
+        directories.join(" ")
+    )
+}
+
+/// Sample the storage of the containers of a local network: the size of the database of node
+/// `i` (mounted at `/app/data/node-<i>/consensus.db`) and the block I/O of its container.
+fn sample_local(containers: &[String]) -> Vec<Option<StorageSnapshot>> {
+    let output = Command::new("docker")
+        .args(["stats", "--no-stream", "--format", "{{.Name}} {{.BlockIO}}"])
+        .args(containers)
+        .output();
+    let stats = match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Err(e) => {
+            warn!("Failed to sample the disk I/O of the nodes: {e}");
+            return vec![None; containers.len()];
+        }
+    };
+
+    containers
+        .iter()
+        .enumerate()
+        .map(|(i, container)| {
+            let (read_bytes, write_bytes) = stats
+                .lines()
+                .filter_map(|line| line.split_once(' '))
+                .find(|(name, _)| name == container)
+                .and_then(|(_, block_io)| parse_block_io(block_io))?;
+            let db = format!("/app/data/node-{i}/consensus.db");
+            let db_bytes = Command::new("docker")
+                .args(["exec", container, "du", "-sb", &db])
+                .output()
+                .ok()
+                .and_then(|output| {
+                    let output = String::from_utf8_lossy(&output.stdout);
+                    output.split_whitespace().next()?.parse().ok()
+                })
+                .unwrap_or(0);
+            Some(StorageSnapshot {
+                db_bytes,
+                read_bytes,
+                write_bytes,
+            })
+        })
+        .collect()
+}
+
+/// Sample the storage of remote instances over ssh, one node per instance.
+async fn sample_remote(
+    ssh_manager: &SshConnectionManager,
+    instances: &[Instance],
+    script: &str,
+) -> Vec<Option<StorageSnapshot>> {
+    let outputs = ssh_manager
+        .execute(
+            instances.to_vec(),
+            script.to_string(),
+            CommandContext::default(),
+        )
+        .await;
+    match outputs {
+        Ok(outputs) => outputs
+            .iter()
+            .map(|(stdout, _)| parse_snapshot(stdout))
+            .collect(),
+        Err(e) => {
+            warn!("Failed to sample the storage of the instances: {e}");
+            vec![None; instances.len()]
+        }
+    }
+}
+
+/// Samples the storage of every node from a background task until stopped. Nodes that cannot
+/// be sampled at some point (e.g. not started yet) simply miss that sample.
+pub struct StorageMonitor {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<Vec<StorageSeries>>,
+}
+
+impl StorageMonitor {
+    /// Sample the docker containers of a local network, in node order.
+    pub fn start_local(containers: Vec<String>, interval: Duration) -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            let start = Instant::now();
+            let mut series = vec![StorageSeries::default(); containers.len()];
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                let last = tokio::select! {
+                    _ = ticker.tick() => false,
+                    _ = &mut stopped => true,
+                };
+                // `docker stats` takes a couple of seconds, keep it off the runtime threads.
+                let containers = containers.clone();
+                let snapshots = Handle::current()
+                    .spawn_blocking(move || sample_local(&containers))
+                    .await
+                    .unwrap_or_default();
+                Self::record(&mut series, start.elapsed(), snapshots);
+                if last {
+                    break series;
+                }
+            }
+        });
+        Self { stop, task }
+    }
+
+    /// Sample remote instances over ssh, in node order.
+    pub fn start_remote(
+        ssh_manager: SshConnectionManager,
+        instances: Vec<Instance>,
+        db_directories: Vec<PathBuf>,
+        interval: Duration,
+    ) -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+        let script = remote_storage_script(&db_directories);
+        let task = tokio::spawn(async move {
+            let start = Instant::now();
+            let mut series = vec![StorageSeries::default(); instances.len()];
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                let last = tokio::select! {
+                    _ = ticker.tick() => false,
+                    _ = &mut stopped => true,
+                };
+                let snapshots = sample_remote(&ssh_manager, &instances, &script).await;
+                Self::record(&mut series, start.elapsed(), snapshots);
+                if last {
+                    break series;
+                }
+            }
+        });
+        Self { stop, task }
+    }
+
+    fn record(
+        series: &mut [StorageSeries],
+        elapsed: Duration,
+        snapshots: Vec<Option<StorageSnapshot>>,
+    ) {
+        for (series, snapshot) in series.iter_mut().zip(snapshots) {
+            if let Some(snapshot) = snapshot {
+                series.push(elapsed, snapshot);
+            }
+        }
+    }
+
+    /// Take a last sample and return the series of every node.
+    pub async fn stop(self) -> Vec<StorageSeries> {
+        let _ = self.stop.send(());
+        self.task.await.unwrap_or_else(|e| {
+            warn!("Storage sampling failed: {e}");
+            Vec::new()
+        })
+    }
+}
+
+/// Summarize the storage series of the nodes into benchmark metadata: the average and highest
+/// database growth rate, the largest final database and the peak disk I/O of any node, plus
+/// the series themselves.
+pub fn to_metadata(series: &[StorageSeries]) -> BTreeMap<String, String> {
+    let growth: Vec<_> = series
+        .iter()
+        .filter_map(StorageSeries::db_growth_rate)
+        .collect();
+    if growth.is_empty() {
+        return BTreeMap::new();
+    }
+    let mean_growth = growth.iter().sum::<f64>() / growth.len() as f64;
+    let max_growth = growth.iter().copied().fold(f64::MIN, f64::max);
+    let final_db = series
+        .iter()
+        .filter_map(StorageSeries::final_db_bytes)
+        .max()
+        .unwrap_or_default();
+    let peak_read = series
+        .iter()
+        .map(StorageSeries::peak_read_rate)
+        .fold(0.0, f64::max);
+    let peak_write = series
+        .iter()
+        .map(StorageSeries::peak_write_rate)
+        .fold(0.0, f64::max);
+
+    [
+        (
+            "db_growth_bytes_per_s_mean".into(),
+            format!("{mean_growth:.0}"),
+        ),
+        (
+            "db_growth_bytes_per_s_max".into(),
+            format!("{max_growth:.0}"),
+        ),
+        ("db_size_final_bytes_max".into(), final_db.to_string()),
+        (
+            "disk_read_peak_bytes_per_s".into(),
+            format!("{peak_read:.0}"),
+        ),
+        (
+            "disk_write_peak_bytes_per_s".into(),
+            format!("{peak_write:.0}"),
+        ),
+        (
+            "storage_series".into(),
+            serde_json::to_string(series).unwrap_or_default(),
+        ),
+    ]
+    .into_iter()
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{StorageSeries, StorageSnapshot, parse_block_io, parse_snapshot, to_metadata};
+
+    #[test]
+    fn parse_storage_output() {
+        assert_eq!(parse_block_io("0B / 0B"), Some((0, 0)));
+        assert_eq!(parse_block_io("4.1kB / 12.5MB"), Some((4_100, 12_500_000)));
+        assert_eq!(parse_block_io("1GiB / 2MiB"), Some((1 << 30, 2 << 20)));
+        assert_eq!(parse_block_io("--"), None);
+
+        let snapshot = parse_snapshot("db_bytes=\nread_bytes=1024\nwrite_bytes=2048\n");
+        assert_eq!(
+            snapshot,
+            Some(StorageSnapshot {
+                db_bytes: 0,
+                read_bytes: 1024,
+                write_bytes: 2048
+            })
+        );
+        assert_eq!(parse_snapshot("db_bytes=10\n"), None);
+    }
+
+    #[test]
+    fn storage_growth_and_peak_io() {
+        let mut series = StorageSeries::default();
+        let counters = [
+            (0, 0, 0),
+            (1_000, 0, 5_000),
+            (3_000, 100, 30_000),
+            (5_000, 100, 35_000),
+        ];
+        for (i, (db_bytes, read_bytes, write_bytes)) in counters.into_iter().enumerate() {
+            let snapshot = StorageSnapshot {
+                db_bytes,
+                read_bytes,
+                write_bytes,
+            };
+            series.push(Duration::from_secs(5 * i as u64), snapshot);
+        }
+
+        assert_eq!(series.final_db_bytes(), Some(5_000));
+        assert_eq!(series.db_growth_rate(), Some(5_000.0 / 15.0));
+        assert_eq!(series.peak_read_rate(), 20.0);
+        assert_eq!(series.peak_write_rate(), 5_000.0);
+
+        let metadata = to_metadata(&[series, StorageSeries::default()]);
+        assert_eq!(metadata["db_growth_bytes_per_s_mean"], "333");
+        assert_eq!(metadata["db_size_final_bytes_max"], "5000");
+        assert_eq!(metadata["disk_write_peak_bytes_per_s"], "5000");
+        assert!(to_metadata(&[StorageSeries::default()]).is_empty());
+    }
+}